@@ -97,8 +97,10 @@ pub mod compat {
 pub mod key_exchange {
     //! Module containing types required for key exchange.
     pub use ockam_key_exchange_core::NewKeyExchanger;
+    #[cfg(feature = "noise_ik")]
+    pub use ockam_key_exchange_ik::IKNewKeyExchanger;
     #[cfg(feature = "noise_xx")]
-    pub use ockam_key_exchange_xx::XXNewKeyExchanger;
+    pub use ockam_key_exchange_xx::{XXCipherSuite, XXCurve, XXNewKeyExchanger};
 }
 
 #[cfg(feature = "ockam_vault")]
@@ -126,5 +128,5 @@ pub use ockam_transport_tcp::{TcpTransport, TCP};
 #[cfg(feature = "ockam_transport_tcp")]
 /// Tcp
 pub mod tcp {
-    pub use ockam_transport_tcp::{InletOptions, OutletOptions};
+    pub use ockam_transport_tcp::{InletOptions, OutletOptions, UdpInletOptions, UdpOutletOptions};
 }
@@ -9,6 +9,7 @@ mod eval;
 mod parser;
 mod policy;
 mod traits;
+mod trust_policy;
 mod types;
 
 pub mod expr;
@@ -21,4 +22,5 @@ pub use expr::Expr;
 pub use parser::parse;
 pub use policy::PolicyAccessControl;
 pub use traits::PolicyStorage;
+pub use trust_policy::AbacTrustPolicy;
 pub use types::{Action, Resource, Subject};
@@ -0,0 +1,247 @@
+use core::{fmt, str};
+use ockam_core::async_trait;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::format;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_identity::authenticated_storage::AuthenticatedStorage;
+use ockam_identity::credential::{
+    AttributesEntry, AttributesStorageUtils, Credential, CredentialData, SchemaRegistry,
+};
+use ockam_identity::{IdentityIdentifier, IdentityVault, PublicIdentity};
+use ockam_identity::{SecureChannelTrustInfo, TrustPolicy};
+use tracing as log;
+
+use crate::eval::eval;
+use crate::expr::str;
+use crate::{Env, Expr};
+
+/// A secure channel trust policy evaluating a policy expression against
+/// the attributes of the other party.
+///
+/// The attributes are taken from the credential the other party presented
+/// during the handshake. This credential is verified against the given
+/// authorities and its attributes are stored, so that access controls
+/// further down the line can use them. If no credential was presented,
+/// attributes previously stored for that identity are used instead.
+///
+/// Attributes are available as `subject.<name>` in the expression, the
+/// identifier of the other party as `subject.identifier`.
+pub struct AbacTrustPolicy<V, S> {
+    expression: Expr,
+    environment: Env,
+    authorities: Vec<PublicIdentity>,
+    schemas: SchemaRegistry,
+    vault: V,
+    attributes: S,
+}
+
+impl<V, S> AbacTrustPolicy<V, S> {
+    /// Create a new `AbacTrustPolicy`.
+    pub fn new(expr: Expr, authorities: Vec<PublicIdentity>, vault: V, store: S) -> Self {
+        Self {
+            expression: expr,
+            environment: Env::new(),
+            authorities,
+            schemas: SchemaRegistry::new(),
+            vault,
+            attributes: store,
+        }
+    }
+
+    /// Add pre-populated environment entries the expression may refer to.
+    pub fn with_environment(mut self, env: Env) -> Self {
+        self.environment = env;
+        self
+    }
+
+    /// Check presented credentials against the given schemas.
+    pub fn with_schema_registry(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
+    }
+}
+
+impl<V, S> fmt::Debug for AbacTrustPolicy<V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AbacTrustPolicy")
+            .field("expression", &self.expression)
+            .field("environment", &self.environment)
+            .finish()
+    }
+}
+
+impl<V, S> AbacTrustPolicy<V, S>
+where
+    V: IdentityVault + Sync,
+    S: AuthenticatedStorage,
+{
+    /// Verify a credential presented during the handshake and store its attributes.
+    async fn accept_credential(&self, id: &IdentityIdentifier, data: &[u8]) -> Result<bool> {
+        let credential: Credential = minicbor::decode(data)?;
+        let issuer = CredentialData::try_from(&credential)?
+            .unverfied_issuer()
+            .clone();
+
+        let authority = match self.authorities.iter().find(|a| a.identifier() == &issuer) {
+            Some(a) => a,
+            None => {
+                log::debug! {
+                    id     = %id,
+                    issuer = %issuer,
+                    "credential issued by unknown authority"
+                }
+                return Ok(false);
+            }
+        };
+
+        let data = match authority
+            .verify_credential_with_schemas(&credential, id, &self.vault, &self.schemas)
+            .await
+        {
+            Ok(data) => data,
+            Err(e) => {
+                log::debug! {
+                    id  = %id,
+                    err = %e,
+                    "credential verification failed"
+                }
+                return Ok(false);
+            }
+        };
+
+        let expires = data.expires_at();
+        AttributesStorageUtils::put_attributes(
+            id,
+            AttributesEntry::new(data.into_attributes(), expires),
+            &self.attributes,
+        )
+        .await?;
+
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl<V, S> TrustPolicy for AbacTrustPolicy<V, S>
+where
+    V: IdentityVault + Sync,
+    S: AuthenticatedStorage,
+{
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        let id = trust_info.their_identity_id();
+
+        if let Some(data) = trust_info.their_credential() {
+            if !self.accept_credential(id, data).await? {
+                return Ok(false);
+            }
+        }
+
+        // Get identity attributes and populate the environment:
+        let attrs: BTreeMap<String, Vec<u8>> =
+            AttributesStorageUtils::get_attributes(id, &self.attributes)
+                .await?
+                .unwrap_or_default();
+
+        let mut e = self.environment.clone();
+        e.put("subject.identifier", str(id.to_string()));
+
+        for (k, v) in &attrs {
+            match str::from_utf8(v) {
+                Ok(s) => {
+                    e.put(format!("subject.{k}"), str(s.to_string()));
+                }
+                Err(e) => {
+                    log::warn! {
+                        id  = %id,
+                        key = %k,
+                        err = %e,
+                        "failed to interpret attribute as string"
+                    }
+                }
+            }
+        }
+
+        // Finally, evaluate the expression and return the result:
+        match eval(&self.expression, &e) {
+            Ok(Expr::Bool(b)) => {
+                log::debug! {
+                    id      = %id,
+                    trusted = %b,
+                    "trust policy evaluated"
+                }
+                Ok(b)
+            }
+            Ok(x) => {
+                log::warn! {
+                    id   = %id,
+                    expr = %x,
+                    "evaluation did not yield a boolean result"
+                }
+                Ok(false)
+            }
+            Err(e) => {
+                log::warn! {
+                    id  = %id,
+                    err = %e,
+                    "trust policy evaluation failed"
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    fn requires_credential(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AbacTrustPolicy;
+    use crate::parser::parse;
+    use ockam_core::Result;
+    use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+    use ockam_identity::credential::Credential;
+    use ockam_identity::{Identity, SecureChannelTrustInfo, TrustPolicy};
+    use ockam_node::Context;
+    use ockam_vault::Vault;
+
+    async fn check_role(ctx: &Context, role: &[u8]) -> Result<bool> {
+        let vault = Vault::create();
+        let authority = Identity::create(ctx, &vault).await?;
+        let client = Identity::create(ctx, &vault).await?;
+
+        let expr = parse(r#"(= subject.role "edge")"#).unwrap().unwrap();
+        let policy = AbacTrustPolicy::new(
+            expr,
+            vec![authority.to_public().await?],
+            vault,
+            InMemoryStorage::new(),
+        );
+
+        let credential =
+            Credential::builder(client.identifier().clone()).with_attribute("role", role);
+        let credential = authority.issue_credential(credential).await?;
+        let trust_info = SecureChannelTrustInfo::new(client.identifier().clone())
+            .with_credential(Some(minicbor::to_vec(&credential)?));
+
+        policy.check(&trust_info).await
+    }
+
+    #[ockam_macros::test]
+    async fn matching_credential_is_trusted(ctx: &mut Context) -> Result<()> {
+        assert!(check_role(ctx, b"edge").await?);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn other_credential_is_not_trusted(ctx: &mut Context) -> Result<()> {
+        assert!(!check_role(ctx, b"cloud").await?);
+
+        ctx.stop().await
+    }
+}
@@ -1,2 +1,25 @@
 #[cfg(feature = "direct-authenticator")]
 pub mod direct;
+
+use ockam_identity::credential::{AttributeSchema, AttributeType, CredentialSchema, SchemaId};
+
+/// Schema identifier for a project membership credential.
+///
+/// The credential will consist of the following attributes:
+///
+/// - `project_id` : bytes
+/// - `role`: b"member"
+pub const PROJECT_MEMBER_SCHEMA: SchemaId = SchemaId(1);
+pub const PROJECT_ID: &str = "project_id";
+pub const ROLE: &str = "role";
+
+/// Description of [`PROJECT_MEMBER_SCHEMA`] for a
+/// [`SchemaRegistry`](ockam_identity::credential::SchemaRegistry).
+///
+/// Enrollers may attach further attributes to members, as long as they are strings.
+pub fn project_member_schema() -> CredentialSchema {
+    CredentialSchema::new(PROJECT_MEMBER_SCHEMA)
+        .with_attribute(AttributeSchema::required(PROJECT_ID, AttributeType::Bytes))
+        .with_attribute(AttributeSchema::optional(ROLE, AttributeType::String))
+        .with_additional_attributes(AttributeType::String)
+}
@@ -8,7 +8,7 @@ use ockam_core::api::{Error, Method, Request, RequestBuilder, Response, Response
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{self, Address, Result, Route, Routed, Worker};
 use ockam_identity::authenticated_storage::AuthenticatedStorage;
-use ockam_identity::credential::{Credential, SchemaId};
+use ockam_identity::credential::{Attributes, Credential, SchemaRegistry};
 use ockam_identity::{Identity, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault};
 use ockam_node::Context;
 use serde_json as json;
@@ -23,19 +23,11 @@ use crate::authenticator::direct::types::{CreateToken, OneTimeCode};
 
 use self::types::Enroller;
 
+pub use super::{project_member_schema, PROJECT_ID, PROJECT_MEMBER_SCHEMA, ROLE};
+
 const MEMBER: &str = "member";
 const MAX_TOKEN_DURATION: Duration = Duration::from_secs(600);
 
-/// Schema identifier for a project membership credential.
-///
-/// The credential will consist of the following attributes:
-///
-/// - `project_id` : bytes
-/// - `role`: b"member"
-pub const PROJECT_MEMBER_SCHEMA: SchemaId = SchemaId(1);
-pub const PROJECT_ID: &str = "project_id";
-pub const ROLE: &str = "role";
-
 pub struct Server<S, V: IdentityVault> {
     project: Vec<u8>,
     store: S,
@@ -43,6 +35,7 @@ pub struct Server<S, V: IdentityVault> {
     epath: PathBuf,
     enrollers: HashMap<IdentityIdentifier, Enroller>,
     tokens: LruCache<[u8; 32], Token>,
+    schemas: SchemaRegistry,
 }
 
 struct Token {
@@ -88,7 +81,27 @@ where
             epath: enrollers.as_ref().to_path_buf(),
             enrollers: HashMap::new(),
             tokens: LruCache::new(NonZeroUsize::new(128).expect("0 < 128")),
+            schemas: SchemaRegistry::new().with_schema(project_member_schema()),
+        }
+    }
+
+    /// Replace the registry member attributes are checked against.
+    pub fn with_schema_registry(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    /// Check that member attributes conform to [`PROJECT_MEMBER_SCHEMA`].
+    fn validate_member_attributes<'a, I>(&self, attrs: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut a = Attributes::new();
+        for (k, v) in attrs {
+            a.put(k, v.as_bytes());
         }
+        a.put(PROJECT_ID, &self.project);
+        self.schemas.validate(Some(PROJECT_MEMBER_SCHEMA), &a)
     }
 
     async fn on_request(&mut self, from: &IdentityIdentifier, data: &[u8]) -> Result<Vec<u8>> {
@@ -111,14 +124,22 @@ where
                 ["tokens"] => match self.check_enroller(&req, from).await {
                     Ok(None) => {
                         let att: CreateToken = dec.decode()?;
-                        let otc = OneTimeCode::new();
-                        let res = Response::ok(req.id()).body(&otc).to_vec()?;
-                        let tkn = Token {
-                            attrs: att.into_owned_attributes(),
-                            time: Instant::now(),
-                        };
-                        self.tokens.put(*otc.code(), tkn);
-                        res
+                        let attrs = att.into_owned_attributes();
+                        let valid = self.validate_member_attributes(
+                            attrs.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                        );
+                        if let Err(e) = valid {
+                            api::bad_request(&req, &e.to_string()).to_vec()?
+                        } else {
+                            let otc = OneTimeCode::new();
+                            let res = Response::ok(req.id()).body(&otc).to_vec()?;
+                            let tkn = Token {
+                                attrs,
+                                time: Instant::now(),
+                            };
+                            self.tokens.put(*otc.code(), tkn);
+                            res
+                        }
                     }
                     Ok(Some(e)) => e.to_vec()?,
                     Err(e) => api::internal_error(&req, &e.to_string()).to_vec()?,
@@ -127,11 +148,18 @@ where
                 ["members"] => match self.check_enroller(&req, from).await {
                     Ok(None) => {
                         let add: AddMember = dec.decode()?;
-                        let attributes = minicbor::to_vec(add.attributes())?;
-                        self.store
-                            .set(add.member().key_id(), MEMBER.to_string(), attributes)
-                            .await?;
-                        Response::ok(req.id()).to_vec()?
+                        let valid = self.validate_member_attributes(
+                            add.attributes().iter().map(|(k, v)| (&**k, &**v)),
+                        );
+                        if let Err(e) = valid {
+                            api::bad_request(&req, &e.to_string()).to_vec()?
+                        } else {
+                            let attributes = minicbor::to_vec(add.attributes())?;
+                            self.store
+                                .set(add.member().key_id(), MEMBER.to_string(), attributes)
+                                .await?;
+                            Response::ok(req.id()).to_vec()?
+                        }
                     }
                     Ok(Some(e)) => e.to_vec()?,
                     Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
@@ -154,6 +182,7 @@ where
                                     crd.with_attribute(a, v.as_bytes())
                                 })
                                 .with_schema(PROJECT_MEMBER_SCHEMA)
+                                .with_schema_registry(&self.schemas)
                                 .with_attribute(PROJECT_ID, &self.project);
                             let crd = self.ident.issue_credential(crd).await?;
                             Response::ok(req.id()).body(crd).to_vec()?
@@ -169,7 +198,8 @@ where
                             .iter()
                             .fold(
                                 Credential::builder(from.clone())
-                                    .with_schema(PROJECT_MEMBER_SCHEMA),
+                                    .with_schema(PROJECT_MEMBER_SCHEMA)
+                                    .with_schema_registry(&self.schemas),
                                 |crd, (a, v)| crd.with_attribute(a, v.as_bytes()),
                             )
                             .with_attribute(PROJECT_ID, &self.project);
@@ -1,14 +1,18 @@
 use crate::nodes::models::transport::{CreateTransportJson, TransportMode, TransportType};
 use ockam_identity::change_history::{IdentityChangeHistory, IdentityHistoryComparison};
 use ockam_identity::{Identity, IdentityIdentifier};
+use ockam_vault::audit::FileAuditLog;
+use ockam_vault::storage::{FileStorage, FileStorageKey};
 use ockam_vault::KeyId;
-use ockam_vault::{storage::FileStorage, Vault};
+use ockam_vault::Vault;
+use once_cell::sync::Lazy;
 use rand::random;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tempfile::{tempdir, Builder};
 use thiserror::Error;
 use tracing::field::debug;
@@ -29,6 +33,8 @@ pub enum CliStateError {
     NotFound(String),
     #[error("invalid state version {0}")]
     InvalidVersion(String),
+    #[error("the vault is encrypted and has not been unlocked")]
+    VaultLocked,
     #[error("unknown error")]
     Unknown,
 }
@@ -96,6 +102,27 @@ impl CliState {
     fn defaults_dir() -> Result<PathBuf> {
         Ok(Self::dir()?.join("defaults"))
     }
+
+    /// Make the default vault and the nodes using the vault `from` use the
+    /// vault `to` instead, returning the names of these nodes
+    pub fn replace_vault(&self, from: &VaultState, to: &VaultState) -> Result<Vec<String>> {
+        let from_path = std::fs::canonicalize(&from.path)?;
+        let default_path = self.vaults.default_path()?;
+        if std::fs::canonicalize(&default_path).ok().as_ref() == Some(&from_path) {
+            std::fs::remove_file(&default_path)?;
+            std::os::unix::fs::symlink(&to.path, &default_path)?;
+        }
+        let mut nodes = vec![];
+        for node in self.nodes.list()? {
+            if node.config.default_vault == from_path {
+                let link = node.path.join("default_vault");
+                std::fs::remove_file(&link)?;
+                std::os::unix::fs::symlink(&to.path, &link)?;
+                nodes.push(node.config.name);
+            }
+        }
+        Ok(nodes)
+    }
 }
 
 #[derive(Clone)]
@@ -186,29 +213,169 @@ pub enum VaultConfig {
 
         #[serde(default)]
         aws_kms: bool,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encryption: Option<VaultEncryption>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pkcs11: Option<Pkcs11Config>,
+
+        /// Append-only log of the operations on persistent secrets
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        audit_log: Option<PathBuf>,
     },
 }
 
+/// PKCS#11 token holding the persistent NIST P-256 keys of a vault
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Pkcs11Config {
+    pub module: PathBuf,
+    pub slot: u64,
+}
+
+/// Where the key encrypting a vault storage comes from
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultEncryption {
+    /// A passphrase, given to [`VaultConfig::unlock`]
+    Passphrase,
+    /// A keyfile at the given path
+    Keyfile(PathBuf),
+}
+
+/// Passphrases of the vaults unlocked by this process, by storage path.
+/// They are kept in memory rather than in the environment, which is
+/// inherited by child processes and readable by other processes of the user
+static PASSPHRASES: Lazy<Mutex<BTreeMap<PathBuf, String>>> = Lazy::new(Default::default);
+
+impl VaultEncryption {
+    fn key(&self, path: &Path) -> Result<FileStorageKey> {
+        Ok(match self {
+            Self::Passphrase => FileStorageKey::Passphrase(
+                PASSPHRASES
+                    .lock()
+                    .unwrap()
+                    .get(path)
+                    .cloned()
+                    .ok_or(CliStateError::VaultLocked)?,
+            ),
+            Self::Keyfile(path) => FileStorageKey::Keyfile(path.clone()),
+        })
+    }
+}
+
 impl VaultConfig {
+    /// Environment variable holding the passphrase of encrypted vaults
+    pub const PASSPHRASE_ENV: &'static str = "OCKAM_VAULT_PASSPHRASE";
+    /// Environment variable holding the user PIN of PKCS#11 tokens
+    pub const PKCS11_PIN_ENV: &'static str = "OCKAM_PKCS11_PIN";
+
     pub fn fs(path: PathBuf, aws_kms: bool) -> Result<Self> {
-        Ok(Self::Fs { path, aws_kms })
+        Ok(Self::Fs {
+            path,
+            aws_kms,
+            encryption: None,
+            pkcs11: None,
+            audit_log: None,
+        })
     }
 
     pub fn fs_default(name: &str, aws_kms: bool) -> Result<Self> {
         Ok(Self::Fs {
             path: Self::fs_path(name, None)?,
             aws_kms,
+            encryption: None,
+            pkcs11: None,
+            audit_log: None,
         })
     }
 
+    /// Encrypt the storage of the vault, see [`FileStorage::with_key`]
+    pub fn with_encryption(mut self, encryption: VaultEncryption) -> Self {
+        match &mut self {
+            Self::Fs { encryption: e, .. } => *e = Some(encryption),
+        }
+        self
+    }
+
+    /// Keep persistent NIST P-256 keys on a PKCS#11 token
+    pub fn with_pkcs11(mut self, config: Pkcs11Config) -> Self {
+        match &mut self {
+            Self::Fs { pkcs11, .. } => *pkcs11 = Some(config),
+        }
+        self
+    }
+
+    /// Record the operations on persistent secrets, see [`FileAuditLog`]
+    pub fn with_audit_log(mut self, path: PathBuf) -> Self {
+        match &mut self {
+            Self::Fs { audit_log, .. } => *audit_log = Some(path),
+        }
+        self
+    }
+
+    /// Unlock the vault for the rest of this process, see [`VaultEncryption::Passphrase`]
+    pub fn unlock(&self, passphrase: String) {
+        match self {
+            Self::Fs { path, .. } => {
+                PASSPHRASES.lock().unwrap().insert(path.clone(), passphrase);
+            }
+        }
+    }
+
+    /// The passphrase the vault was unlocked with, if any
+    pub fn passphrase(&self) -> Option<String> {
+        match self {
+            Self::Fs { path, .. } => PASSPHRASES.lock().unwrap().get(path).cloned(),
+        }
+    }
+
+    pub fn encryption(&self) -> Option<&VaultEncryption> {
+        match self {
+            Self::Fs { encryption, .. } => encryption.as_ref(),
+        }
+    }
+
+    pub fn pkcs11(&self) -> Option<&Pkcs11Config> {
+        match self {
+            Self::Fs { pkcs11, .. } => pkcs11.as_ref(),
+        }
+    }
+
+    pub fn audit_log(&self) -> Option<&PathBuf> {
+        match self {
+            Self::Fs { audit_log, .. } => audit_log.as_ref(),
+        }
+    }
+
     pub async fn get(&self) -> Result<Vault> {
         match &self {
-            VaultConfig::Fs { path, aws_kms } => {
-                let vault_storage = FileStorage::create(path.clone()).await?;
+            VaultConfig::Fs {
+                path,
+                aws_kms,
+                encryption,
+                pkcs11,
+                audit_log,
+            } => {
+                let mut vault_storage = FileStorage::new(path.clone());
+                if let Some(encryption) = encryption {
+                    vault_storage = vault_storage.with_key(encryption.key(path)?);
+                }
+                vault_storage.init().await?;
                 let mut vault = Vault::new(Some(Arc::new(vault_storage)));
                 if *aws_kms {
                     vault.enable_aws_kms().await?
                 }
+                if let Some(pkcs11) = pkcs11 {
+                    let mut config = ockam_vault::pkcs11::Config::new(&pkcs11.module, pkcs11.slot);
+                    if let Ok(pin) = std::env::var(Self::PKCS11_PIN_ENV) {
+                        config = config.pin(pin);
+                    }
+                    vault.enable_pkcs11(config)?
+                }
+                if let Some(audit_log) = audit_log {
+                    vault.enable_audit(FileAuditLog::open(audit_log).await?);
+                }
                 Ok(vault)
             }
         }
@@ -226,6 +393,15 @@ impl VaultConfig {
                 .join(format!("{name}-storage.json"))
         })
     }
+
+    pub fn audit_log_path(name: &str) -> Result<PathBuf> {
+        let state = CliState::new()?;
+        Ok(state
+            .vaults
+            .dir
+            .join("data")
+            .join(format!("{name}-audit.log")))
+    }
 }
 
 #[derive(Clone)]
@@ -271,6 +447,44 @@ impl IdentitiesState {
         Ok(IdentityState { path, config })
     }
 
+    pub fn list(&self) -> Result<Vec<(String, IdentityState)>> {
+        let mut identities = vec![];
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                let name = file_stem(&path)?;
+                let state = self.get(&name)?;
+                identities.push((name, state));
+            }
+        }
+        identities.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(identities)
+    }
+
+    /// Replace the configuration of an existing identity, e.g. after a key rotation
+    pub fn update(&self, name: &str, config: IdentityConfig) -> Result<IdentityState> {
+        let path = self.get(name)?.path;
+        let contents = serde_json::to_string(&config)?;
+        std::fs::write(&path, contents)?;
+        Ok(IdentityState { path, config })
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        if self.is_default(name)? {
+            std::fs::remove_file(self.default_path()?)?;
+        }
+        std::fs::remove_file(self.get(name)?.path)?;
+        Ok(())
+    }
+
+    pub fn is_default(&self, name: &str) -> Result<bool> {
+        let state = self.get(name)?;
+        Ok(match std::fs::canonicalize(self.default_path()?) {
+            Ok(default) => default == std::fs::canonicalize(&state.path)?,
+            Err(_) => false,
+        })
+    }
+
     pub fn default_path(&self) -> Result<PathBuf> {
         Ok(CliState::defaults_dir()?.join("identity"))
     }
@@ -308,6 +522,12 @@ pub struct IdentityState {
     pub config: IdentityConfig,
 }
 
+impl IdentityState {
+    pub fn name(&self) -> Result<String> {
+        file_stem(&self.path)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct IdentityConfig {
     pub identifier: IdentityIdentifier,
@@ -533,10 +753,14 @@ impl NodeConfig {
         })
     }
 
-    pub async fn vault(&self) -> Result<Vault> {
+    pub fn vault_config(&self) -> Result<VaultConfig> {
         let path = std::fs::canonicalize(&self.default_vault)?;
-        let config: VaultConfig = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
-        config.get().await
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub async fn vault(&self) -> Result<Vault> {
+        let vault = self.vault_config()?.get().await?;
+        Ok(vault.with_audit_caller(format!("node {}", self.name)))
     }
 
     pub async fn identity(&self, ctx: &ockam::Context) -> Result<Identity<Vault>> {
@@ -741,6 +965,9 @@ mod tests {
             let got = sut.identities.default().unwrap();
             assert_eq!(got, state);
 
+            let got = sut.identities.list().unwrap();
+            assert_eq!(got, vec![(name.clone(), state)]);
+
             name
         };
 
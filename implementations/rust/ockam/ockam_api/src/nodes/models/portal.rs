@@ -1,6 +1,7 @@
 //! Inlets and outlet request/response types
 
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::Cow;
@@ -32,7 +33,10 @@ pub struct CreateInlet<'a> {
     /// An authorised identity for secure channels.
     /// Only set for non-project addresses as for projects the project's
     /// authorised identity will be used.
-    #[n(5)] authorized: Option<IdentityIdentifier>
+    #[n(5)] authorized: Option<IdentityIdentifier>,
+    /// Close the sessions of UDP clients after this duration without traffic.
+    /// Ignored by TCP inlets.
+    #[n(6)] idle_timeout: Option<Duration>
 }
 
 impl<'a> CreateInlet<'a> {
@@ -45,6 +49,7 @@ impl<'a> CreateInlet<'a> {
             alias: None,
             check_credential,
             authorized: None,
+            idle_timeout: None,
         }
     }
 
@@ -62,6 +67,7 @@ impl<'a> CreateInlet<'a> {
             alias: None,
             check_credential,
             authorized: auth,
+            idle_timeout: None,
         }
     }
 
@@ -69,6 +75,10 @@ impl<'a> CreateInlet<'a> {
         self.alias = Some(CowStr(a.into()))
     }
 
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = Some(idle_timeout)
+    }
+
     pub fn listen_addr(&self) -> SocketAddr {
         self.listen_addr
     }
@@ -88,6 +98,10 @@ impl<'a> CreateInlet<'a> {
     pub fn check_credential(&self) -> Option<bool> {
         self.check_credential
     }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
 }
 
 /// Request body to create an inlet or outlet
@@ -106,6 +120,9 @@ pub struct CreateOutlet<'a> {
     /// Enable credentials authorization.
     /// Defaults to the Node's `enable-credential-checks` value passed upon creation.
     #[n(4)] pub check_credential: Option<bool>,
+    /// Close the sessions of UDP inlet clients after this duration without
+    /// traffic. Ignored by TCP outlets.
+    #[n(5)] pub idle_timeout: Option<Duration>,
 }
 
 impl<'a> CreateOutlet<'a> {
@@ -122,8 +139,14 @@ impl<'a> CreateOutlet<'a> {
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             check_credential,
+            idle_timeout: None,
         }
     }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -2,21 +2,23 @@ use std::time::Duration;
 
 use minicbor::{Decode, Encode};
 
-use crate::nodes::registry::SecureChannelInfo;
+use crate::nodes::registry::{SecureChannelInfo, SecureChannelListenerInfo};
+use ockam_abac::Expr;
 use ockam_core::compat::borrow::Cow;
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 use ockam_core::{route, Address, CowStr, Result};
-use ockam_identity::IdentityIdentifier;
+use ockam_identity::{IdentityIdentifier, ListenerLimits};
 use ockam_multiaddr::MultiAddr;
 use serde::Serialize;
 
 use crate::error::ApiError;
 use crate::route_to_multiaddr;
 
-#[derive(Debug, Clone, Copy, Decode, Encode)]
+#[derive(Debug, Clone, Copy, Decode, Encode, Serialize)]
 #[rustfmt::skip]
 #[cbor(index_only)]
+#[serde(rename_all = "lowercase")]
 pub enum CredentialExchangeMode {
     #[n(0)] None,
     #[n(1)] Oneway,
@@ -33,7 +35,22 @@ pub struct CreateSecureChannelRequest<'a> {
     #[b(1)] pub addr: CowStr<'a>,
     #[b(2)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
     #[n(3)] pub credential_exchange_mode: CredentialExchangeMode,
-    #[n(4)] pub timeout: Option<Duration>
+    #[n(4)] pub timeout: Option<Duration>,
+    /// Pin the identity of the listener on first use, see [`TrustOnFirstUsePolicy`].
+    ///
+    /// [`TrustOnFirstUsePolicy`]: ockam_identity::TrustOnFirstUsePolicy
+    #[n(5)] pub trust_on_first_use: Option<bool>,
+    /// Mix a post-quantum key encapsulation into the handshake, see
+    /// [`SecureChannelOptions::with_hybrid_kem`].
+    ///
+    /// [`SecureChannelOptions::with_hybrid_kem`]: ockam_identity::SecureChannelOptions::with_hybrid_kem
+    #[n(6)] pub hybrid_kem: Option<bool>,
+    /// Hex encoded X25519 key of the listener for a Noise IK handshake, see
+    /// [`SecureChannelOptions::with_ik_remote_static_key`]. Needs exactly one
+    /// authorized identifier, the one of the listener.
+    ///
+    /// [`SecureChannelOptions::with_ik_remote_static_key`]: ockam_identity::SecureChannelOptions::with_ik_remote_static_key
+    #[b(7)] pub ik_remote_static_key: Option<CowStr<'a>>,
 }
 
 impl<'a> CreateSecureChannelRequest<'a> {
@@ -50,8 +67,27 @@ impl<'a> CreateSecureChannelRequest<'a> {
                 .map(|x| x.into_iter().map(|y| y.to_string().into()).collect()),
             credential_exchange_mode,
             timeout: None,
+            trust_on_first_use: None,
+            hybrid_kem: None,
+            ik_remote_static_key: None,
         }
     }
+
+    pub fn with_trust_on_first_use(mut self, trust_on_first_use: bool) -> Self {
+        self.trust_on_first_use = Some(trust_on_first_use);
+        self
+    }
+
+    pub fn with_hybrid_kem(mut self, hybrid_kem: bool) -> Self {
+        self.hybrid_kem = Some(hybrid_kem);
+        self
+    }
+
+    /// Hex encoded X25519 key of the listener
+    pub fn with_ik_remote_static_key(mut self, public_key: Option<String>) -> Self {
+        self.ik_remote_static_key = public_key.map(|key| key.into());
+        self
+    }
 }
 
 /// Response body when instructing a node to create a Secure Channel
@@ -96,6 +132,19 @@ pub struct CreateSecureChannelListenerRequest<'a> {
     #[n(0)] tag: TypeTag<8112242>,
     #[b(1)] pub addr: Cow<'a, str>,
     #[b(2)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
+    /// Policy expression evaluated against the initiator's credential attributes.
+    #[n(3)] pub trust_policy: Option<Expr>,
+    /// Maximum number of channels, including the ones still in their handshake.
+    #[n(4)] pub max_channels: Option<u64>,
+    /// Maximum number of channels of a single initiator identity.
+    #[n(5)] pub max_channels_per_identity: Option<u64>,
+    /// Time after which uncompleted handshakes are aborted.
+    #[n(6)] pub handshake_timeout: Option<Duration>,
+    /// Id of an X25519 secret of the node's vault to accept Noise IK
+    /// handshakes with, see [`SecureChannelOptions::with_ik_static_key`].
+    ///
+    /// [`SecureChannelOptions::with_ik_static_key`]: ockam_identity::SecureChannelOptions::with_ik_static_key
+    #[b(7)] pub ik_static_key: Option<CowStr<'a>>,
 }
 
 impl<'a> CreateSecureChannelListenerRequest<'a> {
@@ -106,6 +155,112 @@ impl<'a> CreateSecureChannelListenerRequest<'a> {
             addr: addr.to_string().into(),
             authorized_identifiers: authorized_identifiers
                 .map(|x| x.into_iter().map(|y| y.to_string().into()).collect()),
+            trust_policy: None,
+            max_channels: None,
+            max_channels_per_identity: None,
+            handshake_timeout: None,
+            ik_static_key: None,
+        }
+    }
+
+    pub fn with_trust_policy(mut self, trust_policy: Option<Expr>) -> Self {
+        self.trust_policy = trust_policy;
+        self
+    }
+
+    pub fn with_limits(
+        mut self,
+        max_channels: Option<u64>,
+        max_channels_per_identity: Option<u64>,
+        handshake_timeout: Option<Duration>,
+    ) -> Self {
+        self.max_channels = max_channels;
+        self.max_channels_per_identity = max_channels_per_identity;
+        self.handshake_timeout = handshake_timeout;
+        self
+    }
+
+    /// Id of an X25519 secret of the node's vault
+    pub fn with_ik_static_key(mut self, key_id: Option<String>) -> Self {
+        self.ik_static_key = key_id.map(|key_id| key_id.into());
+        self
+    }
+
+    /// Limits of the listener, see [`ListenerLimits`]. Limits which do not
+    /// fit in a `usize` cannot be reached, so they saturate.
+    pub fn limits(&self) -> ListenerLimits {
+        let mut limits = ListenerLimits::new();
+        if let Some(max_channels) = self.max_channels {
+            limits = limits.with_max_channels(usize::try_from(max_channels).unwrap_or(usize::MAX));
+        }
+        if let Some(max_channels) = self.max_channels_per_identity {
+            limits = limits.with_max_channels_per_identity(
+                usize::try_from(max_channels).unwrap_or(usize::MAX),
+            );
+        }
+        if let Some(handshake_timeout) = self.handshake_timeout {
+            limits = limits.with_handshake_timeout(handshake_timeout);
+        }
+        limits
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ShowSecureChannelListenerRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1538402>,
+    #[b(1)] pub addr: CowStr<'a>,
+}
+
+impl<'a> ShowSecureChannelListenerRequest<'a> {
+    pub fn new(addr: &Address) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.to_string().into(),
+        }
+    }
+}
+
+/// Limits of a secure channel listener and the channels they rejected
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ShowSecureChannelListenerResponse<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<7304619>,
+    #[b(1)] pub addr: Option<CowStr<'a>>,
+    #[n(2)] pub max_channels: Option<u64>,
+    #[n(3)] pub max_channels_per_identity: Option<u64>,
+    #[n(4)] pub handshake_timeout: Option<Duration>,
+    #[n(5)] pub rejected_too_many_channels: u64,
+    #[n(6)] pub rejected_too_many_for_identity: u64,
+    #[n(7)] pub rejected_handshake_timeouts: u64,
+}
+
+impl<'a> ShowSecureChannelListenerResponse<'a> {
+    pub fn new(addr: &Address, info: Option<&SecureChannelListenerInfo>) -> Self {
+        let limits = info.map(|info| info.limits());
+        let rejections = limits
+            .map(|limits| limits.rejections().stats())
+            .unwrap_or_default();
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: info.map(|_| addr.to_string().into()),
+            max_channels: limits
+                .and_then(|limits| limits.max_channels())
+                .map(|n| n as u64),
+            max_channels_per_identity: limits
+                .and_then(|limits| limits.max_channels_per_identity())
+                .map(|n| n as u64),
+            handshake_timeout: limits.and_then(|limits| limits.handshake_timeout()),
+            rejected_too_many_channels: rejections.too_many_channels(),
+            rejected_too_many_for_identity: rejections.too_many_for_identity(),
+            rejected_handshake_timeouts: rejections.handshake_timeouts(),
         }
     }
 }
@@ -175,10 +330,23 @@ pub struct ShowSecureChannelResponse<'a> {
     #[b(1)] pub channel: Option<Cow<'a, str>>,
     #[b(2)] pub route: Option<Cow<'a, str>>,
     #[b(4)] pub authorized_identifiers: Option<Vec<CowStr<'a>>>,
+    #[b(5)] pub their_identity_id: Option<CowStr<'a>>,
+    #[b(6)] pub key_exchange: Option<CowStr<'a>>,
+    /// Unix timestamp of the creation of the channel
+    #[n(7)] pub created_at: Option<u64>,
+    /// Unix timestamp of the last message sent or received
+    #[n(8)] pub last_activity: Option<u64>,
+    #[n(9)] pub messages_encrypted: Option<u64>,
+    #[n(10)] pub bytes_encrypted: Option<u64>,
+    #[n(11)] pub messages_decrypted: Option<u64>,
+    #[n(12)] pub bytes_decrypted: Option<u64>,
+    #[n(13)] pub decryption_failures: Option<u64>,
+    #[n(14)] pub credential_exchange_mode: Option<CredentialExchangeMode>,
 }
 
 impl<'a> ShowSecureChannelResponse<'a> {
     pub fn new(info: Option<&SecureChannelInfo>) -> Self {
+        let stats = info.map(|info| info.counters().stats());
         Self {
             #[cfg(feature = "tag")]
             tag: TypeTag,
@@ -190,6 +358,93 @@ impl<'a> ShowSecureChannelResponse<'a> {
                         .map(|ids| ids.iter().map(|iid| iid.to_string().into()).collect())
                 })
                 .unwrap_or(None),
+            their_identity_id: stats
+                .as_ref()
+                .and_then(|s| s.their_identity_id())
+                .map(|id| id.to_string().into()),
+            key_exchange: stats
+                .as_ref()
+                .and_then(|s| s.key_exchange())
+                .map(|k| k.to_string().into()),
+            created_at: stats.as_ref().and_then(|s| s.created_at()).map(u64::from),
+            last_activity: stats
+                .as_ref()
+                .and_then(|s| s.last_activity())
+                .map(u64::from),
+            messages_encrypted: stats.as_ref().map(|s| s.messages_encrypted()),
+            bytes_encrypted: stats.as_ref().map(|s| s.bytes_encrypted()),
+            messages_decrypted: stats.as_ref().map(|s| s.messages_decrypted()),
+            bytes_decrypted: stats.as_ref().map(|s| s.bytes_decrypted()),
+            decryption_failures: stats.as_ref().map(|s| s.decryption_failures()),
+            credential_exchange_mode: info.and_then(|info| info.credential_exchange_mode()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ForgetPinnedIdentityRequest<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5031977>,
+    #[b(1)] pub name: CowStr<'a>,
+}
+
+impl<'a> ForgetPinnedIdentityRequest<'a> {
+    pub fn new(name: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name: name.into(),
+        }
+    }
+}
+
+/// An identity pinned to a secure channel route
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PinnedIdentity<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<2806417>,
+    #[b(1)] pub name: CowStr<'a>,
+    #[b(2)] pub identifier: CowStr<'a>,
+    #[n(3)] pub pinned_at: Option<u64>,
+}
+
+impl<'a> PinnedIdentity<'a> {
+    pub fn new(
+        name: impl Into<CowStr<'a>>,
+        identifier: &IdentityIdentifier,
+        pinned_at: Option<u64>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            name: name.into(),
+            identifier: identifier.to_string().into(),
+            pinned_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PinnedIdentityList<'a> {
+    #[cfg(feature = "tag")]
+    #[serde(skip)]
+    #[n(0)] tag: TypeTag<9154230>,
+    #[b(1)] pub list: Vec<PinnedIdentity<'a>>,
+}
+
+impl<'a> PinnedIdentityList<'a> {
+    pub fn new(list: Vec<PinnedIdentity<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
         }
     }
 }
@@ -1,7 +1,8 @@
+use crate::nodes::models::secure_channel::CredentialExchangeMode;
 use crate::nodes::service::Alias;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::{Address, Route};
-use ockam_identity::IdentityIdentifier;
+use ockam_identity::{IdentityIdentifier, ListenerLimits, SecureChannelCounters};
 
 #[derive(Default)]
 pub(crate) struct SecureChannelRegistry {
@@ -17,14 +18,23 @@ impl SecureChannelRegistry {
         self.channels.iter().find(|&x| x.addr() == addr)
     }
 
+    pub fn get_by_addr_mut(&mut self, addr: &Address) -> Option<&mut SecureChannelInfo> {
+        self.channels.iter_mut().find(|x| x.addr() == addr)
+    }
+
     pub fn insert(
         &mut self,
         addr: Address,
         route: Route,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        counters: SecureChannelCounters,
     ) {
-        self.channels
-            .push(SecureChannelInfo::new(route, addr, authorized_identifiers))
+        self.channels.push(SecureChannelInfo::new(
+            route,
+            addr,
+            authorized_identifiers,
+            counters,
+        ))
     }
 
     pub fn remove_by_addr(&mut self, addr: &Address) {
@@ -43,6 +53,10 @@ pub struct SecureChannelInfo {
     // Local address of the created channel
     addr: Address,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+    // Statistics collected by the channel workers
+    counters: SecureChannelCounters,
+    // How credentials were exchanged over the channel, once they were
+    credential_exchange_mode: Option<CredentialExchangeMode>,
 }
 
 impl SecureChannelInfo {
@@ -50,11 +64,14 @@ impl SecureChannelInfo {
         route: Route,
         addr: Address,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        counters: SecureChannelCounters,
     ) -> Self {
         Self {
             addr,
             route,
             authorized_identifiers,
+            counters,
+            credential_exchange_mode: None,
         }
     }
 
@@ -69,10 +86,34 @@ impl SecureChannelInfo {
     pub fn authorized_identifiers(&self) -> Option<&Vec<IdentityIdentifier>> {
         self.authorized_identifiers.as_ref()
     }
+
+    pub fn counters(&self) -> &SecureChannelCounters {
+        &self.counters
+    }
+
+    pub fn credential_exchange_mode(&self) -> Option<CredentialExchangeMode> {
+        self.credential_exchange_mode
+    }
+
+    pub fn set_credential_exchange_mode(&mut self, mode: CredentialExchangeMode) {
+        self.credential_exchange_mode = Some(mode)
+    }
 }
 
 #[derive(Default)]
-pub(crate) struct SecureChannelListenerInfo {}
+pub struct SecureChannelListenerInfo {
+    limits: ListenerLimits,
+}
+
+impl SecureChannelListenerInfo {
+    pub fn new(limits: ListenerLimits) -> Self {
+        Self { limits }
+    }
+
+    pub fn limits(&self) -> &ListenerLimits {
+        &self.limits
+    }
+}
 
 #[derive(Default)]
 pub(crate) struct VaultServiceInfo {}
@@ -161,4 +202,6 @@ pub(crate) struct Registry {
     // FIXME: wow this is a terrible way to store data
     pub(crate) inlets: BTreeMap<Alias, InletInfo>,
     pub(crate) outlets: BTreeMap<Alias, OutletInfo>,
+    pub(crate) udp_inlets: BTreeMap<Alias, InletInfo>,
+    pub(crate) udp_outlets: BTreeMap<Alias, OutletInfo>,
 }
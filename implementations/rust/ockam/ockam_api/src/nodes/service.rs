@@ -12,6 +12,7 @@ use ockam_core::compat::{
 };
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::AsyncTryClone;
+use ockam_identity::credential::SchemaRegistry;
 use ockam_identity::{Identity, IdentityIdentifier, PublicIdentity};
 use ockam_multiaddr::proto::{Project, Secure};
 use ockam_multiaddr::{MultiAddr, Protocol};
@@ -26,6 +27,7 @@ use std::time::Duration;
 use super::models::secure_channel::CredentialExchangeMode;
 use super::registry::Registry;
 use crate::authenticator::direct::types::OneTimeCode;
+use crate::authenticator::project_member_schema;
 use crate::cli_state::CliState;
 use crate::config::cli::AuthoritiesConfig;
 use crate::config::lookup::ProjectLookup;
@@ -78,6 +80,14 @@ impl Authorities {
     pub fn public_identities(&self) -> Vec<PublicIdentity> {
         self.0.iter().map(|x| x.identity.clone()).collect()
     }
+
+    /// Membership credentials issued by the project authorities must
+    /// conform to the project member schema
+    pub fn schema_registry(&self) -> SchemaRegistry {
+        self.0.iter().fold(SchemaRegistry::new(), |registry, x| {
+            registry.with_issuer_schema(x.identity.identifier().clone(), project_member_schema())
+        })
+    }
 }
 
 impl AsRef<[AuthorityInfo]> for Authorities {
@@ -306,6 +316,9 @@ impl NodeManager {
         self.create_secure_channel_listener_impl(
             DefaultAddress::SECURE_CHANNEL_LISTENER.into(),
             None, // Not checking identifiers here in favor of credentials check
+            None,
+            Default::default(),
+            None,
         )
         .await?;
 
@@ -339,7 +352,9 @@ impl NodeManager {
                     multiaddr_to_route(&a).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
                 let i = Some(vec![i]);
                 let m = CredentialExchangeMode::Oneway;
-                let w = self.create_secure_channel_impl(r, i, m, timeout).await?;
+                let w = self
+                    .create_secure_channel_impl(r, i, m, false, false, None, timeout)
+                    .await?;
                 let a = MultiAddr::default().try_with(addr.iter().skip(1))?;
                 return Ok((try_address_to_multiaddr(&w)?, a));
             }
@@ -351,7 +366,9 @@ impl NodeManager {
             let r = multiaddr_to_route(&a).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
             let i = auth.clone().map(|i| vec![i]);
             let m = CredentialExchangeMode::Mutual;
-            let w = self.create_secure_channel_impl(r, i, m, timeout).await?;
+            let w = self
+                .create_secure_channel_impl(r, i, m, false, false, None, timeout)
+                .await?;
             return Ok((try_address_to_multiaddr(&w)?, b));
         }
 
@@ -361,7 +378,9 @@ impl NodeManager {
                 multiaddr_to_route(addr).ok_or_else(|| ApiError::generic("invalid multiaddr"))?;
             let i = auth.clone().map(|i| vec![i]);
             let m = CredentialExchangeMode::Mutual;
-            let w = self.create_secure_channel_impl(r, i, m, timeout).await?;
+            let w = self
+                .create_secure_channel_impl(r, i, m, false, false, None, timeout)
+                .await?;
             return Ok((try_address_to_multiaddr(&w)?, MultiAddr::default()));
         }
 
@@ -405,6 +424,7 @@ impl NodeManagerWorker {
             "request"
         }
 
+        use portals::PortalProtocol::{Tcp, Udp};
         use Method::*;
         let path = req.path();
         let path_segments = req.path_segments::<5>();
@@ -496,10 +516,20 @@ impl NodeManagerWorker {
             (Get, ["node", "show_secure_channel"]) => {
                 self.show_secure_channel(req, dec).await?.to_vec()?
             }
+            (Get, ["node", "show_secure_channel_listener"]) => self
+                .show_secure_channel_listener(req, dec)
+                .await?
+                .to_vec()?,
             (Post, ["node", "secure_channel_listener"]) => self
                 .create_secure_channel_listener(req, dec)
                 .await?
                 .to_vec()?,
+            (Get, ["node", "pinned_identities"]) => {
+                self.list_pinned_identities(req).await?.to_vec()?
+            }
+            (Delete, ["node", "pinned_identities"]) => {
+                self.forget_pinned_identity(req, dec).await?.to_vec()?
+            }
 
             // ==*== Services ==*==
             (Post, ["node", "services", "vault"]) => {
@@ -545,14 +575,28 @@ impl NodeManagerWorker {
             // ==*== Inlets & Outlets ==*==
             (Get, ["node", "inlet"]) => {
                 let node_manager = self.node_manager.read().await;
-                self.get_inlets(req, &node_manager.registry).to_vec()?
+                self.get_inlets(req, &node_manager.registry, Tcp).to_vec()?
             }
             (Get, ["node", "outlet"]) => {
                 let node_manager = self.node_manager.read().await;
-                self.get_outlets(req, &node_manager.registry).to_vec()?
+                self.get_outlets(req, &node_manager.registry, Tcp)
+                    .to_vec()?
+            }
+            (Post, ["node", "inlet"]) => self.create_inlet(ctx, req, dec, Tcp).await?.to_vec()?,
+            (Post, ["node", "outlet"]) => self.create_outlet(req, dec, Tcp).await?.to_vec()?,
+            (Get, ["node", "udp_inlet"]) => {
+                let node_manager = self.node_manager.read().await;
+                self.get_inlets(req, &node_manager.registry, Udp).to_vec()?
+            }
+            (Get, ["node", "udp_outlet"]) => {
+                let node_manager = self.node_manager.read().await;
+                self.get_outlets(req, &node_manager.registry, Udp)
+                    .to_vec()?
             }
-            (Post, ["node", "inlet"]) => self.create_inlet(req, dec).await?.to_vec()?,
-            (Post, ["node", "outlet"]) => self.create_outlet(req, dec).await?.to_vec()?,
+            (Post, ["node", "udp_inlet"]) => {
+                self.create_inlet(ctx, req, dec, Udp).await?.to_vec()?
+            }
+            (Post, ["node", "udp_outlet"]) => self.create_outlet(req, dec, Udp).await?.to_vec()?,
             (Delete, ["node", "portal"]) => todo!(),
 
             (Post, ["policy", resource, action]) => self
@@ -659,6 +703,12 @@ impl Worker for NodeManagerWorker {
     type Context = Context;
 
     async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.start_worker(
+            secure_channel::SecureChannelWatcher::address(),
+            secure_channel::SecureChannelWatcher::new(self.node_manager.clone()),
+        )
+        .await?;
+
         let mut node_manger = self.node_manager.write().await;
         if !node_manger.skip_defaults {
             node_manger.initialize_defaults(ctx).await?;
@@ -48,7 +48,7 @@ impl NodeManager {
 
         debug!("Create secure channel to project authority");
         let sc = self
-            .create_secure_channel_internal(&identity, route, Some(allowed), None)
+            .create_secure_channel_internal(&identity, route, Some(allowed), false, false, None, None)
             .await?;
         debug!("Created secure channel to project authority");
 
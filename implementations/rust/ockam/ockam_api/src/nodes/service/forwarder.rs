@@ -92,8 +92,8 @@ impl NodeManagerWorker {
 /// Create a session replacer.
 ///
 /// This returns a function that accepts the previous ping address (e.g.
-/// the secure channel worker address) and constructs the whole route
-/// again.
+/// the secure channel worker address) and moves the secure channel to a new
+/// connection, or constructs the whole route again if that fails.
 fn replacer(
     manager: Arc<RwLock<NodeManager>>,
     ctx: Arc<Context>,
@@ -110,8 +110,14 @@ fn replacer(
         Box::pin(async move {
             debug!(%prev, %addr, "creating new remote forwarder");
             let f = async {
+                let ping_addr = prev.clone();
                 let prev = try_multiaddr_to_addr(&prev)?;
                 let mut this = manager.write().await;
+                // The forwarder is kept if the secure channel can be migrated
+                match this.migrate_secure_channels(&ctx, &[prev.clone()]).await {
+                    Ok(()) => return Ok(ping_addr),
+                    Err(e) => debug!(%addr, err = %e, "failed to migrate secure channel"),
+                }
                 let _ = this.delete_secure_channel(&prev).await;
                 let timeout = Some(util::MAX_CONNECT_TIME);
                 let (sec, rest) = this.connect(&addr, auth, timeout).await?;
@@ -10,22 +10,62 @@ use crate::{multiaddr_to_route, try_multiaddr_to_addr};
 use minicbor::Decoder;
 use ockam::compat::asynchronous::RwLock;
 use ockam::compat::tokio::time::timeout;
-use ockam::tcp::{InletOptions, OutletOptions};
-use ockam::{Address, Result};
+use ockam::tcp::{InletOptions, OutletOptions, UdpInletOptions, UdpOutletOptions};
+use ockam::{Address, Context, Result, Route};
 use ockam_abac::expr::{and, eq, ident, str};
 use ockam_abac::{Action, Env, PolicyAccessControl, PolicyStorage, Resource};
 use ockam_core::api::{Request, Response, ResponseBuilder};
-use ockam_core::{AccessControl, AllowAll};
+use ockam_core::{AccessControl, AllowAll, AsyncTryClone};
 use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::proto::{Project, Secure, Service};
 use ockam_multiaddr::{MultiAddr, Protocol};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use super::{NodeManager, NodeManagerWorker};
+use super::{Alias, NodeManager, NodeManagerWorker};
 
 const INLET_WORKER: &str = "inlet-worker";
 const OUTER_CHAN: &str = "outer-chan";
 
+/// Protocol of the service exposed by a portal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum PortalProtocol {
+    Tcp,
+    Udp,
+}
+
+impl PortalProtocol {
+    fn inlets(self, registry: &Registry) -> &BTreeMap<Alias, InletInfo> {
+        match self {
+            PortalProtocol::Tcp => &registry.inlets,
+            PortalProtocol::Udp => &registry.udp_inlets,
+        }
+    }
+
+    fn inlets_mut(self, registry: &mut Registry) -> &mut BTreeMap<Alias, InletInfo> {
+        match self {
+            PortalProtocol::Tcp => &mut registry.inlets,
+            PortalProtocol::Udp => &mut registry.udp_inlets,
+        }
+    }
+
+    fn outlets(self, registry: &Registry) -> &BTreeMap<Alias, OutletInfo> {
+        match self {
+            PortalProtocol::Tcp => &registry.outlets,
+            PortalProtocol::Udp => &registry.udp_outlets,
+        }
+    }
+
+    fn outlets_mut(self, registry: &mut Registry) -> &mut BTreeMap<Alias, OutletInfo> {
+        match self {
+            PortalProtocol::Tcp => &mut registry.outlets,
+            PortalProtocol::Udp => &mut registry.udp_outlets,
+        }
+    }
+}
+
 impl NodeManager {
     async fn access_control(
         &self,
@@ -61,6 +101,29 @@ impl NodeManager {
             Ok(Arc::new(AllowAll))
         }
     }
+
+    async fn create_inlet(
+        &self,
+        protocol: PortalProtocol,
+        bind: String,
+        outlet_route: Route,
+        access_control: Arc<dyn AccessControl>,
+        idle_timeout: Option<Duration>,
+    ) -> Result<(Address, SocketAddr)> {
+        match protocol {
+            PortalProtocol::Tcp => {
+                let options = InletOptions::new(bind, outlet_route, access_control);
+                self.tcp_transport.create_inlet_extended(options).await
+            }
+            PortalProtocol::Udp => {
+                let mut options = UdpInletOptions::new(bind, outlet_route, access_control);
+                if let Some(idle_timeout) = idle_timeout {
+                    options = options.with_idle_timeout(idle_timeout);
+                }
+                self.tcp_transport.create_udp_inlet_extended(options).await
+            }
+        }
+    }
 }
 
 impl NodeManagerWorker {
@@ -68,10 +131,11 @@ impl NodeManagerWorker {
         &self,
         req: &Request<'a>,
         registry: &'a Registry,
+        protocol: PortalProtocol,
     ) -> ResponseBuilder<InletList<'a>> {
         Response::ok(req.id()).body(InletList::new(
-            registry
-                .inlets
+            protocol
+                .inlets(registry)
                 .iter()
                 .map(|(alias, info)| {
                     InletStatus::new(
@@ -90,10 +154,11 @@ impl NodeManagerWorker {
         &self,
         req: &Request<'a>,
         registry: &'a Registry,
+        protocol: PortalProtocol,
     ) -> ResponseBuilder<OutletList<'a>> {
         Response::ok(req.id()).body(OutletList::new(
-            registry
-                .outlets
+            protocol
+                .outlets(registry)
                 .iter()
                 .map(|(alias, info)| {
                     OutletStatus::new(&info.tcp_addr, info.worker_addr.to_string(), alias, None)
@@ -104,8 +169,10 @@ impl NodeManagerWorker {
 
     pub(super) async fn create_inlet<'a>(
         &mut self,
+        ctx: &Context,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
+        protocol: PortalProtocol,
     ) -> Result<ResponseBuilder<InletStatus<'a>>> {
         let manager = self.node_manager.clone();
         let mut node_manager = self.node_manager.write().await;
@@ -118,12 +185,13 @@ impl NodeManagerWorker {
             .map(|a| a.to_string())
             .unwrap_or_else(random_alias);
 
-        info!("Handling request to create inlet portal");
+        info!("Handling request to create {:?} inlet portal", protocol);
 
         debug! {
             listen_addr = %req.listen_addr(),
             outlet_addr = %req.outlet_addr(),
             %alias,
+            ?protocol,
             "Creating inlet portal"
         }
 
@@ -186,21 +254,20 @@ impl NodeManagerWorker {
             .access_control(&resource, &actions::HANDLE_MESSAGE, project_id)
             .await?;
 
-        let options = InletOptions::new(
-            listen_addr.clone(),
-            outlet_route.clone(),
-            access_control.clone(),
-        );
-
         let res = node_manager
-            .tcp_transport
-            .create_inlet_extended(options)
+            .create_inlet(
+                protocol,
+                listen_addr.clone(),
+                outlet_route.clone(),
+                access_control.clone(),
+                req.idle_timeout(),
+            )
             .await;
 
         Ok(match res {
             Ok((worker_addr, _)) => {
                 // TODO: Use better way to store inlets?
-                node_manager.registry.inlets.insert(
+                protocol.inlets_mut(&mut node_manager.registry).insert(
                     alias.clone(),
                     InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route),
                 );
@@ -210,11 +277,14 @@ impl NodeManagerWorker {
                     s.data().put(OUTER_CHAN, outer);
                     let repl = replacer(
                         manager,
+                        Arc::new(ctx.async_try_clone().await?),
                         s.data(),
                         listen_addr.clone(),
                         req.outlet_addr().clone(),
                         req.authorized(),
                         access_control.clone(),
+                        protocol,
+                        req.idle_timeout(),
                     );
                     s.set_replacer(repl);
                     node_manager.sessions.lock().unwrap().add(s);
@@ -229,9 +299,9 @@ impl NodeManagerWorker {
                 ))
             }
             Err(e) => {
-                warn!(to = %req.outlet_addr(), err = %e, "failed to create tcp inlet");
+                warn!(to = %req.outlet_addr(), err = %e, ?protocol, "failed to create inlet");
                 // TODO: Use better way to store inlets?
-                node_manager.registry.inlets.insert(
+                protocol.inlets_mut(&mut node_manager.registry).insert(
                     alias.clone(),
                     InletInfo::new(&listen_addr, None, &outlet_route),
                 );
@@ -251,6 +321,7 @@ impl NodeManagerWorker {
         &mut self,
         req: &Request<'_>,
         dec: &mut Decoder<'_>,
+        protocol: PortalProtocol,
     ) -> Result<ResponseBuilder<OutletStatus<'a>>> {
         let mut node_manager = self.node_manager.write().await;
         let CreateOutlet {
@@ -258,6 +329,7 @@ impl NodeManagerWorker {
             worker_addr,
             alias,
             check_credential,
+            idle_timeout,
             ..
         } = dec.decode()?;
         let tcp_addr = tcp_addr.to_string();
@@ -267,7 +339,7 @@ impl NodeManagerWorker {
             .unwrap_or(resources::OUTLET);
         let alias = alias.map(|a| a.0.into()).unwrap_or_else(random_alias);
 
-        info!("Handling request to create outlet portal");
+        info!("Handling request to create {:?} outlet portal", protocol);
         let worker_addr = Address::from(worker_addr.as_ref());
 
         let check_credential = match check_credential {
@@ -284,17 +356,32 @@ impl NodeManagerWorker {
             .access_control(&resource, &actions::HANDLE_MESSAGE, project_id)
             .await?;
 
-        let options = OutletOptions::new(worker_addr.clone(), tcp_addr.clone(), access_control);
-
-        let res = node_manager
-            .tcp_transport
-            .create_outlet_extended(options)
-            .await;
+        let res = match protocol {
+            PortalProtocol::Tcp => {
+                let options =
+                    OutletOptions::new(worker_addr.clone(), tcp_addr.clone(), access_control);
+                node_manager
+                    .tcp_transport
+                    .create_outlet_extended(options)
+                    .await
+            }
+            PortalProtocol::Udp => {
+                let mut options =
+                    UdpOutletOptions::new(worker_addr.clone(), tcp_addr.clone(), access_control);
+                if let Some(idle_timeout) = idle_timeout {
+                    options = options.with_idle_timeout(idle_timeout);
+                }
+                node_manager
+                    .tcp_transport
+                    .create_udp_outlet_extended(options)
+                    .await
+            }
+        };
 
         Ok(match res {
             Ok(_) => {
                 // TODO: Use better way to store outlets?
-                node_manager.registry.outlets.insert(
+                protocol.outlets_mut(&mut node_manager.registry).insert(
                     alias.clone(),
                     OutletInfo::new(&tcp_addr, Some(&worker_addr)),
                 );
@@ -308,9 +395,8 @@ impl NodeManagerWorker {
             }
             Err(e) => {
                 // TODO: Use better way to store outlets?
-                node_manager
-                    .registry
-                    .outlets
+                protocol
+                    .outlets_mut(&mut node_manager.registry)
                     .insert(alias.clone(), OutletInfo::new(&tcp_addr, None));
 
                 Response::bad_request(req.id()).body(OutletStatus::new(
@@ -327,32 +413,51 @@ impl NodeManagerWorker {
 /// Create a session replacer.
 ///
 /// This returns a function that accepts the previous ping address (e.g.
-/// the secure channel worker address) and constructs the whole route
-/// again.
+/// the secure channel worker address) and moves the secure channels to new
+/// connections, or constructs the whole route again if that fails.
+#[allow(clippy::too_many_arguments)]
 fn replacer(
     manager: Arc<RwLock<NodeManager>>,
+    ctx: Arc<Context>,
     data: Data,
     bind: String,
     addr: MultiAddr,
     auth: Option<IdentityIdentifier>,
     access: Arc<dyn AccessControl>,
+    protocol: PortalProtocol,
+    idle_timeout: Option<Duration>,
 ) -> Replacer {
     Box::new(move |prev| {
         let addr = addr.clone();
         let auth = auth.clone();
         let bind = bind.clone();
         let manager = manager.clone();
+        let ctx = ctx.clone();
         let access = access.clone();
         let data = data.clone();
         Box::pin(async move {
-            debug!(%prev, %addr, "creating new tcp inlet");
+            debug!(%prev, %addr, ?protocol, "creating new inlet");
             // The future that recreates the inlet:
             let f = async {
+                let ping_addr = prev.clone();
                 let prev = try_multiaddr_to_addr(&prev)?;
                 let mut this = manager.write().await;
                 let timeout = Some(util::MAX_CONNECT_TIME);
 
-                // First the previous secure channel is deleted, and -- if secure
+                // If the other side still knows the secure channels they are
+                // moved to new connections, and the inlet is kept as it is:
+
+                let mut channels = Vec::new();
+                if let Some(a) = data.get::<MultiAddr>(OUTER_CHAN) {
+                    channels.push(try_multiaddr_to_addr(&a)?);
+                }
+                channels.push(prev.clone());
+                match this.migrate_secure_channels(&ctx, &channels).await {
+                    Ok(()) => return Ok(ping_addr),
+                    Err(e) => debug!(%addr, err = %e, "failed to migrate secure channels"),
+                }
+
+                // Otherwise the previous secure channel is deleted, and -- if secure
                 // channels were nested -- the outer one as well:
 
                 let _ = this.delete_secure_channel(&prev).await;
@@ -390,8 +495,10 @@ fn replacer(
                 }
 
                 // Finally attempt to create a new inlet using the new route:
-                let opts = InletOptions::new(bind, r, access);
-                let wa = this.tcp_transport.create_inlet_extended(opts).await?.0;
+                let wa = this
+                    .create_inlet(protocol, bind, r, access, idle_timeout)
+                    .await?
+                    .0;
                 data.put(INLET_WORKER, wa);
 
                 Ok(without_outlet_address(rest))
@@ -400,11 +507,11 @@ fn replacer(
             // The above future is given some limited time to succeed.
             match timeout(util::MAX_RECOVERY_TIME, f).await {
                 Err(_) => {
-                    warn!(%addr, "timeout creating new tcp inlet");
+                    warn!(%addr, ?protocol, "timeout creating new inlet");
                     Err(ApiError::generic("timeout"))
                 }
                 Ok(Err(e)) => {
-                    warn!(%addr, err = %e, "error creating new tcp inlet");
+                    warn!(%addr, err = %e, ?protocol, "error creating new inlet");
                     Err(e)
                 }
                 Ok(Ok(a)) => Ok(a),
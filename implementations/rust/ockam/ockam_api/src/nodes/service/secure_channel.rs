@@ -5,17 +5,28 @@ use crate::error::ApiError;
 use crate::nodes::models::secure_channel::{
     CreateSecureChannelListenerRequest, CreateSecureChannelRequest, CreateSecureChannelResponse,
     CredentialExchangeMode, DeleteSecureChannelRequest, DeleteSecureChannelResponse,
-    ShowSecureChannelRequest, ShowSecureChannelResponse,
+    ForgetPinnedIdentityRequest, PinnedIdentity, PinnedIdentityList,
+    ShowSecureChannelListenerRequest, ShowSecureChannelListenerResponse, ShowSecureChannelRequest,
+    ShowSecureChannelResponse,
 };
-use crate::nodes::registry::Registry;
+use crate::nodes::registry::{Registry, SecureChannelListenerInfo};
 use crate::nodes::NodeManager;
-use crate::DefaultAddress;
+use crate::session::util;
+use crate::{multiaddr_to_route, route_to_multiaddr, DefaultAddress};
 use minicbor::Decoder;
+use ockam::compat::asynchronous::RwLock;
 use ockam::identity::TrustEveryonePolicy;
-use ockam::{Address, Result, Route};
+use ockam::{Address, Context, Result, Route, Routed, Worker};
+use ockam_abac::{AbacTrustPolicy, Expr};
 use ockam_core::api::{Request, Response, ResponseBuilder};
-use ockam_core::{route, AsyncTryClone};
-use ockam_identity::{Identity, IdentityIdentifier, TrustMultiIdentifiersPolicy};
+use ockam_core::compat::sync::Arc;
+use ockam_core::vault::{KeyId, PublicKey, SecretType};
+use ockam_core::{route, AsyncTryClone, LOCAL};
+use ockam_identity::{
+    Identity, IdentityIdentifier, ListenerLimits, PinnedIdentitiesStorageUtils,
+    SecureChannelClosed, SecureChannelCounters, SecureChannelOptions, TrustMultiIdentifiersPolicy,
+    TrustOnFirstUsePolicy, TrustPolicy,
+};
 use ockam_multiaddr::MultiAddr;
 use ockam_vault::Vault;
 
@@ -35,11 +46,15 @@ impl NodeManager {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn create_secure_channel_internal(
         &mut self,
         identity: &Identity<Vault>,
         sc_route: Route,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        trust_on_first_use: bool,
+        hybrid_kem: bool,
+        ik_remote_static_key: Option<PublicKey>,
         timeout: Option<Duration>,
     ) -> Result<Address> {
         // If channel was already created, do nothing.
@@ -52,49 +67,85 @@ impl NodeManager {
 
         debug!(%sc_route, "Creating secure channel");
         let timeout = timeout.unwrap_or(Duration::from_secs(120));
-        let sc_addr = match authorized_identifiers.clone() {
-            Some(ids) => {
-                identity
-                    .create_secure_channel_extended(
-                        sc_route.clone(),
-                        TrustMultiIdentifiersPolicy::new(ids),
-                        &self.authenticated_storage,
-                        timeout,
-                    )
-                    .await
-            }
-            None => {
-                identity
-                    .create_secure_channel_extended(
-                        sc_route.clone(),
-                        TrustEveryonePolicy,
-                        &self.authenticated_storage,
-                        timeout,
-                    )
-                    .await
+        let tofu_policy = trust_on_first_use.then(|| {
+            // Pin to the multiaddr when possible, which is what users get to see
+            let name = route_to_multiaddr(&sc_route)
+                .map(|ma| ma.to_string())
+                .unwrap_or_else(|| sc_route.to_string());
+            TrustOnFirstUsePolicy::new(name, self.authenticated_storage.clone())
+        });
+        let trust_policy: Box<dyn TrustPolicy> = match (authorized_identifiers.clone(), tofu_policy)
+        {
+            (Some(ids), Some(tofu)) => Box::new(TrustMultiIdentifiersPolicy::new(ids).and(tofu)),
+            (Some(ids), None) => Box::new(TrustMultiIdentifiersPolicy::new(ids)),
+            (None, Some(tofu)) => Box::new(tofu),
+            (None, None) => Box::new(TrustEveryonePolicy),
+        };
+        let counters = SecureChannelCounters::new();
+        let mut options = SecureChannelOptions::new()
+            .with_close_notification(SecureChannelWatcher::address())
+            .with_counters(counters.clone());
+        if hybrid_kem {
+            options = options.with_hybrid_kem();
+        }
+        // The IK static key is only trusted for the identity of the listener
+        if let Some(public_key) = ik_remote_static_key {
+            match authorized_identifiers.as_deref() {
+                Some([identifier]) => {
+                    options = options.with_ik_remote_static_key(identifier.clone(), public_key)
+                }
+                _ => {
+                    return Err(ApiError::generic(
+                        "An IK static key needs exactly one authorized identifier",
+                    ))
+                }
             }
-        }?;
+        }
+        let sc_addr = identity
+            .create_secure_channel_extended(
+                sc_route.clone(),
+                trust_policy,
+                &self.authenticated_storage,
+                timeout,
+                options,
+            )
+            .await?;
 
         debug!(%sc_route, %sc_addr, "Created secure channel");
 
-        self.registry
-            .secure_channels
-            .insert(sc_addr.clone(), sc_route, authorized_identifiers);
+        self.registry.secure_channels.insert(
+            sc_addr.clone(),
+            sc_route,
+            authorized_identifiers,
+            counters,
+        );
 
         Ok(sc_addr)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn create_secure_channel_impl(
         &mut self,
         sc_route: Route,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
         credential_exchange_mode: CredentialExchangeMode,
+        trust_on_first_use: bool,
+        hybrid_kem: bool,
+        ik_remote_static_key: Option<PublicKey>,
         timeout: Option<Duration>,
     ) -> Result<Address> {
         let identity = self.identity()?.async_try_clone().await?;
 
         let sc_addr = self
-            .create_secure_channel_internal(&identity, sc_route, authorized_identifiers, timeout)
+            .create_secure_channel_internal(
+                &identity,
+                sc_route,
+                authorized_identifiers,
+                trust_on_first_use,
+                hybrid_kem,
+                ik_remote_static_key,
+                timeout,
+            )
             .await?;
 
         let actual_exchange_mode = if self.enable_credential_checks {
@@ -130,6 +181,10 @@ impl NodeManager {
             }
         }
 
+        if let Some(info) = self.registry.secure_channels.get_by_addr_mut(&sc_addr) {
+            info.set_credential_exchange_mode(actual_exchange_mode);
+        }
+
         // Return secure channel address
         Ok(sc_addr)
     }
@@ -138,38 +193,53 @@ impl NodeManager {
         &mut self,
         addr: Address,
         authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+        trust_policy: Option<Expr>,
+        limits: ListenerLimits,
+        ik_static_key: Option<KeyId>,
     ) -> Result<()> {
         info!(
             "Handling request to create a new secure channel listener: {}",
             addr
         );
 
+        let abac_policy = trust_policy.map(|expr| {
+            let authorities = self
+                .authorities()
+                .map(|a| a.public_identities())
+                .unwrap_or_default();
+            AbacTrustPolicy::new(
+                expr,
+                authorities,
+                self.vault.clone(),
+                self.authenticated_storage.clone(),
+            )
+        });
+
+        let trust_policy: Box<dyn TrustPolicy> = match (authorized_identifiers, abac_policy) {
+            (Some(ids), Some(abac)) => Box::new(TrustMultiIdentifiersPolicy::new(ids).and(abac)),
+            (Some(ids), None) => Box::new(TrustMultiIdentifiersPolicy::new(ids)),
+            (None, Some(abac)) => Box::new(abac),
+            (None, None) => Box::new(TrustEveryonePolicy),
+        };
+
         let identity = self.identity()?;
 
-        match authorized_identifiers {
-            Some(ids) => {
-                identity
-                    .create_secure_channel_listener(
-                        addr.clone(),
-                        TrustMultiIdentifiersPolicy::new(ids),
-                        &self.authenticated_storage,
-                    )
-                    .await
-            }
-            None => {
-                identity
-                    .create_secure_channel_listener(
-                        addr.clone(),
-                        TrustEveryonePolicy,
-                        &self.authenticated_storage,
-                    )
-                    .await
-            }
-        }?;
+        let mut options = SecureChannelOptions::new().with_listener_limits(limits.clone());
+        if let Some(key_id) = ik_static_key {
+            options = options.with_ik_static_key(key_id);
+        }
+        identity
+            .create_secure_channel_listener_extended(
+                addr.clone(),
+                trust_policy,
+                &self.authenticated_storage,
+                options,
+            )
+            .await?;
 
         self.registry
             .secure_channel_listeners
-            .insert(addr, Default::default());
+            .insert(addr, SecureChannelListenerInfo::new(limits));
 
         Ok(())
     }
@@ -181,6 +251,42 @@ impl NodeManager {
         self.registry.secure_channels.remove_by_addr(addr);
         Ok(())
     }
+
+    /// Move secure channels to new connections along the routes they were
+    /// created with, keeping their addresses. Nested channels are given from
+    /// the outermost one. The last one is checked with a message to the echo
+    /// service of the other side, which fails if it does not know the channel
+    /// anymore.
+    pub(super) async fn migrate_secure_channels(
+        &self,
+        ctx: &Context,
+        channels: &[Address],
+    ) -> Result<()> {
+        let identity = self.identity()?;
+        for addr in channels {
+            debug!(%addr, "migrating secure channel");
+            let info = self
+                .registry
+                .secure_channels
+                .get_by_addr(addr)
+                .ok_or_else(|| ApiError::message(format!("unknown secure channel: {addr}")))?;
+            // The route leads to the listener of the other side
+            let mut route = info.route().clone();
+            route.modify().pop_back();
+            identity.migrate_secure_channel(addr, route).await?;
+        }
+        if let Some(addr) = channels.last() {
+            let timeout = util::MAX_CONNECT_TIME.as_secs();
+            let _: Vec<u8> = ctx
+                .send_and_receive_with_timeout(
+                    route![addr.clone(), DefaultAddress::ECHO_SERVICE],
+                    Vec::<u8>::new(),
+                    timeout,
+                )
+                .await?;
+        }
+        Ok(())
+    }
 }
 
 impl NodeManagerWorker {
@@ -224,6 +330,9 @@ impl NodeManagerWorker {
             authorized_identifiers,
             credential_exchange_mode,
             timeout,
+            trust_on_first_use,
+            hybrid_kem,
+            ik_remote_static_key,
             ..
         } = dec.decode()?;
 
@@ -246,11 +355,23 @@ impl NodeManagerWorker {
         let route = crate::multiaddr_to_route(&addr)
             .ok_or_else(|| ApiError::generic("Invalid Multiaddr"))?;
 
+        let ik_remote_static_key = match ik_remote_static_key {
+            Some(key) => {
+                let key = hex::decode(key.as_bytes())
+                    .map_err(|_| ApiError::generic("Invalid IK static key"))?;
+                Some(PublicKey::new(key, SecretType::X25519))
+            }
+            None => None,
+        };
+
         let channel = node_manager
             .create_secure_channel_impl(
                 route,
                 authorized_identifiers,
                 credential_exchange_mode,
+                trust_on_first_use.unwrap_or(false),
+                hybrid_kem.unwrap_or(false),
+                ik_remote_static_key,
                 timeout,
             )
             .await?;
@@ -308,11 +429,15 @@ impl NodeManagerWorker {
         dec: &mut Decoder<'_>,
     ) -> Result<ResponseBuilder<()>> {
         let mut node_manager = self.node_manager.write().await;
+        let request: CreateSecureChannelListenerRequest = dec.decode()?;
+        let limits = request.limits();
         let CreateSecureChannelListenerRequest {
             addr,
             authorized_identifiers,
+            trust_policy,
+            ik_static_key,
             ..
-        } = dec.decode()?;
+        } = request;
 
         let authorized_identifiers = match authorized_identifiers {
             Some(ids) => {
@@ -332,11 +457,121 @@ impl NodeManagerWorker {
         }
 
         node_manager
-            .create_secure_channel_listener_impl(addr, authorized_identifiers)
+            .create_secure_channel_listener_impl(
+                addr,
+                authorized_identifiers,
+                trust_policy,
+                limits,
+                ik_static_key.map(|key_id| key_id.to_string()),
+            )
             .await?;
 
         let response = Response::ok(req.id());
 
         Ok(response)
     }
+
+    pub(super) async fn show_secure_channel_listener<'a>(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<ShowSecureChannelListenerResponse<'a>>> {
+        let node_manager = self.node_manager.read().await;
+        let body: ShowSecureChannelListenerRequest = dec.decode()?;
+        let addr = Address::from(body.addr.as_ref());
+
+        debug!(%addr, "On show secure channel listener");
+
+        let info = node_manager.registry.secure_channel_listeners.get(&addr);
+
+        Ok(Response::ok(req.id()).body(ShowSecureChannelListenerResponse::new(&addr, info)))
+    }
+
+    pub(super) async fn list_pinned_identities<'a>(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<ResponseBuilder<PinnedIdentityList<'a>>> {
+        let node_manager = self.node_manager.read().await;
+        let pins = PinnedIdentitiesStorageUtils::list(&node_manager.authenticated_storage).await?;
+        let list = pins
+            .iter()
+            .map(|(name, pin)| {
+                PinnedIdentity::new(
+                    name.clone(),
+                    pin.identifier(),
+                    pin.pinned_at().map(u64::from),
+                )
+            })
+            .collect();
+        Ok(Response::ok(req.id()).body(PinnedIdentityList::new(list)))
+    }
+
+    pub(super) async fn forget_pinned_identity(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<()>> {
+        let body: ForgetPinnedIdentityRequest = dec.decode()?;
+        let node_manager = self.node_manager.read().await;
+        info!(name = %body.name, "Handling request to forget pinned identity");
+        let forgotten =
+            PinnedIdentitiesStorageUtils::forget(&body.name, &node_manager.authenticated_storage)
+                .await?;
+        if forgotten {
+            Ok(Response::ok(req.id()))
+        } else {
+            Ok(Response::not_found(req.id()))
+        }
+    }
+}
+
+/// Forgets the secure channels of the registry once they are closed, by
+/// either side, and lets the sessions going through them find a replacement
+/// right away instead of waiting for their pings to fail.
+pub(super) struct SecureChannelWatcher {
+    node_manager: Arc<RwLock<NodeManager>>,
+}
+
+impl SecureChannelWatcher {
+    const NAME: &'static str = "ockam.secure_channel.watcher";
+
+    pub(super) fn new(node_manager: Arc<RwLock<NodeManager>>) -> Self {
+        Self { node_manager }
+    }
+
+    pub(super) fn address() -> Address {
+        Address::new(LOCAL, Self::NAME)
+    }
+}
+
+#[ockam::worker]
+impl Worker for SecureChannelWatcher {
+    type Message = SecureChannelClosed;
+    type Context = Context;
+
+    async fn handle_message(
+        &mut self,
+        _ctx: &mut Context,
+        msg: Routed<SecureChannelClosed>,
+    ) -> Result<()> {
+        let closed = msg.body();
+        let addr = closed.encryptor_address();
+        info!(%addr, reason = ?closed.reason(), "Secure channel closed");
+
+        let mut node_manager = self.node_manager.write().await;
+        node_manager.registry.secure_channels.remove_by_addr(addr);
+
+        let mut sessions = node_manager.sessions.lock().unwrap();
+        for (key, session) in sessions.iter_mut() {
+            let uses_channel = multiaddr_to_route(session.ping_address())
+                .map(|r| r.iter().any(|a| a == addr))
+                .unwrap_or(false);
+            if uses_channel {
+                debug!(%key, %addr, "Session lost its secure channel");
+                session.mark_unresponsive();
+            }
+        }
+
+        Ok(())
+    }
 }
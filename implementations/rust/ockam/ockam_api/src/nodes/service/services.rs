@@ -74,6 +74,10 @@ impl NodeManager {
 
         let authorities = self.authorities()?;
 
+        identity
+            .set_schema_registry(authorities.schema_registry())
+            .await;
+
         identity
             .start_credentials_exchange_worker(
                 authorities.public_identities(),
@@ -330,7 +334,11 @@ impl NodeManagerWorker {
         }
 
         let vault = node_manager.vault.async_try_clone().await?;
-        let vs = crate::verifier::Verifier::new(vault);
+        // Requests name the authorities whose credentials are verified
+        let vs = crate::verifier::Verifier::new(vault).with_schema_registry(
+            ockam_identity::credential::SchemaRegistry::new()
+                .with_schema(crate::authenticator::project_member_schema()),
+        );
         ctx.start_worker(addr.clone(), vs).await?;
 
         node_manager
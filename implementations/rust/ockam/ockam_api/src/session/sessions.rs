@@ -141,6 +141,14 @@ impl Session {
     pub fn clear_pings(&mut self) {
         self.pings.clear()
     }
+
+    /// Consider all pings as lost, e.g. because the route to the session
+    /// is known to be gone, so that the session gets replaced.
+    pub fn mark_unresponsive(&mut self) {
+        while self.pings.len() < super::MAX_FAILURES {
+            self.pings.push(Ping::new())
+        }
+    }
 }
 
 impl Data {
@@ -5,9 +5,10 @@ use core::convert::Infallible;
 use minicbor::encode::Write;
 use minicbor::{Decoder, Encode};
 use models::*;
-use ockam_core::api::{Error, Id, Method, Request, Response, Status};
+use ockam_core::api::{forbidden, Error, Id, Method, Request, Response, Status};
 use ockam_core::vault::{
-    AsymmetricVault, Hasher, KeyId, SecretVault, Signature, Signer, SymmetricVault, Verifier,
+    AsymmetricVault, Hasher, KeyId, SecretUsage, SecretVault, Signature, Signer, SymmetricVault,
+    Verifier,
 };
 use ockam_core::CowStr;
 use ockam_core::{Result, Routed, Worker};
@@ -45,6 +46,21 @@ impl VaultService {
         Ok(())
     }
 
+    fn response_for_forbidden_usage<W>(req: &Request, enc: W) -> Result<()>
+    where
+        W: Write<Error = Infallible>,
+    {
+        forbidden(req, "secret may not be used for this operation").encode(enc)?;
+
+        Ok(())
+    }
+
+    /// Check the usage of a secret before using it on behalf of a remote caller
+    async fn is_allowed(&self, key_id: &KeyId, usage: SecretUsage) -> Result<bool> {
+        let attributes = self.vault.secret_attributes_get(key_id).await?;
+        Ok(attributes.usage().contains(usage))
+    }
+
     fn ok_response<W, B>(req: &Request, body: Option<B>, enc: W) -> Result<()>
     where
         W: Write<Error = Infallible>,
@@ -120,6 +136,9 @@ impl VaultService {
                             Self::ok_response(req, Some(body), enc)
                         }
                         GetSecretRequestOperation::GetSecretBytes => {
+                            if !self.is_allowed(&key_id, SecretUsage::EXPORTABLE).await? {
+                                return Self::response_for_forbidden_usage(req, enc);
+                            }
                             let resp = self.vault.secret_export(&key_id).await?;
                             let body = ExportSecretResponse::new(resp);
 
@@ -166,6 +185,10 @@ impl VaultService {
                     let (secret_key_id, public_key) = args.into_parts();
                     let secret_key_id: KeyId = secret_key_id.into_owned();
 
+                    if !self.is_allowed(&secret_key_id, SecretUsage::ECDH).await? {
+                        return Self::response_for_forbidden_usage(req, enc);
+                    }
+
                     let dh = self
                         .vault
                         .ec_diffie_hellman(&secret_key_id, &public_key)
@@ -208,6 +231,12 @@ impl VaultService {
                     let salt: KeyId = args.salt().to_string();
                     let ikm = args.ikm().map(|i| i.to_string());
 
+                    for key_id in core::iter::once(&salt).chain(ikm.as_ref()) {
+                        if !self.is_allowed(key_id, SecretUsage::DERIVE).await? {
+                            return Self::response_for_forbidden_usage(req, enc);
+                        }
+                    }
+
                     let output = self
                         .vault
                         .hkdf_sha256(
@@ -235,6 +264,10 @@ impl VaultService {
 
                     let key_id: KeyId = args.key_id().to_string();
 
+                    if !self.is_allowed(&key_id, SecretUsage::SIGN).await? {
+                        return Self::response_for_forbidden_usage(req, enc);
+                    }
+
                     let output = self.vault.sign(&key_id, args.data()).await?;
 
                     Self::ok_response(req, Some(SignResponse::new(output.as_ref())), enc)
@@ -265,6 +298,10 @@ impl VaultService {
 
                     let key_id: KeyId = args.key_id().to_string();
 
+                    if !self.is_allowed(&key_id, SecretUsage::ENCRYPT).await? {
+                        return Self::response_for_forbidden_usage(req, enc);
+                    }
+
                     let output = self
                         .vault
                         .aead_aes_gcm_encrypt(&key_id, args.plaintext(), args.nonce(), args.aad())
@@ -281,6 +318,10 @@ impl VaultService {
 
                     let key_id: KeyId = args.key_id().to_string();
 
+                    if !self.is_allowed(&key_id, SecretUsage::ENCRYPT).await? {
+                        return Self::response_for_forbidden_usage(req, enc);
+                    }
+
                     let output = self
                         .vault
                         .aead_aes_gcm_decrypt(&key_id, args.ciphertext(), args.nonce(), args.aad())
@@ -5,7 +5,9 @@ use minicbor::Decoder;
 use ockam_core::api::{self, Id, ResponseBuilder};
 use ockam_core::api::{Error, Method, Request, Response};
 use ockam_core::{self, Result, Routed, Worker};
-use ockam_identity::credential::{Credential, CredentialData, Verified};
+use ockam_identity::credential::{
+    Credential, CredentialData, CredentialPresentation, SchemaRegistry, Verified,
+};
 use ockam_identity::{IdentityVault, PublicIdentity};
 use ockam_node::Context;
 use tracing::trace;
@@ -15,6 +17,7 @@ use self::types::{VerifyRequest, VerifyResponse};
 #[derive(Debug)]
 pub struct Verifier<V> {
     vault: V,
+    schemas: SchemaRegistry,
 }
 
 #[ockam_core::worker]
@@ -36,7 +39,16 @@ where
     V: IdentityVault,
 {
     pub fn new(vault: V) -> Self {
-        Self { vault }
+        Self {
+            vault,
+            schemas: SchemaRegistry::new(),
+        }
+    }
+
+    /// Check credential attributes against the given schemas.
+    pub fn with_schema_registry(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
     }
 
     async fn on_request(&mut self, data: &[u8]) -> Result<Vec<u8>> {
@@ -77,6 +89,22 @@ where
                             .to_vec()?,
                     }
                 }
+                ["verify", "presentation"] => {
+                    let vr: VerifyRequest = dec.decode()?;
+                    let pr: CredentialPresentation = minicbor::decode(vr.credential())?;
+                    match self.verify_presentation(req.id(), &vr, &pr).await {
+                        Ok(Either::Left(err)) => err.to_vec()?,
+                        Ok(Either::Right(dat)) => {
+                            let exp = dat.expires_at();
+                            Response::ok(req.id())
+                                .body(VerifyResponse::new(dat.into_attributes(), exp))
+                                .to_vec()?
+                        }
+                        Err(err) => Response::internal_error(req.id())
+                            .body(err.to_string())
+                            .to_vec()?,
+                    }
+                }
                 _ => api::unknown_path(&req).to_vec()?,
             },
             _ => api::invalid_method(&req).to_vec()?,
@@ -101,7 +129,7 @@ where
         };
 
         let data = match ident
-            .verify_credential(cre, req.subject(), &self.vault)
+            .verify_credential_with_schemas(cre, req.subject(), &self.vault, &self.schemas)
             .await
         {
             Ok(data) => data,
@@ -114,4 +142,36 @@ where
 
         Ok(Either::Right(data))
     }
+
+    async fn verify_presentation<'a>(
+        &self,
+        id: Id,
+        req: &'a VerifyRequest<'a>,
+        pre: &'a CredentialPresentation<'a>,
+    ) -> Result<Either<ResponseBuilder<Error<'_>>, CredentialData<'a, Verified>>> {
+        let data = CredentialData::try_from(pre.credential())?;
+
+        let ident = if let Some(ident) = req.authority(data.unverfied_issuer()) {
+            PublicIdentity::import(ident, &self.vault).await?
+        } else {
+            let err = Error::new("/verify/presentation").with_message("unauthorised issuer");
+            return Ok(Either::Left(Response::unauthorized(id).body(err)));
+        };
+
+        let data = match ident
+            .verify_presentation_with_schemas(pre, req.subject(), &self.vault, &self.schemas)
+            .await
+        {
+            Ok(data) => data,
+            Err(err) => {
+                let err = Error::new("/verify/presentation").with_message(format!(
+                    "error verifying a credential presentation: {}",
+                    err
+                ));
+                return Ok(Either::Left(Response::forbidden(id).body(err)));
+            }
+        };
+
+        Ok(Either::Right(data))
+    }
 }
@@ -8,6 +8,7 @@ use ockam::vault::Vault;
 use ockam_api::authenticator::direct;
 use ockam_api::authenticator::direct::types::Enroller;
 use ockam_core::Result;
+use ockam_identity::credential::{AttributeSchema, AttributeType, SchemaRegistry};
 use ockam_identity::{IdentityIdentifier, PublicIdentity, TrustEveryonePolicy};
 use ockam_node::Context;
 use tempfile::NamedTempFile;
@@ -140,3 +141,56 @@ async fn update_member_format(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+#[ockam_macros::test]
+async fn non_conforming_member_attributes(ctx: &mut Context) -> Result<()> {
+    // Create an enroller identity:
+    let enroller = Identity::create(ctx, &Vault::create()).await?;
+
+    let mut tmpf = NamedTempFile::new().unwrap();
+    let enrollers = [(enroller.identifier().clone(), Enroller::default())];
+    serde_json::to_writer(&mut tmpf, &HashMap::from(enrollers)).unwrap();
+
+    // Create the authority, only accepting members with role "member":
+    let schemas = SchemaRegistry::new().with_schema(
+        direct::project_member_schema().with_attribute(
+            AttributeSchema::optional(direct::ROLE, AttributeType::String)
+                .with_allowed_values(["member"]),
+        ),
+    );
+    let a = Identity::create(ctx, &Vault::create()).await?;
+    a.create_secure_channel_listener("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let auth = direct::Server::new(
+        b"project42".to_vec(),
+        InMemoryStorage::new(),
+        tmpf.path(),
+        a,
+    )
+    .with_schema_registry(schemas);
+    ctx.start_worker("auth", auth).await?;
+
+    let member = Identity::create(ctx, &Vault::create()).await?;
+
+    let e2a = enroller
+        .create_secure_channel("api", TrustEveryonePolicy, &InMemoryStorage::new())
+        .await?;
+    let mut c = direct::Client::new(route![e2a, "auth"], ctx).await?;
+
+    let admin_attrs = HashMap::from([("role", "admin")]);
+    assert!(c
+        .add_member(member.identifier().clone(), admin_attrs)
+        .await
+        .is_err());
+
+    assert!(c
+        .create_token(HashMap::from([("role", "admin")]))
+        .await
+        .is_err());
+
+    let member_attrs = HashMap::from([("role", "member")]);
+    c.add_member(member.identifier().clone(), member_attrs)
+        .await?;
+
+    ctx.stop().await
+}
@@ -0,0 +1,78 @@
+use crate::SecureChannelVault;
+use ockam_core::compat::vec::Vec;
+use ockam_core::vault::{
+    KeyId, SecretType, AES256_SECRET_LENGTH_U32, CHACHA20POLY1305_SECRET_LENGTH_U32,
+};
+use ockam_core::Result;
+
+/// AEAD used by a channel, given by the type of the keys produced by the
+/// key exchange
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Cipher {
+    AesGcm,
+    ChaChaPoly,
+}
+
+impl Cipher {
+    pub(crate) async fn of_key<V: SecureChannelVault>(vault: &V, key: &KeyId) -> Result<Self> {
+        let cipher = match vault.secret_attributes_get(key).await?.stype() {
+            SecretType::ChaCha20Poly1305 => Self::ChaChaPoly,
+            _ => Self::AesGcm,
+        };
+
+        Ok(cipher)
+    }
+
+    /// Type and length of the keys of this cipher
+    pub(crate) fn key_type_and_length(&self) -> (SecretType, u32) {
+        match self {
+            Self::AesGcm => (SecretType::Aes, AES256_SECRET_LENGTH_U32),
+            Self::ChaChaPoly => (
+                SecretType::ChaCha20Poly1305,
+                CHACHA20POLY1305_SECRET_LENGTH_U32,
+            ),
+        }
+    }
+
+    pub(crate) async fn encrypt<V: SecureChannelVault>(
+        &self,
+        vault: &V,
+        key: &KeyId,
+        plain_text: &[u8],
+        nonce: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            Self::AesGcm => {
+                vault
+                    .aead_aes_gcm_encrypt(key, plain_text, nonce, &[])
+                    .await
+            }
+            Self::ChaChaPoly => {
+                vault
+                    .aead_chacha20_poly1305_encrypt(key, plain_text, nonce, &[])
+                    .await
+            }
+        }
+    }
+
+    pub(crate) async fn decrypt<V: SecureChannelVault>(
+        &self,
+        vault: &V,
+        key: &KeyId,
+        cipher_text: &[u8],
+        nonce: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            Self::AesGcm => {
+                vault
+                    .aead_aes_gcm_decrypt(key, cipher_text, nonce, &[])
+                    .await
+            }
+            Self::ChaChaPoly => {
+                vault
+                    .aead_chacha20_poly1305_decrypt(key, cipher_text, nonce, &[])
+                    .await
+            }
+        }
+    }
+}
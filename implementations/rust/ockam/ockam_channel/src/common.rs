@@ -1,11 +1,13 @@
+use crate::cipher::Cipher;
 use ockam_core::vault::KeyId;
-use ockam_core::{Address, Message};
+use ockam_core::{Address, Message, Route};
 use serde::{Deserialize, Serialize};
 
 /// Key Exchange completed message
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Message)]
 pub struct KeyExchangeCompleted {
     address: Address,
+    control_address: Address,
     auth_hash: [u8; 32],
 }
 
@@ -14,17 +16,36 @@ impl KeyExchangeCompleted {
     pub fn address(&self) -> &Address {
         &self.address
     }
+    /// Address to stop or migrate the channel, see [`crate::SecureChannel::stop`]
+    pub fn control_address(&self) -> &Address {
+        &self.control_address
+    }
     /// Authentication hash
     pub fn auth_hash(&self) -> [u8; 32] {
         self.auth_hash
     }
     /// Constructor
-    pub fn new(address: Address, auth_hash: [u8; 32]) -> Self {
-        Self { address, auth_hash }
+    pub fn new(address: Address, control_address: Address, auth_hash: [u8; 32]) -> Self {
+        Self {
+            address,
+            control_address,
+            auth_hash,
+        }
     }
 }
 
+/// Messages sent to the control address of an encryptor rather than through the channel.
+#[derive(Serialize, Deserialize, Message)]
+pub(crate) enum EncryptorControlMessage {
+    /// Stop both workers of the channel
+    Stop,
+    /// Send messages over a route to the other side's node, see
+    /// [`crate::SecureChannel::migrate`]
+    Migrate(Route),
+}
+
 pub(crate) struct ChannelKeys {
+    pub(crate) cipher: Cipher,
     pub(crate) key: KeyId,
     pub(crate) nonce: u64,
 }
@@ -18,6 +18,10 @@ pub enum SecureChannelError {
     InvalidHubResponse,
     /// Invalid LocalInfo type
     InvalidLocalInfoType,
+    /// A message with the same nonce was already received.
+    DuplicateNonce,
+    /// Nonce is too old to be checked against replays.
+    NonceOutsideWindow,
 }
 
 impl From<SecureChannelError> for Error {
@@ -25,9 +29,8 @@ impl From<SecureChannelError> for Error {
         use SecureChannelError::*;
         let kind = match e {
             KeyExchange | KeyExchangeNotComplete => Kind::Protocol,
-            InvalidInternalState | InvalidNonce | InvalidHubResponse | InvalidLocalInfoType => {
-                Kind::Invalid
-            }
+            InvalidInternalState | InvalidNonce | InvalidHubResponse | InvalidLocalInfoType
+            | DuplicateNonce | NonceOutsideWindow => Kind::Invalid,
         };
 
         Self::new(Origin::Channel, kind, e)
@@ -44,6 +47,8 @@ impl core::fmt::Display for SecureChannelError {
             Self::KeyExchangeNotComplete => "key exchange process did not complete.".fmt(f),
             Self::InvalidHubResponse => "invalid response received from the Hub.".fmt(f),
             Self::InvalidLocalInfoType => "invalid LocalInfo type".fmt(f),
+            Self::DuplicateNonce => "a message with the same nonce was already received.".fmt(f),
+            Self::NonceOutsideWindow => "nonce is outside of the replay window.".fmt(f),
         }
     }
 }
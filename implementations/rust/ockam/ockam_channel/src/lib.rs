@@ -22,18 +22,24 @@ extern crate core;
 #[macro_use]
 extern crate alloc;
 
+mod cipher;
 mod common;
 mod error;
 mod local_info;
+mod rekey;
+mod replay;
 mod secure_channel;
 mod secure_channel_decryptor;
 mod secure_channel_encryptor;
 mod secure_channel_listener;
+pub mod test_support;
 mod traits;
 
 pub use common::*;
 pub use error::*;
 pub use local_info::*;
+pub use rekey::RekeyPolicy;
+pub use replay::{ReplayCounters, ReplayStats, REPLAY_WINDOW_SIZE};
 pub use secure_channel::*;
 pub use secure_channel_decryptor::*;
 pub(crate) use secure_channel_encryptor::*;
@@ -42,11 +48,14 @@ pub use traits::*;
 
 #[cfg(test)]
 mod tests {
-    use crate::SecureChannel;
+    use crate::test_support::Duplicator;
+    use crate::{RekeyPolicy, ReplayCounters, SecureChannel, SecureChannelListener};
+    use core::sync::atomic::{AtomicBool, Ordering};
     use ockam_core::compat::string::{String, ToString};
-    use ockam_core::{AsyncTryClone, Result, Route};
+    use ockam_core::compat::sync::Arc;
+    use ockam_core::{route, AsyncTryClone, Result, Route};
     use ockam_key_exchange_core::NewKeyExchanger;
-    use ockam_key_exchange_xx::XXNewKeyExchanger;
+    use ockam_key_exchange_xx::{XXCipherSuite, XXNewKeyExchanger};
     use ockam_node::Context;
     use ockam_vault::Vault;
 
@@ -67,6 +76,8 @@ mod tests {
             None,
             new_key_exchanger.initiator().await?,
             vault,
+            RekeyPolicy::default(),
+            ReplayCounters::default(),
         )
         .await?;
 
@@ -79,4 +90,196 @@ mod tests {
         assert_eq!(ctx.receive::<String>().await?, test_msg);
         ctx.stop().await
     }
+
+    #[ockam_macros::test]
+    async fn rekeying_channel(ctx: &mut Context) -> Result<()> {
+        rekeying_channel_with(ctx, XXCipherSuite::AesGcm).await?;
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn rekeying_chacha_channel(ctx: &mut Context) -> Result<()> {
+        rekeying_channel_with(ctx, XXCipherSuite::ChaChaPoly).await?;
+        ctx.stop().await
+    }
+
+    async fn rekeying_channel_with(ctx: &mut Context, cipher_suite: XXCipherSuite) -> Result<()> {
+        let vault = Vault::create();
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?)
+            .with_cipher_suites(vec![cipher_suite]);
+        let listener = SecureChannelListener::new(
+            new_key_exchanger.async_try_clone().await?,
+            vault.async_try_clone().await?,
+        )
+        .with_rekey_policy(RekeyPolicy::new().with_message_limit(1));
+        ctx.start_worker("secure_channel_listener", listener)
+            .await?;
+
+        let initiator = SecureChannel::create_extended(
+            ctx,
+            Route::new().append("secure_channel_listener"),
+            None,
+            new_key_exchanger.initiator().await?,
+            vault,
+            RekeyPolicy::new().with_message_limit(2),
+            ReplayCounters::default(),
+        )
+        .await?;
+
+        // Send several messages before reading them, so that some of them
+        // are still in flight when the keys change
+        for i in 0..5 {
+            ctx.send(
+                Route::new().append(initiator.address()).append("app"),
+                i.to_string(),
+            )
+            .await?;
+        }
+        for i in 0..5 {
+            let msg = ctx.receive::<String>().await?.take();
+            assert_eq!(msg.body(), i.to_string());
+            ctx.send(msg.return_route(), i.to_string()).await?;
+            assert_eq!(ctx.receive::<String>().await?.take().body(), i.to_string());
+        }
+
+        Ok(())
+    }
+
+    #[ockam_macros::test]
+    async fn replayed_messages_are_rejected(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        let counters = ReplayCounters::new();
+        let listener = SecureChannelListener::new(
+            new_key_exchanger.async_try_clone().await?,
+            vault.async_try_clone().await?,
+        )
+        .with_replay_counters(counters.clone());
+        ctx.start_worker("secure_channel_listener", listener)
+            .await?;
+
+        let duplicate = Arc::new(AtomicBool::new(false));
+        ctx.start_worker("duplicator", Duplicator::new(duplicate.clone()))
+            .await?;
+
+        let initiator = SecureChannel::create_extended(
+            ctx,
+            route!["duplicator", "secure_channel_listener"],
+            None,
+            new_key_exchanger.initiator().await?,
+            vault,
+            RekeyPolicy::default(),
+            ReplayCounters::default(),
+        )
+        .await?;
+
+        duplicate.store(true, Ordering::Relaxed);
+        for i in 0..3 {
+            ctx.send(route![initiator.address(), "app"], i.to_string())
+                .await?;
+            assert_eq!(ctx.receive::<String>().await?.take().body(), i.to_string());
+        }
+
+        // Once this message is received all the copies were handled
+        duplicate.store(false, Ordering::Relaxed);
+        ctx.send(route![initiator.address(), "app"], "last".to_string())
+            .await?;
+        assert_eq!(ctx.receive::<String>().await?.take().body(), "last");
+
+        assert_eq!(counters.stats().duplicates(), 3);
+        assert_eq!(counters.stats().outside_window(), 0);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn migrated_channel(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        SecureChannel::create_listener_extended(
+            ctx,
+            "secure_channel_listener".to_string(),
+            new_key_exchanger.async_try_clone().await?,
+            vault.async_try_clone().await?,
+        )
+        .await?;
+
+        for hop in ["hop1", "hop2"] {
+            ctx.start_worker(hop, Duplicator::default()).await?;
+        }
+
+        let initiator = SecureChannel::create_extended(
+            ctx,
+            route!["hop1", "secure_channel_listener"],
+            None,
+            new_key_exchanger.initiator().await?,
+            vault,
+            RekeyPolicy::default(),
+            ReplayCounters::default(),
+        )
+        .await?;
+
+        ctx.send(route![initiator.address(), "app"], "first".to_string())
+            .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!(msg.body(), "first");
+
+        // Both directions have to use the new route once the first one is gone
+        ctx.stop_worker("hop1").await?;
+        SecureChannel::migrate(ctx, initiator.control_address(), route!["hop2"]).await?;
+
+        ctx.send(route![initiator.address(), "app"], "second".to_string())
+            .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!(msg.body(), "second");
+        ctx.send(msg.return_route(), "reply".to_string()).await?;
+        assert_eq!(ctx.receive::<String>().await?.take().body(), "reply");
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn control_messages_are_only_accepted_on_control_address(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let vault = Vault::create();
+        let new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?);
+        SecureChannel::create_listener_extended(
+            ctx,
+            "secure_channel_listener".to_string(),
+            new_key_exchanger.async_try_clone().await?,
+            vault.async_try_clone().await?,
+        )
+        .await?;
+
+        let initiator = SecureChannel::create_extended(
+            ctx,
+            route!["secure_channel_listener"],
+            None,
+            new_key_exchanger.initiator().await?,
+            vault,
+            RekeyPolicy::default(),
+            ReplayCounters::default(),
+        )
+        .await?;
+
+        // Neither a message ending at the encryptor nor an unknown control
+        // message stops the channel
+        ctx.send(route![initiator.address()], "stop".to_string())
+            .await?;
+        ctx.send(route![initiator.control_address()], "stop".to_string())
+            .await?;
+
+        ctx.send(route![initiator.address(), "app"], "hello".to_string())
+            .await?;
+        assert_eq!(ctx.receive::<String>().await?.take().body(), "hello");
+
+        SecureChannel::stop(ctx, initiator.control_address()).await?;
+        let res = ctx
+            .send(route![initiator.address(), "app"], "ignored".to_string())
+            .await;
+        assert!(res.is_err() || ctx.receive_timeout::<String>(1).await.is_err());
+
+        ctx.stop().await
+    }
 }
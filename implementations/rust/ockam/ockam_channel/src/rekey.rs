@@ -0,0 +1,145 @@
+use crate::cipher::Cipher;
+use crate::{SecureChannelEncryptor, SecureChannelVault};
+use core::time::Duration;
+use ockam_core::vault::{
+    KeyId, Secret, SecretAttributes, SecretKey, SecretPersistence, AES256_SECRET_LENGTH_USIZE,
+};
+use ockam_core::Result;
+
+/// Number of low bits of the nonce counting messages inside of a key epoch.
+///
+/// The key used for a message is derived from the initial key by applying
+/// `REKEY` as many times as the epoch of its nonce (`nonce >> EPOCH_BITS`).
+/// Since the nonce is sent along with every message, the decryptor knows
+/// which key to use without any further coordination.
+pub(crate) const EPOCH_BITS: u32 = 32;
+
+/// Maximum number of epochs the decryptor accepts to skip at once, e.g.
+/// because all messages of an epoch were lost.
+pub(crate) const MAX_EPOCH_SKIP: u64 = 16;
+
+/// Epoch of a nonce
+pub(crate) fn epoch(nonce: u64) -> u64 {
+    nonce >> EPOCH_BITS
+}
+
+/// First nonce of an epoch
+pub(crate) fn first_nonce(epoch: u64) -> u64 {
+    epoch << EPOCH_BITS
+}
+
+/// When a secure channel encryptor switches to a new key.
+///
+/// Keys are always changed after 2^32 messages. The policy allows to change
+/// them more often, by message count and/or elapsed time. Both sides of a
+/// channel can use different policies, a decryptor follows the key changes
+/// of the other side whatever its own policy is.
+///
+/// Rekeying by elapsed time is only available with the `std` feature, and
+/// happens when the next message is sent once the interval has elapsed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RekeyPolicy {
+    messages: Option<u64>,
+    interval: Option<Duration>,
+}
+
+impl RekeyPolicy {
+    /// Only change keys after 2^32 messages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Change keys after the given number of messages, at most 2^32
+    pub fn with_message_limit(mut self, messages: u64) -> Self {
+        self.messages = Some(messages.clamp(1, 1 << EPOCH_BITS));
+        self
+    }
+
+    /// Change keys once the given time has elapsed since the last change
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Number of messages after which keys are changed, if set
+    pub fn message_limit(&self) -> Option<u64> {
+        self.messages
+    }
+
+    /// Time after which keys are changed, if set
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+}
+
+/// Noise `REKEY(k)`: the first 32 bytes of `ENCRYPTWITHAD(k, maxnonce, zerolen, zeros)`
+pub(crate) async fn rekey<V: SecureChannelVault>(
+    vault: &V,
+    cipher: Cipher,
+    key: &KeyId,
+) -> Result<KeyId> {
+    let (_, nonce) = SecureChannelEncryptor::<V>::convert_nonce_from_u64(u64::MAX);
+    let zeros = [0u8; AES256_SECRET_LENGTH_USIZE];
+
+    let mut new_key = cipher.encrypt(vault, key, &zeros, &nonce).await?;
+    new_key.truncate(AES256_SECRET_LENGTH_USIZE);
+
+    let (stype, length) = cipher.key_type_and_length();
+    let attributes = SecretAttributes::new(stype, SecretPersistence::Ephemeral, length);
+    vault
+        .secret_import(Secret::Key(SecretKey::new(new_key)), attributes)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::vault::{SecretType, SecretVault, SymmetricVault, AES256_SECRET_LENGTH_U32};
+    use ockam_vault::Vault;
+
+    #[tokio::test]
+    async fn rekey_is_deterministic() -> Result<()> {
+        let vault = Vault::create();
+        let attributes = SecretAttributes::new(
+            SecretType::Aes,
+            SecretPersistence::Ephemeral,
+            AES256_SECRET_LENGTH_U32,
+        );
+        let key = vault
+            .secret_import(Secret::Key(SecretKey::new(vec![7; 32])), attributes)
+            .await?;
+
+        let k1 = rekey(&vault, Cipher::AesGcm, &key).await?;
+        let k2 = rekey(&vault, Cipher::AesGcm, &key).await?;
+        assert_ne!(
+            vault.secret_export(&k1).await?,
+            vault.secret_export(&key).await?
+        );
+        assert_eq!(
+            vault.secret_export(&k1).await?,
+            vault.secret_export(&k2).await?
+        );
+
+        let (_, nonce) = SecureChannelEncryptor::<Vault>::convert_nonce_from_u64(1);
+        let cipher_text = vault
+            .aead_aes_gcm_encrypt(&k1, b"hello", &nonce, &[])
+            .await?;
+        let plain_text = vault
+            .aead_aes_gcm_decrypt(&k2, &cipher_text, &nonce, &[])
+            .await?;
+        assert_eq!(plain_text, b"hello");
+
+        Ok(())
+    }
+
+    #[test]
+    fn epochs() {
+        assert_eq!(epoch(0), 0);
+        assert_eq!(epoch((1 << EPOCH_BITS) - 1), 0);
+        assert_eq!(epoch(first_nonce(3)), 3);
+        assert_eq!(
+            RekeyPolicy::new().with_message_limit(0).message_limit(),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::SecureChannelError;
+use ockam_core::compat::sync::{Arc, RwLock};
+
+/// Number of nonces below the highest received one that are still accepted,
+/// so that messages reordered by the transport are not dropped.
+pub const REPLAY_WINDOW_SIZE: u64 = 2048;
+
+const WORDS: usize = (REPLAY_WINDOW_SIZE / 64) as usize;
+
+/// Sliding window of the nonces received with a given key, similar to the
+/// anti-replay window of IPsec and WireGuard.
+///
+/// A nonce is only recorded once the message it came with was successfully
+/// decrypted, so that forged messages can't move the window.
+#[derive(Clone, Default)]
+pub(crate) struct ReplayWindow {
+    highest: Option<u64>,
+    bitmap: [u64; WORDS],
+}
+
+impl ReplayWindow {
+    fn position(nonce: u64) -> (usize, u64) {
+        let index = nonce % REPLAY_WINDOW_SIZE;
+        ((index / 64) as usize, 1 << (index % 64))
+    }
+
+    /// Check that a message with this nonce may be accepted
+    pub(crate) fn check(&self, nonce: u64) -> Result<(), SecureChannelError> {
+        let highest = match self.highest {
+            Some(highest) if nonce <= highest => highest,
+            _ => return Ok(()),
+        };
+        if highest - nonce >= REPLAY_WINDOW_SIZE {
+            return Err(SecureChannelError::NonceOutsideWindow);
+        }
+        let (word, bit) = Self::position(nonce);
+        if self.bitmap[word] & bit != 0 {
+            return Err(SecureChannelError::DuplicateNonce);
+        }
+        Ok(())
+    }
+
+    /// Record a nonce which passed [`ReplayWindow::check`]
+    pub(crate) fn accept(&mut self, nonce: u64) {
+        match self.highest {
+            Some(highest) if nonce <= highest => {}
+            Some(highest) if nonce - highest < REPLAY_WINDOW_SIZE => {
+                // Forget the nonces which just left the window
+                for n in highest + 1..=nonce {
+                    let (word, bit) = Self::position(n);
+                    self.bitmap[word] &= !bit;
+                }
+                self.highest = Some(nonce);
+            }
+            _ => {
+                self.bitmap = [0; WORDS];
+                self.highest = Some(nonce);
+            }
+        }
+        let (word, bit) = Self::position(nonce);
+        self.bitmap[word] |= bit;
+    }
+}
+
+/// Number of messages rejected by secure channel decryptors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    duplicates: u64,
+    outside_window: u64,
+    invalid: u64,
+}
+
+impl ReplayStats {
+    /// Messages whose nonce was already received
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Messages whose nonce was too old to be checked
+    pub fn outside_window(&self) -> u64 {
+        self.outside_window
+    }
+
+    /// Messages which could not be authenticated
+    pub fn invalid(&self) -> u64 {
+        self.invalid
+    }
+
+    /// All rejected messages
+    pub fn total(&self) -> u64 {
+        self.duplicates + self.outside_window + self.invalid
+    }
+}
+
+/// Shared handle to the [`ReplayStats`] of one or more decryptors.
+#[derive(Clone, Default)]
+pub struct ReplayCounters(Arc<RwLock<ReplayStats>>);
+
+impl ReplayCounters {
+    /// New counters, starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current values of the counters
+    pub fn stats(&self) -> ReplayStats {
+        self.0.read().map(|stats| *stats).unwrap_or_default()
+    }
+
+    pub(crate) fn record(&self, err: &SecureChannelError) {
+        if let Ok(mut stats) = self.0.write() {
+            match err {
+                SecureChannelError::DuplicateNonce => stats.duplicates += 1,
+                SecureChannelError::NonceOutsideWindow => stats.outside_window += 1,
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn record_invalid(&self) {
+        if let Ok(mut stats) = self.0.write() {
+            stats.invalid += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_duplicates() {
+        let mut window = ReplayWindow::default();
+        for n in [0, 1, 5, 3] {
+            assert!(window.check(n).is_ok());
+            window.accept(n);
+        }
+        for n in [0, 1, 3, 5] {
+            assert!(window.check(n).is_err());
+        }
+        assert!(window.check(2).is_ok());
+        assert!(window.check(4).is_ok());
+    }
+
+    #[test]
+    fn slides() {
+        let mut window = ReplayWindow::default();
+        window.accept(10);
+        window.accept(10 + REPLAY_WINDOW_SIZE - 1);
+        assert!(window.check(10).is_err());
+        assert!(window.check(11).is_ok());
+
+        // The bit of 10 is reused for 10 + REPLAY_WINDOW_SIZE
+        window.accept(10 + REPLAY_WINDOW_SIZE);
+        assert!(window.check(10).is_err());
+        assert!(window.check(10 + REPLAY_WINDOW_SIZE).is_err());
+        assert!(window.check(11).is_ok());
+
+        window.accept(12 + 3 * REPLAY_WINDOW_SIZE);
+        assert!(window.check(12 + 3 * REPLAY_WINDOW_SIZE - 1).is_ok());
+        assert!(window.check(12 + 2 * REPLAY_WINDOW_SIZE + 1).is_ok());
+        assert!(window.check(12 + 2 * REPLAY_WINDOW_SIZE).is_err());
+    }
+}
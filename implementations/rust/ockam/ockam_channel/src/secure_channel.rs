@@ -1,9 +1,10 @@
 use crate::{
-    KeyExchangeCompleted, SecureChannelDecryptor, SecureChannelKeyExchanger, SecureChannelListener,
+    EncryptorControlMessage, KeyExchangeCompleted, RekeyPolicy, ReplayCounters,
+    SecureChannelDecryptor, SecureChannelKeyExchanger, SecureChannelListener,
     SecureChannelNewKeyExchanger, SecureChannelVault,
 };
 use ockam_core::compat::{sync::Arc, vec::Vec};
-use ockam_core::{Address, Mailbox, Mailboxes, Result, Route};
+use ockam_core::{route, Address, Mailbox, Mailboxes, Result, Route};
 use ockam_node::{Context, WorkerBuilder};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
@@ -14,6 +15,7 @@ use tracing::{debug, info};
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub struct SecureChannelInfo {
     worker_address: Address,
+    control_address: Address,
     auth_hash: [u8; 32],
 }
 
@@ -22,6 +24,10 @@ impl SecureChannelInfo {
     pub fn address(&self) -> Address {
         self.worker_address.clone()
     }
+    /// Return a clone of the address to stop or migrate the channel.
+    pub fn control_address(&self) -> Address {
+        self.control_address.clone()
+    }
     /// Return the auth hash.
     pub fn auth_hash(&self) -> [u8; 32] {
         self.auth_hash
@@ -85,17 +91,24 @@ impl SecureChannel {
             None,
             new_key_exchanger.initiator().await?,
             vault.async_try_clone().await?,
+            RekeyPolicy::default(),
+            ReplayCounters::default(),
         )
         .await
     }
 
     /// Create initiator channel with given route to a remote channel listener.
+    ///
+    /// The [`RekeyPolicy`] only applies to messages sent by the initiator, the
+    /// [`ReplayCounters`] to messages it receives.
     pub async fn create_extended(
         ctx: &Context,
         route: impl Into<Route>,
         custom_payload: Option<Vec<u8>>,
         key_exchanger: impl SecureChannelKeyExchanger,
         vault: impl SecureChannelVault,
+        rekey_policy: RekeyPolicy,
+        replay_counters: ReplayCounters,
     ) -> Result<SecureChannelInfo> {
         let route = route.into();
 
@@ -117,6 +130,8 @@ impl SecureChannel {
             route,
             custom_payload,
             vault.async_try_clone().await?,
+            rekey_policy,
+            replay_counters,
         )
         .await?;
 
@@ -144,9 +159,45 @@ impl SecureChannel {
 
         let info = SecureChannelInfo {
             worker_address: resp.address().clone(),
+            control_address: resp.control_address().clone(),
             auth_hash: resp.auth_hash(),
         };
 
         Ok(info)
     }
+
+    /// Stop a channel given the control address of its encryptor, see
+    /// [`SecureChannelInfo::control_address`] and [`KeyExchangeCompleted`].
+    /// Messages sent to the channel before are still delivered to the other side.
+    ///
+    /// Only messages from this node are accepted on the control address.
+    pub async fn stop(ctx: &Context, control_address: impl Into<Address>) -> Result<()> {
+        ctx.send(route![control_address], EncryptorControlMessage::Stop)
+            .await
+    }
+
+    /// Move a channel to a new route, e.g. after its transport connection was
+    /// reestablished. The route leads to the node of the other side, without
+    /// the address of its channel which is appended to it.
+    ///
+    /// The channel keeps its keys and addresses. The other side replies over
+    /// the route the migration came through once it has authenticated it, so
+    /// only the side which can reach the other one needs to migrate.
+    ///
+    /// The other side does not check that route against anything it asked
+    /// for: only the migration message is authenticated, not the return route
+    /// it was given by the transports. Whoever can rewrite routes on the path
+    /// can then redirect the replies, which stay encrypted, like they could
+    /// drop them before the migration.
+    pub async fn migrate(
+        ctx: &Context,
+        control_address: impl Into<Address>,
+        route: impl Into<Route>,
+    ) -> Result<()> {
+        ctx.send(
+            route![control_address],
+            EncryptorControlMessage::Migrate(route.into()),
+        )
+        .await
+    }
 }
@@ -1,18 +1,48 @@
+use crate::cipher::Cipher;
+use crate::rekey::{epoch, rekey, MAX_EPOCH_SKIP};
+use crate::replay::ReplayWindow;
 use crate::{
-    ChannelKeys, CreateResponderChannelMessage, KeyExchangeCompleted, Role, SecureChannelEncryptor,
-    SecureChannelError, SecureChannelKeyExchanger, SecureChannelLocalInfo, SecureChannelVault,
+    ChannelKeys, CreateResponderChannelMessage, KeyExchangeCompleted, RekeyPolicy, ReplayCounters,
+    Role, SecureChannelEncryptor, SecureChannelError, SecureChannelKeyExchanger,
+    SecureChannelLocalInfo, SecureChannelVault,
 };
-use ockam_core::compat::{boxed::Box, string::String, vec::Vec};
-use ockam_core::{async_trait, route};
+use ockam_core::compat::{
+    boxed::Box,
+    string::String,
+    sync::{Arc, RwLock},
+    vec::Vec,
+};
+use ockam_core::vault::KeyId;
+use ockam_core::{async_trait, route, AllowAll, DenyAll, Mailbox, Mailboxes};
 use ockam_core::{
     Address, Any, Decodable, LocalMessage, Result, Route, Routed, TransportMessage, Worker,
 };
-use ockam_node::Context;
-use tracing::{debug, info};
+use ockam_node::access_control::LocalOriginOnly;
+use ockam_node::{Context, WorkerBuilder};
+use tracing::{debug, info, warn};
 
 struct DecryptorReadyState {
     keys: ChannelKeys,
+    /// Epoch of the current key, see [`crate::rekey`]
+    epoch: u64,
+    /// Nonces received with the current key
+    window: ReplayWindow,
+    /// Key of the previous epoch and its nonces, kept for messages still in flight
+    previous: Option<(KeyId, ReplayWindow)>,
+    replay_counters: ReplayCounters,
     encryptor_address: Address,
+    /// Route used by our encryptor, see [`crate::SecureChannel::migrate`]
+    remote_route: Arc<RwLock<Route>>,
+}
+
+impl DecryptorReadyState {
+    fn check_nonce(&self, window: &ReplayWindow, nonce: u64) -> Result<()> {
+        window.check(nonce).map_err(|err| {
+            self.replay_counters.record(&err);
+            warn!("SecureChannel decryptor rejected message: {}", err);
+            err.into()
+        })
+    }
 }
 
 /// Secure Channel Decryptor
@@ -26,6 +56,8 @@ pub struct SecureChannelDecryptor<V: SecureChannelVault, K: SecureChannelKeyExch
     custom_payload: Option<Vec<u8>>,
     vault: V,
     key_exchange_name: String,
+    rekey_policy: RekeyPolicy,
+    replay_counters: ReplayCounters,
 }
 
 impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor<V, K> {
@@ -36,6 +68,8 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
         remote_route: Route,
         custom_payload: Option<Vec<u8>>,
         vault: V,
+        rekey_policy: RekeyPolicy,
+        replay_counters: ReplayCounters,
     ) -> Result<Self> {
         let key_exchange_name = key_exchanger.name().await?;
         Ok(Self {
@@ -46,6 +80,8 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
             custom_payload,
             vault,
             key_exchange_name,
+            rekey_policy,
+            replay_counters,
             state: None,
         })
     }
@@ -66,17 +102,131 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
             custom_payload: None,
             vault,
             key_exchange_name,
+            rekey_policy: RekeyPolicy::default(),
+            replay_counters: ReplayCounters::default(),
             state: None,
         })
     }
 
-    /// Restore 12-byte nonce needed for AES GCM from 8 byte that we use for noise
-    fn convert_nonce_from_small(b: &[u8]) -> Result<[u8; 12]> {
+    /// Set the [`RekeyPolicy`] of the encryptor started once the key exchange completes
+    pub fn with_rekey_policy(mut self, rekey_policy: RekeyPolicy) -> Self {
+        self.rekey_policy = rekey_policy;
+        self
+    }
+
+    /// Count rejected messages in the given counters
+    pub fn with_replay_counters(mut self, replay_counters: ReplayCounters) -> Self {
+        self.replay_counters = replay_counters;
+        self
+    }
+
+    /// Counters of rejected messages
+    pub fn replay_counters(&self) -> ReplayCounters {
+        self.replay_counters.clone()
+    }
+
+    /// Restore u64 nonce from the 8 byte that we use for noise
+    fn convert_nonce_from_small(b: &[u8]) -> Result<u64> {
         let bytes: [u8; 8] = b.try_into().map_err(|_| SecureChannelError::InvalidNonce)?;
 
-        let nonce = u64::from_be_bytes(bytes);
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Decrypt with the key of the epoch of the nonce. Keys of newer epochs
+    /// are derived on demand and only kept if the message could be decrypted.
+    /// Nonces are checked against the replay window of their key before
+    /// decryption, and recorded in it once the message is authenticated.
+    async fn decrypt(
+        vault: &V,
+        state: &mut DecryptorReadyState,
+        nonce: u64,
+        cipher_text: &[u8],
+    ) -> Result<Vec<u8>> {
+        let (_, aes_nonce) = SecureChannelEncryptor::<V>::convert_nonce_from_u64(nonce);
+        let msg_epoch = epoch(nonce);
+        let cipher = state.keys.cipher;
+        let replay_counters = state.replay_counters.clone();
+        let invalid = |err| {
+            replay_counters.record_invalid();
+            err
+        };
+
+        if msg_epoch == state.epoch {
+            state.check_nonce(&state.window, nonce)?;
+            let plain_text = cipher
+                .decrypt(vault, &state.keys.key, cipher_text, &aes_nonce)
+                .await
+                .map_err(invalid)?;
+            state.window.accept(nonce);
+            return Ok(plain_text);
+        }
+
+        if msg_epoch + 1 == state.epoch {
+            let (previous_key, previous_window) = state
+                .previous
+                .as_ref()
+                .ok_or(SecureChannelError::InvalidNonce)?;
+            state.check_nonce(previous_window, nonce)?;
+            let plain_text = cipher
+                .decrypt(vault, previous_key, cipher_text, &aes_nonce)
+                .await
+                .map_err(invalid)?;
+            if let Some((_, previous_window)) = state.previous.as_mut() {
+                previous_window.accept(nonce);
+            }
+            return Ok(plain_text);
+        }
+
+        if msg_epoch < state.epoch || msg_epoch - state.epoch > MAX_EPOCH_SKIP {
+            return Err(SecureChannelError::InvalidNonce.into());
+        }
+
+        let mut keys = vec![state.keys.key.clone()];
+        for _ in state.epoch..msg_epoch {
+            let key = rekey(vault, cipher, &keys[keys.len() - 1]).await?;
+            keys.push(key);
+        }
+
+        let result = cipher
+            .decrypt(vault, &keys[keys.len() - 1], cipher_text, &aes_nonce)
+            .await;
+        let plain_text = match result {
+            Ok(plain_text) => plain_text,
+            Err(err) => {
+                for key in keys.drain(1..) {
+                    vault.secret_destroy(key).await?;
+                }
+                return Err(invalid(err));
+            }
+        };
+
+        debug!(
+            "SecureChannel decryptor switched to key epoch {}",
+            msg_epoch
+        );
+
+        let mut window = ReplayWindow::default();
+        window.accept(nonce);
+        let current_window = core::mem::replace(&mut state.window, window);
+
+        let current_key = keys.pop().ok_or(SecureChannelError::InvalidInternalState)?;
+        // The window of the previous epoch is only known if no epoch was skipped
+        let previous = keys.pop().map(|key| {
+            let window = if msg_epoch == state.epoch + 1 {
+                current_window
+            } else {
+                ReplayWindow::default()
+            };
+            (key, window)
+        });
+        let obsolete = core::mem::replace(&mut state.previous, previous);
+        for key in keys.into_iter().chain(obsolete.map(|(key, _)| key)) {
+            vault.secret_destroy(key).await?;
+        }
+        state.keys.key = current_key;
+        state.epoch = msg_epoch;
 
-        Ok(SecureChannelEncryptor::<V>::convert_nonce_from_u64(nonce).1)
+        Ok(plain_text)
     }
 
     async fn send_key_exchange_payload(
@@ -105,6 +255,7 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
     ) -> Result<()> {
         debug!("SecureChannel received Decrypt");
 
+        let return_route = msg.return_route();
         let state = self
             .state
             .as_mut()
@@ -121,13 +272,27 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
 
             let nonce = Self::convert_nonce_from_small(&payload.as_slice()[..8])?;
 
-            self.vault
-                .aead_aes_gcm_decrypt(&state.keys.key, &payload[8..], &nonce, &[])
-                .await?
+            Self::decrypt(&self.vault, state, nonce, &payload[8..]).await?
         };
 
         let mut transport_message = TransportMessage::decode(&payload)?;
 
+        // The other side moved the channel, reply over the route its message
+        // came through. The message was authenticated by the decryption, its
+        // return route was not, see `SecureChannel::migrate`.
+        if transport_message.onward_route.iter().next().is_none() {
+            if return_route.iter().next().is_none() {
+                return Err(SecureChannelError::InvalidInternalState.into());
+            }
+            info!("SecureChannel migrated to {}", return_route);
+            let mut remote_route = state
+                .remote_route
+                .write()
+                .map_err(|_| SecureChannelError::InvalidInternalState)?;
+            *remote_route = return_route;
+            return Ok(());
+        }
+
         transport_message
             .return_route
             .modify()
@@ -179,6 +344,7 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
             .ok_or(SecureChannelError::InvalidInternalState)?;
 
         let keys = key_exchanger.finalize().await?;
+        let cipher = Cipher::of_key(&self.vault, keys.encrypt_key()).await?;
 
         let role_str = match self.role {
             Role::Initiator => "initiator",
@@ -186,15 +352,36 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
         };
         let address_local =
             Address::random_tagged(&format!("SecureChannel.{}.encryptor", role_str));
+        let control_address =
+            Address::random_tagged(&format!("SecureChannel.{}.encryptor.control", role_str));
+        let remote_route = Arc::new(RwLock::new(self.remote_route.clone()));
         let encryptor = SecureChannelEncryptor::new(
             ChannelKeys {
+                cipher,
                 key: keys.encrypt_key().clone(),
                 nonce: 0,
             },
-            self.remote_route.clone(),
+            self.rekey_policy,
+            remote_route.clone(),
+            ctx.address(),
+            control_address.clone(),
             self.vault.async_try_clone().await?,
         );
-        ctx.start_worker(address_local.clone(), encryptor).await?;
+        let mailboxes = Mailboxes::new(
+            Mailbox::new(
+                address_local.clone(),
+                Arc::new(AllowAll),
+                Arc::new(AllowAll),
+            ),
+            vec![Mailbox::new(
+                control_address.clone(),
+                Arc::new(LocalOriginOnly),
+                Arc::new(DenyAll),
+            )],
+        );
+        WorkerBuilder::with_mailboxes(mailboxes, encryptor)
+            .start(ctx)
+            .await?;
 
         info!(
             "Started SecureChannel {} at local: {}, remote: {}",
@@ -207,17 +394,23 @@ impl<V: SecureChannelVault, K: SecureChannelKeyExchanger> SecureChannelDecryptor
         if let Some(r) = self.key_exchange_completed_callback_route.take() {
             ctx.send(
                 r,
-                KeyExchangeCompleted::new(address_local.clone(), *keys.h()),
+                KeyExchangeCompleted::new(address_local.clone(), control_address, *keys.h()),
             )
             .await?;
         }
 
         self.state = Some(DecryptorReadyState {
             keys: ChannelKeys {
+                cipher,
                 key: keys.decrypt_key().clone(),
                 nonce: 0,
             },
+            epoch: 0,
+            window: ReplayWindow::default(),
+            previous: None,
+            replay_counters: self.replay_counters.clone(),
             encryptor_address: address_local,
+            remote_route,
         });
 
         Ok(())
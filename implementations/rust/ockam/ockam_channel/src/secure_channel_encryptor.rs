@@ -1,21 +1,53 @@
-use crate::{ChannelKeys, SecureChannelError, SecureChannelVault};
-use ockam_core::async_trait;
-use ockam_core::compat::{boxed::Box, vec::Vec};
-use ockam_core::{Any, Encodable, Result, Route, Routed, TransportMessage, Worker};
+use crate::rekey::{epoch, first_nonce, rekey, EPOCH_BITS};
+use crate::{
+    ChannelKeys, EncryptorControlMessage, RekeyPolicy, SecureChannelError, SecureChannelVault,
+};
+use ockam_core::compat::{
+    boxed::Box,
+    sync::{Arc, RwLock},
+    vec::Vec,
+};
+use ockam_core::{async_trait, route};
+use ockam_core::{
+    Address, Any, Decodable, Encodable, Result, Route, Routed, TransportMessage, Worker,
+};
 use ockam_node::Context;
-use tracing::debug;
+use tracing::{debug, info};
 
 pub(crate) struct SecureChannelEncryptor<V: SecureChannelVault> {
     keys: ChannelKeys,
-    remote_route: Route,
+    /// Epoch of the current key, see [`crate::rekey`]
+    epoch: u64,
+    rekey_policy: RekeyPolicy,
+    #[cfg(feature = "std")]
+    epoch_started_at: std::time::Instant,
+    /// Route to the other side's decryptor, shared with our decryptor which
+    /// updates it when the other side migrates the channel
+    remote_route: Arc<RwLock<Route>>,
+    decryptor_address: Address,
+    /// Address for [`EncryptorControlMessage`]s
+    control_address: Address,
     vault: V,
 }
 
 impl<V: SecureChannelVault> SecureChannelEncryptor<V> {
-    pub(crate) fn new(keys: ChannelKeys, remote_route: Route, vault: V) -> Self {
+    pub(crate) fn new(
+        keys: ChannelKeys,
+        rekey_policy: RekeyPolicy,
+        remote_route: Arc<RwLock<Route>>,
+        decryptor_address: Address,
+        control_address: Address,
+        vault: V,
+    ) -> Self {
         Self {
+            epoch: epoch(keys.nonce),
             keys,
+            rekey_policy,
+            #[cfg(feature = "std")]
+            epoch_started_at: std::time::Instant::now(),
             remote_route,
+            decryptor_address,
+            control_address,
             vault,
         }
     }
@@ -32,6 +64,57 @@ impl<V: SecureChannelVault> SecureChannelEncryptor<V> {
         (b, n)
     }
 
+    /// Whether the policy asks for a new key before sending the next message
+    fn rekey_due(&self) -> bool {
+        let sent = self.keys.nonce - first_nonce(self.epoch);
+        if sent == 0 {
+            // The current key was not used yet
+            return false;
+        }
+        if let Some(limit) = self.rekey_policy.message_limit() {
+            if sent >= limit {
+                return true;
+            }
+        }
+        #[cfg(feature = "std")]
+        if let Some(interval) = self.rekey_policy.interval() {
+            if self.epoch_started_at.elapsed() >= interval {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Switch to the key of the next epoch if the nonce reached it or the
+    /// policy asks for it. In the latter case the nonce jumps to the first
+    /// nonce of the next epoch, which tells the decryptor to switch as well.
+    async fn rekey_if_needed(&mut self) -> Result<()> {
+        if epoch(self.keys.nonce) == self.epoch && !self.rekey_due() {
+            return Ok(());
+        }
+        if self.epoch == u64::MAX >> EPOCH_BITS {
+            return Err(SecureChannelError::InvalidNonce.into());
+        }
+
+        let next_epoch = self.epoch + 1;
+        let new_key = rekey(&self.vault, self.keys.cipher, &self.keys.key).await?;
+        let old_key = core::mem::replace(&mut self.keys.key, new_key);
+        self.vault.secret_destroy(old_key).await?;
+
+        self.keys.nonce = self.keys.nonce.max(first_nonce(next_epoch));
+        self.epoch = next_epoch;
+        #[cfg(feature = "std")]
+        {
+            self.epoch_started_at = std::time::Instant::now();
+        }
+        debug!(
+            "SecureChannel encryptor switched to key epoch {}",
+            next_epoch
+        );
+
+        Ok(())
+    }
+
     async fn handle_encrypt(
         &mut self,
         ctx: &mut <Self as Worker>::Context,
@@ -46,9 +129,65 @@ impl<V: SecureChannelVault> SecureChannelEncryptor<V> {
 
         let _ = onward_route.step();
 
+        // The other side would take a message without onward route for a migration
+        if onward_route.iter().next().is_none() {
+            return Err(SecureChannelError::InvalidInternalState.into());
+        }
+
         let msg = TransportMessage::v1(onward_route, reply, payload.to_vec());
+        self.encrypt_and_send(ctx, msg).await
+    }
+
+    /// Handle a message sent to the control address. Messages sent to the
+    /// channel before it are still encrypted since the mailbox is ordered.
+    async fn handle_control(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        msg: Routed<<Self as Worker>::Message>,
+    ) -> Result<()> {
+        match EncryptorControlMessage::decode(msg.payload())? {
+            EncryptorControlMessage::Stop => {
+                debug!("SecureChannel received Stop");
+                ctx.stop_worker(self.decryptor_address.clone()).await?;
+                ctx.stop_worker(ctx.address()).await
+            }
+            EncryptorControlMessage::Migrate(route) => self.migrate(ctx, route).await,
+        }
+    }
+
+    /// Send the next messages over a new route to the other side's node, and
+    /// tell the other side's decryptor to reply over the route the message
+    /// came through. The message is encrypted like any other, so nobody else
+    /// can move the channel.
+    async fn migrate(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        mut route: Route,
+    ) -> Result<()> {
+        {
+            let mut remote_route = self
+                .remote_route
+                .write()
+                .map_err(|_| SecureChannelError::InvalidInternalState)?;
+            let remote_decryptor = remote_route.recipient();
+            *remote_route = route.modify().append(remote_decryptor).into();
+            info!("SecureChannel migrating to {}", *remote_route);
+        }
+
+        // An empty onward route tells the decryptor it is not a message to forward
+        let msg = TransportMessage::v1(route![], route![], Vec::new());
+        self.encrypt_and_send(ctx, msg).await
+    }
+
+    async fn encrypt_and_send(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        msg: TransportMessage,
+    ) -> Result<()> {
         let payload = msg.encode()?;
 
+        self.rekey_if_needed().await?;
+
         let payload = {
             let nonce = self.keys.nonce;
 
@@ -61,8 +200,9 @@ impl<V: SecureChannelVault> SecureChannelEncryptor<V> {
             let (small_nonce, nonce) = Self::convert_nonce_from_u64(nonce);
 
             let mut cipher_text = self
-                .vault
-                .aead_aes_gcm_encrypt(&self.keys.key, payload.as_slice(), &nonce, &[])
+                .keys
+                .cipher
+                .encrypt(&self.vault, &self.keys.key, payload.as_slice(), &nonce)
                 .await?;
 
             let mut res = Vec::new();
@@ -72,7 +212,12 @@ impl<V: SecureChannelVault> SecureChannelEncryptor<V> {
             res
         };
 
-        ctx.send(self.remote_route.clone(), payload).await
+        let remote_route = self
+            .remote_route
+            .read()
+            .map_err(|_| SecureChannelError::InvalidInternalState)?
+            .clone();
+        ctx.send(remote_route, payload).await
     }
 }
 
@@ -86,6 +231,10 @@ impl<V: SecureChannelVault> Worker for SecureChannelEncryptor<V> {
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
-        self.handle_encrypt(ctx, msg).await
+        if msg.msg_addr() == self.control_address {
+            self.handle_control(ctx, msg).await
+        } else {
+            self.handle_encrypt(ctx, msg).await
+        }
     }
 }
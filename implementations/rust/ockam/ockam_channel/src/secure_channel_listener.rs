@@ -1,4 +1,7 @@
-use crate::{SecureChannelDecryptor, SecureChannelNewKeyExchanger, SecureChannelVault};
+use crate::{
+    RekeyPolicy, ReplayCounters, SecureChannelDecryptor, SecureChannelNewKeyExchanger,
+    SecureChannelVault,
+};
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::{async_trait, Mailbox, Mailboxes};
@@ -14,6 +17,8 @@ use tracing::debug;
 pub struct SecureChannelListener<V: SecureChannelVault, N: SecureChannelNewKeyExchanger> {
     new_key_exchanger: N,
     vault: V,
+    rekey_policy: RekeyPolicy,
+    replay_counters: ReplayCounters,
 }
 
 impl<V: SecureChannelVault, N: SecureChannelNewKeyExchanger> SecureChannelListener<V, N> {
@@ -22,8 +27,22 @@ impl<V: SecureChannelVault, N: SecureChannelNewKeyExchanger> SecureChannelListen
         Self {
             new_key_exchanger,
             vault,
+            rekey_policy: RekeyPolicy::default(),
+            replay_counters: ReplayCounters::default(),
         }
     }
+
+    /// Set the [`RekeyPolicy`] of the responder channels.
+    pub fn with_rekey_policy(mut self, rekey_policy: RekeyPolicy) -> Self {
+        self.rekey_policy = rekey_policy;
+        self
+    }
+
+    /// Count messages rejected by any of the responder channels because of their nonce.
+    pub fn with_replay_counters(mut self, replay_counters: ReplayCounters) -> Self {
+        self.replay_counters = replay_counters;
+        self
+    }
 }
 
 /// SecureChannelListener message wrapper.
@@ -78,7 +97,10 @@ impl<V: SecureChannelVault, N: SecureChannelNewKeyExchanger> Worker
 
         let key_exchanger = self.new_key_exchanger.responder().await?;
         let vault = self.vault.async_try_clone().await?;
-        let decryptor = SecureChannelDecryptor::new_responder(key_exchanger, None, vault).await?;
+        let decryptor = SecureChannelDecryptor::new_responder(key_exchanger, None, vault)
+            .await?
+            .with_rekey_policy(self.rekey_policy)
+            .with_replay_counters(self.replay_counters.clone());
 
         let mailbox = Mailbox::new(
             address_remote.clone(),
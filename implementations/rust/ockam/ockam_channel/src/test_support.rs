@@ -0,0 +1,37 @@
+//! Workers to help with testing secure channels.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use ockam_core::compat::{boxed::Box, sync::Arc};
+use ockam_core::{async_trait, Any, Result, Routed, Worker};
+use ockam_node::Context;
+
+/// Forwards messages like a transport connection would, twice while
+/// duplication is switched on
+#[derive(Clone, Default)]
+pub struct Duplicator {
+    duplicate: Arc<AtomicBool>,
+}
+
+impl Duplicator {
+    /// Duplicate messages while `duplicate` is set
+    pub fn new(duplicate: Arc<AtomicBool>) -> Self {
+        Self { duplicate }
+    }
+}
+
+#[async_trait]
+impl Worker for Duplicator {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let mut local_msg = msg.into_local_message();
+        let transport = local_msg.transport_mut();
+        transport.onward_route.step()?;
+        transport.return_route.modify().prepend(ctx.address());
+        if self.duplicate.load(Ordering::Relaxed) {
+            ctx.forward(local_msg.clone()).await?;
+        }
+        ctx.forward(local_msg).await
+    }
+}
@@ -54,14 +54,21 @@ async fn run_impl(
     } else {
         options.state.vaults.default()?.config
     };
-    let vault = vault_config.get().await?;
+    crate::vault::unlock(&vault_config)?;
+    let vault = vault_config
+        .get()
+        .await?
+        .with_audit_caller("ockam identity create");
     let identity = if let Some(kid) = cmd.key_id {
         let attrs = SecretAttributes::new(SecretType::NistP256, SecretPersistence::Persistent, 32);
         let kid = vault
-            .secret_import(Secret::Aws(kid.to_string()), attrs)
+            .secret_import(Secret::Kms(kid.to_string()), attrs)
             .await?;
         let attrs = KeyAttributes::new(IdentityStateConst::ROOT_LABEL.to_string(), attrs);
         Identity::create_ext(&ctx, &vault, &kid, attrs).await?
+    } else if vault_config.pkcs11().is_some() {
+        let attrs = KeyAttributes::nist_p256_with_label(IdentityStateConst::ROOT_LABEL);
+        Identity::create_with_attributes(&ctx, &vault, attrs).await?
     } else {
         Identity::create(&ctx, &vault).await?
     };
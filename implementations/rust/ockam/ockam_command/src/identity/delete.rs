@@ -0,0 +1,27 @@
+use crate::{help, CommandGlobalOpts};
+use clap::Args;
+
+/// Delete an identity
+///
+/// Its keys are left in the vault.
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, after_long_help = help::template(super::HELP_DETAIL))]
+pub struct DeleteCommand {
+    /// Name of the identity to delete
+    name: String,
+}
+
+impl DeleteCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        if let Err(e) = run_impl(options, self) {
+            eprintln!("{}", e);
+            std::process::exit(e.code());
+        }
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: DeleteCommand) -> crate::Result<()> {
+    opts.state.identities.delete(&cmd.name)?;
+    println!("Identity deleted: {}", cmd.name);
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use crate::util::exitcode;
+use crate::{help, CommandGlobalOpts};
+use anyhow::anyhow;
+use clap::Args;
+use ockam_identity::export::IdentityExport;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Export the public part of an identity
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(super::HELP_DETAIL))]
+pub struct ExportCommand {
+    /// Name of the identity to export, the default identity if omitted
+    name: Option<String>,
+
+    /// Write the armored text form instead of binary CBOR
+    #[arg(long)]
+    armor: bool,
+
+    /// File to write the export to, stdout if omitted
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+impl ExportCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        if let Err(e) = run_impl(options, self) {
+            eprintln!("{}", e);
+            std::process::exit(e.code());
+        }
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts, cmd: ExportCommand) -> crate::Result<()> {
+    let state = super::get_identity_state(&opts, cmd.name.as_deref())?;
+    let export = IdentityExport::new(state.config.identifier, &state.config.change_history)?;
+    let data = if cmd.armor {
+        export.to_armored()?.into_bytes()
+    } else {
+        export.to_bytes()?
+    };
+    match cmd.output {
+        Some(path) => std::fs::write(path, data)?,
+        None => {
+            if !cmd.armor && atty::is(atty::Stream::Stdout) {
+                return Err(crate::Error::new(
+                    exitcode::USAGE,
+                    anyhow!("Refusing to write binary data to a terminal, use --armor or --output"),
+                ));
+            }
+            std::io::stdout().write_all(&data)?
+        }
+    }
+    Ok(())
+}
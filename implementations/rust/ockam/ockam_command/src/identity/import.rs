@@ -0,0 +1,54 @@
+use crate::util::node_rpc;
+use crate::{help, CommandGlobalOpts};
+use clap::Args;
+use ockam::Context;
+use ockam_api::cli_state::IdentityConfig;
+use ockam_identity::export::IdentityExport;
+use ockam_vault::Vault;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Import an identity exported with `ockam identity export`
+///
+/// The identity can only be used to create nodes if its keys
+/// are available in the vault of these nodes.
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(super::HELP_DETAIL))]
+pub struct ImportCommand {
+    /// Name to give to the imported identity
+    name: String,
+
+    /// File to read the export from, either binary or armored, stdin if omitted
+    #[arg(long, short)]
+    input: Option<PathBuf>,
+}
+
+impl ImportCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ImportCommand),
+) -> crate::Result<()> {
+    let data = match &cmd.input {
+        Some(path) => std::fs::read(path)?,
+        None => {
+            let mut data = Vec::new();
+            std::io::stdin().read_to_end(&mut data)?;
+            data
+        }
+    };
+    let export = IdentityExport::parse(&data)?;
+    // Verifying the change history only involves public keys
+    let public = export.public_identity(&Vault::create()).await?;
+    let config = IdentityConfig {
+        identifier: public.identifier().clone(),
+        change_history: export.change_history()?,
+    };
+    opts.state.identities.create(&cmd.name, config)?;
+    println!("Identity imported: {}", public.identifier());
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use crate::{help, CommandGlobalOpts, OutputFormat};
+use clap::Args;
+use serde_json::json;
+
+/// List identities
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(super::HELP_DETAIL))]
+pub struct ListCommand;
+
+impl ListCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        if let Err(e) = run_impl(options) {
+            eprintln!("{}", e);
+            std::process::exit(e.code());
+        }
+    }
+}
+
+fn run_impl(opts: CommandGlobalOpts) -> crate::Result<()> {
+    let identities = opts.state.identities.list()?;
+    if opts.global_args.output_format == OutputFormat::Json {
+        let mut list = Vec::with_capacity(identities.len());
+        for (name, state) in &identities {
+            list.push(json!({
+                "name": name,
+                "identifier": state.config.identifier.to_string(),
+                "default": opts.state.identities.is_default(name)?,
+            }));
+        }
+        println!("{}", serde_json::to_string_pretty(&list)?);
+    } else {
+        for (name, state) in &identities {
+            let default = if opts.state.identities.is_default(name)? {
+                " (default)"
+            } else {
+                ""
+            };
+            println!("{name}: {}{default}", state.config.identifier);
+        }
+    }
+    Ok(())
+}
@@ -1,11 +1,36 @@
 mod create;
+mod delete;
+mod export;
+mod import;
+mod list;
+mod rotate_key;
 mod show;
 
 pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use export::ExportCommand;
+pub(crate) use import::ImportCommand;
+pub(crate) use list::ListCommand;
+pub(crate) use rotate_key::RotateKeyCommand;
 pub(crate) use show::ShowCommand;
 
 use crate::CommandGlobalOpts;
 use clap::{Args, Subcommand};
+use ockam_api::cli_state::IdentityState;
+
+const HELP_DETAIL: &str = "\
+About:
+    Identities are exported in a versioned CBOR format containing the identifier and the
+    change history of the identity. No secret key is ever exported. The armored form
+    is the hex encoding of the same data, enclosed in BEGIN/END lines.
+
+```sh
+    $ ockam identity export alice --armor > alice.txt
+    $ ockam identity import alice-copy --input alice.txt
+    $ ockam identity list
+    $ ockam identity rotate-key alice
+```
+";
 
 /// Manage Identities
 #[derive(Clone, Debug, Args)]
@@ -20,6 +45,16 @@ pub enum IdentitySubcommand {
     Create(CreateCommand),
     /// Print short existing identity, `--full` for long identity
     Show(ShowCommand),
+    /// List identities
+    List(ListCommand),
+    /// Delete an identity
+    Delete(DeleteCommand),
+    /// Export the public part of an identity
+    Export(ExportCommand),
+    /// Import an identity exported with `ockam identity export`
+    Import(ImportCommand),
+    /// Rotate a key of an identity
+    RotateKey(RotateKeyCommand),
 }
 
 impl IdentityCommand {
@@ -27,6 +62,22 @@ impl IdentityCommand {
         match self.subcommand {
             IdentitySubcommand::Create(c) => c.run(options),
             IdentitySubcommand::Show(c) => c.run(options),
+            IdentitySubcommand::List(c) => c.run(options),
+            IdentitySubcommand::Delete(c) => c.run(options),
+            IdentitySubcommand::Export(c) => c.run(options),
+            IdentitySubcommand::Import(c) => c.run(options),
+            IdentitySubcommand::RotateKey(c) => c.run(options),
         }
     }
 }
+
+/// Return the identity with the given name, or the default identity
+fn get_identity_state(
+    opts: &CommandGlobalOpts,
+    name: Option<&str>,
+) -> crate::Result<IdentityState> {
+    Ok(match name {
+        Some(name) => opts.state.identities.get(name)?,
+        None => opts.state.identities.default()?,
+    })
+}
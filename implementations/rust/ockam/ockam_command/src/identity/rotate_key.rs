@@ -0,0 +1,55 @@
+use crate::util::node_rpc;
+use crate::{help, CommandGlobalOpts};
+use clap::Args;
+use ockam::Context;
+use ockam_api::cli_state::IdentityConfig;
+
+/// Rotate a key of an identity
+///
+/// Nodes already running with this identity keep using
+/// the previous key until they are restarted.
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(super::HELP_DETAIL))]
+pub struct RotateKeyCommand {
+    /// Name of the identity, the default identity if omitted
+    name: Option<String>,
+
+    /// Label of the key to rotate, the root key if omitted
+    #[arg(long)]
+    label: Option<String>,
+
+    /// Vault holding the keys of the identity, the default vault if omitted
+    #[arg(long)]
+    vault: Option<String>,
+}
+
+impl RotateKeyCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, RotateKeyCommand),
+) -> crate::Result<()> {
+    let state = super::get_identity_state(&opts, cmd.name.as_deref())?;
+    let vault_config = match &cmd.vault {
+        Some(vault_name) => opts.state.vaults.get(vault_name)?.config,
+        None => opts.state.vaults.default()?.config,
+    };
+    crate::vault::unlock(&vault_config)?;
+    let vault = vault_config
+        .get()
+        .await?
+        .with_audit_caller("ockam identity rotate-key");
+    let identity = state.config.get(&ctx, &vault).await?;
+    match &cmd.label {
+        Some(label) => identity.rotate_key(label).await?,
+        None => identity.rotate_root_key().await?,
+    }
+    let config = IdentityConfig::new(&identity).await;
+    opts.state.identities.update(&state.name()?, config)?;
+    println!("Key rotated for identity: {}", identity.identifier());
+    Ok(())
+}
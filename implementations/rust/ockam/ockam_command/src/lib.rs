@@ -23,6 +23,7 @@ mod space;
 mod subscription;
 mod tcp;
 mod terminal;
+mod udp;
 mod upgrade;
 mod util;
 mod vault;
@@ -49,6 +50,7 @@ use tcp::{
     connection::TcpConnectionCommand, inlet::TcpInletCommand, listener::TcpListenerCommand,
     outlet::TcpOutletCommand,
 };
+use udp::{inlet::UdpInletCommand, outlet::UdpOutletCommand};
 use util::{exitcode, exitcode::ExitCode, setup_logging, OckamConfig};
 use vault::VaultCommand;
 use version::Version;
@@ -256,6 +258,8 @@ pub enum OckamSubcommand {
     TcpOutlet(TcpOutletCommand),
     #[command(display_order = 816)]
     TcpInlet(TcpInletCommand),
+    UdpOutlet(UdpOutletCommand),
+    UdpInlet(UdpInletCommand),
     #[command(display_order = 817)]
     SecureChannelListener(SecureChannelListenerCommand),
     #[command(display_order = 818)]
@@ -326,6 +330,8 @@ impl OckamCommand {
             OckamSubcommand::TcpInlet(c) => c.run(options),
             OckamSubcommand::TcpListener(c) => c.run(options),
             OckamSubcommand::TcpOutlet(c) => c.run(options),
+            OckamSubcommand::UdpInlet(c) => c.run(options),
+            OckamSubcommand::UdpOutlet(c) => c.run(options),
             OckamSubcommand::Vault(c) => c.run(options),
             OckamSubcommand::Identity(c) => c.run(options),
             OckamSubcommand::SecureChannel(c) => c.run(options),
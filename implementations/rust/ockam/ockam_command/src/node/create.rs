@@ -230,6 +230,9 @@ async fn run_foreground_node(
             cmd.key_id.as_ref(),
         )
         .await?;
+    } else {
+        let node_state = opts.state.nodes.get(&cmd.node_name)?;
+        crate::vault::unlock_from_stdin(&node_state.config.vault_config()?)?;
     }
 
     let project_id = match &cmd.project {
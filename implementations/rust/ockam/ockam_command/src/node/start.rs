@@ -32,6 +32,7 @@ async fn run_impl(
     let node_state = opts.state.nodes.get(node_name)?;
     node_state.kill_process(false)?;
     let node_setup = node_state.setup()?;
+    crate::vault::unlock(&node_state.config.vault_config()?)?;
 
     // Restart node
     spawn_node(
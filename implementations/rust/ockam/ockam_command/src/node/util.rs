@@ -103,6 +103,7 @@ pub(super) async fn init_node_state(
         let c = cli_state::VaultConfig::fs_default(&n, aws)?;
         opts.state.vaults.create(&n, c).await?
     };
+    crate::vault::unlock(&vault_state.config)?;
 
     // Get identity specified in the argument
     let identity_state = if let Some(idt) = identity {
@@ -118,10 +119,13 @@ pub(super) async fn init_node_state(
             let attrs =
                 SecretAttributes::new(SecretType::NistP256, SecretPersistence::Persistent, 32);
             let kid = vault
-                .secret_import(Secret::Aws(kid.to_string()), attrs)
+                .secret_import(Secret::Kms(kid.to_string()), attrs)
                 .await?;
             let attrs = KeyAttributes::new(IdentityStateConst::ROOT_LABEL.to_string(), attrs);
             Identity::create_ext(ctx, &vault, &kid, attrs).await?
+        } else if vault_state.config.pkcs11().is_some() {
+            let attrs = KeyAttributes::nist_p256_with_label(IdentityStateConst::ROOT_LABEL);
+            Identity::create_with_attributes(ctx, &vault, attrs).await?
         } else {
             Identity::create(ctx, &vault).await?
         };
@@ -112,6 +112,9 @@ async fn create_secure_channel_to_project(
         project_access_route,
         Some(authorized_identifier),
         credential_exchange_mode,
+        false,
+        false,
+        None,
     ))
     .await?;
     let sc = rpc.parse_response::<CreateSecureChannelResponse>()?;
@@ -132,6 +135,9 @@ pub async fn create_secure_channel_to_authority(
         addr,
         Some(allowed),
         CredentialExchangeMode::None,
+        false,
+        false,
+        None,
     ))
     .await?;
     let res = rpc.parse_response::<CreateSecureChannelResponse>()?;
@@ -37,6 +37,19 @@ pub struct CreateCommand {
     #[arg(value_name = "IDENTIFIER", long, short, display_order = 801)]
     pub authorized: Option<Vec<IdentityIdentifier>>,
 
+    /// Pin the identity presented by the listener the first time and refuse any other later on
+    #[arg(long, display_order = 802)]
+    pub trust_on_first_use: bool,
+
+    /// Mix a post-quantum key encapsulation (Kyber768) into the handshake, next to X25519
+    #[arg(long, display_order = 803)]
+    pub post_quantum: bool,
+
+    /// Hex encoded X25519 key of the listener to save a round trip with a Noise IK handshake.
+    /// Only trusted for the identity of the listener, given as the single `--authorized` one
+    #[arg(value_name = "HEX", long, requires = "authorized", display_order = 804)]
+    pub ik_key: Option<String>,
+
     /// Orchestrator address to resolve projects present in the `at` argument
     #[command(flatten)]
     cloud_opts: CloudOpts,
@@ -163,8 +176,14 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
 
     // Delegate the request to create a secure channel to the from node.
     let mut rpc = RpcBuilder::new(&ctx, &opts, from).tcp(&tcp)?.build();
-    let request =
-        api::create_secure_channel(to, authorized_identifiers, CredentialExchangeMode::Mutual);
+    let request = api::create_secure_channel(
+        to,
+        authorized_identifiers,
+        CredentialExchangeMode::Mutual,
+        cmd.trust_on_first_use,
+        cmd.post_quantum,
+        cmd.ik_key.clone(),
+    );
 
     rpc.request(request).await?;
     let response = rpc.parse_response::<CreateSecureChannelResponse>()?;
@@ -1,8 +1,10 @@
 use anyhow::anyhow;
 use clap::Args;
+use std::time::Duration;
 
 use ockam::identity::IdentityIdentifier;
 use ockam::Context;
+use ockam_abac::Expr;
 use ockam_api::nodes::models::secure_channel::CreateSecureChannelListenerRequest;
 use ockam_api::nodes::NODEMANAGER_ADDR;
 use ockam_core::api::{Request, Status};
@@ -25,6 +27,27 @@ pub struct CreateCommand {
     /// Authorized Identifiers of secure channel initiators
     #[arg(short, long, value_name = "IDENTIFIERS")]
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
+
+    /// Policy expression the attributes of secure channel initiators must satisfy,
+    /// e.g. '(= subject.role "edge")'
+    #[arg(short, long, value_name = "EXPRESSION")]
+    trust_policy: Option<Expr>,
+
+    /// Maximum number of channels, including the ones still in their handshake
+    #[arg(long, value_name = "COUNT")]
+    max_channels: Option<u64>,
+
+    /// Maximum number of channels of a single initiator identity
+    #[arg(long, value_name = "COUNT")]
+    max_channels_per_identity: Option<u64>,
+
+    /// Abort handshakes which are not completed after that many seconds
+    #[arg(long, value_name = "SECONDS")]
+    handshake_timeout: Option<u64>,
+
+    /// Id of an X25519 key of the node's vault to also accept Noise IK handshakes with
+    #[arg(long, value_name = "KEY_ID")]
+    ik_key_id: Option<String>,
 }
 
 #[derive(Clone, Debug, Args)]
@@ -51,7 +74,14 @@ async fn run_impl(
     let node = extract_address_value(&cmd.node_opts.at)?;
     let mut rpc = Rpc::background(ctx, &opts, &node)?;
     let req = Request::post("/node/secure_channel_listener").body(
-        CreateSecureChannelListenerRequest::new(&cmd.address, cmd.authorized_identifiers),
+        CreateSecureChannelListenerRequest::new(&cmd.address, cmd.authorized_identifiers)
+            .with_trust_policy(cmd.trust_policy)
+            .with_limits(
+                cmd.max_channels,
+                cmd.max_channels_per_identity,
+                cmd.handshake_timeout.map(Duration::from_secs),
+            )
+            .with_ik_static_key(cmd.ik_key_id),
     );
     rpc.request(req).await?;
     match rpc.is_ok() {
@@ -1,8 +1,10 @@
 pub mod create;
 pub mod list;
+pub mod show;
 
 pub(crate) use create::CreateCommand;
 pub(crate) use list::ListCommand;
+pub(crate) use show::ShowCommand;
 
 use crate::secure_channel::HELP_DETAIL;
 use crate::{help, CommandGlobalOpts};
@@ -26,6 +28,8 @@ pub enum SecureChannelListenerSubcommand {
     Create(CreateCommand),
     #[command(display_order = 800)]
     List(ListCommand),
+    #[command(display_order = 800)]
+    Show(ShowCommand),
 }
 
 impl SecureChannelListenerCommand {
@@ -33,6 +37,7 @@ impl SecureChannelListenerCommand {
         match self.subcommand {
             SecureChannelListenerSubcommand::Create(c) => c.run(options),
             SecureChannelListenerSubcommand::List(c) => c.run(options),
+            SecureChannelListenerSubcommand::Show(c) => c.run(options),
         }
     }
 }
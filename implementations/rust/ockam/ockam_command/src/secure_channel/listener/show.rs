@@ -0,0 +1,37 @@
+use clap::Args;
+
+use ockam::Context;
+use ockam_api::nodes::models::secure_channel::ShowSecureChannelListenerResponse;
+use ockam_core::Address;
+
+use crate::node::NodeOpts;
+use crate::secure_channel::HELP_DETAIL;
+use crate::util::{api, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts};
+
+/// Show the limits of a Secure Channel Listener and the channels it rejected
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, after_long_help = help::template(HELP_DETAIL))]
+pub struct ShowCommand {
+    /// Node of the listener
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Address of the listener
+    address: Address,
+}
+
+impl ShowCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, ShowCommand)) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_opts.api_node)?;
+    rpc.request(api::show_secure_channel_listener(&cmd.address))
+        .await?;
+    let response = rpc.parse_response::<ShowSecureChannelListenerResponse>()?;
+    rpc.print_response(response)?;
+    Ok(())
+}
@@ -3,11 +3,13 @@ pub(crate) mod listener;
 mod create;
 mod delete;
 mod list;
+mod pinned;
 mod show;
 
 pub use create::CreateCommand;
 pub use delete::DeleteCommand;
 pub use list::ListCommand;
+pub use pinned::PinnedCommand;
 pub use show::ShowCommand;
 
 use crate::{help, CommandGlobalOpts};
@@ -119,6 +121,21 @@ About:
 ```
 
 
+    Trust on first use
+    ------
+
+    Instead of listing authorized identifiers up front, the identity presented by the
+    listener can be pinned the first time a channel is created to a given route. Later
+    channels to that route are refused if the listener presents a different identity.
+
+```sh
+    $ ockam secure-channel create --from /node/n1 --to /node/n2/service/api --trust-on-first-use
+    $ ockam secure-channel pinned list --at n1
+    /ip4/127.0.0.1/tcp/6002/service/api: Pe86be15e83d1c93e24dd1a7b9f1ffb72ca66e1ed9d8e2c2ea2b7e0a3b2f5e6c1
+    $ ockam secure-channel pinned forget /ip4/127.0.0.1/tcp/6002/service/api --at n1
+```
+
+
     Custom Secure Channel Listeners
     ------
 
@@ -158,6 +175,8 @@ enum SecureChannelSubcommand {
     List(ListCommand),
     #[command(display_order = 800)]
     Show(ShowCommand),
+    #[command(display_order = 800)]
+    Pinned(PinnedCommand),
 }
 
 impl SecureChannelCommand {
@@ -167,6 +186,7 @@ impl SecureChannelCommand {
             SecureChannelSubcommand::Delete(c) => c.run(options),
             SecureChannelSubcommand::List(c) => c.run(options),
             SecureChannelSubcommand::Show(c) => c.run(options),
+            SecureChannelSubcommand::Pinned(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,65 @@
+use crate::secure_channel::HELP_DETAIL;
+use crate::util::{api, extract_address_value, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts, OutputFormat, Result};
+use clap::{Args, Subcommand};
+use ockam::Context;
+use ockam_api::nodes::models::secure_channel::PinnedIdentityList;
+
+/// Manage identities pinned by trust-on-first-use secure channels
+#[derive(Clone, Debug, Args)]
+#[command(arg_required_else_help = true, after_long_help = help::template(HELP_DETAIL))]
+pub struct PinnedCommand {
+    #[command(subcommand)]
+    subcommand: PinnedSubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum PinnedSubcommand {
+    /// List pinned identities
+    List {
+        /// Node at which the identities were pinned
+        #[arg(value_name = "NODE", long, display_order = 800)]
+        at: String,
+    },
+    /// Forget a pinned identity, the next identity seen for that route gets pinned
+    Forget {
+        /// Node at which the identity was pinned
+        #[arg(value_name = "NODE", long, display_order = 800)]
+        at: String,
+
+        /// Route the identity was pinned to, as shown by `list`
+        #[arg(value_name = "ROUTE", display_order = 800)]
+        name: String,
+    },
+}
+
+impl PinnedCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(rpc, (opts, self))
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, PinnedCommand)) -> Result<()> {
+    match cmd.subcommand {
+        PinnedSubcommand::List { at } => {
+            let node = extract_address_value(&at)?;
+            let mut rpc = Rpc::background(&ctx, &opts, &node)?;
+            rpc.request(api::list_pinned_identities()).await?;
+            let pins: PinnedIdentityList = rpc.parse_response()?;
+            if opts.global_args.output_format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&pins.list)?);
+            } else {
+                for pin in &pins.list {
+                    println!("{}: {}", pin.name, pin.identifier)
+                }
+            }
+        }
+        PinnedSubcommand::Forget { at, name } => {
+            let node = extract_address_value(&at)?;
+            let mut rpc = Rpc::background(&ctx, &opts, &node)?;
+            rpc.request(api::forget_pinned_identity(&name)).await?;
+            rpc.is_ok()?
+        }
+    }
+    Ok(())
+}
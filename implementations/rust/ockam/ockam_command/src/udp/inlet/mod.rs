@@ -0,0 +1,25 @@
+mod create;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+use create::CreateCommand;
+
+/// Manage UDP Inlets
+#[derive(Clone, Debug, Args)]
+pub struct UdpInletCommand {
+    #[command(subcommand)]
+    subcommand: UdpInletSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum UdpInletSubCommand {
+    Create(CreateCommand),
+}
+
+impl UdpInletCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            UdpInletSubCommand::Create(c) => c.run(options),
+        }
+    }
+}
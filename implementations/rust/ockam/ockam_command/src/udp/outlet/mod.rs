@@ -0,0 +1,25 @@
+mod create;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+use create::CreateCommand;
+
+/// Manage UDP Outlets
+#[derive(Clone, Debug, Args)]
+pub struct UdpOutletCommand {
+    #[command(subcommand)]
+    subcommand: UdpOutletSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum UdpOutletSubCommand {
+    Create(CreateCommand),
+}
+
+impl UdpOutletCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            UdpOutletSubCommand::Create(c) => c.run(options),
+        }
+    }
+}
@@ -82,15 +82,34 @@ pub(crate) fn create_secure_channel(
     addr: &MultiAddr,
     authorized_identifiers: Option<Vec<IdentityIdentifier>>,
     credential_exchange_mode: CredentialExchangeMode,
+    trust_on_first_use: bool,
+    hybrid_kem: bool,
+    ik_remote_static_key: Option<String>,
 ) -> RequestBuilder<'static, models::secure_channel::CreateSecureChannelRequest<'static>> {
     let payload = models::secure_channel::CreateSecureChannelRequest::new(
         addr,
         authorized_identifiers,
         credential_exchange_mode,
-    );
+    )
+    .with_trust_on_first_use(trust_on_first_use)
+    .with_hybrid_kem(hybrid_kem)
+    .with_ik_remote_static_key(ik_remote_static_key);
     Request::post("/node/secure_channel").body(payload)
 }
 
+/// Construct a request to list the identities pinned by trust-on-first-use secure channels
+pub(crate) fn list_pinned_identities() -> RequestBuilder<'static, ()> {
+    Request::get("/node/pinned_identities")
+}
+
+/// Construct a request to forget the identity pinned to the given route
+pub(crate) fn forget_pinned_identity(
+    name: &str,
+) -> RequestBuilder<'static, models::secure_channel::ForgetPinnedIdentityRequest<'static>> {
+    let payload = models::secure_channel::ForgetPinnedIdentityRequest::new(name.to_string());
+    Request::delete("/node/pinned_identities").body(payload)
+}
+
 pub(crate) fn delete_secure_channel(
     addr: &Address,
 ) -> RequestBuilder<'static, models::secure_channel::DeleteSecureChannelRequest<'static>> {
@@ -127,6 +146,14 @@ pub(crate) fn list_secure_channel_listener() -> RequestBuilder<'static, ()> {
     Request::get("/node/secure_channel_listener")
 }
 
+/// Construct a request to show the limits of a Secure Channel Listener
+pub(crate) fn show_secure_channel_listener(
+    addr: &Address,
+) -> RequestBuilder<'static, models::secure_channel::ShowSecureChannelListenerRequest<'static>> {
+    let payload = models::secure_channel::ShowSecureChannelListenerRequest::new(addr);
+    Request::get("/node/show_secure_channel_listener").body(payload)
+}
+
 /// Construct a request to start a Vault Service
 pub(crate) fn start_vault_service(addr: &str) -> RequestBuilder<'static, StartVaultServiceRequest> {
     let payload = StartVaultServiceRequest::new(addr);
@@ -9,7 +9,7 @@ use crate::util::comma_separated;
 use colorful::Colorful;
 use ockam_api::cloud::space::Space;
 use ockam_api::nodes::models::secure_channel::{
-    CreateSecureChannelResponse, ShowSecureChannelResponse,
+    CreateSecureChannelResponse, ShowSecureChannelListenerResponse, ShowSecureChannelResponse,
 };
 use ockam_api::route_to_multiaddr;
 use ockam_core::route;
@@ -169,7 +169,7 @@ impl Output for ShowSecureChannelResponse<'_> {
     fn output(&self) -> anyhow::Result<String> {
         let s = match &self.channel {
             Some(addr) => {
-                format!(
+                let mut s = format!(
                     "\n  Secure Channel:\n{} {}\n{} {}\n{} {}",
                     "  •         At: ".light_magenta(),
                     route_to_multiaddr(&route![addr.to_string()])
@@ -186,7 +186,66 @@ impl Output for ShowSecureChannelResponse<'_> {
                         .map(|id| id.light_yellow().to_string())
                         .collect::<Vec<String>>()
                         .join("\n\t")
-                )
+                );
+                let unknown = || "unknown".to_string();
+                let stats = [
+                    (
+                        "  •       Peer: ",
+                        self.their_identity_id
+                            .as_ref()
+                            .map(|id| id.to_string())
+                            .unwrap_or_else(unknown),
+                    ),
+                    (
+                        "  •   Exchange: ",
+                        self.key_exchange
+                            .as_ref()
+                            .map(|k| k.to_string())
+                            .unwrap_or_else(unknown),
+                    ),
+                    (
+                        "  •    Created: ",
+                        self.created_at
+                            .map(|t| t.to_string())
+                            .unwrap_or_else(unknown),
+                    ),
+                    (
+                        "  •     Active: ",
+                        self.last_activity
+                            .map(|t| t.to_string())
+                            .unwrap_or_else(unknown),
+                    ),
+                    (
+                        "  •       Sent: ",
+                        format!(
+                            "{} messages, {} bytes",
+                            self.messages_encrypted.unwrap_or(0),
+                            self.bytes_encrypted.unwrap_or(0)
+                        ),
+                    ),
+                    (
+                        "  •   Received: ",
+                        format!(
+                            "{} messages, {} bytes",
+                            self.messages_decrypted.unwrap_or(0),
+                            self.bytes_decrypted.unwrap_or(0)
+                        ),
+                    ),
+                    (
+                        "  •   Rejected: ",
+                        format!("{} messages", self.decryption_failures.unwrap_or(0)),
+                    ),
+                    (
+                        "  • Credential: ",
+                        self.credential_exchange_mode
+                            .map(|mode| format!("{:?}", mode).to_lowercase())
+                            .unwrap_or_else(|| "none".to_string()),
+                    ),
+                ];
+                for (label, value) in stats {
+                    write!(s, "\n{} {}", label.light_magenta(), value.light_yellow())?;
+                }
+                s
             }
             None => format!("{}", "Channel not found".red()),
         };
@@ -195,6 +254,51 @@ impl Output for ShowSecureChannelResponse<'_> {
     }
 }
 
+impl Output for ShowSecureChannelListenerResponse<'_> {
+    fn output(&self) -> anyhow::Result<String> {
+        let addr = match &self.addr {
+            Some(addr) => addr,
+            None => return Ok(format!("{}", "Listener not found".red())),
+        };
+        let unlimited = || "unlimited".to_string();
+        let mut s = String::from("\n  Secure Channel Listener:");
+        let lines = [
+            ("  •                At: ", format!("/service/{}", addr)),
+            (
+                "  •      Max channels: ",
+                self.max_channels
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(unlimited),
+            ),
+            (
+                "  •  Max per identity: ",
+                self.max_channels_per_identity
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(unlimited),
+            ),
+            (
+                "  • Handshake timeout: ",
+                self.handshake_timeout
+                    .map(|t| format!("{}s", t.as_secs()))
+                    .unwrap_or_else(|| "none".to_string()),
+            ),
+            (
+                "  •          Rejected: ",
+                format!(
+                    "{} (too many channels), {} (too many for identity), {} (handshake timeout)",
+                    self.rejected_too_many_channels,
+                    self.rejected_too_many_for_identity,
+                    self.rejected_handshake_timeouts
+                ),
+            ),
+        ];
+        for (label, value) in lines {
+            write!(s, "\n{} {}", label.light_magenta(), value.light_yellow())?;
+        }
+        Ok(s)
+    }
+}
+
 impl Output for Enroller<'_> {
     fn output(&self) -> anyhow::Result<String> {
         let mut w = String::new();
@@ -8,8 +8,9 @@ use anyhow::Context;
 use nix::sys::signal::{self, Signal};
 use nix::unistd::Pid;
 use ockam_api::authenticator::direct::types::OneTimeCode;
+use ockam_api::cli_state::VaultConfig;
 use std::collections::VecDeque;
-use std::io::Stdout;
+use std::io::{Stdout, Write};
 use std::process::Stdio;
 use std::{
     env::current_exe,
@@ -104,11 +105,24 @@ pub fn spawn_node(
 
     args.push(name.to_owned());
 
-    let child = Command::new(ockam_exe)
+    let mut command = Command::new(ockam_exe);
+    command
         .args(args)
         .stdout(main_log_file)
-        .stderr(stderr_log_file)
-        .spawn()?;
+        .stderr(stderr_log_file);
+
+    // The passphrase of the vault is handed over on stdin rather than in the
+    // environment, which other processes of the user can read
+    let passphrase = node_state.config.vault_config()?.passphrase();
+    if passphrase.is_some() {
+        command
+            .env_remove(VaultConfig::PASSPHRASE_ENV)
+            .stdin(Stdio::piped());
+    }
+    let mut child = command.spawn()?;
+    if let (Some(passphrase), Some(mut stdin)) = (passphrase, child.stdin.take()) {
+        stdin.write_all(passphrase.as_bytes())?;
+    }
     node_state.set_pid(child.id() as i32)?;
 
     Ok(())
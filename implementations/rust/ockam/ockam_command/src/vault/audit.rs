@@ -0,0 +1,57 @@
+use crate::util::{exitcode, node_rpc};
+use crate::{CommandGlobalOpts, OutputFormat, Result};
+use anyhow::anyhow;
+use clap::Args;
+use ockam::Context;
+use ockam_vault::audit::FileAuditLog;
+
+/// Show the audit log of a vault, checking that it wasn't altered
+#[derive(Clone, Debug, Args)]
+pub struct AuditCommand {
+    /// Name of the vault, the default vault if omitted
+    name: Option<String>,
+
+    /// Only show the operations on this key
+    #[arg(long, value_name = "KEY_ID")]
+    key_id: Option<String>,
+}
+
+impl AuditCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(_ctx: Context, (options, cmd): (CommandGlobalOpts, AuditCommand)) -> Result<()> {
+    let state = match &cmd.name {
+        Some(name) => options.state.vaults.get(name)?,
+        None => options.state.vaults.default()?,
+    };
+    let path = state.config.audit_log().ok_or_else(|| {
+        crate::Error::new(
+            exitcode::CONFIG,
+            anyhow!("The vault has no audit log, create it with --audit to record one"),
+        )
+    })?;
+    let records: Vec<_> = FileAuditLog::read(path)
+        .await?
+        .into_iter()
+        .filter(|r| cmd.key_id.as_ref().map_or(true, |id| r.key_id() == id))
+        .collect();
+
+    if options.global_args.output_format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    } else {
+        for r in &records {
+            println!(
+                "{} {} {} ({}) by {}",
+                r.timestamp(),
+                r.operation(),
+                r.key_id(),
+                r.attributes(),
+                r.caller().unwrap_or("unknown caller")
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use crate::util::node_rpc;
+use crate::vault::{passphrase, unlock, BACKUP_PASSPHRASE_ENV};
+use crate::CommandGlobalOpts;
+use crate::Result;
+use clap::Args;
+use ockam::Context;
+use ockam_vault::storage::FileStorageKey;
+use std::path::PathBuf;
+
+/// Write an encrypted backup of the keys of a vault
+#[derive(Clone, Debug, Args)]
+pub struct BackupCommand {
+    /// Name of the vault, the default vault if omitted
+    name: Option<String>,
+
+    /// File to write the backup to
+    #[arg(long, short)]
+    output: PathBuf,
+}
+
+impl BackupCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(_ctx: Context, (options, cmd): (CommandGlobalOpts, BackupCommand)) -> Result<()> {
+    let state = match &cmd.name {
+        Some(name) => options.state.vaults.get(name)?,
+        None => options.state.vaults.default()?,
+    };
+    unlock(&state.config)?;
+    let vault = state
+        .config
+        .get()
+        .await?
+        .with_audit_caller("ockam vault backup");
+    let key = FileStorageKey::Passphrase(passphrase(BACKUP_PASSPHRASE_ENV, true)?);
+    let backup = vault.backup(&key).await?;
+    std::fs::write(&cmd.output, serde_json::to_vec(&backup)?)?;
+    println!("Vault backup written to {}", cmd.output.display());
+    Ok(())
+}
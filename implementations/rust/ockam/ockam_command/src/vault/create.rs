@@ -1,10 +1,12 @@
 use crate::util::node_rpc;
+use crate::vault::passphrase;
 use crate::CommandGlobalOpts;
 use crate::Result;
 use clap::Args;
 use ockam::Context;
-use ockam_api::cli_state;
+use ockam_api::cli_state::{self, Pkcs11Config, VaultEncryption};
 use rand::prelude::random;
+use std::path::PathBuf;
 
 /// Create vaults
 #[derive(Clone, Debug, Args)]
@@ -18,6 +20,35 @@ pub struct CreateCommand {
 
     #[arg(long, default_value = "false")]
     aws_kms: bool,
+
+    /// Encrypt the Vault storage file with a passphrase, read from
+    /// OCKAM_VAULT_PASSPHRASE or prompted for
+    #[arg(long, default_value = "false", conflicts_with = "keyfile")]
+    encrypted: bool,
+
+    /// Encrypt the Vault storage file with a key derived from a file
+    /// of at least 32 random bytes
+    #[arg(long, value_name = "PATH")]
+    keyfile: Option<PathBuf>,
+
+    /// Keep identity keys on a PKCS#11 token, using the library at this path
+    /// and the user PIN from OCKAM_PKCS11_PIN
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "slot",
+        conflicts_with = "aws_kms"
+    )]
+    pkcs11_module: Option<PathBuf>,
+
+    /// Slot of the PKCS#11 token
+    #[arg(long, value_name = "ID", requires = "pkcs11_module")]
+    slot: Option<u64>,
+
+    /// Record the operations on the keys of the vault in an audit log,
+    /// shown by `ockam vault audit`
+    #[arg(long, default_value = "false")]
+    audit: bool,
 }
 
 impl CreateCommand {
@@ -28,7 +59,22 @@ impl CreateCommand {
 
 async fn run_impl(_ctx: Context, (options, cmd): (CommandGlobalOpts, CreateCommand)) -> Result<()> {
     let path = cli_state::VaultConfig::fs_path(&cmd.name, cmd.path)?;
-    let config = cli_state::VaultConfig::fs(path, cmd.aws_kms)?;
+    let mut config = cli_state::VaultConfig::fs(path, cmd.aws_kms)?;
+    if cmd.encrypted {
+        config = config.with_encryption(VaultEncryption::Passphrase);
+        config.unlock(passphrase(cli_state::VaultConfig::PASSPHRASE_ENV, true)?);
+    } else if let Some(keyfile) = cmd.keyfile {
+        config = config.with_encryption(VaultEncryption::Keyfile(std::fs::canonicalize(keyfile)?));
+    }
+    if let (Some(module), Some(slot)) = (cmd.pkcs11_module, cmd.slot) {
+        config = config.with_pkcs11(Pkcs11Config {
+            module: std::fs::canonicalize(module)?,
+            slot,
+        });
+    }
+    if cmd.audit {
+        config = config.with_audit_log(cli_state::VaultConfig::audit_log_path(&cmd.name)?);
+    }
     options
         .state
         .vaults
@@ -0,0 +1,63 @@
+use crate::util::node_rpc;
+use crate::vault::unlock;
+use crate::CommandGlobalOpts;
+use crate::Result;
+use clap::Args;
+use ockam::Context;
+
+/// Copy the keys of a vault to another one, e.g. backed by a PKCS#11 token
+/// or a KMS, and switch the nodes using the first vault to the second one
+#[derive(Clone, Debug, Args)]
+pub struct MigrateCommand {
+    /// Name of the vault to copy the keys from
+    from: String,
+
+    /// Name of the vault to copy the keys to
+    to: String,
+
+    /// Delete the keys from the first vault once they are all copied
+    #[arg(long)]
+    delete_source: bool,
+}
+
+impl MigrateCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (options, cmd): (CommandGlobalOpts, MigrateCommand),
+) -> Result<()> {
+    let from = options.state.vaults.get(&cmd.from)?;
+    let to = options.state.vaults.get(&cmd.to)?;
+    unlock(&from.config)?;
+    unlock(&to.config)?;
+    let key_ids = from
+        .config
+        .get()
+        .await?
+        .with_audit_caller("ockam vault migrate")
+        .migrate_to(
+            &to.config
+                .get()
+                .await?
+                .with_audit_caller("ockam vault migrate"),
+            cmd.delete_source,
+        )
+        .await?;
+    println!("Copied {} keys to vault {}", key_ids.len(), &cmd.to);
+    if !cmd.delete_source {
+        eprintln!(
+            "Vault {} still holds the keys, delete it or migrate with --delete-source",
+            &cmd.from
+        );
+    }
+
+    // Key ids don't change, so identities keep working with the new vault
+    for node in options.state.replace_vault(&from, &to)? {
+        println!("Node {node} now uses vault {}", &cmd.to);
+    }
+    Ok(())
+}
@@ -1,10 +1,23 @@
+mod audit;
+mod backup;
 mod create;
+mod migrate;
+mod restore;
 
+pub(crate) use audit::AuditCommand;
+pub(crate) use backup::BackupCommand;
 pub(crate) use create::CreateCommand;
+pub(crate) use migrate::MigrateCommand;
+pub(crate) use restore::RestoreCommand;
 
 use crate::help;
+use crate::util::exitcode;
 use crate::CommandGlobalOpts;
+use anyhow::anyhow;
 use clap::{Args, Subcommand};
+use dialoguer::Password;
+use ockam_api::cli_state::{VaultConfig, VaultEncryption};
+use std::io::Read;
 
 #[derive(Clone, Debug, Args)]
 #[command(hide = help::hide())]
@@ -16,12 +29,63 @@ pub struct VaultCommand {
 #[derive(Clone, Debug, Subcommand)]
 pub enum VaultSubcommand {
     Create(CreateCommand),
+    Backup(BackupCommand),
+    Restore(RestoreCommand),
+    Migrate(MigrateCommand),
+    Audit(AuditCommand),
 }
 
 impl VaultCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         match self.subcommand {
             VaultSubcommand::Create(c) => c.run(options),
+            VaultSubcommand::Backup(c) => c.run(options),
+            VaultSubcommand::Restore(c) => c.run(options),
+            VaultSubcommand::Migrate(c) => c.run(options),
+            VaultSubcommand::Audit(c) => c.run(options),
         }
     }
 }
+
+/// Environment variable holding the passphrase of vault backups
+pub(crate) const BACKUP_PASSPHRASE_ENV: &str = "OCKAM_VAULT_BACKUP_PASSPHRASE";
+
+/// Read a passphrase from the `env` environment variable, or prompt for
+/// it when running in a terminal
+pub(crate) fn passphrase(env: &str, confirm: bool) -> crate::Result<String> {
+    if let Ok(passphrase) = std::env::var(env) {
+        return Ok(passphrase);
+    }
+    if !atty::is(atty::Stream::Stdin) {
+        return Err(crate::Error::new(
+            exitcode::CONFIG,
+            anyhow!("A passphrase is needed, set {env} to provide it"),
+        ));
+    }
+    let mut prompt = Password::new();
+    prompt.with_prompt("Passphrase");
+    if confirm {
+        prompt.with_confirmation("Repeat the passphrase", "The passphrases don't match");
+    }
+    Ok(prompt.interact()?)
+}
+
+/// Unlock a vault for this process before it is opened, see
+/// [`VaultConfig::unlock`]
+pub(crate) fn unlock(config: &VaultConfig) -> crate::Result<()> {
+    if config.encryption() == Some(&VaultEncryption::Passphrase) && config.passphrase().is_none() {
+        config.unlock(passphrase(VaultConfig::PASSPHRASE_ENV, false)?);
+    }
+    Ok(())
+}
+
+/// Unlock the vault of a node process, whose passphrase is written to its
+/// stdin by [`spawn_node`](crate::util::startup::spawn_node)
+pub(crate) fn unlock_from_stdin(config: &VaultConfig) -> crate::Result<()> {
+    if config.encryption() == Some(&VaultEncryption::Passphrase) {
+        let mut passphrase = String::new();
+        std::io::stdin().read_to_string(&mut passphrase)?;
+        config.unlock(passphrase);
+    }
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use crate::util::node_rpc;
+use crate::vault::{passphrase, unlock, BACKUP_PASSPHRASE_ENV};
+use crate::CommandGlobalOpts;
+use crate::Result;
+use clap::Args;
+use ockam::Context;
+use ockam_vault::storage::{FileStorageKey, VaultBackup};
+use std::path::PathBuf;
+
+/// Restore the keys of a backup into a vault
+#[derive(Clone, Debug, Args)]
+pub struct RestoreCommand {
+    /// Name of the vault, e.g. a freshly created one
+    name: String,
+
+    /// File to read the backup from
+    #[arg(long, short)]
+    input: PathBuf,
+}
+
+impl RestoreCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (options, cmd): (CommandGlobalOpts, RestoreCommand),
+) -> Result<()> {
+    let backup: VaultBackup = serde_json::from_slice(&std::fs::read(&cmd.input)?)?;
+    let state = options.state.vaults.get(&cmd.name)?;
+    unlock(&state.config)?;
+    let vault = state.config.get().await?;
+    let key = FileStorageKey::Passphrase(passphrase(BACKUP_PASSPHRASE_ENV, false)?);
+    let key_ids = vault.restore(&backup, &key).await?;
+    println!("Restored {} keys into vault {}", key_ids.len(), &cmd.name);
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use crate::vault::{KeyId, PublicKey};
+use crate::compat::vec::Vec;
+use crate::vault::{unsupported, KeyId, PublicKey};
 use crate::Result;
 use crate::{async_trait, compat::boxed::Box};
 
@@ -12,4 +13,25 @@ pub trait AsymmetricVault {
 
     /// Compute and return the `KeyId` for a given public key.
     async fn compute_key_id_for_public_key(&self, public_key: &PublicKey) -> Result<KeyId>;
+
+    /// Encapsulate a fresh shared secret to the specified public key of a key
+    /// encapsulation mechanism. Returns the ciphertext to send to the owner of
+    /// the key, and the shared secret as an ephemeral buffer.
+    ///
+    /// Not supported by default.
+    async fn kem_encapsulate(&self, _peer_public_key: &PublicKey) -> Result<(Vec<u8>, KeyId)> {
+        Err(unsupported(
+            "Key encapsulation is not supported by this vault",
+        ))
+    }
+
+    /// Decapsulate the shared secret of a ciphertext using this secret key of
+    /// a key encapsulation mechanism.
+    ///
+    /// Not supported by default.
+    async fn kem_decapsulate(&self, _secret: &KeyId, _ciphertext: &[u8]) -> Result<KeyId> {
+        Err(unsupported(
+            "Key encapsulation is not supported by this vault",
+        ))
+    }
 }
@@ -30,3 +30,11 @@ pub use signer::*;
 pub use symmetric_vault::*;
 pub use types::*;
 pub use verifier::*;
+
+use crate::errcode::{Kind, Origin};
+use crate::Error;
+
+/// Error of the optional operations a vault does not implement
+pub(crate) fn unsupported(message: &'static str) -> Error {
+    Error::new(Origin::Vault, Kind::Unsupported, message)
+}
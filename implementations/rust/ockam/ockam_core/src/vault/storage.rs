@@ -1,6 +1,6 @@
 use crate::vault::{KeyId, VaultEntry};
 use crate::Result;
-use crate::{async_trait, compat::boxed::Box};
+use crate::{async_trait, compat::boxed::Box, compat::vec::Vec};
 
 /// Defines Storage interface for Ockam vaults.
 #[async_trait]
@@ -11,4 +11,8 @@ pub trait Storage: Sync + Send + 'static {
     async fn load(&self, key_id: &KeyId) -> Result<VaultEntry>;
     /// Delete secret
     async fn delete(&self, key_id: &KeyId) -> Result<VaultEntry>;
+    /// List the ids of the stored secrets
+    async fn list(&self) -> Result<Vec<KeyId>> {
+        Err(super::unsupported("listing stored secrets"))
+    }
 }
@@ -1,4 +1,4 @@
-use crate::vault::{Buffer, KeyId};
+use crate::vault::{unsupported, Buffer, KeyId};
 use crate::Result;
 use crate::{async_trait, compat::boxed::Box};
 
@@ -22,4 +22,30 @@ pub trait SymmetricVault {
         nonce: &[u8],
         aad: &[u8],
     ) -> Result<Buffer<u8>>;
+
+    /// Encrypt a payload using ChaCha20-Poly1305. Not supported by default.
+    async fn aead_chacha20_poly1305_encrypt(
+        &self,
+        _key_id: &KeyId,
+        _plaintext: &[u8],
+        _nonce: &[u8],
+        _aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        Err(unsupported(
+            "ChaCha20-Poly1305 is not supported by this vault",
+        ))
+    }
+
+    /// Decrypt a payload using ChaCha20-Poly1305. Not supported by default.
+    async fn aead_chacha20_poly1305_decrypt(
+        &self,
+        _key_id: &KeyId,
+        _cipher_text: &[u8],
+        _nonce: &[u8],
+        _aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        Err(unsupported(
+            "ChaCha20-Poly1305 is not supported by this vault",
+        ))
+    }
 }
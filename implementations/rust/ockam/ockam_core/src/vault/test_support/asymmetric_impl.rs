@@ -1,6 +1,6 @@
 use crate::vault::{
     AsymmetricVault, SecretAttributes, SecretPersistence, SecretType, SecretVault,
-    CURVE25519_SECRET_LENGTH_U32,
+    CURVE25519_SECRET_LENGTH_U32, NISTP256_PUBLIC_LENGTH_USIZE, NISTP256_SECRET_LENGTH_U32,
 };
 
 pub async fn ec_diffie_hellman_curve25519(vault: &mut (impl AsymmetricVault + SecretVault)) {
@@ -23,3 +23,24 @@ pub async fn ec_diffie_hellman_curve25519(vault: &mut (impl AsymmetricVault + Se
     let _ss2 = res2.unwrap();
     // TODO: Check result against test vector
 }
+
+pub async fn ec_diffie_hellman_nist_p256(vault: &mut (impl AsymmetricVault + SecretVault)) {
+    let attributes = SecretAttributes::new(
+        SecretType::NistP256,
+        SecretPersistence::Ephemeral,
+        NISTP256_SECRET_LENGTH_U32,
+    );
+    let sk_ctx_1 = vault.secret_generate(attributes).await.unwrap();
+    let sk_ctx_2 = vault.secret_generate(attributes).await.unwrap();
+    let pk_1 = vault.secret_public_key_get(&sk_ctx_1).await.unwrap();
+    let pk_2 = vault.secret_public_key_get(&sk_ctx_2).await.unwrap();
+    assert_eq!(pk_1.data().len(), NISTP256_PUBLIC_LENGTH_USIZE);
+
+    let ss1 = vault.ec_diffie_hellman(&sk_ctx_1, &pk_2).await.unwrap();
+    let ss2 = vault.ec_diffie_hellman(&sk_ctx_2, &pk_1).await.unwrap();
+
+    let ss1 = vault.secret_export(&ss1).await.unwrap();
+    let ss2 = vault.secret_export(&ss2).await.unwrap();
+    assert_eq!(ss1.try_as_key().unwrap().as_ref().len(), 32);
+    assert_eq!(ss1, ss2);
+}
@@ -0,0 +1,33 @@
+use crate::vault::{
+    AsymmetricVault, SecretAttributes, SecretPersistence, SecretType, SecretVault,
+    KYBER768_CIPHERTEXT_LENGTH_USIZE, KYBER768_PUBLIC_LENGTH_USIZE, KYBER768_SECRET_LENGTH_U32,
+};
+
+pub async fn kem_encapsulate_decapsulate_kyber768(
+    vault: &mut (impl AsymmetricVault + SecretVault),
+) {
+    let attributes = SecretAttributes::new(
+        SecretType::Kyber768,
+        SecretPersistence::Ephemeral,
+        KYBER768_SECRET_LENGTH_U32,
+    );
+    let sk = vault.secret_generate(attributes).await.unwrap();
+    let pk = vault.secret_public_key_get(&sk).await.unwrap();
+    assert_eq!(pk.stype(), SecretType::Kyber768);
+    assert_eq!(pk.data().len(), KYBER768_PUBLIC_LENGTH_USIZE);
+
+    let (ciphertext, ss1) = vault.kem_encapsulate(&pk).await.unwrap();
+    assert_eq!(ciphertext.len(), KYBER768_CIPHERTEXT_LENGTH_USIZE);
+    let ss2 = vault.kem_decapsulate(&sk, &ciphertext).await.unwrap();
+
+    let ss1 = vault.secret_export(&ss1).await.unwrap();
+    let ss2 = vault.secret_export(&ss2).await.unwrap();
+    assert_eq!(ss1, ss2);
+
+    // Another key can't recover the shared secret
+    let other = vault.secret_generate(attributes).await.unwrap();
+    if let Ok(ss3) = vault.kem_decapsulate(&other, &ciphertext).await {
+        let ss3 = vault.secret_export(&ss3).await.unwrap();
+        assert_ne!(ss1, ss3);
+    }
+}
@@ -4,6 +4,7 @@
 
 mod asymmetric_impl;
 mod hasher_impl;
+mod kem_impl;
 mod key_id_impl;
 mod secret_impl;
 mod signer_impl;
@@ -11,6 +12,7 @@ mod symmetric_impl;
 
 pub use asymmetric_impl::*;
 pub use hasher_impl::*;
+pub use kem_impl::*;
 pub use key_id_impl::*;
 pub use secret_impl::*;
 pub use signer_impl::*;
@@ -4,6 +4,7 @@ use crate::{
 };
 use cfg_if::cfg_if;
 use core::fmt;
+use core::ops::BitOr;
 use minicbor::{Decode, Encode};
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
@@ -21,6 +22,12 @@ pub const CURVE25519_PUBLIC_LENGTH_U32: u32 = 32;
 /// Curve25519 public key length.
 pub const CURVE25519_PUBLIC_LENGTH_USIZE: usize = 32;
 
+/// NIST P-256 private key length.
+pub const NISTP256_SECRET_LENGTH_U32: u32 = 32;
+
+/// NIST P-256 public key length, as a DER encoded SubjectPublicKeyInfo.
+pub const NISTP256_PUBLIC_LENGTH_USIZE: usize = 91;
+
 /// AES256 private key length.
 pub const AES256_SECRET_LENGTH_U32: u32 = 32;
 /// AES256 private key length.
@@ -31,6 +38,27 @@ pub const AES128_SECRET_LENGTH_U32: u32 = 16;
 /// AES128 private key length.
 pub const AES128_SECRET_LENGTH_USIZE: usize = 16;
 
+/// ChaCha20-Poly1305 private key length.
+pub const CHACHA20POLY1305_SECRET_LENGTH_U32: u32 = 32;
+/// ChaCha20-Poly1305 private key length.
+pub const CHACHA20POLY1305_SECRET_LENGTH_USIZE: usize = 32;
+
+/// Kyber768 decapsulation key length.
+pub const KYBER768_SECRET_LENGTH_U32: u32 = 2400;
+/// Kyber768 decapsulation key length.
+pub const KYBER768_SECRET_LENGTH_USIZE: usize = 2400;
+
+/// Kyber768 encapsulation key length.
+pub const KYBER768_PUBLIC_LENGTH_U32: u32 = 1184;
+/// Kyber768 encapsulation key length.
+pub const KYBER768_PUBLIC_LENGTH_USIZE: usize = 1184;
+
+/// Kyber768 ciphertext length.
+pub const KYBER768_CIPHERTEXT_LENGTH_USIZE: usize = 1088;
+
+/// Kyber768 shared secret length.
+pub const KYBER768_SHARED_SECRET_LENGTH_U32: u32 = 32;
+
 cfg_if! {
     if #[cfg(not(feature = "alloc"))] {
         /// Secret Key Vector. The maximum size is 32 bytes.
@@ -197,7 +225,11 @@ pub enum SecretType {
     /// Curve 22519 key
     #[n(4)] Ed25519,
     /// NIST P-256 key
-    #[n(5)] NistP256
+    #[n(5)] NistP256,
+    /// ChaCha20-Poly1305 key
+    #[n(6)] ChaCha20Poly1305,
+    /// Kyber768 decapsulation key
+    #[n(7)] Kyber768,
 }
 
 /// All possible [`SecretKey`] persistence types
@@ -211,13 +243,73 @@ pub enum SecretPersistence {
     #[n(2)] Persistent,
 }
 
+/// Operations a secret may be used for.
+#[derive(Serialize, Deserialize, Copy, Encode, Decode, Clone, Debug, Eq, PartialEq)]
+#[serde(transparent)]
+#[cbor(transparent)]
+pub struct SecretUsage(#[n(0)] u8);
+
+impl SecretUsage {
+    /// No operation.
+    pub const NONE: Self = Self(0);
+    /// Sign data.
+    pub const SIGN: Self = Self(1);
+    /// Verify signatures. Verifying only needs the public key of a secret,
+    /// so vaults can't enforce this one.
+    pub const VERIFY: Self = Self(1 << 1);
+    /// Key agreements: ECDH and KEM decapsulation.
+    pub const ECDH: Self = Self(1 << 2);
+    /// AEAD encryption and decryption.
+    pub const ENCRYPT: Self = Self(1 << 3);
+    /// HKDF salt or input key material.
+    pub const DERIVE: Self = Self(1 << 4);
+    /// Export of the secret itself.
+    pub const EXPORTABLE: Self = Self(1 << 5);
+    /// Every operation.
+    pub const ALL: Self = Self(0b11_1111);
+
+    /// Whether all the operations of `usage` are allowed.
+    pub fn contains(&self, usage: SecretUsage) -> bool {
+        self.0 & usage.0 == usage.0
+    }
+
+    /// Allow the operations of both `self` and `usage`.
+    pub const fn union(self, usage: SecretUsage) -> Self {
+        Self(self.0 | usage.0)
+    }
+
+    /// Disallow the operations of `usage`.
+    pub fn without(self, usage: SecretUsage) -> Self {
+        Self(self.0 & !usage.0)
+    }
+}
+
+impl Default for SecretUsage {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl BitOr for SecretUsage {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
 /// Attributes for a specific vault.
+///
+/// The usage is not part of the serde encoding, which identity change
+/// histories rely on: storages have to keep it next to the attributes.
 #[derive(Serialize, Deserialize, Copy, Encode, Decode, Clone, Debug, Eq, PartialEq)]
 #[rustfmt::skip]
 pub struct SecretAttributes {
     #[n(1)] stype: SecretType,
     #[n(2)] persistence: SecretPersistence,
     #[n(3)] length: u32,
+    #[serde(skip)]
+    #[n(4)] usage: Option<SecretUsage>,
 }
 
 impl SecretAttributes {
@@ -233,6 +325,10 @@ impl SecretAttributes {
     pub fn length(&self) -> u32 {
         self.length
     }
+    /// Return the operations the secret may be used for, all of them by default.
+    pub fn usage(&self) -> SecretUsage {
+        self.usage.unwrap_or_default()
+    }
 }
 
 impl SecretAttributes {
@@ -242,8 +338,15 @@ impl SecretAttributes {
             stype,
             persistence,
             length,
+            usage: None,
         }
     }
+
+    /// Restrict the operations the secret may be used for.
+    pub fn with_usage(mut self, usage: SecretUsage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
 }
 
 impl fmt::Display for SecretAttributes {
@@ -297,8 +400,10 @@ pub struct VaultEntry {
 pub enum Secret {
     /// A secret key.
     #[n(0)] Key(#[n(0)] SecretKey),
-    /// Reference to an unmanaged, external secret key of AWS KMS.
-    #[n(1)] Aws(#[n(1)] KeyId)
+    /// Reference to an unmanaged, external secret key of a KMS, e.g. AWS KMS
+    /// or a PKCS#11 token.
+    #[serde(alias = "Aws")]
+    #[n(1)] Kms(#[n(1)] KeyId)
 }
 
 impl Secret {
@@ -354,11 +459,11 @@ impl VaultEntry {
         }
     }
 
-    /// Create a new vault entry with an external secret key from AWS KMS.
-    pub fn new_aws(key_attributes: SecretAttributes, kid: KeyId) -> Self {
+    /// Create a new vault entry with an external secret key of a KMS.
+    pub fn new_kms(key_attributes: SecretAttributes, kid: KeyId) -> Self {
         VaultEntry {
             key_attributes,
-            secret: Secret::Aws(kid),
+            secret: Secret::Kms(kid),
         }
     }
 }
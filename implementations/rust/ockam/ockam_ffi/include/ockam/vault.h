@@ -39,6 +39,8 @@ typedef enum {
     OCKAM_VAULT_SECRET_TYPE_AES_KEY,
     OCKAM_VAULT_SECRET_TYPE_CURVE25519_PRIVATEKEY,
     OCKAM_VAULT_SECRET_TYPE_P256_PRIVATEKEY,
+    OCKAM_VAULT_SECRET_TYPE_CHACHA20POLY1305_KEY = 5,
+    OCKAM_VAULT_SECRET_TYPE_KYBER768_PRIVATEKEY = 6,
 } ockam_vault_secret_type_t;
 
 /**
@@ -173,6 +175,41 @@ ockam_vault_extern_error_t ockam_vault_ecdh(ockam_vault_t         vault,
                                             uint32_t              peer_publickey_length,
                                             ockam_vault_secret_t* shared_secret);
 
+/**
+* @brief   Encapsulate a fresh shared secret to the supplied Kyber peer_publickey. The ciphertext is written to the
+*          output buffer and the shared secret is returned as an ockam vault secret of type buffer.
+* @param   vault[in]                 Vault object to use for encapsulation.
+* @param   peer_publickey[in]        Public key data to encapsulate to.
+* @param   peer_publickey_length[in] Length of the public key.
+* @param   ciphertext[out]           Buffer to place the ciphertext in.
+* @param   ciphertext_size[in]       Size of the ciphertext buffer.
+* @param   ciphertext_length[out]    Amount of data placed in the ciphertext buffer.
+* @param   shared_secret[out]        Resulting shared secret. Invalid if encapsulation failed.
+* @return  an error, which should be freed using @ref ockam_vault_free_error.
+*/
+ockam_vault_extern_error_t ockam_vault_kem_encapsulate(ockam_vault_t         vault,
+                                                       const uint8_t*        peer_publickey,
+                                                       uint32_t              peer_publickey_length,
+                                                       uint8_t*              ciphertext,
+                                                       uint32_t              ciphertext_size,
+                                                       uint32_t*             ciphertext_length,
+                                                       ockam_vault_secret_t* shared_secret);
+
+/**
+* @brief   Decapsulate the shared secret of a Kyber ciphertext using the supplied ockam vault secret.
+* @param   vault[in]             Vault object to use for decapsulation.
+* @param   privatekey[in]        The ockam vault secret to use for the private key.
+* @param   ciphertext[in]        Ciphertext received from the peer.
+* @param   ciphertext_length[in] Length of the ciphertext.
+* @param   shared_secret[out]    Resulting shared secret. Invalid if decapsulation failed.
+* @return  an error, which should be freed using @ref ockam_vault_free_error.
+*/
+ockam_vault_extern_error_t ockam_vault_kem_decapsulate(ockam_vault_t         vault,
+                                                       ockam_vault_secret_t  privatekey,
+                                                       const uint8_t*        ciphertext,
+                                                       uint32_t              ciphertext_length,
+                                                       ockam_vault_secret_t* shared_secret);
+
 /**
  * @brief   Perform an HMAC-SHA256 based key derivation function on the supplied salt and input key material.
  * @param   vault[in]                      Vault object to use for encryption.
@@ -240,6 +277,56 @@ ockam_vault_extern_error_t ockam_vault_aead_aes_gcm_decrypt(ockam_vault_t
                                                             uint32_t             plaintext_size,
                                                             uint32_t*            plaintext_length);
 
+/**
+ * @brief   Encrypt a payload using ChaCha20-Poly1305.
+ * @param   vault[in]                       Vault object to use for encryption.
+ * @param   key[in]                         Ockam secret key to use for encryption.
+ * @param   nonce[in]                       Nonce value to use for encryption.
+ * @param   additional_data[in]             Additional data to use for encryption.
+ * @param   additional_data_length[in]      Length of the additional data.
+ * @param   plaintext[in]                   Buffer containing plaintext data to encrypt.
+ * @param   plaintext_length[in]            Length of plaintext data to encrypt.
+ * @param   ciphertext_and_tag[in]          Buffer containing the generated ciphertext and tag data.
+ * @param   ciphertext_and_tag_size[in]     Size of the ciphertext + tag buffer. Must be plaintext_size + 16.
+ * @param   ciphertext_and_tag_length[out]  Amount of data placed in the ciphertext + tag buffer.
+ * @return  an error, which should be freed using @ref ockam_vault_free_error.
+ */
+ockam_vault_extern_error_t ockam_vault_aead_chacha20_poly1305_encrypt(ockam_vault_t        vault,
+                                                                      ockam_vault_secret_t key,
+                                                                      uint16_t             nonce,
+                                                                      const uint8_t*       additional_data,
+                                                                      uint32_t             additional_data_length,
+                                                                      const uint8_t*       plaintext,
+                                                                      uint32_t             plaintext_length,
+                                                                      uint8_t*             ciphertext_and_tag,
+                                                                      uint32_t             ciphertext_and_tag_size,
+                                                                      uint32_t*            ciphertext_and_tag_length);
+
+/**
+ * @brief   Decrypt a payload using ChaCha20-Poly1305.
+ * @param   vault[in]                     Vault object to use for decryption.
+ * @param   key[in]                       Ockam secret key to use for decryption.
+ * @param   nonce[in]                     Nonce value to use for decryption.
+ * @param   additional_data[in]           Additional data to use for decryption.
+ * @param   additional_data_length[in]    Length of the additional data.
+ * @param   ciphertext_and_tag[in]        The ciphertext + tag data to decrypt.
+ * @param   ciphertext_and_tag_length[in] Length of the ciphertext + tag data to decrypt.
+ * @param   plaintext[out]                Buffer to place the decrypted data in.
+ * @param   plaintext_size[in]            Size of the plaintext buffer. Must be ciphertext_tag_size - 16.
+ * @param   plaintext_length[out]         Amount of data placed in the plaintext buffer.
+ * @return  an error, which should be freed using @ref ockam_vault_free_error.
+ */
+ockam_vault_extern_error_t ockam_vault_aead_chacha20_poly1305_decrypt(ockam_vault_t       vault,
+                                                                      ockam_vault_secret_t key,
+                                                                      uint16_t             nonce,
+                                                                      const uint8_t*       additional_data,
+                                                                      uint32_t             additional_data_length,
+                                                                      const uint8_t*       ciphertext_and_tag,
+                                                                      uint32_t             ciphertext_and_tag_length,
+                                                                      uint8_t*             plaintext,
+                                                                      uint32_t             plaintext_size,
+                                                                      uint32_t*            plaintext_length);
+
 /**
  * @brief   Deinitialize the specified ockam vault object
  * @param   vault[in] The ockam vault object to deinitialize.
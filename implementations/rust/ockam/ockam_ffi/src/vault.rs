@@ -7,8 +7,8 @@ use lazy_static::lazy_static;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::sync::Arc;
 use ockam_core::vault::{
-    AsymmetricVault, Hasher, KeyId, PublicKey, Secret, SecretAttributes, SecretKey, SecretVault,
-    SymmetricVault,
+    AsymmetricVault, Hasher, KeyId, PublicKey, Secret, SecretAttributes, SecretKey, SecretType,
+    SecretVault, SymmetricVault,
 };
 use ockam_core::{Error, Result};
 use ockam_vault::Vault;
@@ -317,6 +317,70 @@ pub extern "C" fn ockam_vault_ecdh(
     })
 }
 
+/// Encapsulate a fresh shared secret to a Kyber public key. The ciphertext is copied to
+/// the output buffer.
+#[no_mangle]
+pub extern "C" fn ockam_vault_kem_encapsulate(
+    context: FfiVaultFatPointer,
+    peer_publickey: *const u8,
+    peer_publickey_length: u32,
+    ciphertext: *mut u8,
+    ciphertext_size: u32,
+    ciphertext_length: &mut u32,
+    shared_secret: &mut SecretKeyHandle,
+) -> FfiOckamError {
+    *ciphertext_length = 0;
+    handle_panics(|| {
+        check_buffer!(peer_publickey, peer_publickey_length);
+
+        let peer_publickey =
+            unsafe { core::slice::from_raw_parts(peer_publickey, peer_publickey_length as usize) };
+
+        *shared_secret = block_future(async move {
+            let entry = get_vault_entry(context).await?;
+            let pubkey = PublicKey::new(peer_publickey.to_vec(), SecretType::Kyber768);
+            let (data, shared_ctx) = entry.vault.kem_encapsulate(&pubkey).await?;
+            if ciphertext_size < data.len() as u32 {
+                return Err(FfiError::BufferTooSmall.into());
+            }
+            *ciphertext_length = data.len() as u32;
+
+            unsafe {
+                std::ptr::copy_nonoverlapping(data.as_ptr(), ciphertext, data.len());
+            };
+            let index = entry.insert(shared_ctx).await;
+            Ok::<u64, Error>(index)
+        })?;
+        Ok(())
+    })
+}
+
+/// Decapsulate the shared secret of a Kyber ciphertext using a secret key.
+#[no_mangle]
+pub extern "C" fn ockam_vault_kem_decapsulate(
+    context: FfiVaultFatPointer,
+    secret: SecretKeyHandle,
+    ciphertext: *const u8,
+    ciphertext_length: u32,
+    shared_secret: &mut SecretKeyHandle,
+) -> FfiOckamError {
+    handle_panics(|| {
+        check_buffer!(ciphertext, ciphertext_length);
+
+        let ciphertext =
+            unsafe { core::slice::from_raw_parts(ciphertext, ciphertext_length as usize) };
+
+        *shared_secret = block_future(async move {
+            let entry = get_vault_entry(context).await?;
+            let key_id = entry.get(secret).await?;
+            let shared_ctx = entry.vault.kem_decapsulate(&key_id, ciphertext).await?;
+            let index = entry.insert(shared_ctx).await;
+            Ok::<u64, Error>(index)
+        })?;
+        Ok(())
+    })
+}
+
 /// Perform an HMAC-SHA256 based key derivation function on the supplied salt and input key
 /// material.
 #[no_mangle]
@@ -484,6 +548,113 @@ pub extern "C" fn ockam_vault_aead_aes_gcm_decrypt(
     })
 }
 
+/// Encrypt a payload using ChaCha20-Poly1305.
+#[no_mangle]
+pub extern "C" fn ockam_vault_aead_chacha20_poly1305_encrypt(
+    context: FfiVaultFatPointer,
+    secret: SecretKeyHandle,
+    nonce: u16,
+    additional_data: *const u8,
+    additional_data_length: u32,
+    plaintext: *const u8,
+    plaintext_length: u32,
+    ciphertext_and_tag: &mut u8,
+    ciphertext_and_tag_size: u32,
+    ciphertext_and_tag_length: &mut u32,
+) -> FfiOckamError {
+    *ciphertext_and_tag_length = 0;
+    handle_panics(|| {
+        check_buffer!(additional_data);
+        check_buffer!(plaintext);
+
+        let additional_data = unsafe {
+            core::slice::from_raw_parts(additional_data, additional_data_length as usize)
+        };
+
+        let plaintext =
+            unsafe { core::slice::from_raw_parts(plaintext, plaintext_length as usize) };
+
+        block_future(async move {
+            let entry = get_vault_entry(context).await?;
+            let key_id = entry.get(secret).await?;
+            let mut nonce_vec = vec![0; 12 - 2];
+            nonce_vec.extend_from_slice(&nonce.to_be_bytes());
+            let ciphertext = entry
+                .vault
+                .aead_chacha20_poly1305_encrypt(&key_id, plaintext, &nonce_vec, additional_data)
+                .await?;
+
+            if ciphertext_and_tag_size < ciphertext.len() as u32 {
+                return Err(FfiError::BufferTooSmall.into());
+            }
+            *ciphertext_and_tag_length = ciphertext.len() as u32;
+
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    ciphertext.as_ptr(),
+                    ciphertext_and_tag,
+                    ciphertext.len(),
+                )
+            };
+            Ok::<(), Error>(())
+        })?;
+        Ok(())
+    })
+}
+
+/// Decrypt a payload using ChaCha20-Poly1305.
+#[no_mangle]
+pub extern "C" fn ockam_vault_aead_chacha20_poly1305_decrypt(
+    context: FfiVaultFatPointer,
+    secret: SecretKeyHandle,
+    nonce: u16,
+    additional_data: *const u8,
+    additional_data_length: u32,
+    ciphertext_and_tag: *const u8,
+    ciphertext_and_tag_length: u32,
+    plaintext: &mut u8,
+    plaintext_size: u32,
+    plaintext_length: &mut u32,
+) -> FfiOckamError {
+    *plaintext_length = 0;
+    handle_panics(|| {
+        check_buffer!(ciphertext_and_tag, ciphertext_and_tag_length);
+        check_buffer!(additional_data);
+
+        let additional_data = unsafe {
+            core::slice::from_raw_parts(additional_data, additional_data_length as usize)
+        };
+
+        let ciphertext_and_tag = unsafe {
+            core::slice::from_raw_parts(ciphertext_and_tag, ciphertext_and_tag_length as usize)
+        };
+
+        block_future(async move {
+            let entry = get_vault_entry(context).await?;
+            let key_id = entry.get(secret).await?;
+            let mut nonce_vec = vec![0; 12 - 2];
+            nonce_vec.extend_from_slice(&nonce.to_be_bytes());
+            let plain = entry
+                .vault
+                .aead_chacha20_poly1305_decrypt(
+                    &key_id,
+                    ciphertext_and_tag,
+                    &nonce_vec,
+                    additional_data,
+                )
+                .await?;
+            if plaintext_size < plain.len() as u32 {
+                return Err(FfiError::BufferTooSmall.into());
+            }
+            *plaintext_length = plain.len() as u32;
+
+            unsafe { std::ptr::copy_nonoverlapping(plain.as_ptr(), plaintext, plain.len()) };
+            Ok::<(), Error>(())
+        })?;
+        Ok(())
+    })
+}
+
 /// De-initialize an Ockam Vault.
 #[no_mangle]
 pub extern "C" fn ockam_vault_deinit(context: FfiVaultFatPointer) -> FfiOckamError {
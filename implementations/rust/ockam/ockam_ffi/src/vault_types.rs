@@ -72,6 +72,8 @@ impl From<SecretAttributes> for FfiSecretAttributes {
             SecretType::X25519 => 2,
             SecretType::Ed25519 => 3,
             SecretType::NistP256 => 4,
+            SecretType::ChaCha20Poly1305 => 5,
+            SecretType::Kyber768 => 6,
         };
 
         let persistence = match attrs.persistence() {
@@ -92,6 +94,8 @@ impl TryFrom<FfiSecretAttributes> for SecretAttributes {
             1 => Ok(SecretType::Aes),
             2 => Ok(SecretType::X25519),
             3 => Ok(SecretType::Ed25519),
+            5 => Ok(SecretType::ChaCha20Poly1305),
+            6 => Ok(SecretType::Kyber768),
             _ => Err(FfiError::InvalidParam),
         }?;
 
@@ -1,4 +1,4 @@
-use crate::ChangeIdentifier;
+use crate::{ChangeIdentifier, KeyAttributes};
 use core::fmt;
 use ockam_core::compat::vec::Vec;
 use ockam_core::vault::PublicKey;
@@ -43,6 +43,13 @@ impl IdentityChange {
         }
     }
 
+    pub(crate) fn key_attributes(&self) -> &KeyAttributes {
+        match self {
+            IdentityChange::CreateKey(data) => data.key_attributes(),
+            IdentityChange::RotateKey(data) => data.key_attributes(),
+        }
+    }
+
     pub(crate) fn public_key(&self) -> Result<PublicKey> {
         Ok(match self {
             IdentityChange::CreateKey(data) => data.public_key(),
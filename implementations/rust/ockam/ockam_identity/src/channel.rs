@@ -12,12 +12,22 @@ pub use trust_policy::*;
 pub mod access_control;
 mod local_info;
 pub use local_info::*;
+mod options;
+pub use ockam_channel::RekeyPolicy;
+pub use options::*;
+mod stats;
+pub use stats::*;
+mod limits;
+pub use limits::*;
+mod registry;
+pub(crate) use registry::*;
 
 use crate::authenticated_storage::AuthenticatedStorage;
-use crate::{Identity, IdentityVault};
+use crate::{Identity, IdentityError, IdentityVault};
 use core::time::Duration;
 use ockam_core::compat::sync::Arc;
-use ockam_core::{Address, AllowAll, AsyncTryClone, Mailbox, Mailboxes, Result, Route};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{route, Address, AllowAll, AsyncTryClone, Mailbox, Mailboxes, Result, Route};
 
 impl<V: IdentityVault> Identity<V> {
     pub async fn create_secure_channel_listener(
@@ -25,10 +35,28 @@ impl<V: IdentityVault> Identity<V> {
         address: impl Into<Address>,
         trust_policy: impl TrustPolicy,
         storage: &impl AuthenticatedStorage,
+    ) -> Result<()> {
+        self.create_secure_channel_listener_extended(
+            address,
+            trust_policy,
+            storage,
+            SecureChannelOptions::default(),
+        )
+        .await
+    }
+
+    /// Create a secure channel listener whose channels use the given [`SecureChannelOptions`].
+    pub async fn create_secure_channel_listener_extended(
+        &self,
+        address: impl Into<Address>,
+        trust_policy: impl TrustPolicy,
+        storage: &impl AuthenticatedStorage,
+        options: SecureChannelOptions,
     ) -> Result<()> {
         let identity_clone = self.async_try_clone().await?;
         let storage_clone = storage.async_try_clone().await?;
-        let listener = IdentityChannelListener::new(trust_policy, identity_clone, storage_clone);
+        let listener = IdentityChannelListener::new(trust_policy, identity_clone, storage_clone)
+            .with_options(options);
 
         // TODO @ac
         let mailbox = Mailbox::new(
@@ -59,16 +87,19 @@ impl<V: IdentityVault> Identity<V> {
             storage_clone,
             Arc::new(trust_policy),
             Duration::from_secs(120),
+            SecureChannelOptions::default(),
         )
         .await
     }
 
+    /// Create a secure channel with a custom handshake timeout and [`SecureChannelOptions`].
     pub async fn create_secure_channel_extended(
         &self,
         route: impl Into<Route>,
         trust_policy: impl TrustPolicy,
         storage: &impl AuthenticatedStorage,
         timeout: Duration,
+        options: SecureChannelOptions,
     ) -> Result<Address> {
         let identity_clone = self.async_try_clone().await?;
         let storage_clone = storage.async_try_clone().await?;
@@ -80,12 +111,59 @@ impl<V: IdentityVault> Identity<V> {
             storage_clone,
             Arc::new(trust_policy),
             timeout,
+            options,
         )
         .await
     }
 
+    /// Close a secure channel. The other side is told to close it as well,
+    /// after the messages sent before are delivered.
     pub async fn stop_secure_channel(&self, channel: &Address) -> Result<()> {
-        self.ctx.stop_worker(channel.clone()).await
+        self.ctx
+            .send(
+                route![self.secure_channel_control_address(channel)?],
+                DecryptorControlMessage::Close,
+            )
+            .await
+    }
+
+    /// Move a secure channel to a new route to the node of the other side,
+    /// e.g. once a dropped transport connection was reestablished. The
+    /// channel keeps its address, see [`ockam_channel::SecureChannel::migrate`].
+    pub async fn migrate_secure_channel(
+        &self,
+        channel: &Address,
+        route: impl Into<Route>,
+    ) -> Result<()> {
+        self.ctx
+            .send(
+                route![self.secure_channel_control_address(channel)?],
+                DecryptorControlMessage::Migrate {
+                    route: route.into(),
+                },
+            )
+            .await
+    }
+
+    /// Statistics of a channel of this identity, which it created or accepted
+    pub fn secure_channel_stats(&self, channel: &Address) -> Option<SecureChannelStats> {
+        self.secure_channels
+            .get(channel)
+            .map(|entry| entry.counters.stats())
+    }
+
+    /// Addresses of the channels of this identity which are not closed yet
+    pub fn secure_channels(&self) -> Vec<Address> {
+        self.secure_channels.addresses()
+    }
+
+    /// Address to control a channel of this identity, which only accepts
+    /// messages from this node
+    fn secure_channel_control_address(&self, channel: &Address) -> Result<Address> {
+        self.secure_channels
+            .get(channel)
+            .map(|entry| entry.control_address)
+            .ok_or_else(|| IdentityError::UnknownChannelMsgDestination.into())
     }
 }
 
@@ -94,11 +172,18 @@ mod test {
     use super::*;
     use crate::access_control::IdentityAccessControlBuilder;
     use crate::authenticated_storage::mem::InMemoryStorage;
-    use crate::Identity;
+    use crate::{Identity, IdentityStateConst, KeyAttributes};
     use core::sync::atomic::{AtomicU8, Ordering};
     use core::time::Duration;
+    use ockam_channel::test_support::Duplicator;
+    use ockam_channel::CreateResponderChannelMessage;
     use ockam_core::compat::sync::Arc;
-    use ockam_core::{route, AllowAll, Any, Result, Routed, Worker};
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, SecretVault, CURVE25519_SECRET_LENGTH_U32,
+    };
+    use ockam_core::{route, AllowAll, Any, Encodable, Result, Routed, Worker};
+    use ockam_key_exchange_core::{KeyExchanger, NewKeyExchanger};
+    use ockam_key_exchange_xx::{XXCurve, XXNewKeyExchanger};
     use ockam_node::{Context, WorkerBuilder};
     use ockam_vault::Vault;
     use tokio::time::sleep;
@@ -150,6 +235,651 @@ mod test {
         ctx.stop().await
     }
 
+    #[ockam_macros::test]
+    async fn test_channel_rekeying(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        // Both sides change keys at different paces
+        bob.create_secure_channel_listener_extended(
+            "bob_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            SecureChannelOptions::new().with_rekey_policy(RekeyPolicy::new().with_message_limit(2)),
+        )
+        .await?;
+
+        let alice_channel = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(10),
+                SecureChannelOptions::new()
+                    .with_rekey_policy(RekeyPolicy::new().with_message_limit(3)),
+            )
+            .await?;
+
+        for i in 0..10 {
+            ctx.send(route![alice_channel.clone(), ctx.address()], i.to_string())
+                .await?;
+            let msg = ctx.receive::<String>().await?.take();
+            let return_route = msg.return_route();
+            assert_eq!(i.to_string(), msg.body());
+
+            ctx.send(return_route, i.to_string()).await?;
+            let msg = ctx.receive::<String>().await?.take();
+            assert_eq!(i.to_string(), msg.body());
+        }
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_stats(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+
+        let alice_counters = SecureChannelCounters::new();
+        let alice_channel = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(10),
+                SecureChannelOptions::new().with_counters(alice_counters.clone()),
+            )
+            .await?;
+
+        let mut bob_channel = None;
+        for _ in 0..3 {
+            ctx.send(
+                route![alice_channel.clone(), ctx.address()],
+                "Hello".to_string(),
+            )
+            .await?;
+            let msg = ctx.receive::<String>().await?.take();
+            bob_channel = Some(msg.return_route().next()?.clone());
+        }
+        let bob_channel = bob_channel.unwrap();
+
+        // Another channel accepted by the same listener has its own statistics
+        let carol = Identity::create(ctx, &vault).await?;
+        let carol_channel = carol
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+        ctx.send(route![carol_channel, ctx.address()], "Hi".to_string())
+            .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let bob_carol_channel = msg.return_route().next()?.clone();
+        assert_ne!(bob_channel, bob_carol_channel);
+        assert_eq!(bob.secure_channels().len(), 2);
+
+        let alice_stats = alice_counters.stats();
+        assert_eq!(alice_stats.their_identity_id(), Some(bob.identifier()));
+        assert_eq!(alice_stats.key_exchange(), Some("NOISE_XX"));
+        assert!(alice_stats.created_at().is_some());
+        assert_eq!(alice_stats.messages_encrypted(), 3);
+        assert!(alice_stats.bytes_encrypted() > 0);
+        assert_eq!(alice_stats.messages_decrypted(), 0);
+
+        let bob_stats = bob.secure_channel_stats(&bob_channel).unwrap();
+        assert_eq!(bob_stats.their_identity_id(), Some(alice.identifier()));
+        assert_eq!(bob_stats.messages_decrypted(), 3);
+        assert_eq!(bob_stats.bytes_decrypted(), alice_stats.bytes_encrypted());
+        assert_eq!(bob_stats.decryption_failures(), 0);
+
+        let bob_carol_stats = bob.secure_channel_stats(&bob_carol_channel).unwrap();
+        assert_eq!(
+            bob_carol_stats.their_identity_id(),
+            Some(carol.identifier())
+        );
+        assert_eq!(bob_carol_stats.messages_decrypted(), 1);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_ik(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let bob_static_key = vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::X25519,
+                SecretPersistence::Ephemeral,
+                CURVE25519_SECRET_LENGTH_U32,
+            ))
+            .await?;
+        let bob_static_public_key = vault.secret_public_key_get(&bob_static_key).await?;
+
+        bob.create_secure_channel_listener_extended(
+            "bob_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            SecureChannelOptions::new().with_ik_static_key(bob_static_key.clone()),
+        )
+        .await?;
+
+        let alice_counters = SecureChannelCounters::new();
+        let alice_channel = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustIdentifierPolicy::new(bob.identifier().clone()),
+                &alice_storage,
+                Duration::from_secs(10),
+                SecureChannelOptions::new()
+                    .with_ik_remote_static_key(
+                        bob.identifier().clone(),
+                        bob_static_public_key.clone(),
+                    )
+                    .with_counters(alice_counters.clone()),
+            )
+            .await?;
+
+        ctx.send(
+            route![alice_channel, ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!("Hello, Bob!", msg.body());
+        let bob_channel = msg.return_route().next()?.clone();
+
+        assert_eq!(alice_counters.stats().key_exchange(), Some("NOISE_IK"));
+        assert_eq!(
+            bob.secure_channel_stats(&bob_channel)
+                .and_then(|s| s.key_exchange().map(|k| k.to_string())),
+            Some("NOISE_IK".to_string())
+        );
+
+        // The listener still accepts XX handshakes
+        let alice_channel = alice
+            .create_secure_channel(route!["bob_listener"], TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        ctx.send(
+            route![alice_channel, ctx.address()],
+            "Hello again, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!("Hello again, Bob!", msg.body());
+
+        // Bob's key is not trusted for Carol, even with the secret key
+        let carol = Identity::create(ctx, &vault).await?;
+        let carol_storage = InMemoryStorage::new();
+        carol
+            .create_secure_channel_listener_extended(
+                "carol_listener",
+                TrustEveryonePolicy,
+                &carol_storage,
+                SecureChannelOptions::new().with_ik_static_key(bob_static_key),
+            )
+            .await?;
+        let res = alice
+            .create_secure_channel_extended(
+                route!["carol_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(1),
+                SecureChannelOptions::new().with_ik_remote_static_key(
+                    bob.identifier().clone(),
+                    bob_static_public_key.clone(),
+                ),
+            )
+            .await;
+        assert!(res.is_err());
+
+        // A listener without IK static key refuses IK handshakes
+        bob.create_secure_channel_listener("bob_xx_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+        let res = alice
+            .create_secure_channel_extended(
+                route!["bob_xx_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(1),
+                SecureChannelOptions::new()
+                    .with_ik_remote_static_key(bob.identifier().clone(), bob_static_public_key),
+            )
+            .await;
+        assert!(res.is_err());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_nist_p256(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let root = || KeyAttributes::nist_p256_with_label(IdentityStateConst::ROOT_LABEL);
+        let alice = Identity::create_with_attributes(ctx, &vault, root()).await?;
+        let bob = Identity::create_with_attributes(ctx, &vault, root()).await?;
+
+        bob.create_secure_channel_listener_extended(
+            "bob_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            SecureChannelOptions::new().with_curve(XXCurve::NistP256),
+        )
+        .await?;
+
+        let alice_channel = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustIdentifierPolicy::new(bob.identifier().clone()),
+                &alice_storage,
+                Duration::from_secs(10),
+                SecureChannelOptions::new().with_curve(XXCurve::NistP256),
+            )
+            .await?;
+
+        ctx.send(
+            route![alice_channel, ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!("Hello, Bob!", msg.body());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_hybrid_kem(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let bob_static_key = vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::X25519,
+                SecretPersistence::Ephemeral,
+                CURVE25519_SECRET_LENGTH_U32,
+            ))
+            .await?;
+        let bob_static_public_key = vault.secret_public_key_get(&bob_static_key).await?;
+
+        // The hybrid handshake is XX, even to a listener accepting IK
+        bob.create_secure_channel_listener_extended(
+            "bob_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            SecureChannelOptions::new().with_ik_static_key(bob_static_key),
+        )
+        .await?;
+
+        let alice_counters = SecureChannelCounters::new();
+        let alice_channel = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustIdentifierPolicy::new(bob.identifier().clone()),
+                &alice_storage,
+                Duration::from_secs(10),
+                SecureChannelOptions::new()
+                    .with_ik_remote_static_key(bob.identifier().clone(), bob_static_public_key)
+                    .with_hybrid_kem()
+                    .with_counters(alice_counters.clone()),
+            )
+            .await?;
+
+        ctx.send(
+            route![alice_channel, ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!("Hello, Bob!", msg.body());
+
+        assert_eq!(alice_counters.stats().key_exchange(), Some("NOISE_XX"));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_listener_limits(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let limits = ListenerLimits::new().with_max_channels_per_identity(1);
+        bob.create_secure_channel_listener_extended(
+            "bob_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            SecureChannelOptions::new().with_listener_limits(limits.clone()),
+        )
+        .await?;
+
+        let alice_channel = alice
+            .create_secure_channel("bob_listener", TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        // Alice already has a channel to Bob
+        let res = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(1),
+                SecureChannelOptions::new(),
+            )
+            .await;
+        assert!(res.is_err());
+        assert_eq!(limits.rejections().stats().too_many_for_identity(), 1);
+
+        // The slot of Alice is released once her channel is closed
+        alice.stop_secure_channel(&alice_channel).await?;
+        sleep(Duration::from_millis(250)).await;
+        alice
+            .create_secure_channel("bob_listener", TrustEveryonePolicy, &alice_storage)
+            .await?;
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_handshake_timeout(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        let limits = ListenerLimits::new()
+            .with_max_channels(1)
+            .with_handshake_timeout(Duration::from_millis(500));
+        bob.create_secure_channel_listener_extended(
+            "bob_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            SecureChannelOptions::new().with_listener_limits(limits.clone()),
+        )
+        .await?;
+
+        // An initiator which stalls after the first message of the handshake
+        let mut initiator = XXNewKeyExchanger::new(vault.clone()).initiator().await?;
+        let message_1 = initiator.generate_request(&[]).await?;
+        let hello = InitiatorHello {
+            address: ctx.address(),
+            credentials: true,
+            key_exchange: KeyExchangeKind::Xx,
+        };
+        ctx.send(
+            route!["bob_listener"],
+            CreateResponderChannelMessage::new(message_1, Some(hello.encode()?)),
+        )
+        .await?;
+
+        // The stalled handshake takes the only slot of the listener
+        sleep(Duration::from_millis(100)).await;
+        let res = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_millis(250),
+                SecureChannelOptions::new(),
+            )
+            .await;
+        assert!(res.is_err());
+        assert_eq!(limits.rejections().stats().too_many_channels(), 1);
+        assert_eq!(limits.rejections().stats().handshake_timeouts(), 0);
+
+        // The slot is released once the handshake is aborted
+        sleep(Duration::from_millis(500)).await;
+        assert_eq!(limits.rejections().stats().handshake_timeouts(), 1);
+        alice
+            .create_secure_channel("bob_listener", TrustEveryonePolicy, &alice_storage)
+            .await?;
+        assert_eq!(limits.rejections().stats().handshake_timeouts(), 1);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_close(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener_extended(
+            "bob_listener",
+            TrustEveryonePolicy,
+            &bob_storage,
+            SecureChannelOptions::new().with_close_notification(ctx.address()),
+        )
+        .await?;
+
+        let alice_channel = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(10),
+                SecureChannelOptions::new().with_close_notification(ctx.address()),
+            )
+            .await?;
+
+        ctx.send(
+            route![alice_channel.clone(), ctx.address()],
+            "Hello".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        let bob_channel = msg.return_route().next()?.clone();
+
+        alice.stop_secure_channel(&alice_channel).await?;
+
+        let mut reasons = vec![];
+        for _ in 0..2 {
+            let closed = ctx.receive::<SecureChannelClosed>().await?.take().body();
+            reasons.push((closed.encryptor_address().clone(), closed.reason()));
+        }
+        assert!(reasons.contains(&(alice_channel.clone(), CloseReason::Local)));
+        assert!(reasons.contains(&(bob_channel.clone(), CloseReason::Remote)));
+
+        let workers = ctx.list_workers().await?;
+        assert!(!workers.contains(&alice_channel));
+        assert!(!workers.contains(&bob_channel));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_idle_timeout(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+
+        let alice_channel = alice
+            .create_secure_channel_extended(
+                route!["bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+                Duration::from_secs(10),
+                SecureChannelOptions::new()
+                    .with_idle_timeout(Duration::from_millis(400))
+                    .with_close_notification(ctx.address()),
+            )
+            .await?;
+
+        // Traffic keeps the channel open
+        for _ in 0..5 {
+            sleep(Duration::from_millis(200)).await;
+            ctx.send(
+                route![alice_channel.clone(), ctx.address()],
+                "Hello".to_string(),
+            )
+            .await?;
+            ctx.receive::<String>().await?;
+        }
+
+        let closed = ctx
+            .receive_duration_timeout::<SecureChannelClosed>(Duration::from_secs(2))
+            .await?
+            .take()
+            .body();
+        assert_eq!(closed.encryptor_address(), &alice_channel);
+        assert_eq!(closed.reason(), CloseReason::Idle);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_channel_migration(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+
+        ctx.start_worker("hop1", Duplicator::default()).await?;
+        ctx.start_worker("hop2", Duplicator::default()).await?;
+
+        let alice_channel = alice
+            .create_secure_channel(
+                route!["hop1", "bob_listener"],
+                TrustEveryonePolicy,
+                &alice_storage,
+            )
+            .await?;
+
+        ctx.stop_worker("hop1").await?;
+        alice
+            .migrate_secure_channel(&alice_channel, route!["hop2"])
+            .await?;
+
+        ctx.send(
+            route![alice_channel.clone(), ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!("Hello, Bob!", msg.body());
+
+        // Bob's channel keeps its address and replies over the new route
+        let bob_channel = msg.return_route().next()?.clone();
+        ctx.send(msg.return_route(), "Hello, Alice!".to_string())
+            .await?;
+        let msg = ctx.receive::<String>().await?.take();
+        assert_eq!("Hello, Alice!", msg.body());
+        assert_eq!(msg.return_route().next()?, &alice_channel);
+
+        ctx.send(route![bob_channel, ctx.address()], "Again".to_string())
+            .await?;
+        assert_eq!("Again", ctx.receive::<String>().await?.take().body());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_trust_on_first_use(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let alice_storage = InMemoryStorage::new();
+        let bob_storage = InMemoryStorage::new();
+        let carol_storage = InMemoryStorage::new();
+
+        let alice = Identity::create(ctx, &vault).await?;
+        let bob = Identity::create(ctx, &vault).await?;
+        let carol = Identity::create(ctx, &vault).await?;
+
+        bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy, &bob_storage)
+            .await?;
+        carol
+            .create_secure_channel_listener("carol_listener", TrustEveryonePolicy, &carol_storage)
+            .await?;
+
+        let policy = TrustOnFirstUsePolicy::new("bob", alice_storage.clone());
+
+        // First use pins Bob
+        alice
+            .create_secure_channel("bob_listener", policy.clone(), &alice_storage)
+            .await?;
+        let pinned = PinnedIdentitiesStorageUtils::get("bob", &alice_storage)
+            .await?
+            .unwrap();
+        assert_eq!(pinned.identifier(), bob.identifier());
+
+        // Bob is still trusted after rotating his key
+        bob.rotate_root_key().await?;
+        alice
+            .create_secure_channel("bob_listener", policy.clone(), &alice_storage)
+            .await?;
+
+        // Carol is not Bob
+        let res = alice
+            .create_secure_channel_extended(
+                "carol_listener",
+                policy.clone(),
+                &alice_storage,
+                Duration::from_secs(1),
+                SecureChannelOptions::new(),
+            )
+            .await;
+        assert!(res.is_err());
+
+        // Once forgotten, the next identity seen gets pinned
+        assert!(PinnedIdentitiesStorageUtils::forget("bob", &alice_storage).await?);
+        alice
+            .create_secure_channel("carol_listener", policy, &alice_storage)
+            .await?;
+        let pins = PinnedIdentitiesStorageUtils::list(&alice_storage).await?;
+        assert_eq!(pins.len(), 1);
+        assert_eq!(pins["bob"].identifier(), carol.identifier());
+
+        ctx.stop().await
+    }
+
     #[ockam_macros::test]
     async fn test_tunneled_secure_channel_works(ctx: &mut Context) -> Result<()> {
         let vault = Vault::create();
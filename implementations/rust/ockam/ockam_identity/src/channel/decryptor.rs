@@ -1,32 +1,40 @@
 use crate::authenticated_storage::AuthenticatedStorage;
 use crate::{
-    EncryptorWorker, Identity, IdentityChannelMessage, IdentityError, IdentityIdentifier,
-    IdentitySecureChannelLocalInfo, IdentityVault, PublicIdentity, SecureChannelTrustInfo,
-    TrustPolicy,
+    CloseReason, DecryptorControlMessage, EncryptorWorker, Identity, IdentityChannelMessage,
+    IdentityError, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault,
+    InitiatorHello, KeyExchangeKind, ListenerSlot, PublicIdentity, Rejection, SecureChannelClosed,
+    SecureChannelOptions, SecureChannelRegistryEntry, SecureChannelTrustInfo, TrustPolicy,
 };
 use core::future::Future;
 use core::pin::Pin;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use core::time::Duration;
 use ockam_channel::{
-    CreateResponderChannelMessage, KeyExchangeCompleted, SecureChannel, SecureChannelDecryptor,
-    SecureChannelInfo,
+    CreateResponderChannelMessage, KeyExchangeCompleted, RekeyPolicy, ReplayCounters,
+    SecureChannel, SecureChannelDecryptor, SecureChannelInfo, SecureChannelKeyExchanger,
 };
-use ockam_core::compat::{boxed::Box, sync::Arc, vec::Vec};
+use ockam_core::compat::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use ockam_core::vault::Signature;
-use ockam_core::{async_trait, AllowAll, Mailbox, Mailboxes};
+use ockam_core::{async_trait, AllowAll, DenyAll, Mailbox, Mailboxes};
 use ockam_core::{
     route, Address, Any, Decodable, Encodable, LocalMessage, Message, Result, Route, Routed,
     TransportMessage, Worker,
 };
-use ockam_key_exchange_core::NewKeyExchanger;
-use ockam_key_exchange_xx::XXNewKeyExchanger;
-use ockam_node::{Context, WorkerBuilder};
+use ockam_key_exchange_core::{KeyExchanger, NewKeyExchanger};
+use ockam_key_exchange_ik::IKNewKeyExchanger;
+use ockam_key_exchange_xx::{XXCurve, XXNewKeyExchanger};
+use ockam_node::access_control::LocalOriginOnly;
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
 #[derive(Serialize, Deserialize, Message)]
 pub(crate) struct AuthenticationConfirmation(pub Address);
 
+/// Number of idle checks per idle timeout. A channel is closed after being
+/// idle for between one and `1 + 1/IDLE_CHECKS` idle timeouts.
+const IDLE_CHECKS: u32 = 4;
+
 trait StartSecureChannelFuture: Future<Output = Result<SecureChannelInfo>> + Send + 'static {}
 
 impl<T> StartSecureChannelFuture for T where
@@ -41,6 +49,9 @@ struct InitiatorStartChannel {
 
 struct ResponderWaitForKex {
     first_responder_address: Address,
+    /// The initiator can be asked for its credential
+    credentials_supported: bool,
+    regular_decryptor_address: Address,
 }
 
 struct InitiatorSendIdentity {
@@ -51,11 +62,14 @@ struct InitiatorSendIdentity {
 struct ResponderWaitForIdentity {
     auth_hash: [u8; 32],
     local_secure_channel_address: Address,
+    local_secure_channel_control_address: Address,
 }
 
 #[derive(Clone)]
 struct Initialized {
     local_secure_channel_address: Address,
+    local_secure_channel_control_address: Address,
+    remote_identity_secure_channel_address: Address,
     their_identity_id: IdentityIdentifier,
     encryptor_address: Address,
 }
@@ -76,6 +90,20 @@ pub(crate) struct DecryptorWorker<V: IdentityVault, S: AuthenticatedStorage> {
     storage: S,
     trust_policy: Arc<dyn TrustPolicy>,
     state: Option<State>,
+    /// Name of the key exchange of the underlying channel
+    key_exchange: String,
+    /// Address for [`DecryptorControlMessage`]s
+    control_address: Address,
+    options: SecureChannelOptions,
+    /// Number of messages handled by the channel, shared with the encryptor
+    activity: Arc<AtomicUsize>,
+    /// Activity seen at the last idle check
+    last_activity: usize,
+    idle_checks: u32,
+    idle_check: Option<DelayedEvent<DecryptorControlMessage>>,
+    /// Slot of the channel in the listener which accepted it
+    listener_slot: Option<ListenerSlot>,
+    handshake_timer: Option<DelayedEvent<DecryptorControlMessage>>,
 }
 
 impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
@@ -86,6 +114,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         storage: S,
         trust_policy: Arc<dyn TrustPolicy>,
         timeout: Duration,
+        options: SecureChannelOptions,
     ) -> Result<Address> {
         let child_address = Address::random_tagged(
             "IdentitySecureChannel.initiator.decryptor.kex_callback_address",
@@ -105,22 +134,71 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         let mut child_ctx = ctx.new_detached_with_mailboxes(mailboxes).await?;
 
         let self_address = Address::random_tagged("IdentitySecureChannel.initiator.decryptor.self");
+        let control_address =
+            Address::random_tagged("IdentitySecureChannel.initiator.decryptor.control");
+        let rekey_policy = options.rekey_policy();
 
         let vault = identity.vault.async_try_clone().await?;
-        let initiator = XXNewKeyExchanger::new(vault.async_try_clone().await?)
-            .initiator()
-            .await?;
+        let replay_counters = options.counters().replay_counters();
+        let cipher_suites = options.cipher_suites().to_vec();
+        let ik_remote_static_key = options.initiator_ik().map(|(_, key)| key);
         // Create regular secure channel and set self address as first responder
-        let custom_payload = self_address.encode()?;
+        let custom_payload = InitiatorHello {
+            address: self_address.clone(),
+            credentials: true,
+            key_exchange: if ik_remote_static_key.is_some() {
+                KeyExchangeKind::Ik
+            } else {
+                KeyExchangeKind::Xx
+            },
+        }
+        .encode()?;
         let temp_ctx = ctx
             .new_detached(Address::random_tagged(
                 "IdentitySecureChannel.initiator.decryptor.temp",
             ))
             .await?;
-        let channel_future = Box::pin(async move {
-            SecureChannel::create_extended(&temp_ctx, route, Some(custom_payload), initiator, vault)
-                .await
-        });
+        let (key_exchange, channel_future) = match ik_remote_static_key {
+            Some(public_key) => {
+                let initiator = IKNewKeyExchanger::new(vault.async_try_clone().await?)
+                    .with_remote_static_public_key(public_key.clone())
+                    .initiator()
+                    .await?;
+                (
+                    initiator.name().await?,
+                    Self::start_channel(
+                        temp_ctx,
+                        route,
+                        custom_payload,
+                        initiator,
+                        vault,
+                        rekey_policy,
+                        replay_counters,
+                    ),
+                )
+            }
+            None => {
+                let mut new_key_exchanger = XXNewKeyExchanger::new(vault.async_try_clone().await?)
+                    .with_cipher_suites(cipher_suites)
+                    .with_curve(options.curve());
+                if options.hybrid_kem() {
+                    new_key_exchanger = new_key_exchanger.with_hybrid_kem();
+                }
+                let initiator = new_key_exchanger.initiator().await?;
+                (
+                    initiator.name().await?,
+                    Self::start_channel(
+                        temp_ctx,
+                        route,
+                        custom_payload,
+                        initiator,
+                        vault,
+                        rekey_policy,
+                        replay_counters,
+                    ),
+                )
+            }
+        };
 
         let state = State::InitiatorStartChannel(InitiatorStartChannel {
             channel_future,
@@ -135,6 +213,15 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             trust_policy,
             storage,
             state: Some(state),
+            key_exchange,
+            control_address: control_address.clone(),
+            options,
+            activity: Arc::new(AtomicUsize::new(0)),
+            last_activity: 0,
+            idle_checks: 0,
+            idle_check: None,
+            listener_slot: None,
+            handshake_timer: None,
         };
 
         // TODO @ac 0#DecryptorWorker_create_initiator
@@ -145,7 +232,12 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             Arc::new(ockam_core::ToDoAccessControl),
             Arc::new(ockam_core::ToDoAccessControl),
         );
-        WorkerBuilder::with_mailboxes(Mailboxes::new(mailbox, vec![]), worker)
+        let control_mailbox = Mailbox::new(
+            control_address,
+            Arc::new(LocalOriginOnly),
+            Arc::new(DenyAll),
+        );
+        WorkerBuilder::with_mailboxes(Mailboxes::new(mailbox, vec![control_mailbox]), worker)
             .start(ctx)
             .await?;
 
@@ -164,33 +256,127 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         Ok(encryptor_address)
     }
 
+    fn start_channel(
+        ctx: Context,
+        route: Route,
+        custom_payload: Vec<u8>,
+        initiator: impl SecureChannelKeyExchanger,
+        vault: V,
+        rekey_policy: RekeyPolicy,
+        replay_counters: ReplayCounters,
+    ) -> Pin<Box<dyn StartSecureChannelFuture>> {
+        Box::pin(async move {
+            SecureChannel::create_extended(
+                &ctx,
+                route,
+                Some(custom_payload),
+                initiator,
+                vault,
+                rekey_policy,
+                replay_counters,
+            )
+            .await
+        })
+    }
+
     pub(crate) async fn create_responder(
         ctx: &Context,
         identity: Identity<V>,
         storage: S,
         trust_policy: Arc<dyn TrustPolicy>,
+        options: SecureChannelOptions,
+        listener_slot: ListenerSlot,
         msg: Routed<CreateResponderChannelMessage>,
     ) -> Result<()> {
-        let return_route = msg.return_route();
-        let mut onward_route = msg.onward_route();
-        let body = msg.body();
+        let vault = identity.vault.async_try_clone().await?;
         // This is the address of Worker on the other end, that Initiator gave us to perform further negotiations.
-        let custom_payload = body
+        let custom_payload = msg
+            .as_body()
             .custom_payload()
             .as_ref()
             .ok_or(IdentityError::SecureChannelCannotBeAuthenticated)?;
-        let first_responder_address = Address::decode(custom_payload)?;
+        let hello = InitiatorHello::decode_custom_payload(custom_payload)?;
+        match (hello.key_exchange, options.ik_static_key()) {
+            (KeyExchangeKind::Ik, Some(key_id)) if options.curve() == XXCurve::X25519 => {
+                let responder = IKNewKeyExchanger::new(vault)
+                    .with_static_key(key_id.clone())
+                    .responder()
+                    .await?;
+                Self::start_responder(
+                    ctx,
+                    identity,
+                    storage,
+                    trust_policy,
+                    options,
+                    listener_slot,
+                    msg,
+                    hello,
+                    responder,
+                )
+                .await
+            }
+            (KeyExchangeKind::Ik, _) => {
+                warn!("SecureChannel listener refused an IK handshake: no IK static key");
+                Err(IdentityError::UnsupportedKeyExchange.into())
+            }
+            (KeyExchangeKind::Xx, _) => {
+                let responder = XXNewKeyExchanger::new(vault)
+                    .with_cipher_suites(options.cipher_suites().to_vec())
+                    .with_curve(options.curve())
+                    .responder()
+                    .await?;
+                Self::start_responder(
+                    ctx,
+                    identity,
+                    storage,
+                    trust_policy,
+                    options,
+                    listener_slot,
+                    msg,
+                    hello,
+                    responder,
+                )
+                .await
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn start_responder(
+        ctx: &Context,
+        identity: Identity<V>,
+        storage: S,
+        trust_policy: Arc<dyn TrustPolicy>,
+        options: SecureChannelOptions,
+        listener_slot: ListenerSlot,
+        msg: Routed<CreateResponderChannelMessage>,
+        hello: InitiatorHello,
+        responder: impl SecureChannelKeyExchanger,
+    ) -> Result<()> {
+        let return_route = msg.return_route();
+        let mut onward_route = msg.onward_route();
+        let body = msg.body();
 
         let self_address = Address::random_tagged("IdentitySecureChannel.responder.decryptor.self");
+        let control_address =
+            Address::random_tagged("IdentitySecureChannel.responder.decryptor.control");
+        let rekey_policy = options.rekey_policy();
+
+        let regular_responder_address = Address::random_tagged("SecureChannel.responder.decryptor");
 
         let vault = identity.vault.async_try_clone().await?;
         let state = State::ResponderWaitForKex(ResponderWaitForKex {
-            first_responder_address,
+            first_responder_address: hello.address,
+            credentials_supported: hello.credentials,
+            regular_decryptor_address: regular_responder_address.clone(),
         });
 
+        let key_exchange = responder.name().await?;
+
         let kex_callback_address = Address::random_tagged(
             "IdentitySecureChannel.responder.decryptor.kex_callback_address",
         );
+        let replay_counters = options.counters().replay_counters();
         let worker = DecryptorWorker {
             is_initiator: false,
             self_address: self_address.clone(),
@@ -199,16 +385,32 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             storage,
             kex_callback_address: Some(kex_callback_address.clone()),
             state: Some(state),
+            key_exchange,
+            control_address: control_address.clone(),
+            options,
+            activity: Arc::new(AtomicUsize::new(0)),
+            last_activity: 0,
+            idle_checks: 0,
+            idle_check: None,
+            listener_slot: Some(listener_slot),
+            handshake_timer: None,
         };
 
         // TODO: @ac
         let mailboxes = Mailboxes::new(
             Mailbox::allow_all(self_address.clone()),
-            vec![Mailbox::new(
-                kex_callback_address.clone(),
-                Arc::new(AllowAll), // TODO: @ac only kex
-                Arc::new(AllowAll), // TODO: @ac deny all
-            )],
+            vec![
+                Mailbox::new(
+                    kex_callback_address.clone(),
+                    Arc::new(AllowAll), // TODO: @ac only kex
+                    Arc::new(AllowAll), // TODO: @ac deny all
+                ),
+                Mailbox::new(
+                    control_address,
+                    Arc::new(LocalOriginOnly),
+                    Arc::new(DenyAll),
+                ),
+            ],
         );
         WorkerBuilder::with_mailboxes(mailboxes, worker)
             .start(ctx)
@@ -219,16 +421,12 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             &self_address
         );
 
-        let regular_responder_address = Address::random_tagged("SecureChannel.responder.decryptor");
-
-        let responder = XXNewKeyExchanger::new(vault.async_try_clone().await?)
-            .responder()
-            .await?;
-
         let vault = vault.async_try_clone().await?;
         let regular_decryptor =
             SecureChannelDecryptor::new_responder(responder, Some(kex_callback_address), vault)
-                .await?;
+                .await?
+                .with_rekey_policy(rekey_policy)
+                .with_replay_counters(replay_counters);
 
         // TODO: @ac
         let mailboxes = Mailboxes::new(
@@ -267,9 +465,19 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
             .create_signature(&kex_msg.auth_hash(), None)
             .await?;
         let identity = self.identity.export().await?;
-        let msg = IdentityChannelMessage::Request {
-            identity,
-            signature: signature.as_ref().to_vec(),
+        // Initiators not knowing about credentials can't decode the extended message
+        let msg = if self.trust_policy.requires_credential() && state.credentials_supported {
+            IdentityChannelMessage::RequestWithCredential {
+                identity,
+                signature: signature.as_ref().to_vec(),
+                credential: self.encoded_credential().await?,
+                credential_requested: true,
+            }
+        } else {
+            IdentityChannelMessage::Request {
+                identity,
+                signature: signature.as_ref().to_vec(),
+            }
         };
         ctx.send_from_address(
             route![kex_msg.address().clone(), state.first_responder_address],
@@ -282,6 +490,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         self.state = Some(State::ResponderWaitForIdentity(ResponderWaitForIdentity {
             auth_hash: kex_msg.auth_hash(),
             local_secure_channel_address: kex_msg.address().clone(),
+            local_secure_channel_control_address: kex_msg.control_address().clone(),
         }));
 
         Ok(())
@@ -304,102 +513,144 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
 
         // Wait for responder to send us his Identity and Identity Proof.
         // In case of using Noise XX this is m4 message.
-        if let IdentityChannelMessage::Request {
-            identity,
-            signature,
-        } = body
-        {
-            debug!("Received Authentication request");
-
-            let their_identity = PublicIdentity::import(&identity, &self.identity.vault).await?;
-            let their_identity_id = their_identity.identifier();
-
-            // Verify responder posses their Identity key
-            let verified = their_identity
-                .verify_signature(
-                    &Signature::new(signature),
-                    &state.channel.auth_hash(),
-                    None,
-                    &self.identity.vault,
-                )
-                .await?;
+        let (identity, signature, their_credential, credential_requested) = match body {
+            IdentityChannelMessage::Request {
+                identity,
+                signature,
+            } => (identity, signature, None, false),
+            IdentityChannelMessage::RequestWithCredential {
+                identity,
+                signature,
+                credential,
+                credential_requested,
+            } => (identity, signature, credential, credential_requested),
+            _ => return Err(IdentityError::InvalidSecureChannelInternalState.into()),
+        };
 
-            if !verified {
-                return Err(IdentityError::SecureChannelVerificationFailed.into());
-            }
+        debug!("Received Authentication request");
 
-            self.identity
-                .update_known_identity(their_identity_id, &their_identity, &self.storage)
-                .await?;
+        let their_identity = PublicIdentity::import(&identity, &self.identity.vault).await?;
+        let their_identity_id = their_identity.identifier();
 
-            info!(
-                "Initiator verified SecureChannel from: {}",
-                their_identity_id
-            );
+        // Verify responder posses their Identity key
+        let verified = their_identity
+            .verify_signature(
+                &Signature::new(signature),
+                &state.channel.auth_hash(),
+                None,
+                &self.identity.vault,
+            )
+            .await?;
 
-            // Check our TrustPolicy
-            let trust_info = SecureChannelTrustInfo::new(their_identity_id.clone());
-            let trusted = self.trust_policy.check(&trust_info).await?;
-            if !trusted {
-                // TODO: Shutdown? Communicate error?
+        if !verified {
+            return Err(IdentityError::SecureChannelVerificationFailed.into());
+        }
+
+        // The IK static key of the listener was only trusted for its identity
+        if let Some(ik_remote_identity) = self.ik_remote_identity() {
+            if ik_remote_identity != their_identity_id {
+                warn!(
+                    "IK static key of {} used by {}",
+                    ik_remote_identity, their_identity_id
+                );
                 return Err(IdentityError::SecureChannelTrustCheckFailed.into());
             }
-            info!(
-                "Initiator checked trust policy for SecureChannel from: {}",
-                their_identity_id
-            );
+        }
 
-            // Prove we posses our Identity key
-            let identity = self.identity.export().await?;
-            let signature = self
-                .identity
-                .create_signature(&state.channel.auth_hash(), None)
-                .await?;
+        self.identity
+            .update_known_identity(their_identity_id, &their_identity, &self.storage)
+            .await?;
 
-            let auth_msg = IdentityChannelMessage::Response {
-                identity,
-                signature: signature.as_ref().to_vec(),
-            };
+        info!(
+            "Initiator verified SecureChannel from: {}",
+            their_identity_id
+        );
 
-            let remote_identity_secure_channel_address = return_route.recipient();
+        // Check our TrustPolicy
+        let trust_info = SecureChannelTrustInfo::new(their_identity_id.clone())
+            .with_credential(their_credential);
+        let trusted = self.trust_policy.check(&trust_info).await?;
+        if !trusted {
+            // TODO: Shutdown? Communicate error?
+            return Err(IdentityError::SecureChannelTrustCheckFailed.into());
+        }
+        info!(
+            "Initiator checked trust policy for SecureChannel from: {}",
+            their_identity_id
+        );
 
-            ctx.send_from_address(return_route, auth_msg, self.self_address.clone())
-                .await?;
-            debug!("Sent Authentication response");
+        // Prove we posses our Identity key
+        let identity = self.identity.export().await?;
+        let signature = self
+            .identity
+            .create_signature(&state.channel.auth_hash(), None)
+            .await?;
 
-            let encryptor_address =
-                Address::random_tagged("IdentitySecureChannel.initiator.encryptor");
+        // Only answer with the extended message if the other side asked for it,
+        // so that we stay compatible with responders not knowing about it
+        let auth_msg = if credential_requested {
+            IdentityChannelMessage::ResponseWithCredential {
+                identity,
+                signature: signature.as_ref().to_vec(),
+                credential: self.encoded_credential().await?,
+            }
+        } else {
+            IdentityChannelMessage::Response {
+                identity,
+                signature: signature.as_ref().to_vec(),
+            }
+        };
 
-            self.state = Some(State::Initialized(Initialized {
-                local_secure_channel_address: state.channel.address(),
-                their_identity_id: their_identity_id.clone(),
-                encryptor_address: encryptor_address.clone(),
-            }));
+        let remote_identity_secure_channel_address = return_route.recipient();
 
-            let encryptor = EncryptorWorker::new(
-                self.is_initiator,
-                remote_identity_secure_channel_address,
-                state.channel.address(),
-            );
+        ctx.send_from_address(return_route, auth_msg, self.self_address.clone())
+            .await?;
+        debug!("Sent Authentication response");
 
-            ctx.start_worker(encryptor_address.clone(), encryptor)
-                .await?;
+        let encryptor_address = Address::random_tagged("IdentitySecureChannel.initiator.encryptor");
 
-            info!(
-                "Initialized IdentitySecureChannel Initiator at local: {}, remote: {}",
-                &encryptor_address, &self.self_address
-            );
+        self.state = Some(State::Initialized(Initialized {
+            local_secure_channel_address: state.channel.address(),
+            local_secure_channel_control_address: state.channel.control_address(),
+            remote_identity_secure_channel_address: remote_identity_secure_channel_address.clone(),
+            their_identity_id: their_identity_id.clone(),
+            encryptor_address: encryptor_address.clone(),
+        }));
 
-            ctx.send(
-                state.callback_address,
-                AuthenticationConfirmation(encryptor_address),
-            )
+        let encryptor = EncryptorWorker::new(
+            self.is_initiator,
+            remote_identity_secure_channel_address,
+            state.channel.address(),
+            self.activity.clone(),
+            self.options.counters.clone(),
+        );
+
+        ctx.start_worker(encryptor_address.clone(), encryptor)
             .await?;
+        self.identity.secure_channels.register(
+            encryptor_address.clone(),
+            SecureChannelRegistryEntry {
+                control_address: self.control_address.clone(),
+                counters: self.options.counters.clone(),
+            },
+        );
+        self.options
+            .counters
+            .record_established(&their_identity_id, &self.key_exchange);
+        self.schedule_idle_check(ctx).await?;
+
+        info!(
+            "Initialized IdentitySecureChannel Initiator at local: {}, remote: {}",
+            &encryptor_address, &self.self_address
+        );
 
-            Ok(())
-        } else {
-            Err(IdentityError::InvalidSecureChannelInternalState.into())
-        }
+        ctx.send(
+            state.callback_address,
+            AuthenticationConfirmation(encryptor_address),
+        )
+        .await?;
+
+        Ok(())
     }
 
     async fn handle_receive_identity(
@@ -419,79 +670,272 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
 
         // Wait for responder to send us his Identity and Identity Proof.
         // In case of using Noise XX this is m4 message.
-        if let IdentityChannelMessage::Response {
-            identity,
-            signature,
-        } = body
-        {
-            debug!("Received Authentication response");
-
-            let their_identity = PublicIdentity::import(&identity, &self.identity.vault).await?;
-            let their_identity_id = their_identity.identifier();
-
-            // Verify initiator posses their Identity key
-            let verified = their_identity
-                .verify_signature(
-                    &Signature::new(signature),
-                    &state.auth_hash,
-                    None,
-                    &self.identity.vault,
-                )
-                .await?;
+        let (identity, signature, their_credential) = match body {
+            IdentityChannelMessage::Response {
+                identity,
+                signature,
+            } => (identity, signature, None),
+            IdentityChannelMessage::ResponseWithCredential {
+                identity,
+                signature,
+                credential,
+            } => (identity, signature, credential),
+            _ => return Err(IdentityError::InvalidSecureChannelInternalState.into()),
+        };
+
+        debug!("Received Authentication response");
+
+        let their_identity = PublicIdentity::import(&identity, &self.identity.vault).await?;
+        let their_identity_id = their_identity.identifier();
+
+        // Verify initiator posses their Identity key
+        let verified = their_identity
+            .verify_signature(
+                &Signature::new(signature),
+                &state.auth_hash,
+                None,
+                &self.identity.vault,
+            )
+            .await?;
 
-            if !verified {
-                return Err(IdentityError::SecureChannelVerificationFailed.into());
+        if !verified {
+            return Err(IdentityError::SecureChannelVerificationFailed.into());
+        }
+
+        self.identity
+            .update_known_identity(their_identity_id, &their_identity, &self.storage)
+            .await?;
+
+        info!(
+            "Responder verified SecureChannel from: {}",
+            their_identity_id
+        );
+
+        // Check our TrustPolicy
+        let trust_info = SecureChannelTrustInfo::new(their_identity_id.clone())
+            .with_credential(their_credential);
+        let trusted = self.trust_policy.check(&trust_info).await?;
+        if !trusted {
+            // TODO: Shutdown? Communicate error?
+            return Err(IdentityError::SecureChannelTrustCheckFailed.into());
+        }
+        info!(
+            "Responder checked trust policy for SecureChannel from: {}",
+            their_identity_id
+        );
+
+        if let Some(listener_slot) = self.listener_slot.as_mut() {
+            let limits = self.options.listener_limits();
+            if !listener_slot.assign(their_identity_id, limits) {
+                limits.rejections().record(Rejection::TooManyForIdentity);
+                warn!(
+                    "Aborting SecureChannel handshake: too many channels from {}",
+                    their_identity_id
+                );
+                SecureChannel::stop(ctx, state.local_secure_channel_control_address).await?;
+                return ctx.stop_worker(self.self_address.clone()).await;
             }
+        }
+        self.handshake_timer = None;
 
-            self.identity
-                .update_known_identity(their_identity_id, &their_identity, &self.storage)
-                .await?;
+        let remote_identity_secure_channel_address = return_route.recipient();
 
-            info!(
-                "Responder verified SecureChannel from: {}",
-                their_identity_id
-            );
+        let encryptor_address = Address::random_tagged("IdentitySecureChannel.responder.encryptor");
 
-            // Check our TrustPolicy
-            let trust_info = SecureChannelTrustInfo::new(their_identity_id.clone());
-            let trusted = self.trust_policy.check(&trust_info).await?;
-            if !trusted {
-                // TODO: Shutdown? Communicate error?
-                return Err(IdentityError::SecureChannelTrustCheckFailed.into());
+        self.state = Some(State::Initialized(Initialized {
+            local_secure_channel_address: state.local_secure_channel_address.clone(),
+            local_secure_channel_control_address: state
+                .local_secure_channel_control_address
+                .clone(),
+            remote_identity_secure_channel_address: remote_identity_secure_channel_address.clone(),
+            their_identity_id: their_identity_id.clone(),
+            encryptor_address: encryptor_address.clone(),
+        }));
+
+        let encryptor = EncryptorWorker::new(
+            self.is_initiator,
+            remote_identity_secure_channel_address,
+            state.local_secure_channel_address,
+            self.activity.clone(),
+            self.options.counters.clone(),
+        );
+
+        ctx.start_worker(encryptor_address.clone(), encryptor)
+            .await?;
+        self.identity.secure_channels.register(
+            encryptor_address.clone(),
+            SecureChannelRegistryEntry {
+                control_address: self.control_address.clone(),
+                counters: self.options.counters.clone(),
+            },
+        );
+        self.options
+            .counters
+            .record_established(&their_identity_id, &self.key_exchange);
+        self.schedule_idle_check(ctx).await?;
+
+        info!(
+            "Initialized IdentitySecureChannel Responder at local: {}, remote: {}",
+            &encryptor_address, &self.self_address
+        );
+
+        Ok(())
+    }
+
+    async fn schedule_idle_check(&mut self, ctx: &Context) -> Result<()> {
+        let idle_timeout = match self.options.idle_timeout() {
+            Some(idle_timeout) => idle_timeout,
+            None => return Ok(()),
+        };
+        if self.idle_check.is_none() {
+            let idle_check = DelayedEvent::create(
+                ctx,
+                self.control_address.clone(),
+                DecryptorControlMessage::IdleCheck,
+            )
+            .await?;
+            self.idle_check = Some(idle_check);
+        }
+        if let Some(idle_check) = self.idle_check.as_mut() {
+            idle_check.schedule(idle_timeout / IDLE_CHECKS).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_control(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        msg: Routed<<Self as Worker>::Message>,
+    ) -> Result<()> {
+        let msg = DecryptorControlMessage::decode(msg.payload())?;
+        if let DecryptorControlMessage::HandshakeTimeout = msg {
+            return self.handle_handshake_timeout(ctx).await;
+        }
+
+        let state = match &self.state {
+            Some(State::Initialized(state)) => state.clone(),
+            _ => return Err(IdentityError::InvalidSecureChannelInternalState.into()),
+        };
+
+        match msg {
+            DecryptorControlMessage::IdleCheck => {
+                let activity = self.activity.load(Ordering::Relaxed);
+                if activity != self.last_activity {
+                    self.last_activity = activity;
+                    self.idle_checks = 0;
+                } else {
+                    self.idle_checks += 1;
+                }
+
+                if self.idle_checks >= IDLE_CHECKS {
+                    self.close(ctx, &state, CloseReason::Idle).await
+                } else {
+                    self.schedule_idle_check(ctx).await
+                }
             }
-            info!(
-                "Responder checked trust policy for SecureChannel from: {}",
-                their_identity_id
-            );
+            DecryptorControlMessage::Close => self.close(ctx, &state, CloseReason::Local).await,
+            DecryptorControlMessage::Migrate { route } => {
+                debug!("IdentitySecureChannel received Migrate");
+                SecureChannel::migrate(
+                    ctx,
+                    state.local_secure_channel_control_address.clone(),
+                    route,
+                )
+                .await
+            }
+            DecryptorControlMessage::HandshakeTimeout => Ok(()),
+        }
+    }
 
-            let remote_identity_secure_channel_address = return_route.recipient();
+    /// Stop a responder whose handshake is still not completed, along with
+    /// its regular channel
+    async fn handle_handshake_timeout(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+    ) -> Result<()> {
+        match &self.state {
+            Some(State::ResponderWaitForKex(state)) => {
+                ctx.stop_worker(state.regular_decryptor_address.clone())
+                    .await?
+            }
+            Some(State::ResponderWaitForIdentity(state)) => {
+                SecureChannel::stop(ctx, state.local_secure_channel_control_address.clone()).await?
+            }
+            _ => return Ok(()),
+        }
 
-            let encryptor_address =
-                Address::random_tagged("IdentitySecureChannel.responder.encryptor");
+        let limits = self.options.listener_limits();
+        limits.rejections().record(Rejection::HandshakeTimeout);
+        warn!(
+            "Aborting SecureChannel handshake at {}: timed out",
+            self.self_address
+        );
 
-            self.state = Some(State::Initialized(Initialized {
-                local_secure_channel_address: state.local_secure_channel_address.clone(),
-                their_identity_id: their_identity_id.clone(),
-                encryptor_address: encryptor_address.clone(),
-            }));
+        ctx.stop_worker(self.self_address.clone()).await
+    }
 
-            let encryptor = EncryptorWorker::new(
-                self.is_initiator,
-                remote_identity_secure_channel_address,
-                state.local_secure_channel_address,
-            );
+    /// Stop both sides of the channel. The other side is told to do the same,
+    /// unless it is the one which closed the channel.
+    async fn close(
+        &mut self,
+        ctx: &mut <Self as Worker>::Context,
+        state: &Initialized,
+        reason: CloseReason,
+    ) -> Result<()> {
+        info!(
+            "Closing IdentitySecureChannel {} with {} ({:?})",
+            state.encryptor_address, state.their_identity_id, reason
+        );
 
-            ctx.start_worker(encryptor_address.clone(), encryptor)
-                .await?;
+        self.idle_check = None;
 
-            info!(
-                "Initialized IdentitySecureChannel Responder at local: {}, remote: {}",
-                &encryptor_address, &self.self_address
+        if reason != CloseReason::Remote {
+            // Sent before stopping the regular channel, which handles
+            // messages in order
+            ctx.send(
+                route![
+                    state.local_secure_channel_address.clone(),
+                    state.remote_identity_secure_channel_address.clone()
+                ],
+                IdentityChannelMessage::Close,
+            )
+            .await?;
+        }
+
+        SecureChannel::stop(ctx, state.local_secure_channel_control_address.clone()).await?;
+        ctx.stop_worker(state.encryptor_address.clone()).await?;
+        self.identity
+            .secure_channels
+            .unregister(&state.encryptor_address);
+
+        if let Some(address) = self.options.close_notification_address.take() {
+            let closed = SecureChannelClosed::new(
+                state.encryptor_address.clone(),
+                state.their_identity_id.clone(),
+                reason,
             );
+            if let Err(err) = ctx.send(address.clone(), closed).await {
+                warn!("{} notifying {} of a closed channel", err, address);
+            }
+        }
 
-            Ok(())
+        ctx.stop_worker(self.self_address.clone()).await
+    }
+
+    /// Identity of the listener, if the initiator used its IK static key
+    fn ik_remote_identity(&self) -> Option<&IdentityIdentifier> {
+        if self.is_initiator {
+            self.options.initiator_ik().map(|(identity, _)| identity)
         } else {
-            Err(IdentityError::InvalidSecureChannelInternalState.into())
+            None
+        }
+    }
+
+    /// Our own credential, CBOR-encoded, to be presented during the handshake.
+    async fn encoded_credential(&self) -> Result<Option<Vec<u8>>> {
+        match self.identity.credential().await {
+            Some(c) => Ok(Some(minicbor::to_vec(&c)?)),
+            None => Ok(None),
         }
     }
 
@@ -526,6 +970,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
 
         // Ensure message came from dedicated SecureChannel
         if return_route.next()? != &state.local_secure_channel_address {
+            self.options.counters.record_failure();
             return Err(IdentityError::UnknownChannelMsgDestination.into());
         }
 
@@ -533,9 +978,19 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, S> {
         let local_info = local_msg.local_info().to_vec();
         let payload = local_msg.into_transport_message().payload;
 
-        // Forward to local workers
         let _ = onward_route.step()?;
 
+        // A message addressed to the decryptor itself is meant for the channel
+        if onward_route.iter().next().is_none() {
+            if let Ok(IdentityChannelMessage::Close) = IdentityChannelMessage::decode(&payload) {
+                return self.close(ctx, &state, CloseReason::Remote).await;
+            }
+        }
+
+        self.activity.fetch_add(1, Ordering::Relaxed);
+        self.options.counters.record_decrypted(payload.len());
+
+        // Forward to local workers
         let return_route = return_route
             .modify()
             .pop_front()
@@ -568,7 +1023,20 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Worker for DecryptorWorker<V, S>
     type Message = Any;
     type Context = Context;
 
-    async fn initialize(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        if let Some(handshake_timeout) = self.options.listener_limits().handshake_timeout() {
+            if !self.is_initiator {
+                let mut handshake_timer = DelayedEvent::create(
+                    ctx,
+                    self.control_address.clone(),
+                    DecryptorControlMessage::HandshakeTimeout,
+                )
+                .await?;
+                handshake_timer.schedule(handshake_timeout).await?;
+                self.handshake_timer = Some(handshake_timer);
+            }
+        }
+
         if self.is_initiator {
             match self.take_state()? {
                 State::InitiatorStartChannel(s) => {
@@ -593,6 +1061,10 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Worker for DecryptorWorker<V, S>
     ) -> Result<()> {
         let msg_addr = msg.msg_addr();
 
+        if msg_addr == self.control_address {
+            return self.handle_control(ctx, msg).await;
+        }
+
         match self.take_state()? {
             State::InitiatorStartChannel(_) => {
                 return Err(IdentityError::InvalidSecureChannelInternalState.into())
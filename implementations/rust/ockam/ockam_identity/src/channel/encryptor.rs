@@ -1,5 +1,7 @@
+use crate::{IdentityError, SecureChannelCounters};
+use core::sync::atomic::{AtomicUsize, Ordering};
 use ockam_core::async_trait;
-use ockam_core::compat::{boxed::Box, vec::Vec};
+use ockam_core::compat::{boxed::Box, sync::Arc, vec::Vec};
 use ockam_core::{Address, Any, LocalMessage, Result, Routed, TransportMessage, Worker};
 use ockam_node::Context;
 use tracing::debug;
@@ -8,6 +10,9 @@ pub(crate) struct EncryptorWorker {
     is_initiator: bool,
     remote_identity_secure_channel_address: Address,
     local_secure_channel_address: Address,
+    /// Number of messages handled by the channel, shared with the decryptor
+    activity: Arc<AtomicUsize>,
+    counters: SecureChannelCounters,
 }
 
 impl EncryptorWorker {
@@ -15,11 +20,15 @@ impl EncryptorWorker {
         is_initiator: bool,
         remote_identity_secure_channel_address: Address,
         local_secure_channel_address: Address,
+        activity: Arc<AtomicUsize>,
+        counters: SecureChannelCounters,
     ) -> Self {
         Self {
             is_initiator,
             remote_identity_secure_channel_address,
             local_secure_channel_address,
+            activity,
+            counters,
         }
     }
 
@@ -41,8 +50,18 @@ impl EncryptorWorker {
         let return_route = msg.return_route();
         let payload = msg.payload().to_vec();
 
-        // Send to the other party using local regular SecureChannel
         let _ = onward_route.step()?;
+
+        // The other side's decryptor would take a message without onward
+        // route for a message to the channel itself, e.g. to close it
+        if onward_route.iter().next().is_none() {
+            return Err(IdentityError::UnknownChannelMsgDestination.into());
+        }
+
+        self.activity.fetch_add(1, Ordering::Relaxed);
+        self.counters.record_encrypted(payload.len());
+
+        // Send to the other party using local regular SecureChannel
         let onward_route = onward_route
             .modify()
             .prepend(self.remote_identity_secure_channel_address.clone())
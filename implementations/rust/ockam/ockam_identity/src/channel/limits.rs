@@ -0,0 +1,222 @@
+use crate::IdentityIdentifier;
+use core::time::Duration;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::{Arc, Mutex, RwLock};
+
+/// Limits of the channels accepted by a secure channel listener, see
+/// [`crate::SecureChannelOptions::with_listener_limits`].
+///
+/// Channels still in their handshake count towards the maximum number of
+/// channels, they only count towards the limit of an identity once it is
+/// authenticated.
+#[derive(Clone, Debug, Default)]
+pub struct ListenerLimits {
+    max_channels: Option<usize>,
+    max_channels_per_identity: Option<usize>,
+    handshake_timeout: Option<Duration>,
+    rejections: ListenerRejections,
+}
+
+impl ListenerLimits {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse new handshakes once the listener has that many channels
+    pub fn with_max_channels(mut self, max_channels: usize) -> Self {
+        self.max_channels = Some(max_channels);
+        self
+    }
+
+    /// Refuse to authenticate an identity which already has that many channels
+    pub fn with_max_channels_per_identity(mut self, max_channels: usize) -> Self {
+        self.max_channels_per_identity = Some(max_channels);
+        self
+    }
+
+    /// Abort handshakes which are not completed after the given time
+    pub fn with_handshake_timeout(mut self, handshake_timeout: Duration) -> Self {
+        self.handshake_timeout = Some(handshake_timeout);
+        self
+    }
+
+    /// Count rejected channels in the given counters
+    pub fn with_rejections(mut self, rejections: ListenerRejections) -> Self {
+        self.rejections = rejections;
+        self
+    }
+
+    pub fn max_channels(&self) -> Option<usize> {
+        self.max_channels
+    }
+
+    pub fn max_channels_per_identity(&self) -> Option<usize> {
+        self.max_channels_per_identity
+    }
+
+    pub fn handshake_timeout(&self) -> Option<Duration> {
+        self.handshake_timeout
+    }
+
+    pub fn rejections(&self) -> &ListenerRejections {
+        &self.rejections
+    }
+}
+
+/// Number of channels rejected by a secure channel listener.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ListenerRejectionStats {
+    too_many_channels: u64,
+    too_many_for_identity: u64,
+    handshake_timeouts: u64,
+}
+
+impl ListenerRejectionStats {
+    /// Handshakes refused because the listener had too many channels
+    pub fn too_many_channels(&self) -> u64 {
+        self.too_many_channels
+    }
+
+    /// Handshakes aborted because the identity had too many channels
+    pub fn too_many_for_identity(&self) -> u64 {
+        self.too_many_for_identity
+    }
+
+    /// Handshakes aborted because they took too long
+    pub fn handshake_timeouts(&self) -> u64 {
+        self.handshake_timeouts
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Rejection {
+    TooManyChannels,
+    TooManyForIdentity,
+    HandshakeTimeout,
+}
+
+/// Shared handle to the [`ListenerRejectionStats`] of a listener.
+#[derive(Clone, Debug, Default)]
+pub struct ListenerRejections(Arc<RwLock<ListenerRejectionStats>>);
+
+impl ListenerRejections {
+    /// New counters, starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current values of the counters
+    pub fn stats(&self) -> ListenerRejectionStats {
+        self.0.read().map(|stats| *stats).unwrap_or_default()
+    }
+
+    pub(crate) fn record(&self, rejection: Rejection) {
+        if let Ok(mut stats) = self.0.write() {
+            match rejection {
+                Rejection::TooManyChannels => stats.too_many_channels += 1,
+                Rejection::TooManyForIdentity => stats.too_many_for_identity += 1,
+                Rejection::HandshakeTimeout => stats.handshake_timeouts += 1,
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Slots {
+    channels: usize,
+    identities: BTreeMap<IdentityIdentifier, usize>,
+}
+
+/// Channels of a listener, shared with the workers of its channels.
+#[derive(Clone, Default)]
+pub(crate) struct ListenerSlots(Arc<Mutex<Slots>>);
+
+impl ListenerSlots {
+    /// Reserve a slot for a new channel, unless the listener has too many
+    pub(crate) fn acquire(&self, limits: &ListenerLimits) -> Option<ListenerSlot> {
+        let mut slots = self.0.lock().ok()?;
+        if let Some(max_channels) = limits.max_channels {
+            if slots.channels >= max_channels {
+                return None;
+            }
+        }
+        slots.channels += 1;
+        Some(ListenerSlot {
+            slots: self.clone(),
+            identity: None,
+        })
+    }
+}
+
+/// A channel of a listener, which frees its slot when dropped along with the
+/// decryptor of the channel.
+pub(crate) struct ListenerSlot {
+    slots: ListenerSlots,
+    identity: Option<IdentityIdentifier>,
+}
+
+impl ListenerSlot {
+    /// Assign the slot to an authenticated identity, unless it already has
+    /// too many channels
+    pub(crate) fn assign(
+        &mut self,
+        identity: &IdentityIdentifier,
+        limits: &ListenerLimits,
+    ) -> bool {
+        let mut slots = match self.slots.0.lock() {
+            Ok(slots) => slots,
+            Err(_) => return false,
+        };
+        let count = slots.identities.get(identity).copied().unwrap_or(0);
+        if let Some(max_channels) = limits.max_channels_per_identity {
+            if count >= max_channels {
+                return false;
+            }
+        }
+        slots.identities.insert(identity.clone(), count + 1);
+        self.identity = Some(identity.clone());
+        true
+    }
+}
+
+impl Drop for ListenerSlot {
+    fn drop(&mut self) {
+        if let Ok(mut slots) = self.slots.0.lock() {
+            slots.channels -= 1;
+            if let Some(identity) = self.identity.take() {
+                if let Some(count) = slots.identities.get_mut(&identity) {
+                    *count -= 1;
+                    if *count == 0 {
+                        slots.identities.remove(&identity);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_released() {
+        let limits = ListenerLimits::new()
+            .with_max_channels(2)
+            .with_max_channels_per_identity(1);
+        let slots = ListenerSlots::default();
+        let alice = IdentityIdentifier::try_from("P123").unwrap();
+
+        let mut first = slots.acquire(&limits).unwrap();
+        let mut second = slots.acquire(&limits).unwrap();
+        assert!(slots.acquire(&limits).is_none());
+
+        assert!(first.assign(&alice, &limits));
+        assert!(!second.assign(&alice, &limits));
+
+        drop(first);
+        assert!(second.assign(&alice, &limits));
+        assert!(slots.acquire(&limits).is_some());
+    }
+}
@@ -1,14 +1,20 @@
 use crate::authenticated_storage::AuthenticatedStorage;
-use crate::{DecryptorWorker, Identity, IdentityVault, TrustPolicy};
+use crate::{
+    DecryptorWorker, Identity, IdentityVault, ListenerSlots, Rejection, SecureChannelCounters,
+    SecureChannelOptions, TrustPolicy,
+};
 use ockam_channel::CreateResponderChannelMessage;
 use ockam_core::compat::{boxed::Box, sync::Arc};
 use ockam_core::{AsyncTryClone, Result, Routed, Worker};
 use ockam_node::Context;
+use tracing::warn;
 
 pub(crate) struct IdentityChannelListener<V: IdentityVault, S: AuthenticatedStorage> {
     trust_policy: Arc<dyn TrustPolicy>,
     identity: Identity<V>,
     storage: S,
+    options: SecureChannelOptions,
+    slots: ListenerSlots,
 }
 
 impl<V: IdentityVault, S: AuthenticatedStorage> IdentityChannelListener<V, S> {
@@ -17,8 +23,15 @@ impl<V: IdentityVault, S: AuthenticatedStorage> IdentityChannelListener<V, S> {
             trust_policy: Arc::new(trust_policy),
             identity,
             storage,
+            options: SecureChannelOptions::default(),
+            slots: ListenerSlots::default(),
         }
     }
+
+    pub fn with_options(mut self, options: SecureChannelOptions) -> Self {
+        self.options = options;
+        self
+    }
 }
 
 #[ockam_core::worker]
@@ -31,6 +44,19 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Worker for IdentityChannelListen
         ctx: &mut Self::Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
+        let limits = self.options.listener_limits();
+        let slot = match self.slots.acquire(limits) {
+            Some(slot) => slot,
+            None => {
+                limits.rejections().record(Rejection::TooManyChannels);
+                warn!(
+                    "Secure channel listener {} refused a handshake: too many channels",
+                    ctx.address()
+                );
+                return Ok(());
+            }
+        };
+
         let trust_policy = Arc::clone(&self.trust_policy);
         let identity = self.identity.async_try_clone().await?;
         DecryptorWorker::create_responder(
@@ -38,6 +64,11 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Worker for IdentityChannelListen
             identity,
             self.storage.async_try_clone().await?,
             trust_policy,
+            // Each channel has its own statistics
+            self.options
+                .clone()
+                .with_counters(SecureChannelCounters::new()),
+            slot,
             msg,
         )
         .await
@@ -1,5 +1,5 @@
 use ockam_core::compat::vec::Vec;
-use ockam_core::Message;
+use ockam_core::{Address, Decodable, Message, Result, Route};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Message)]
@@ -13,4 +13,76 @@ pub(crate) enum IdentityChannelMessage {
         signature: Vec<u8>,
     },
     Confirm,
+    RequestWithCredential {
+        identity: Vec<u8>,
+        signature: Vec<u8>,
+        credential: Option<Vec<u8>>,
+        credential_requested: bool,
+    },
+    ResponseWithCredential {
+        identity: Vec<u8>,
+        signature: Vec<u8>,
+        credential: Option<Vec<u8>>,
+    },
+    /// Sent through the channel to tell the other side it is closed
+    Close,
+}
+
+/// Handshake of the underlying channel, announced by the initiator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyExchangeKind {
+    /// Noise XX, with or without offers and hybrid KEM
+    Xx,
+    /// Noise IK, to the static key of the listener
+    Ik,
+}
+
+/// Custom payload an initiator attaches to the first handshake message.
+///
+/// It starts with the address of the initiator's decryptor, which is all
+/// that older initiators send and older responders read. Fields are only
+/// appended, initiators which don't send one mean its default.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct InitiatorHello {
+    pub(crate) address: Address,
+    /// The initiator understands [`IdentityChannelMessage::RequestWithCredential`]
+    pub(crate) credentials: bool,
+    pub(crate) key_exchange: KeyExchangeKind,
+}
+
+/// [`InitiatorHello`] of initiators which only know XX
+#[derive(Deserialize)]
+struct InitiatorHelloXx {
+    address: Address,
+    credentials: bool,
+}
+
+impl InitiatorHello {
+    pub(crate) fn decode_custom_payload(data: &[u8]) -> Result<Self> {
+        if let Ok(hello) = Self::decode(data) {
+            return Ok(hello);
+        }
+        let (address, credentials) = match serde_bare::from_slice::<InitiatorHelloXx>(data) {
+            Ok(hello) => (hello.address, hello.credentials),
+            Err(_) => (Address::decode(data)?, false),
+        };
+        Ok(Self {
+            address,
+            credentials,
+            key_exchange: KeyExchangeKind::Xx,
+        })
+    }
+}
+
+/// Messages a decryptor sends to itself or gets from its encryptor.
+#[derive(Serialize, Deserialize, Clone, Message)]
+pub(crate) enum DecryptorControlMessage {
+    /// Check whether the channel was idle since the last check
+    IdleCheck,
+    /// Close the channel, asked locally
+    Close,
+    /// Abort the handshake if it is not completed yet
+    HandshakeTimeout,
+    /// Move the underlying channel to a new route, asked locally
+    Migrate { route: Route },
 }
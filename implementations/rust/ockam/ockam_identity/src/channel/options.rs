@@ -0,0 +1,206 @@
+use crate::{IdentityIdentifier, ListenerLimits, RekeyPolicy, SecureChannelCounters};
+use core::time::Duration;
+use ockam_core::compat::vec::Vec;
+use ockam_core::vault::{KeyId, PublicKey};
+use ockam_core::{Address, Message};
+use ockam_key_exchange_xx::{XXCipherSuite, XXCurve};
+use serde::{Deserialize, Serialize};
+
+/// Options of the secure channels created by an initiator or a listener.
+#[derive(Clone, Debug, Default)]
+pub struct SecureChannelOptions {
+    pub(crate) rekey_policy: RekeyPolicy,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) close_notification_address: Option<Address>,
+    pub(crate) counters: SecureChannelCounters,
+    pub(crate) listener_limits: ListenerLimits,
+    pub(crate) ik_static_key: Option<KeyId>,
+    pub(crate) ik_remote_static_key: Option<(IdentityIdentifier, PublicKey)>,
+    pub(crate) cipher_suites: Vec<XXCipherSuite>,
+    pub(crate) curve: XXCurve,
+    pub(crate) hybrid_kem: bool,
+}
+
+impl SecureChannelOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Change keys according to the given policy when sending messages
+    pub fn with_rekey_policy(mut self, rekey_policy: RekeyPolicy) -> Self {
+        self.rekey_policy = rekey_policy;
+        self
+    }
+
+    /// Close the channel once no message was sent or received for the given time
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Send a [`SecureChannelClosed`] message to the given address when the
+    /// channel is closed, whichever side closed it
+    pub fn with_close_notification(mut self, address: impl Into<Address>) -> Self {
+        self.close_notification_address = Some(address.into());
+        self
+    }
+
+    /// Collect the statistics of the channel in the given counters. Ignored
+    /// by listeners, which give each channel its own counters, see
+    /// [`crate::Identity::secure_channel_stats`].
+    pub fn with_counters(mut self, counters: SecureChannelCounters) -> Self {
+        self.counters = counters;
+        self
+    }
+
+    /// Limit the channels accepted by a listener, ignored by initiators
+    pub fn with_listener_limits(mut self, listener_limits: ListenerLimits) -> Self {
+        self.listener_limits = listener_limits;
+        self
+    }
+
+    /// Let a listener accept Noise IK handshakes to the given X25519 key,
+    /// besides the XX handshakes. Initiators announce which handshake they
+    /// use. Ignored by initiators.
+    pub fn with_ik_static_key(mut self, key_id: KeyId) -> Self {
+        self.ik_static_key = Some(key_id);
+        self
+    }
+
+    /// Use a Noise IK handshake, which saves a round trip over XX, to a
+    /// listener of the `identity` which was given the secret key of
+    /// `public_key` with [`SecureChannelOptions::with_ik_static_key`].
+    ///
+    /// The key is only trusted for that identity: the channel fails if the
+    /// listener proves another one. Ignored by listeners.
+    pub fn with_ik_remote_static_key(
+        mut self,
+        identity: IdentityIdentifier,
+        public_key: PublicKey,
+    ) -> Self {
+        self.ik_remote_static_key = Some((identity, public_key));
+        self
+    }
+
+    /// Cipher suites of XX handshakes, in order of preference. An initiator
+    /// offers them and a listener picks the first offered one it supports.
+    /// Peers which only support the default AES-GCM suite can't handle offers.
+    pub fn with_cipher_suites(mut self, cipher_suites: Vec<XXCipherSuite>) -> Self {
+        self.cipher_suites = cipher_suites;
+        self
+    }
+
+    /// Curve of the XX handshake keys, which both sides have to agree on.
+    /// IK handshakes, which only support X25519, are not used with NIST P-256.
+    pub fn with_curve(mut self, curve: XXCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Mix a Kyber encapsulation into the XX handshake against attackers
+    /// who record traffic to decrypt it once they have a quantum computer.
+    /// Takes precedence over IK. Ignored by listeners, which accept both.
+    pub fn with_hybrid_kem(mut self) -> Self {
+        self.hybrid_kem = true;
+        self
+    }
+
+    pub fn rekey_policy(&self) -> RekeyPolicy {
+        self.rekey_policy
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    pub fn counters(&self) -> &SecureChannelCounters {
+        &self.counters
+    }
+
+    pub fn listener_limits(&self) -> &ListenerLimits {
+        &self.listener_limits
+    }
+
+    pub fn ik_static_key(&self) -> Option<&KeyId> {
+        self.ik_static_key.as_ref()
+    }
+
+    pub fn ik_remote_static_key(&self) -> Option<&PublicKey> {
+        self.ik_remote_static_key.as_ref().map(|(_, key)| key)
+    }
+
+    /// Identity of the listener owning [`Self::ik_remote_static_key`]
+    pub fn ik_remote_identity(&self) -> Option<&IdentityIdentifier> {
+        self.ik_remote_static_key
+            .as_ref()
+            .map(|(identity, _)| identity)
+    }
+
+    /// IK saves a round trip when we already know the static key of the
+    /// listener, unless the hybrid XX handshake or another curve was asked for
+    pub(crate) fn initiator_ik(&self) -> Option<(&IdentityIdentifier, &PublicKey)> {
+        self.ik_remote_static_key
+            .as_ref()
+            .filter(|_| !self.hybrid_kem && self.curve == XXCurve::X25519)
+            .map(|(identity, key)| (identity, key))
+    }
+
+    pub fn cipher_suites(&self) -> &[XXCipherSuite] {
+        &self.cipher_suites
+    }
+
+    pub fn curve(&self) -> XXCurve {
+        self.curve
+    }
+
+    pub fn hybrid_kem(&self) -> bool {
+        self.hybrid_kem
+    }
+}
+
+/// Why a secure channel was closed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseReason {
+    /// Closed with [`crate::Identity::stop_secure_channel`]
+    Local,
+    /// Closed by the other side
+    Remote,
+    /// No traffic during the idle timeout
+    Idle,
+}
+
+/// Notification sent when a secure channel is closed, see
+/// [`SecureChannelOptions::with_close_notification`].
+#[derive(Serialize, Deserialize, Clone, Debug, Message)]
+pub struct SecureChannelClosed {
+    encryptor_address: Address,
+    their_identity_id: IdentityIdentifier,
+    reason: CloseReason,
+}
+
+impl SecureChannelClosed {
+    pub(crate) fn new(
+        encryptor_address: Address,
+        their_identity_id: IdentityIdentifier,
+        reason: CloseReason,
+    ) -> Self {
+        Self {
+            encryptor_address,
+            their_identity_id,
+            reason,
+        }
+    }
+
+    /// Address of the channel, as returned when it was created
+    pub fn encryptor_address(&self) -> &Address {
+        &self.encryptor_address
+    }
+
+    pub fn their_identity_id(&self) -> &IdentityIdentifier {
+        &self.their_identity_id
+    }
+
+    pub fn reason(&self) -> CloseReason {
+        self.reason
+    }
+}
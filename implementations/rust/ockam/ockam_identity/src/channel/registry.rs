@@ -0,0 +1,49 @@
+use crate::SecureChannelCounters;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Address;
+
+/// Entry of a secure channel in a [`SecureChannelRegistry`]
+#[derive(Clone, Debug)]
+pub(crate) struct SecureChannelRegistryEntry {
+    /// Address for the [`crate::DecryptorControlMessage`]s of the channel
+    pub(crate) control_address: Address,
+    /// Statistics of the channel
+    pub(crate) counters: SecureChannelCounters,
+}
+
+/// Secure channels of an [`Identity`](crate::Identity), by the address of
+/// their encryptor, i.e. the address returned when creating them
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SecureChannelRegistry {
+    channels: Arc<RwLock<BTreeMap<Address, SecureChannelRegistryEntry>>>,
+}
+
+impl SecureChannelRegistry {
+    pub(crate) fn register(&self, encryptor_address: Address, entry: SecureChannelRegistryEntry) {
+        if let Ok(mut channels) = self.channels.write() {
+            channels.insert(encryptor_address, entry);
+        }
+    }
+
+    pub(crate) fn unregister(&self, encryptor_address: &Address) {
+        if let Ok(mut channels) = self.channels.write() {
+            channels.remove(encryptor_address);
+        }
+    }
+
+    pub(crate) fn get(&self, encryptor_address: &Address) -> Option<SecureChannelRegistryEntry> {
+        self.channels
+            .read()
+            .ok()
+            .and_then(|channels| channels.get(encryptor_address).cloned())
+    }
+
+    pub(crate) fn addresses(&self) -> Vec<Address> {
+        self.channels
+            .read()
+            .map(|channels| channels.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
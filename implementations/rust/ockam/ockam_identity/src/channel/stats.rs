@@ -0,0 +1,141 @@
+use crate::credential::Timestamp;
+use crate::IdentityIdentifier;
+use core::fmt;
+use ockam_channel::ReplayCounters;
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::{Arc, RwLock};
+
+/// Statistics of a secure channel, see [`SecureChannelCounters`].
+///
+/// Times are only known with the `std` feature.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SecureChannelStats {
+    their_identity_id: Option<IdentityIdentifier>,
+    key_exchange: Option<String>,
+    created_at: Option<Timestamp>,
+    last_activity: Option<Timestamp>,
+    messages_encrypted: u64,
+    bytes_encrypted: u64,
+    messages_decrypted: u64,
+    bytes_decrypted: u64,
+    decryption_failures: u64,
+}
+
+impl SecureChannelStats {
+    /// Identity of the other side, once authenticated
+    pub fn their_identity_id(&self) -> Option<&IdentityIdentifier> {
+        self.their_identity_id.as_ref()
+    }
+
+    /// Name of the key exchange which established the channel
+    pub fn key_exchange(&self) -> Option<&str> {
+        self.key_exchange.as_deref()
+    }
+
+    /// When the channel was established
+    pub fn created_at(&self) -> Option<Timestamp> {
+        self.created_at
+    }
+
+    /// When a message was last sent or received
+    pub fn last_activity(&self) -> Option<Timestamp> {
+        self.last_activity
+    }
+
+    pub fn messages_encrypted(&self) -> u64 {
+        self.messages_encrypted
+    }
+
+    /// Size of the encrypted payloads, before encryption
+    pub fn bytes_encrypted(&self) -> u64 {
+        self.bytes_encrypted
+    }
+
+    pub fn messages_decrypted(&self) -> u64 {
+        self.messages_decrypted
+    }
+
+    /// Size of the decrypted payloads, after decryption
+    pub fn bytes_decrypted(&self) -> u64 {
+        self.bytes_decrypted
+    }
+
+    /// Messages which were received but rejected, e.g. replays or forgeries
+    pub fn decryption_failures(&self) -> u64 {
+        self.decryption_failures
+    }
+}
+
+/// Shared handle to the [`SecureChannelStats`] of a secure channel, updated by
+/// its workers, see [`crate::SecureChannelOptions::with_counters`].
+#[derive(Clone, Default)]
+pub struct SecureChannelCounters {
+    stats: Arc<RwLock<SecureChannelStats>>,
+    replay_counters: ReplayCounters,
+}
+
+impl fmt::Debug for SecureChannelCounters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SecureChannelCounters")
+            .field(&self.stats())
+            .finish()
+    }
+}
+
+impl SecureChannelCounters {
+    /// New counters, starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current values of the counters
+    pub fn stats(&self) -> SecureChannelStats {
+        let mut stats = self
+            .stats
+            .read()
+            .map(|stats| stats.clone())
+            .unwrap_or_default();
+        stats.decryption_failures += self.replay_counters.stats().total();
+        stats
+    }
+
+    /// Counters of the messages rejected by the underlying channel
+    pub(crate) fn replay_counters(&self) -> ReplayCounters {
+        self.replay_counters.clone()
+    }
+
+    pub(crate) fn record_established(
+        &self,
+        their_identity_id: &IdentityIdentifier,
+        key_exchange: &str,
+    ) {
+        if let Ok(mut stats) = self.stats.write() {
+            stats.their_identity_id = Some(their_identity_id.clone());
+            stats.key_exchange = Some(key_exchange.into());
+            stats.created_at = Timestamp::now();
+            stats.last_activity = stats.created_at;
+        }
+    }
+
+    pub(crate) fn record_encrypted(&self, len: usize) {
+        if let Ok(mut stats) = self.stats.write() {
+            stats.messages_encrypted += 1;
+            stats.bytes_encrypted += len as u64;
+            stats.last_activity = Timestamp::now();
+        }
+    }
+
+    pub(crate) fn record_decrypted(&self, len: usize) {
+        if let Ok(mut stats) = self.stats.write() {
+            stats.messages_decrypted += 1;
+            stats.bytes_decrypted += len as u64;
+            stats.last_activity = Timestamp::now();
+        }
+    }
+
+    pub(crate) fn record_failure(&self) {
+        if let Ok(mut stats) = self.stats.write() {
+            stats.decryption_failures += 1;
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::IdentityIdentifier;
 use ockam_core::{
     async_trait,
-    compat::{boxed::Box, sync::Arc},
+    compat::{boxed::Box, sync::Arc, vec::Vec},
     Result,
 };
 use serde::{Deserialize, Serialize};
@@ -18,21 +18,38 @@ mod trust_everyone_policy;
 pub use trust_everyone_policy::*;
 mod trust_public_key_policy;
 pub use trust_public_key_policy::*;
+mod trust_on_first_use_policy;
+pub use trust_on_first_use_policy::*;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SecureChannelTrustInfo {
     their_identity_id: IdentityIdentifier,
+    their_credential: Option<Vec<u8>>,
 }
 
 impl SecureChannelTrustInfo {
     pub fn their_identity_id(&self) -> &IdentityIdentifier {
         &self.their_identity_id
     }
+
+    /// CBOR-encoded [`Credential`](crate::credential::Credential) the other
+    /// party presented during the handshake, if any. It is not verified yet.
+    pub fn their_credential(&self) -> Option<&[u8]> {
+        self.their_credential.as_deref()
+    }
 }
 
 impl SecureChannelTrustInfo {
     pub fn new(their_identity_id: IdentityIdentifier) -> Self {
-        Self { their_identity_id }
+        Self {
+            their_identity_id,
+            their_credential: None,
+        }
+    }
+
+    pub fn with_credential(mut self, credential: Option<Vec<u8>>) -> Self {
+        self.their_credential = credential;
+        self
     }
 }
 
@@ -40,6 +57,12 @@ impl SecureChannelTrustInfo {
 pub trait TrustPolicy: Send + Sync + 'static {
     async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool>;
 
+    /// Whether the other party should present its credential during the
+    /// handshake so that it's available in [`SecureChannelTrustInfo`].
+    fn requires_credential(&self) -> bool {
+        false
+    }
+
     fn and<O: TrustPolicy>(self, other: O) -> AllTrustPolicy<Self, O>
     where
         Self: Sized,
@@ -61,6 +84,10 @@ impl<T: TrustPolicy + ?Sized> TrustPolicy for Box<T> {
     async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
         T::check(&**self, trust_info).await
     }
+
+    fn requires_credential(&self) -> bool {
+        T::requires_credential(&**self)
+    }
 }
 
 #[async_trait]
@@ -68,4 +95,8 @@ impl<T: TrustPolicy + ?Sized> TrustPolicy for Arc<T> {
     async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
         T::check(&**self, trust_info).await
     }
+
+    fn requires_credential(&self) -> bool {
+        T::requires_credential(&**self)
+    }
 }
@@ -21,6 +21,10 @@ impl<F: TrustPolicy, S: TrustPolicy> TrustPolicy for AllTrustPolicy<F, S> {
     async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
         Ok(self.first.check(trust_info).await? && self.second.check(trust_info).await?)
     }
+
+    fn requires_credential(&self) -> bool {
+        self.first.requires_credential() || self.second.requires_credential()
+    }
 }
 
 #[cfg(test)]
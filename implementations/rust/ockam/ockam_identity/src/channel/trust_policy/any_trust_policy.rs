@@ -22,6 +22,10 @@ impl<F: TrustPolicy, S: TrustPolicy> TrustPolicy for AnyTrustPolicy<F, S> {
         // TODO: is the short circuit here a side channel?
         Ok(self.first.check(trust_info).await? || self.second.check(trust_info).await?)
     }
+
+    fn requires_credential(&self) -> bool {
+        self.first.requires_credential() || self.second.requires_credential()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,221 @@
+use crate::authenticated_storage::AuthenticatedStorage;
+use crate::change_history::{IdentityChangeHistory, IdentityHistoryComparison};
+use crate::credential::Timestamp;
+use crate::{IdentityError, IdentityIdentifier, IdentityStateConst};
+use crate::{SecureChannelTrustInfo, TrustPolicy};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use ockam_core::{async_trait, CowBytes, Result};
+use tracing::{debug, info, warn};
+
+/// An identity pinned to a name by [`TrustOnFirstUsePolicy`].
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PinnedIdentity<'a> {
+    #[n(1)] identifier: IdentityIdentifier,
+    #[b(2)] change_history: CowBytes<'a>,
+    #[n(3)] pinned_at: Option<Timestamp>,
+}
+
+impl<'a> PinnedIdentity<'a> {
+    pub fn new(
+        identifier: IdentityIdentifier,
+        change_history: Vec<u8>,
+        pinned_at: Option<Timestamp>,
+    ) -> Self {
+        Self {
+            identifier,
+            change_history: change_history.into(),
+            pinned_at,
+        }
+    }
+
+    pub fn identifier(&self) -> &IdentityIdentifier {
+        &self.identifier
+    }
+
+    /// Exported [`IdentityChangeHistory`] of the pinned identity, as of the
+    /// last time it was seen.
+    pub fn change_history(&self) -> &[u8] {
+        &self.change_history
+    }
+
+    /// When the identity was first seen, if the time was available.
+    pub fn pinned_at(&self) -> Option<Timestamp> {
+        self.pinned_at
+    }
+
+    pub fn to_owned<'r>(&self) -> PinnedIdentity<'r> {
+        PinnedIdentity {
+            identifier: self.identifier.clone(),
+            change_history: self.change_history.to_owned(),
+            pinned_at: self.pinned_at,
+        }
+    }
+}
+
+/// All pinned identities, indexed by name.
+pub type PinnedIdentities = BTreeMap<String, PinnedIdentity<'static>>;
+
+/// Helper to manage the identities pinned by [`TrustOnFirstUsePolicy`].
+///
+/// All pins are kept in a single [`AuthenticatedStorage`] entry, since the
+/// storage does not support enumerating its keys.
+pub struct PinnedIdentitiesStorageUtils;
+
+impl PinnedIdentitiesStorageUtils {
+    /// [`AuthenticatedStorage`] id under which pins are stored
+    pub const STORAGE_ID: &'static str = "TRUST_ON_FIRST_USE";
+    /// [`AuthenticatedStorage`] key under which pins are stored
+    pub const PINNED_IDENTITIES_KEY: &'static str = "PINNED_IDENTITIES";
+
+    /// Return all pinned identities
+    pub async fn list(storage: &impl AuthenticatedStorage) -> Result<PinnedIdentities> {
+        match storage
+            .get(Self::STORAGE_ID, Self::PINNED_IDENTITIES_KEY)
+            .await?
+        {
+            Some(data) => {
+                let pins: BTreeMap<String, PinnedIdentity> = minicbor::decode(&data)?;
+                Ok(pins
+                    .into_iter()
+                    .map(|(name, pin)| (name, pin.to_owned()))
+                    .collect())
+            }
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    /// Return the identity pinned to the given name, if any
+    pub async fn get(
+        name: &str,
+        storage: &impl AuthenticatedStorage,
+    ) -> Result<Option<PinnedIdentity<'static>>> {
+        Ok(Self::list(storage).await?.remove(name))
+    }
+
+    /// Pin an identity to the given name, replacing any previous pin
+    pub async fn pin(
+        name: &str,
+        pinned: PinnedIdentity<'_>,
+        storage: &impl AuthenticatedStorage,
+    ) -> Result<()> {
+        let mut pins = Self::list(storage).await?;
+        pins.insert(name.to_string(), pinned.to_owned());
+        Self::store(&pins, storage).await
+    }
+
+    /// Forget the identity pinned to the given name.
+    ///
+    /// Returns `false` if no identity was pinned to that name.
+    pub async fn forget(name: &str, storage: &impl AuthenticatedStorage) -> Result<bool> {
+        let mut pins = Self::list(storage).await?;
+        if pins.remove(name).is_none() {
+            return Ok(false);
+        }
+        Self::store(&pins, storage).await?;
+        Ok(true)
+    }
+
+    async fn store(pins: &PinnedIdentities, storage: &impl AuthenticatedStorage) -> Result<()> {
+        storage
+            .set(
+                Self::STORAGE_ID,
+                Self::PINNED_IDENTITIES_KEY.to_string(),
+                minicbor::to_vec(pins)?,
+            )
+            .await
+    }
+}
+
+/// Trust-on-first-use policy.
+///
+/// The first identity seen under the given name (e.g. the route of the other
+/// party) is pinned and trusted. From then on only that identity is accepted,
+/// including newer versions of it, e.g. after a key rotation. Any other
+/// identity is refused with [`IdentityError::PinnedIdentityMismatch`].
+///
+/// The storage must be the one used by the secure channel, since the change
+/// history of the other party is read from there.
+#[derive(Clone)]
+pub struct TrustOnFirstUsePolicy<S: AuthenticatedStorage> {
+    name: String,
+    storage: S,
+}
+
+impl<S: AuthenticatedStorage> TrustOnFirstUsePolicy<S> {
+    pub fn new(name: impl Into<String>, storage: S) -> Self {
+        Self {
+            name: name.into(),
+            storage,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: AuthenticatedStorage> TrustPolicy for TrustOnFirstUsePolicy<S> {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        let their_id = trust_info.their_identity_id();
+
+        let current = match self
+            .storage
+            .get(
+                &their_id.to_string(),
+                IdentityStateConst::CHANGE_HISTORY_KEY,
+            )
+            .await?
+        {
+            Some(current) => current,
+            None => {
+                warn!("No change history known for {}", their_id);
+                return Ok(false);
+            }
+        };
+
+        let pinned = match PinnedIdentitiesStorageUtils::get(&self.name, &self.storage).await? {
+            Some(pinned) => pinned,
+            None => {
+                info!("Pinning identity {} to {}", their_id, self.name);
+                let pinned = PinnedIdentity::new(their_id.clone(), current, Timestamp::now());
+                PinnedIdentitiesStorageUtils::pin(&self.name, pinned, &self.storage).await?;
+                return Ok(true);
+            }
+        };
+
+        if pinned.identifier() != their_id {
+            warn!(
+                "Identity {} presented for {} does not match the pinned identity {}",
+                their_id,
+                self.name,
+                pinned.identifier()
+            );
+            return Err(IdentityError::PinnedIdentityMismatch.into());
+        }
+
+        let current_history = IdentityChangeHistory::import(&current)?;
+        let pinned_history = IdentityChangeHistory::import(pinned.change_history())?;
+
+        match current_history.compare(&pinned_history) {
+            IdentityHistoryComparison::Equal => Ok(true),
+            IdentityHistoryComparison::Newer => {
+                debug!("Updating identity {} pinned to {}", their_id, self.name);
+                let updated = PinnedIdentity::new(their_id.clone(), current, pinned.pinned_at());
+                PinnedIdentitiesStorageUtils::pin(&self.name, updated, &self.storage).await?;
+                Ok(true)
+            }
+            IdentityHistoryComparison::Older | IdentityHistoryComparison::Conflict => {
+                warn!(
+                    "Change history of {} is inconsistent with the one pinned to {}",
+                    their_id, self.name
+                );
+                Err(IdentityError::PinnedIdentityMismatch.into())
+            }
+        }
+    }
+}
@@ -2,11 +2,15 @@
 
 mod identity;
 mod public_identity;
+mod schema;
+mod selective;
 mod storage_utils;
 mod worker;
 
 pub mod access_control;
 
+pub use schema::*;
+pub use selective::*;
 pub use storage_utils::*;
 
 use crate::IdentityIdentifier;
@@ -129,6 +133,7 @@ impl<'a> Credential<'a> {
             subject,
             attrs: Attributes::new(),
             validity: MAX_CREDENTIAL_VALIDITY,
+            registry: None,
         }
     }
 
@@ -315,6 +320,7 @@ pub struct CredentialBuilder<'a> {
     attrs: Attributes<'a>,
     subject: IdentityIdentifier,
     validity: Duration,
+    registry: Option<SchemaRegistry>,
 }
 
 impl<'a> CredentialBuilder<'a> {
@@ -330,6 +336,22 @@ impl<'a> CredentialBuilder<'a> {
         self
     }
 
+    /// Check the attributes against the given registry when the credential is issued.
+    pub fn with_schema_registry(mut self, r: &SchemaRegistry) -> Self {
+        self.registry = Some(r.clone());
+        self
+    }
+
+    /// Check that the attributes conform to the schema of the credential.
+    ///
+    /// Always succeeds if no [`SchemaRegistry`] has been set.
+    pub fn validate(&self) -> Result<()> {
+        match &self.registry {
+            Some(r) => r.validate(self.schema, &self.attrs),
+            None => Ok(()),
+        }
+    }
+
     /// Set validity duration of the credential.
     ///
     /// # Panics
@@ -1,17 +1,20 @@
 use crate::authenticated_storage::AuthenticatedStorage;
+use crate::credential::selective::{attribute_commitment, generate_salt};
 use crate::credential::worker::CredentialExchangeWorker;
 use crate::credential::{
-    AttributesEntry, AttributesStorageUtils, Credential, CredentialBuilder, CredentialData,
-    Timestamp, Unverified, Verified,
+    Attributes, AttributesEntry, AttributesStorageUtils, Credential, CredentialBuilder,
+    CredentialData, CredentialPresentation, Disclosure, SchemaId, SchemaRegistry,
+    SelectiveCredential, Timestamp, Unverified, Verified, SELECTIVE_DISCLOSURE_SCHEMA,
 };
 use crate::{
     Identity, IdentityError, IdentityIdentifier, IdentitySecureChannelLocalInfo,
     IdentityStateConst, IdentityVault, PublicIdentity,
 };
 use core::marker::PhantomData;
+use core::time::Duration;
 use minicbor::Decoder;
 use ockam_core::api::{Request, Response, Status};
-use ockam_core::compat::vec::Vec;
+use ockam_core::compat::{collections::BTreeMap, string::ToString, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::vault::SignatureVec;
 use ockam_core::{Address, AsyncTryClone, CowStr, Error, Result, Route};
@@ -27,19 +30,86 @@ impl<V: IdentityVault> Identity<V> {
         self.credential.read().await.clone()
     }
 
+    /// Set the schemas the attributes of credentials presented to this
+    /// identity must conform to.
+    pub async fn set_schema_registry(&self, schemas: SchemaRegistry) {
+        *self.schemas.write().await = schemas;
+    }
+
+    pub async fn schema_registry(&self) -> SchemaRegistry {
+        self.schemas.read().await.clone()
+    }
+
     /// Create a signed credential based on the given values.
     pub async fn issue_credential<'a>(
         &self,
         builder: CredentialBuilder<'a>,
+    ) -> Result<Credential<'a>> {
+        builder.validate()?;
+        self.sign_credential(
+            builder.schema,
+            builder.attrs,
+            builder.subject,
+            builder.validity,
+        )
+        .await
+    }
+
+    /// Create a signed credential whose attributes can be disclosed selectively.
+    ///
+    /// The credential schema is set to [`SELECTIVE_DISCLOSURE_SCHEMA`] and every
+    /// attribute value is replaced by a salted commitment. The returned value
+    /// holds the salts and values needed to present the credential and should
+    /// be handed over to the subject only.
+    pub async fn issue_selective_credential(
+        &self,
+        builder: CredentialBuilder<'_>,
+    ) -> Result<SelectiveCredential<'static>> {
+        builder.validate()?;
+        let mut commitments = Vec::new();
+        let mut openings = BTreeMap::new();
+        for (k, v) in builder.attrs.iter() {
+            let salt = generate_salt(&self.vault).await?;
+            let commitment = attribute_commitment(&self.vault, &salt, k, v).await?;
+            commitments.push((k, commitment));
+            openings.insert(
+                CowStr::from(k.to_string()),
+                Disclosure::new(salt, v.to_vec()),
+            );
+        }
+
+        let mut attrs = Attributes::new();
+        for (k, commitment) in &commitments {
+            attrs.put(k, commitment);
+        }
+
+        let credential = self
+            .sign_credential(
+                Some(SELECTIVE_DISCLOSURE_SCHEMA),
+                attrs,
+                builder.subject,
+                builder.validity,
+            )
+            .await?;
+
+        Ok(SelectiveCredential::new(credential, openings))
+    }
+
+    async fn sign_credential<'a>(
+        &self,
+        schema: Option<SchemaId>,
+        attributes: Attributes<'_>,
+        subject: IdentityIdentifier,
+        validity: Duration,
     ) -> Result<Credential<'a>> {
         let key_label = IdentityStateConst::ROOT_LABEL;
         let now = Timestamp::now()
             .ok_or_else(|| Error::new(Origin::Core, Kind::Internal, "invalid system time"))?;
-        let exp = Timestamp(u64::from(now).saturating_add(builder.validity.as_secs()));
+        let exp = Timestamp(u64::from(now).saturating_add(validity.as_secs()));
         let dat = CredentialData {
-            schema: builder.schema,
-            attributes: builder.attrs,
-            subject: builder.subject,
+            schema,
+            attributes,
+            subject,
             issuer: self.identifier().clone(),
             issuer_key_label: CowStr(key_label.into()),
             created: now,
@@ -105,6 +175,46 @@ impl<V: IdentityVault> Identity<V> {
         }
     }
 
+    /// Present a subset of the attributes of a selective disclosure credential to
+    /// other party, route shall use secure channel
+    pub async fn present_selective_credential<I>(
+        &self,
+        route: impl Into<Route>,
+        credential: &SelectiveCredential<'_>,
+        attributes: I,
+    ) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let presentation = credential.present(attributes)?;
+
+        let mut child_ctx = self
+            .ctx
+            .new_detached(Address::random_tagged(
+                "Identity.present_selective_credential.detached",
+            ))
+            .await?;
+        let buf = request(
+            &mut child_ctx,
+            "credential_presentation",
+            None,
+            route.into(),
+            Request::post("actions/present_selective").body(presentation),
+        )
+        .await?;
+
+        let res: Response = minicbor::decode(&buf)?;
+        match res.status() {
+            Some(Status::Ok) => Ok(()),
+            _ => Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                "credential presentation failed",
+            )),
+        }
+    }
+
     /// Present credential to other party, route shall use secure channel. Other party is expected
     /// to present its credential in response, otherwise this call errors.
     pub async fn present_credential_mutual(
@@ -170,6 +280,7 @@ impl<V: IdentityVault> Identity<V> {
         credential: &'a Credential<'a>,
         authorities: impl IntoIterator<Item = &PublicIdentity>,
         vault: &impl IdentityVault,
+        schemas: &SchemaRegistry,
     ) -> Result<CredentialData<'a, Verified>> {
         let credential_data: CredentialData<Unverified> = match minicbor::decode(&credential.data) {
             Ok(c) => c,
@@ -189,6 +300,50 @@ impl<V: IdentityVault> Identity<V> {
             Err(_) => return Err(IdentityError::CredentialVerificationFailed.into()),
         };
 
+        schemas.validate_issued(
+            &credential_data.issuer,
+            credential_data.schema,
+            &credential_data.attributes,
+        )?;
+
+        Ok(credential_data)
+    }
+
+    async fn verify_presentation<'a>(
+        sender: &IdentityIdentifier,
+        presentation: &'a CredentialPresentation<'a>,
+        authorities: impl IntoIterator<Item = &PublicIdentity>,
+        vault: &impl IdentityVault,
+        schemas: &SchemaRegistry,
+    ) -> Result<CredentialData<'a, Verified>> {
+        let credential_data: CredentialData<Unverified> =
+            match minicbor::decode(presentation.credential().unverified_data()) {
+                Ok(c) => c,
+                Err(_) => return Err(IdentityError::InvalidCredentialFormat.into()),
+            };
+
+        let issuer = authorities
+            .into_iter()
+            .find(|&x| x.identifier() == &credential_data.issuer);
+        let issuer = match issuer {
+            Some(i) => i,
+            None => return Err(IdentityError::UnknownAuthority.into()),
+        };
+
+        let credential_data = match issuer
+            .verify_presentation(presentation, sender, vault)
+            .await
+        {
+            Ok(d) => d,
+            Err(_) => return Err(IdentityError::CredentialVerificationFailed.into()),
+        };
+
+        schemas.validate_issued(
+            &credential_data.issuer,
+            credential_data.schema,
+            &credential_data.attributes,
+        )?;
+
         Ok(credential_data)
     }
 
@@ -197,8 +352,15 @@ impl<V: IdentityVault> Identity<V> {
         credential: &'a Credential<'a>,
         authorities: impl IntoIterator<Item = &PublicIdentity>,
     ) -> Result<()> {
-        let _ = Self::verify_credential(self.identifier(), credential, authorities, &self.vault)
-            .await?;
+        let schemas = self.schemas.read().await;
+        let _ = Self::verify_credential(
+            self.identifier(),
+            credential,
+            authorities,
+            &self.vault,
+            &schemas,
+        )
+        .await?;
         Ok(())
     }
 
@@ -209,8 +371,32 @@ impl<V: IdentityVault> Identity<V> {
         authorities: impl IntoIterator<Item = &PublicIdentity>,
         authenticated_storage: &impl AuthenticatedStorage,
     ) -> Result<()> {
+        let schemas = self.schemas.read().await;
+        let credential_data =
+            Self::verify_credential(&sender, &credential, authorities, &self.vault, &schemas)
+                .await?;
+
+        AttributesStorageUtils::put_attributes(
+            &sender,
+            AttributesEntry::new(credential_data.attributes, credential_data.expires),
+            authenticated_storage,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub(crate) async fn receive_presented_selective_credential(
+        &self,
+        sender: IdentityIdentifier,
+        presentation: CredentialPresentation<'_>,
+        authorities: impl IntoIterator<Item = &PublicIdentity>,
+        authenticated_storage: &impl AuthenticatedStorage,
+    ) -> Result<()> {
+        let schemas = self.schemas.read().await;
         let credential_data =
-            Self::verify_credential(&sender, &credential, authorities, &self.vault).await?;
+            Self::verify_presentation(&sender, &presentation, authorities, &self.vault, &schemas)
+                .await?;
 
         AttributesStorageUtils::put_attributes(
             &sender,
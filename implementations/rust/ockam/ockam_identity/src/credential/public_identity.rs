@@ -1,5 +1,9 @@
 use crate::authenticated_storage::AuthenticatedStorage;
-use crate::credential::{AttributesStorageUtils, Credential, CredentialData, Timestamp, Verified};
+use crate::credential::selective::attribute_commitment;
+use crate::credential::{
+    Attributes, AttributesStorageUtils, Credential, CredentialData, CredentialPresentation,
+    SchemaRegistry, Timestamp, Verified, SELECTIVE_DISCLOSURE_SCHEMA,
+};
 use crate::PublicIdentity;
 use crate::{IdentityIdentifier, IdentityStateConst, IdentityVault};
 use ockam_core::compat::collections::BTreeMap;
@@ -7,16 +11,42 @@ use ockam_core::compat::{string::String, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::vault::Signature;
 use ockam_core::{Error, Result};
+use subtle::ConstantTimeEq;
 
 impl PublicIdentity {
     /// Perform a signature check with the given identity.
     ///
+    /// Selective disclosure credentials only hold commitments to their
+    /// attribute values and are rejected, they must be checked with
+    /// [`PublicIdentity::verify_presentation`].
+    ///
     /// If successful, the credential data are returned.
     pub async fn verify_credential<'a, 'b: 'a>(
         &self,
         credential: &'b Credential<'b>,
         subject: &IdentityIdentifier,
         vault: &impl IdentityVault,
+    ) -> Result<CredentialData<'a, Verified>> {
+        let dat = self
+            .verify_signed_credential(credential, subject, vault)
+            .await?;
+
+        if dat.schema == Some(SELECTIVE_DISCLOSURE_SCHEMA) {
+            return Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                "selective disclosure credentials must be presented with their disclosures",
+            ));
+        }
+
+        Ok(dat)
+    }
+
+    async fn verify_signed_credential<'a, 'b: 'a>(
+        &self,
+        credential: &'b Credential<'b>,
+        subject: &IdentityIdentifier,
+        vault: &impl IdentityVault,
     ) -> Result<CredentialData<'a, Verified>> {
         let dat = CredentialData::try_from(credential)?;
         if dat.unverfied_key_label() != IdentityStateConst::ROOT_LABEL {
@@ -73,6 +103,89 @@ impl PublicIdentity {
         Ok(dat.into_verified())
     }
 
+    /// Perform a signature check with the given identity and check that the
+    /// credential attributes conform to its schema.
+    ///
+    /// If successful, the credential data are returned.
+    pub async fn verify_credential_with_schemas<'a, 'b: 'a>(
+        &self,
+        credential: &'b Credential<'b>,
+        subject: &IdentityIdentifier,
+        vault: &impl IdentityVault,
+        schemas: &SchemaRegistry,
+    ) -> Result<CredentialData<'a, Verified>> {
+        let dat = self.verify_credential(credential, subject, vault).await?;
+        schemas.validate_issued(self.identifier(), dat.schema, &dat.attributes)?;
+        Ok(dat)
+    }
+
+    /// Perform a signature check of a presented selective disclosure credential
+    /// and check every disclosed attribute against its commitment.
+    ///
+    /// If successful, the credential data are returned, containing only the
+    /// disclosed attributes.
+    pub async fn verify_presentation<'a, 'b: 'a>(
+        &self,
+        presentation: &'b CredentialPresentation<'b>,
+        subject: &IdentityIdentifier,
+        vault: &impl IdentityVault,
+    ) -> Result<CredentialData<'a, Verified>> {
+        let mut dat = self
+            .verify_signed_credential(presentation.credential(), subject, vault)
+            .await?;
+
+        if dat.schema != Some(SELECTIVE_DISCLOSURE_SCHEMA) {
+            return Err(Error::new(
+                Origin::Application,
+                Kind::Invalid,
+                "credential does not support selective disclosure",
+            ));
+        }
+
+        let mut disclosed = Attributes::new();
+        for (key, disclosure) in presentation.disclosed() {
+            let commitment = dat.attributes.get(key).ok_or_else(|| {
+                Error::new(
+                    Origin::Application,
+                    Kind::Invalid,
+                    "disclosed attribute is not part of the credential",
+                )
+            })?;
+            let expected =
+                attribute_commitment(vault, disclosure.salt(), key, disclosure.value()).await?;
+            if !bool::from(commitment.ct_eq(&expected[..])) {
+                return Err(Error::new(
+                    Origin::Application,
+                    Kind::Invalid,
+                    "invalid attribute disclosure",
+                ));
+            }
+            disclosed.put(key, disclosure.value());
+        }
+
+        dat.attributes = disclosed;
+        Ok(dat)
+    }
+
+    /// Perform the checks of [`PublicIdentity::verify_presentation`] and check
+    /// that the disclosed attributes conform to the schema registered for
+    /// [`SELECTIVE_DISCLOSURE_SCHEMA`].
+    ///
+    /// If successful, the credential data are returned.
+    pub async fn verify_presentation_with_schemas<'a, 'b: 'a>(
+        &self,
+        presentation: &'b CredentialPresentation<'b>,
+        subject: &IdentityIdentifier,
+        vault: &impl IdentityVault,
+        schemas: &SchemaRegistry,
+    ) -> Result<CredentialData<'a, Verified>> {
+        let dat = self
+            .verify_presentation(presentation, subject, vault)
+            .await?;
+        schemas.validate_issued(self.identifier(), dat.schema, &dat.attributes)?;
+        Ok(dat)
+    }
+
     /// Return authenticated non-expired attributes attached to that Identity
     pub async fn get_attributes(
         &self,
@@ -0,0 +1,368 @@
+use crate::credential::{Attributes, SchemaId};
+use crate::IdentityIdentifier;
+use core::str;
+use ockam_core::compat::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+
+/// Type of a credential attribute value.
+///
+/// Attribute values are always transmitted as bytes, the type describes
+/// how these bytes must be interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    /// Arbitrary bytes.
+    Bytes,
+    /// A UTF-8 string.
+    String,
+    /// A signed integer, as UTF-8 decimal string.
+    Integer,
+    /// `true` or `false`, as UTF-8 string.
+    Boolean,
+    /// An [`IdentityIdentifier`], as UTF-8 string.
+    Identifier,
+}
+
+impl AttributeType {
+    fn check(&self, value: &[u8]) -> bool {
+        match self {
+            AttributeType::Bytes => true,
+            AttributeType::String => str::from_utf8(value).is_ok(),
+            AttributeType::Integer => str::from_utf8(value)
+                .map(|s| s.parse::<i64>().is_ok())
+                .unwrap_or(false),
+            AttributeType::Boolean => value == b"true" || value == b"false",
+            AttributeType::Identifier => str::from_utf8(value)
+                .map(|s| IdentityIdentifier::try_from(s).is_ok())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Description of a single credential attribute.
+#[derive(Debug, Clone)]
+pub struct AttributeSchema {
+    name: String,
+    typ: AttributeType,
+    required: bool,
+    allowed_values: Option<Vec<Vec<u8>>>,
+}
+
+impl AttributeSchema {
+    /// An attribute every credential of the schema must have.
+    pub fn required(name: impl Into<String>, typ: AttributeType) -> Self {
+        Self {
+            name: name.into(),
+            typ,
+            required: true,
+            allowed_values: None,
+        }
+    }
+
+    /// An attribute credentials of the schema may have.
+    pub fn optional(name: impl Into<String>, typ: AttributeType) -> Self {
+        Self {
+            name: name.into(),
+            typ,
+            required: false,
+            allowed_values: None,
+        }
+    }
+
+    /// Restrict the attribute to the given values.
+    pub fn with_allowed_values<I, T>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        self.allowed_values = Some(values.into_iter().map(|v| v.as_ref().to_vec()).collect());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn attribute_type(&self) -> AttributeType {
+        self.typ
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn check(&self, value: &[u8]) -> Result<()> {
+        if !self.typ.check(value) {
+            return Err(invalid(format!(
+                "attribute `{}` is not of type {:?}",
+                self.name, self.typ
+            )));
+        }
+        if let Some(allowed) = &self.allowed_values {
+            if !allowed.iter().any(|v| v == value) {
+                return Err(invalid(format!(
+                    "attribute `{}` has a value which is not allowed",
+                    self.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Description of the attributes of credentials with a given [`SchemaId`].
+#[derive(Debug, Clone)]
+pub struct CredentialSchema {
+    id: SchemaId,
+    attributes: BTreeMap<String, AttributeSchema>,
+    additional: Option<AttributeType>,
+}
+
+impl CredentialSchema {
+    /// A schema without any attribute.
+    pub fn new(id: SchemaId) -> Self {
+        Self {
+            id,
+            attributes: BTreeMap::new(),
+            additional: None,
+        }
+    }
+
+    /// Describe an attribute of the schema.
+    pub fn with_attribute(mut self, attribute: AttributeSchema) -> Self {
+        self.attributes.insert(attribute.name.clone(), attribute);
+        self
+    }
+
+    /// Allow attributes not described by the schema, as long as
+    /// their values are of the given type.
+    pub fn with_additional_attributes(mut self, typ: AttributeType) -> Self {
+        self.additional = Some(typ);
+        self
+    }
+
+    pub fn id(&self) -> SchemaId {
+        self.id
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = &AttributeSchema> {
+        self.attributes.values()
+    }
+
+    /// Check that the given attributes conform to this schema.
+    pub fn validate(&self, attributes: &Attributes<'_>) -> Result<()> {
+        for schema in self.attributes.values() {
+            if schema.required && attributes.get(&schema.name).is_none() {
+                return Err(invalid(format!(
+                    "missing required attribute `{}`",
+                    schema.name
+                )));
+            }
+        }
+        for (name, value) in attributes.iter() {
+            match (self.attributes.get(name), self.additional) {
+                (Some(schema), _) => schema.check(value)?,
+                (None, Some(typ)) if typ.check(value) => {}
+                (None, Some(typ)) => {
+                    return Err(invalid(format!(
+                        "attribute `{}` is not of type {:?}",
+                        name, typ
+                    )))
+                }
+                (None, None) => {
+                    return Err(invalid(format!(
+                        "attribute `{}` is not part of schema {}",
+                        name,
+                        u64::from(self.id)
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A set of [`CredentialSchema`]s indexed by their [`SchemaId`].
+///
+/// Schemas either apply to credentials of every issuer, or only to
+/// credentials of a given issuer. Credentials without schema or with a
+/// schema unknown to the registry are accepted as is, unless the registry
+/// is strict.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: Arc<BTreeMap<SchemaId, CredentialSchema>>,
+    issuer_schemas: Arc<BTreeMap<(IdentityIdentifier, SchemaId), CredentialSchema>>,
+    strict: bool,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a schema, replacing any previous schema with the same identifier.
+    pub fn with_schema(mut self, schema: CredentialSchema) -> Self {
+        Arc::make_mut(&mut self.schemas).insert(schema.id, schema);
+        self
+    }
+
+    /// Add a schema which only applies to credentials issued by `issuer`.
+    ///
+    /// It takes precedence over a schema with the same identifier added
+    /// with [`SchemaRegistry::with_schema`].
+    pub fn with_issuer_schema(
+        mut self,
+        issuer: IdentityIdentifier,
+        schema: CredentialSchema,
+    ) -> Self {
+        Arc::make_mut(&mut self.issuer_schemas).insert((issuer, schema.id), schema);
+        self
+    }
+
+    /// Refuse credentials without schema or with an unknown schema.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn get(&self, id: SchemaId) -> Option<&CredentialSchema> {
+        self.schemas.get(&id)
+    }
+
+    /// Check that the given attributes conform to the given schema.
+    ///
+    /// Schemas which only apply to a given issuer are ignored.
+    pub fn validate(&self, id: Option<SchemaId>, attributes: &Attributes<'_>) -> Result<()> {
+        self.validate_with(id.and_then(|id| self.schemas.get(&id)), id, attributes)
+    }
+
+    /// Check that the attributes of a credential issued by `issuer` conform
+    /// to the given schema.
+    pub fn validate_issued(
+        &self,
+        issuer: &IdentityIdentifier,
+        id: Option<SchemaId>,
+        attributes: &Attributes<'_>,
+    ) -> Result<()> {
+        let schema = id.and_then(|id| {
+            self.issuer_schemas
+                .get(&(issuer.clone(), id))
+                .or_else(|| self.schemas.get(&id))
+        });
+        self.validate_with(schema, id, attributes)
+    }
+
+    fn validate_with(
+        &self,
+        schema: Option<&CredentialSchema>,
+        id: Option<SchemaId>,
+        attributes: &Attributes<'_>,
+    ) -> Result<()> {
+        match schema {
+            Some(schema) => schema.validate(attributes),
+            None if !self.strict => Ok(()),
+            None => Err(invalid(match id {
+                Some(id) => format!("unknown credential schema {}", u64::from(id)),
+                None => "credential without schema".to_string(),
+            })),
+        }
+    }
+}
+
+fn invalid(msg: String) -> Error {
+    Error::new(Origin::Application, Kind::Invalid, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> CredentialSchema {
+        CredentialSchema::new(SchemaId(42))
+            .with_attribute(AttributeSchema::required("name", AttributeType::String))
+            .with_attribute(
+                AttributeSchema::optional("role", AttributeType::String)
+                    .with_allowed_values(["admin", "member"]),
+            )
+            .with_attribute(AttributeSchema::optional("age", AttributeType::Integer))
+    }
+
+    #[test]
+    fn conforming_attributes() {
+        let mut attrs = Attributes::new();
+        attrs
+            .put("name", b"alice")
+            .put("role", b"member")
+            .put("age", b"42");
+        assert!(schema().validate(&attrs).is_ok());
+    }
+
+    #[test]
+    fn non_conforming_attributes() {
+        let mut missing = Attributes::new();
+        missing.put("role", b"member");
+        assert!(schema().validate(&missing).is_err());
+
+        let mut not_allowed = Attributes::new();
+        not_allowed.put("name", b"alice").put("role", b"owner");
+        assert!(schema().validate(&not_allowed).is_err());
+
+        let mut wrong_type = Attributes::new();
+        wrong_type.put("name", b"alice").put("age", b"old");
+        assert!(schema().validate(&wrong_type).is_err());
+
+        let mut unknown = Attributes::new();
+        unknown
+            .put("name", b"alice")
+            .put("email", b"alice@example.com");
+        assert!(schema().validate(&unknown).is_err());
+        let additional = schema().with_additional_attributes(AttributeType::String);
+        assert!(additional.validate(&unknown).is_ok());
+    }
+
+    #[test]
+    fn registry() {
+        let attrs = Attributes::new();
+        let registry = SchemaRegistry::new().with_schema(schema());
+        assert!(registry.validate(Some(SchemaId(42)), &attrs).is_err());
+        assert!(registry.validate(Some(SchemaId(7)), &attrs).is_ok());
+        assert!(registry.validate(None, &attrs).is_ok());
+
+        let registry = registry.strict();
+        assert!(registry.validate(Some(SchemaId(7)), &attrs).is_err());
+        assert!(registry.validate(None, &attrs).is_err());
+    }
+
+    #[test]
+    fn issuer_registry() {
+        let issuer = IdentityIdentifier::from_key_id("issuer");
+        let other = IdentityIdentifier::from_key_id("other");
+        let mut attrs = Attributes::new();
+        attrs.put("name", b"alice");
+
+        let registry = SchemaRegistry::new()
+            .with_schema(CredentialSchema::new(SchemaId(42)))
+            .with_issuer_schema(issuer.clone(), schema());
+        assert!(registry
+            .validate_issued(&issuer, Some(SchemaId(42)), &attrs)
+            .is_ok());
+        assert!(registry
+            .validate_issued(&other, Some(SchemaId(42)), &attrs)
+            .is_err());
+        assert!(registry.validate(Some(SchemaId(42)), &attrs).is_err());
+
+        let registry = SchemaRegistry::new().with_issuer_schema(issuer.clone(), schema());
+        assert!(registry
+            .validate_issued(&issuer, Some(SchemaId(42)), &Attributes::new())
+            .is_err());
+        assert!(registry
+            .validate_issued(&other, Some(SchemaId(42)), &Attributes::new())
+            .is_ok());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::credential::{Credential, SchemaId};
+use crate::IdentityVault;
+use minicbor::bytes::ByteSlice;
+use minicbor::{Decode, Encode};
+use ockam_core::compat::borrow::Cow;
+use ockam_core::compat::{collections::BTreeMap, vec::Vec};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::vault::{Hasher, SecretAttributes, SecretPersistence, SecretType, SecretVault};
+use ockam_core::{CowBytes, CowStr, Error, Result};
+
+#[cfg(feature = "tag")]
+use crate::TypeTag;
+
+/// Schema identifier for credentials supporting selective disclosure.
+///
+/// Every attribute value of such a credential is replaced by a commitment,
+/// i.e. the SHA-256 hash of the CBOR-encoded triple `(salt, key, value)`.
+/// The issuer signs the commitments and hands the salts and values over to
+/// the holder, who can later reveal any subset of them to a verifier.
+///
+/// A [`SchemaRegistry`](crate::credential::SchemaRegistry) checks the
+/// disclosed attributes against the schema registered under this identifier.
+pub const SELECTIVE_DISCLOSURE_SCHEMA: SchemaId = SchemaId(2);
+
+/// Length in bytes of the random salt of every attribute commitment.
+pub const DISCLOSURE_SALT_LENGTH: u32 = 16;
+
+/// The opening of a committed attribute: its salt and its actual value.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct Disclosure<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1938472>,
+    #[b(1)] salt: CowBytes<'a>,
+    #[b(2)] value: CowBytes<'a>,
+}
+
+impl<'a> Disclosure<'a> {
+    pub fn new<S, V>(salt: S, value: V) -> Self
+    where
+        S: Into<Cow<'a, [u8]>>,
+        V: Into<Cow<'a, [u8]>>,
+    {
+        Disclosure {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            salt: CowBytes(salt.into()),
+            value: CowBytes(value.into()),
+        }
+    }
+
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    pub fn to_owned<'r>(&self) -> Disclosure<'r> {
+        Disclosure {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            salt: self.salt.to_owned(),
+            value: self.value.to_owned(),
+        }
+    }
+}
+
+/// A credential with committed attributes, together with their openings.
+///
+/// This is what the holder receives from the issuer. It must be kept private,
+/// only [`CredentialPresentation`]s derived from it are sent to verifiers.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SelectiveCredential<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5273619>,
+    #[b(1)] credential: Credential<'a>,
+    #[b(2)] openings: BTreeMap<CowStr<'a>, Disclosure<'a>>,
+}
+
+impl<'a> SelectiveCredential<'a> {
+    pub(crate) fn new(
+        credential: Credential<'a>,
+        openings: BTreeMap<CowStr<'a>, Disclosure<'a>>,
+    ) -> Self {
+        SelectiveCredential {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            credential,
+            openings,
+        }
+    }
+
+    /// The signed credential containing the attribute commitments.
+    pub fn credential(&self) -> &Credential<'a> {
+        &self.credential
+    }
+
+    /// Names of all attributes which can be disclosed.
+    pub fn attribute_names(&self) -> impl Iterator<Item = &str> {
+        self.openings.keys().map(|k| &**k)
+    }
+
+    /// Create a presentation revealing only the given attributes.
+    pub fn present<'b, I>(&'b self, attributes: I) -> Result<CredentialPresentation<'b>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut disclosed = BTreeMap::new();
+        for name in attributes {
+            let name = name.as_ref();
+            let (k, d) = self
+                .openings
+                .iter()
+                .find(|(k, _)| *k == name)
+                .ok_or_else(|| {
+                    Error::new(
+                        Origin::Application,
+                        Kind::NotFound,
+                        format!("unknown credential attribute: {}", name),
+                    )
+                })?;
+            disclosed.insert(CowStr::from(&**k), Disclosure::new(d.salt(), d.value()));
+        }
+        Ok(CredentialPresentation::new(
+            Credential::new(
+                self.credential.unverified_data(),
+                self.credential.signature(),
+            ),
+            disclosed,
+        ))
+    }
+
+    pub fn to_owned<'r>(&self) -> SelectiveCredential<'r> {
+        SelectiveCredential {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            credential: self.credential.to_owned(),
+            openings: self
+                .openings
+                .iter()
+                .map(|(k, d)| (k.to_owned(), d.to_owned()))
+                .collect(),
+        }
+    }
+}
+
+/// A credential presented with a subset of its attributes disclosed.
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialPresentation<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7462015>,
+    #[b(1)] credential: Credential<'a>,
+    #[b(2)] disclosed: BTreeMap<CowStr<'a>, Disclosure<'a>>,
+}
+
+impl<'a> CredentialPresentation<'a> {
+    fn new(credential: Credential<'a>, disclosed: BTreeMap<CowStr<'a>, Disclosure<'a>>) -> Self {
+        CredentialPresentation {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            credential,
+            disclosed,
+        }
+    }
+
+    pub fn credential(&self) -> &Credential<'a> {
+        &self.credential
+    }
+
+    /// The disclosed attributes, which still need to be checked against the
+    /// credential commitments.
+    pub fn disclosed(&self) -> impl Iterator<Item = (&str, &Disclosure<'a>)> {
+        self.disclosed.iter().map(|(k, d)| (&**k, d))
+    }
+}
+
+/// Compute the commitment to an attribute value.
+pub(crate) async fn attribute_commitment(
+    vault: &impl IdentityVault,
+    salt: &[u8],
+    key: &str,
+    value: &[u8],
+) -> Result<[u8; 32]> {
+    let salt: &ByteSlice = salt.into();
+    let value: &ByteSlice = value.into();
+    let bytes = minicbor::to_vec((salt, key, value))?;
+    vault.sha256(&bytes).await
+}
+
+/// Generate a fresh random salt using the vault.
+pub(crate) async fn generate_salt(vault: &impl IdentityVault) -> Result<Vec<u8>> {
+    let attributes = SecretAttributes::new(
+        SecretType::Buffer,
+        SecretPersistence::Ephemeral,
+        DISCLOSURE_SALT_LENGTH,
+    );
+    let key_id = vault.secret_generate(attributes).await?;
+    // Don't leave the secret behind if it can't be exported
+    let salt = vault.secret_export(&key_id).await;
+    vault.secret_destroy(key_id).await?;
+    Ok(salt?.try_as_key()?.as_ref().to_vec())
+}
@@ -64,7 +64,7 @@ impl AttributesStorageUtils {
         Ok(Some(attrs))
     }
 
-    pub(crate) async fn put_attributes(
+    pub async fn put_attributes(
         sender: &IdentityIdentifier,
         entry: AttributesEntry<'_>,
         authenticated_storage: &impl AuthenticatedStorage,
@@ -1,5 +1,5 @@
 use crate::authenticated_storage::AuthenticatedStorage;
-use crate::credential::Credential;
+use crate::credential::{Credential, CredentialPresentation};
 use crate::{
     Identity, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault, PublicIdentity,
 };
@@ -104,6 +104,37 @@ impl<S: AuthenticatedStorage, V: IdentityVault> CredentialExchangeWorker<S, V> {
                     }
                 }
             }
+            (Post, ["actions", "present_selective"]) => {
+                debug!(
+                    "Received selective credential presentation request from {}",
+                    sender
+                );
+                let presentation: CredentialPresentation = dec.decode()?;
+
+                let res = self
+                    .identity
+                    .receive_presented_selective_credential(
+                        sender.clone(),
+                        presentation,
+                        self.authorities.iter(),
+                        &self.authenticated_storage,
+                    )
+                    .await;
+
+                match res {
+                    Ok(()) => {
+                        debug!("Selective credential presentation request processed successfully with {}", sender);
+                        Response::ok(req.id()).to_vec()?
+                    }
+                    Err(err) => {
+                        debug!(
+                            "Selective credential presentation request processing error: {} for {}",
+                            err, sender
+                        );
+                        Self::bad_request(req.id(), req.path(), &err.to_string()).to_vec()?
+                    }
+                }
+            }
             (Post, ["actions", "present_mutual"]) => {
                 debug!(
                     "Received mutual credential presentation request from {}",
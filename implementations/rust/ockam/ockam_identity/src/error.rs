@@ -20,6 +20,10 @@ pub enum IdentityError {
     InvalidCredentialFormat,
     UnknownAuthority,
     CredentialVerificationFailed,
+    PinnedIdentityMismatch,
+    InvalidIdentityExport,
+    UnsupportedIdentityExportVersion,
+    UnsupportedKeyExchange,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
@@ -0,0 +1,217 @@
+//! Portable export format for identities.
+//!
+//! An [`IdentityExport`] is a CBOR map with the following fields:
+//!
+//! | key | name             | type    | description                                         |
+//! |-----|------------------|---------|-----------------------------------------------------|
+//! | 1   | `version`        | uint    | version of the export format, currently `1`         |
+//! | 2   | `identifier`     | text    | [`IdentityIdentifier`] of the identity              |
+//! | 3   | `change_history` | bytes   | the [`IdentityChangeHistory`] as produced by its `export` |
+//!
+//! The change history is kept in its native encoding since change signatures
+//! are computed over it. Readers must refuse versions they do not know.
+//!
+//! The armored text form wraps the hex-encoded CBOR between
+//! [`ARMOR_HEADER`] and [`ARMOR_FOOTER`] lines, split in lines of
+//! [`ARMOR_LINE_LENGTH`] characters, so that it can be copied around as text.
+
+use crate::change_history::IdentityChangeHistory;
+use crate::{IdentityError, IdentityIdentifier, IdentityVault, PublicIdentity};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::string::String;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{CowBytes, Result};
+
+#[cfg(feature = "tag")]
+use crate::TypeTag;
+
+/// Current version of the export format.
+pub const EXPORT_FORMAT_VERSION: u8 = 1;
+
+/// First line of the armored text form.
+pub const ARMOR_HEADER: &str = "-----BEGIN OCKAM IDENTITY-----";
+
+/// Last line of the armored text form.
+pub const ARMOR_FOOTER: &str = "-----END OCKAM IDENTITY-----";
+
+/// Maximum number of characters per line of armored text.
+pub const ARMOR_LINE_LENGTH: usize = 64;
+
+/// Versioned export of a public identity, see the [module documentation](self).
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct IdentityExport<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4120596>,
+    #[n(1)] version: u8,
+    #[n(2)] identifier: IdentityIdentifier,
+    #[b(3)] change_history: CowBytes<'a>,
+}
+
+impl<'a> IdentityExport<'a> {
+    /// Export the given change history of an identity.
+    pub fn new(
+        identifier: IdentityIdentifier,
+        change_history: &IdentityChangeHistory,
+    ) -> Result<Self> {
+        Ok(Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            version: EXPORT_FORMAT_VERSION,
+            identifier,
+            change_history: change_history.export()?.into(),
+        })
+    }
+
+    /// Export a public identity.
+    pub fn from_public_identity(identity: &PublicIdentity) -> Result<Self> {
+        Self::new(identity.identifier().clone(), identity.changes())
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Identifier claimed by the export. It is only checked against the
+    /// change history by [`IdentityExport::public_identity`].
+    pub fn identifier(&self) -> &IdentityIdentifier {
+        &self.identifier
+    }
+
+    /// The change history, checked for consistency but not verified.
+    pub fn change_history(&self) -> Result<IdentityChangeHistory> {
+        IdentityChangeHistory::import(&self.change_history)
+    }
+
+    /// Verify the change history and turn it into a [`PublicIdentity`].
+    pub async fn public_identity(&self, vault: &impl IdentityVault) -> Result<PublicIdentity> {
+        let identity = PublicIdentity::import(&self.change_history, vault).await?;
+        if identity.identifier() != &self.identifier {
+            return Err(IdentityError::InvalidIdentityExport.into());
+        }
+        Ok(identity)
+    }
+
+    /// Encode to CBOR.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(minicbor::to_vec(self)?)
+    }
+
+    /// Decode from CBOR, refusing unknown format versions.
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self> {
+        let export: Self =
+            minicbor::decode(data).map_err(|_| IdentityError::InvalidIdentityExport)?;
+        if export.version != EXPORT_FORMAT_VERSION {
+            return Err(IdentityError::UnsupportedIdentityExportVersion.into());
+        }
+        Ok(export)
+    }
+
+    /// Encode to the armored text form.
+    pub fn to_armored(&self) -> Result<String> {
+        let encoded = hex::encode(self.to_bytes()?);
+        let mut armored = String::new();
+        armored.push_str(ARMOR_HEADER);
+        armored.push('\n');
+        for line in encoded.as_bytes().chunks(ARMOR_LINE_LENGTH) {
+            // Hex only produces ASCII characters
+            armored.push_str(core::str::from_utf8(line).unwrap_or_default());
+            armored.push('\n');
+        }
+        armored.push_str(ARMOR_FOOTER);
+        armored.push('\n');
+        Ok(armored)
+    }
+
+    /// Decode from the armored text form.
+    pub fn from_armored(text: &str) -> Result<IdentityExport<'static>> {
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next() != Some(ARMOR_HEADER) {
+            return Err(IdentityError::InvalidIdentityExport.into());
+        }
+        let mut encoded = String::new();
+        let mut terminated = false;
+        for line in lines.by_ref() {
+            if line == ARMOR_FOOTER {
+                terminated = true;
+                break;
+            }
+            encoded.push_str(line);
+        }
+        if !terminated || lines.next().is_some() {
+            return Err(IdentityError::InvalidIdentityExport.into());
+        }
+        let data = hex::decode(encoded).map_err(|_| IdentityError::InvalidIdentityExport)?;
+        Ok(IdentityExport::from_bytes(&data)?.to_owned())
+    }
+
+    /// Decode either the armored text form or plain CBOR.
+    pub fn parse(data: &[u8]) -> Result<IdentityExport<'static>> {
+        match core::str::from_utf8(data) {
+            Ok(text) if text.trim_start().starts_with(ARMOR_HEADER) => Self::from_armored(text),
+            _ => Ok(IdentityExport::from_bytes(data)?.to_owned()),
+        }
+    }
+
+    pub fn to_owned<'r>(&self) -> IdentityExport<'r> {
+        IdentityExport {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            version: self.version,
+            identifier: self.identifier.clone(),
+            change_history: self.change_history.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Identity;
+    use ockam_core::Result;
+    use ockam_node::Context;
+    use ockam_vault::Vault;
+
+    #[ockam_macros::test]
+    async fn export_round_trip(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let identity = Identity::create(ctx, &vault).await?;
+        identity.rotate_root_key().await?;
+
+        let export = IdentityExport::from_public_identity(&identity.to_public().await?)?;
+
+        let bytes = export.to_bytes()?;
+        let decoded = IdentityExport::parse(&bytes)?;
+        let public = decoded.public_identity(&vault).await?;
+        assert_eq!(public.identifier(), identity.identifier());
+
+        let armored = export.to_armored()?;
+        assert!(armored.starts_with(ARMOR_HEADER));
+        assert!(armored
+            .lines()
+            .all(|l| l.len() <= ARMOR_LINE_LENGTH || l == ARMOR_HEADER || l == ARMOR_FOOTER));
+        let decoded = IdentityExport::parse(armored.as_bytes())?;
+        let public = decoded.public_identity(&vault).await?;
+        assert_eq!(public.identifier(), identity.identifier());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn export_rejects_invalid_input(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let identity = Identity::create(ctx, &vault).await?;
+
+        let mut export = IdentityExport::from_public_identity(&identity.to_public().await?)?;
+        export.version = EXPORT_FORMAT_VERSION + 1;
+        let bytes = export.to_bytes()?;
+        assert!(IdentityExport::from_bytes(&bytes).is_err());
+
+        let mut armored = export.to_armored()?;
+        armored.truncate(armored.len() - ARMOR_FOOTER.len() - 1);
+        assert!(IdentityExport::from_armored(&armored).is_err());
+
+        ctx.stop().await
+    }
+}
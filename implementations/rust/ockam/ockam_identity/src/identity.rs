@@ -1,10 +1,10 @@
 use crate::authenticated_storage::AuthenticatedStorage;
 use crate::change::IdentitySignedChange;
 use crate::change_history::{IdentityChangeHistory, IdentityHistoryComparison};
-use crate::credential::Credential;
+use crate::credential::{Credential, SchemaRegistry};
 use crate::{
     ChangeIdentifier, IdentityError, IdentityIdentifier, IdentityVault, KeyAttributes,
-    PublicIdentity,
+    PublicIdentity, SecureChannelRegistry,
 };
 use ockam_core::compat::{
     boxed::Box,
@@ -25,6 +25,8 @@ use ockam_vault::{KeyId, SecretAttributes};
 pub struct Identity<V: IdentityVault> {
     id: IdentityIdentifier,
     pub(crate) credential: Arc<RwLock<Option<Credential<'static>>>>,
+    pub(crate) schemas: Arc<RwLock<SchemaRegistry>>,
+    pub(crate) secure_channels: SecureChannelRegistry,
     pub(crate) change_history: Arc<RwLock<IdentityChangeHistory>>,
     pub(crate) ctx: Context,
     pub(crate) vault: V,
@@ -57,6 +59,8 @@ impl<V: IdentityVault> Identity<V> {
         Self {
             id,
             credential: Arc::new(RwLock::new(None)),
+            schemas: Arc::new(RwLock::new(SchemaRegistry::new())),
+            secure_channels: SecureChannelRegistry::default(),
             change_history: Arc::new(RwLock::new(change_history)),
             ctx,
             vault,
@@ -115,11 +119,22 @@ impl<V: IdentityVault> Identity<V> {
                 SecretType::Ed25519,
                 SecretPersistence::Persistent,
                 CURVE25519_SECRET_LENGTH_U32,
-            ),
+            )
+            .with_usage(KeyAttributes::KEY_USAGE),
         );
         Self::create_impl(ctx, vault, None, attrs).await
     }
 
+    /// Create Identity with a new secret key of the given attributes, e.g.
+    /// [`KeyAttributes::nist_p256_with_label`] for a NIST P-256 root key.
+    pub async fn create_with_attributes(
+        ctx: &Context,
+        vault: &V,
+        attrs: KeyAttributes,
+    ) -> Result<Self> {
+        Self::create_impl(ctx, vault, None, attrs).await
+    }
+
     async fn create_impl(
         ctx: &Context,
         vault: &V,
@@ -205,21 +220,31 @@ impl<V: IdentityVault> Identity<V> {
     }
 
     pub async fn rotate_key(&self, label: &str) -> Result<()> {
-        let change = self
-            .make_rotate_key_change(KeyAttributes::default_with_label(label.to_string()))
-            .await?;
+        let key_attribs = self.rotated_key_attributes(label).await?;
+        let change = self.make_rotate_key_change(key_attribs).await?;
 
         self.add_change(change).await
     }
 
     pub async fn rotate_root_key(&self) -> Result<()> {
-        let change = self
-            .make_rotate_key_change(KeyAttributes::default_with_label(
-                IdentityStateConst::ROOT_LABEL.to_string(),
-            ))
-            .await?;
+        self.rotate_key(IdentityStateConst::ROOT_LABEL).await
+    }
 
-        self.add_change(change).await
+    /// New keys are of the same type as the keys they replace. Change
+    /// histories don't record usages, so the default one of identity keys applies.
+    async fn rotated_key_attributes(&self, label: &str) -> Result<KeyAttributes> {
+        let change_history = self.change_history.read().await;
+        let last_change =
+            IdentityChangeHistory::find_last_key_change(change_history.as_ref(), label)?;
+
+        Ok(KeyAttributes::new(
+            label.to_string(),
+            last_change
+                .change()
+                .key_attributes()
+                .secret_attributes()
+                .with_usage(KeyAttributes::KEY_USAGE),
+        ))
     }
 
     /// Get [`Secret`] key. Key is uniquely identified by label in [`KeyAttributes`]
@@ -319,7 +344,7 @@ impl<V: IdentityVault> Identity<V> {
 mod test {
     use super::*;
     use ockam_core::errcode::{Kind, Origin};
-    use ockam_core::vault::PublicKey;
+    use ockam_core::vault::{AsymmetricVault, PublicKey, SecretVault};
     use ockam_core::Error;
     use ockam_vault::Vault;
 
@@ -402,4 +427,49 @@ mod test {
 
         Ok(())
     }
+
+    #[ockam_macros::test]
+    async fn test_identity_keys_are_not_exportable(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let identity = Identity::create(ctx, &vault).await?;
+        let root = identity.get_root_secret_key().await?;
+        assert!(vault.secret_export(&root).await.is_err());
+        assert!(vault
+            .ec_diffie_hellman(&root, &identity.get_root_public_key().await?)
+            .await
+            .is_err());
+
+        identity.rotate_root_key().await?;
+        let root = identity.get_root_secret_key().await?;
+        assert!(vault.secret_export(&root).await.is_err());
+
+        identity.create_key("Truck management".to_string()).await?;
+        let key = identity.get_secret_key("Truck management").await?;
+        assert!(vault.secret_export(&key).await.is_err());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_nist_p256_identity(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let attrs = KeyAttributes::nist_p256_with_label(IdentityStateConst::ROOT_LABEL);
+        let identity = Identity::create_with_attributes(ctx, &vault, attrs).await?;
+        let public1 = identity.get_root_public_key().await?;
+        assert_eq!(public1.stype(), SecretType::NistP256);
+
+        identity.rotate_root_key().await?;
+
+        if !identity.verify_changes().await? {
+            return test_error("verify_changes failed");
+        }
+
+        let public2 = identity.get_root_public_key().await?;
+        assert_eq!(public2.stype(), SecretType::NistP256);
+        assert_ne!(public1, public2);
+
+        ctx.stop().await
+    }
 }
@@ -161,6 +161,30 @@ impl<V: IdentityVault> SymmetricVault for CrazyVault<V> {
             .aead_aes_gcm_decrypt(key_id, cipher_text, nonce, aad)
             .await
     }
+
+    async fn aead_chacha20_poly1305_encrypt(
+        &self,
+        key_id: &KeyId,
+        plaintext: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        self.vault
+            .aead_chacha20_poly1305_encrypt(key_id, plaintext, nonce, aad)
+            .await
+    }
+
+    async fn aead_chacha20_poly1305_decrypt(
+        &self,
+        key_id: &KeyId,
+        cipher_text: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        self.vault
+            .aead_chacha20_poly1305_decrypt(key_id, cipher_text, nonce, aad)
+            .await
+    }
 }
 
 #[async_trait]
@@ -195,6 +219,14 @@ impl<V: IdentityVault> AsymmetricVault for CrazyVault<V> {
     async fn compute_key_id_for_public_key(&self, public_key: &PublicKey) -> Result<KeyId> {
         self.vault.compute_key_id_for_public_key(public_key).await
     }
+
+    async fn kem_encapsulate(&self, peer_public_key: &PublicKey) -> Result<(Vec<u8>, KeyId)> {
+        self.vault.kem_encapsulate(peer_public_key).await
+    }
+
+    async fn kem_decapsulate(&self, secret: &KeyId, ciphertext: &[u8]) -> Result<KeyId> {
+        self.vault.kem_decapsulate(secret, ciphertext).await
+    }
 }
 
 #[async_trait]
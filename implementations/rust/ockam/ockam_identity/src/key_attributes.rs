@@ -1,6 +1,9 @@
 use core::fmt;
 use ockam_core::compat::string::String;
-use ockam_core::vault::{SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32};
+use ockam_core::vault::{
+    SecretPersistence, SecretType, SecretUsage, CURVE25519_SECRET_LENGTH_U32,
+    NISTP256_SECRET_LENGTH_U32,
+};
 use ockam_vault::SecretAttributes;
 use serde::{Deserialize, Serialize};
 
@@ -22,6 +25,9 @@ impl KeyAttributes {
 }
 
 impl KeyAttributes {
+    /// Identity keys only sign changes and credentials, and can't be exported
+    pub const KEY_USAGE: SecretUsage = SecretUsage::SIGN.union(SecretUsage::VERIFY);
+
     pub fn default_with_label(label: impl Into<String>) -> Self {
         Self::new(
             label.into(),
@@ -29,7 +35,21 @@ impl KeyAttributes {
                 SecretType::Ed25519,
                 SecretPersistence::Persistent,
                 CURVE25519_SECRET_LENGTH_U32,
-            ),
+            )
+            .with_usage(Self::KEY_USAGE),
+        )
+    }
+
+    /// Attributes of a NIST P-256 key, for deployments restricted to FIPS curves
+    pub fn nist_p256_with_label(label: impl Into<String>) -> Self {
+        Self::new(
+            label.into(),
+            SecretAttributes::new(
+                SecretType::NistP256,
+                SecretPersistence::Persistent,
+                NISTP256_SECRET_LENGTH_U32,
+            )
+            .with_usage(Self::KEY_USAGE),
         )
     }
 
@@ -30,6 +30,7 @@ pub mod change_history;
 pub mod credential;
 
 pub mod error;
+pub mod export;
 
 pub use error::*;
 
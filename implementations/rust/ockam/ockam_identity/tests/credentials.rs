@@ -3,9 +3,16 @@ use ockam_core::{async_trait, AllowAll, Any};
 use ockam_core::{route, Result, Routed, Worker};
 use ockam_identity::authenticated_storage::mem::InMemoryStorage;
 use ockam_identity::credential::access_control::CredentialAccessControl;
-use ockam_identity::credential::{AttributesStorageUtils, Credential};
-use ockam_identity::{Identity, TrustEveryonePolicy, TrustIdentifierPolicy};
+use ockam_identity::credential::{
+    AttributeSchema, AttributeType, AttributesStorageUtils, Credential, CredentialSchema, SchemaId,
+    SchemaRegistry, SelectiveCredential, SELECTIVE_DISCLOSURE_SCHEMA,
+};
+use ockam_identity::{
+    Identity, IdentityStateConst, KeyAttributes, SecureChannelTrustInfo, TrustEveryonePolicy,
+    TrustIdentifierPolicy, TrustPolicy,
+};
 use ockam_node::{Context, WorkerBuilder};
+use ockam_vault::kms::MockKms;
 use ockam_vault::Vault;
 use std::sync::atomic::{AtomicI8, Ordering};
 use std::time::Duration;
@@ -66,6 +73,67 @@ async fn full_flow_oneway(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn full_flow_kms_keys(ctx: &mut Context) -> Result<()> {
+    let kms = MockKms::new();
+    let mut vault = Vault::create();
+    vault.enable_kms(kms.clone());
+
+    let root = || KeyAttributes::nist_p256_with_label(IdentityStateConst::ROOT_LABEL);
+    let authority = Identity::create_with_attributes(ctx, &vault, root()).await?;
+
+    let server = Identity::create_with_attributes(ctx, &vault, root()).await?;
+    let server_storage = InMemoryStorage::new();
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy, &server_storage)
+        .await?;
+
+    let authorities = vec![authority.to_public().await?];
+
+    server
+        .start_credentials_exchange_worker(
+            authorities,
+            "credential_exchange",
+            false,
+            server_storage.clone(),
+        )
+        .await?;
+
+    let client = Identity::create_with_attributes(ctx, &vault, root()).await?;
+    let client_storage = InMemoryStorage::new();
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+            &client_storage,
+        )
+        .await?;
+
+    // Every root key is held by the KMS
+    assert_eq!(kms.len().await, 3);
+
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("is_superuser", b"true");
+    let credential = authority.issue_credential(credential).await?;
+
+    client.set_credential(Some(credential)).await;
+
+    client
+        .present_credential(route![channel, "credential_exchange"])
+        .await?;
+
+    let attrs = AttributesStorageUtils::get_attributes(client.identifier(), &server_storage)
+        .await?
+        .unwrap();
+
+    let val = attrs.get("is_superuser").unwrap();
+
+    assert_eq!(val.as_slice(), b"true");
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn full_flow_twoway(ctx: &mut Context) -> Result<()> {
     let vault = Vault::create();
@@ -131,6 +199,352 @@ async fn full_flow_twoway(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn full_flow_selective_disclosure(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy, &server_storage)
+        .await?;
+
+    let authorities = vec![authority.to_public().await?];
+
+    server
+        .start_credentials_exchange_worker(
+            authorities,
+            "credential_exchange",
+            false,
+            server_storage.clone(),
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+            &client_storage,
+        )
+        .await?;
+
+    let credential = Credential::builder(client.identifier().clone())
+        .with_attribute("project_id", b"1234")
+        .with_attribute("role", b"member")
+        .with_attribute("email", b"alice@example.com");
+
+    let credential = authority.issue_selective_credential(credential).await?;
+
+    client
+        .present_selective_credential(
+            route![channel.clone(), "credential_exchange"],
+            &credential,
+            ["project_id", "role"],
+        )
+        .await?;
+
+    let attrs = AttributesStorageUtils::get_attributes(client.identifier(), &server_storage)
+        .await?
+        .unwrap();
+
+    assert_eq!(attrs.get("project_id").unwrap().as_slice(), b"1234");
+    assert_eq!(attrs.get("role").unwrap().as_slice(), b"member");
+    assert!(attrs.get("email").is_none());
+
+    let res = client
+        .present_selective_credential(
+            route![channel, "credential_exchange"],
+            &credential,
+            ["unknown"],
+        )
+        .await;
+    assert!(res.is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn selective_credential_with_wrong_disclosure_is_rejected(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy, &server_storage)
+        .await?;
+
+    let authorities = vec![authority.to_public().await?];
+
+    server
+        .start_credentials_exchange_worker(
+            authorities,
+            "credential_exchange",
+            false,
+            server_storage.clone(),
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+            &client_storage,
+        )
+        .await?;
+
+    let credential = Credential::builder(client.identifier().clone())
+        .with_attribute("project_id", b"1234")
+        .with_attribute("role", b"member");
+    let credential = authority.issue_selective_credential(credential).await?;
+
+    // Replace the disclosed role by another value of the same length,
+    // which doesn't match its commitment anymore
+    let mut encoded = minicbor::to_vec(&credential)?;
+    let position = encoded.windows(6).position(|w| w == b"member").unwrap();
+    encoded[position..position + 6].copy_from_slice(b"leader");
+    let tampered: SelectiveCredential = minicbor::decode(&encoded)?;
+
+    let res = client
+        .present_selective_credential(
+            route![channel.clone(), "credential_exchange"],
+            &tampered,
+            ["project_id", "role"],
+        )
+        .await;
+    assert!(res.is_err());
+
+    // The commitments can't be presented as attribute values either
+    client
+        .set_credential(Some(credential.credential().to_owned()))
+        .await;
+    let res = client
+        .present_credential(route![channel, "credential_exchange"])
+        .await;
+    assert!(res.is_err());
+
+    let attrs =
+        AttributesStorageUtils::get_attributes(client.identifier(), &server_storage).await?;
+    assert!(attrs.is_none());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn non_conforming_credential_is_rejected(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy, &server_storage)
+        .await?;
+
+    server
+        .set_schema_registry(
+            SchemaRegistry::new().with_schema(
+                CredentialSchema::new(SchemaId(42)).with_attribute(
+                    AttributeSchema::required("role", AttributeType::String)
+                        .with_allowed_values(["admin", "member"]),
+                ),
+            ),
+        )
+        .await;
+
+    let authorities = vec![authority.to_public().await?];
+
+    server
+        .start_credentials_exchange_worker(
+            authorities,
+            "credential_exchange",
+            false,
+            server_storage.clone(),
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+            &client_storage,
+        )
+        .await?;
+
+    let credential = Credential::builder(client.identifier().clone())
+        .with_schema(SchemaId(42))
+        .with_attribute("role", b"owner");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(Some(credential)).await;
+
+    let res = client
+        .present_credential(route![channel, "credential_exchange"])
+        .await;
+    assert!(res.is_err());
+
+    let attrs =
+        AttributesStorageUtils::get_attributes(client.identifier(), &server_storage).await?;
+    assert!(attrs.is_none());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn selective_presentation_is_checked_against_schemas(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy, &server_storage)
+        .await?;
+
+    let authorities = vec![authority.to_public().await?];
+
+    server
+        .start_credentials_exchange_worker(
+            authorities,
+            "credential_exchange",
+            false,
+            server_storage.clone(),
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+            &client_storage,
+        )
+        .await?;
+
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("role", b"owner");
+    let credential = authority.issue_selective_credential(credential).await?;
+
+    // A strict registry without a schema for selective disclosure
+    server
+        .set_schema_registry(SchemaRegistry::new().strict())
+        .await;
+    let res = client
+        .present_selective_credential(
+            route![channel.clone(), "credential_exchange"],
+            &credential,
+            ["role"],
+        )
+        .await;
+    assert!(res.is_err());
+
+    // Disclosed attributes must conform to the selective disclosure schema
+    server
+        .set_schema_registry(
+            SchemaRegistry::new().with_schema(
+                CredentialSchema::new(SELECTIVE_DISCLOSURE_SCHEMA).with_attribute(
+                    AttributeSchema::optional("role", AttributeType::String)
+                        .with_allowed_values(["admin", "member"]),
+                ),
+            ),
+        )
+        .await;
+    let res = client
+        .present_selective_credential(
+            route![channel, "credential_exchange"],
+            &credential,
+            ["role"],
+        )
+        .await;
+    assert!(res.is_err());
+
+    let attrs =
+        AttributesStorageUtils::get_attributes(client.identifier(), &server_storage).await?;
+    assert!(attrs.is_none());
+
+    ctx.stop().await
+}
+
+struct CredentialTrustPolicy {
+    presented: Arc<AtomicI8>,
+}
+
+#[async_trait]
+impl TrustPolicy for CredentialTrustPolicy {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        if let Some(data) = trust_info.their_credential() {
+            let _: Credential = minicbor::decode(data)?;
+            self.presented.fetch_add(1, Ordering::Relaxed);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn requires_credential(&self) -> bool {
+        true
+    }
+}
+
+#[ockam_macros::test]
+async fn credential_presented_during_handshake(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+    let server_storage = InMemoryStorage::new();
+
+    let presented = Arc::new(AtomicI8::new(0));
+    server
+        .create_secure_channel_listener(
+            "listener",
+            CredentialTrustPolicy {
+                presented: presented.clone(),
+            },
+            &server_storage,
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let client_storage = InMemoryStorage::new();
+
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("role", b"edge");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(Some(credential)).await;
+
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+            &client_storage,
+        )
+        .await?;
+
+    ctx.send(route![channel, ctx.address()], "Hello".to_string())
+        .await?;
+    let msg = ctx.receive::<String>().await?.take();
+    assert_eq!("Hello", msg.body());
+    assert_eq!(presented.load(Ordering::Relaxed), 1);
+
+    ctx.stop().await
+}
+
 struct CountingWorker {
     msgs_count: Arc<AtomicI8>,
 }
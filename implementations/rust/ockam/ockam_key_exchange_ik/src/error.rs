@@ -0,0 +1,49 @@
+use ockam_core::compat::{error::Error as StdError, fmt};
+use ockam_core::{
+    errcode::{Kind, Origin},
+    Error,
+};
+
+/// Represents the failures that can occur in
+/// an Ockam IK Key Agreement
+#[derive(Clone, Copy, Debug)]
+pub enum IKError {
+    /// The key exchange protocol is in an invalid state.
+    InvalidState = 1,
+    /// An internal Vault error has occurred.
+    InternalVaultError,
+    /// A message had an unexpected length.
+    MessageLenMismatch,
+    /// The static key of the responder is needed to initiate a handshake.
+    MissingRemoteStaticKey,
+    /// A static key is needed to respond to a handshake.
+    MissingStaticKey,
+}
+
+impl StdError for IKError {}
+
+impl fmt::Display for IKError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidState => write!(f, "invalid state"),
+            Self::InternalVaultError => write!(f, "internal vault error"),
+            Self::MessageLenMismatch => write!(f, "message length mismatch"),
+            Self::MissingRemoteStaticKey => write!(f, "missing static key of the responder"),
+            Self::MissingStaticKey => write!(f, "missing static key"),
+        }
+    }
+}
+
+impl From<IKError> for Error {
+    #[track_caller]
+    fn from(err: IKError) -> Self {
+        let kind = match err {
+            IKError::InvalidState => Kind::Invalid,
+            IKError::InternalVaultError => Kind::Internal,
+            IKError::MessageLenMismatch => Kind::Misuse,
+            IKError::MissingRemoteStaticKey | IKError::MissingStaticKey => Kind::Misuse,
+        };
+
+        Error::new(Origin::KeyExchange, kind, err)
+    }
+}
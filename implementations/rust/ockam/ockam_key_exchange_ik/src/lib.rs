@@ -0,0 +1,173 @@
+//! IK (Noise Protocol) implementation of an Ockam Key Exchanger.
+//!
+//! The initiator of an IK handshake has to know the static key of the
+//! responder beforehand, in exchange the handshake takes one round trip
+//! instead of the one and a half of XX.
+//!
+//! This crate contains the key exchange types of the Ockam library and is intended
+//! for use by other crates that provide features and add-ons to the main
+//! Ockam library.
+//!
+//! The main Ockam crate re-exports types defined in this crate.
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate core;
+
+#[cfg(feature = "alloc")]
+#[macro_use]
+extern crate alloc;
+
+mod error;
+
+pub use error::*;
+use ockam_core::vault::CURVE25519_PUBLIC_LENGTH_USIZE;
+use ockam_core::AsyncTryClone;
+
+/// The number of bytes in a SHA256 digest
+pub const SHA256_SIZE_U32: u32 = 32;
+/// The number of bytes in a SHA256 digest
+pub const SHA256_SIZE_USIZE: usize = 32;
+
+/// The number of bytes in AES-GCM tag
+pub const AES_GCM_TAGSIZE_U32: u32 = 16;
+/// The number of bytes in AES-GCM tag
+pub const AES_GCM_TAGSIZE_USIZE: usize = 16;
+
+/// The number of bytes of the first message of a handshake with an empty
+/// payload: the ephemeral key, the encrypted static key and the payload tag.
+pub const IK_MESSAGE_1_MIN_LENGTH: usize =
+    2 * CURVE25519_PUBLIC_LENGTH_USIZE + 2 * AES_GCM_TAGSIZE_USIZE;
+
+/// Vault with IK required functionality
+pub trait IKVault:
+    SecretVault + Hasher + AsymmetricVault + SymmetricVault + AsyncTryClone + Send + Sync + 'static
+{
+}
+
+impl<D> IKVault for D where
+    D: SecretVault
+        + Hasher
+        + AsymmetricVault
+        + SymmetricVault
+        + AsyncTryClone
+        + Send
+        + Sync
+        + 'static
+{
+}
+
+mod initiator;
+mod state;
+pub use initiator::*;
+mod responder;
+pub use responder::*;
+mod new_key_exchanger;
+pub use new_key_exchanger::*;
+use ockam_core::vault::{AsymmetricVault, Hasher, SecretVault, SymmetricVault};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32,
+    };
+    use ockam_core::Result;
+    use ockam_key_exchange_core::{KeyExchanger, NewKeyExchanger};
+    use ockam_node::Context;
+    use ockam_vault::Vault;
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn full_flow__correct_credentials__keys_should_match(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let attributes = SecretAttributes::new(
+            SecretType::X25519,
+            SecretPersistence::Ephemeral,
+            CURVE25519_SECRET_LENGTH_U32,
+        );
+        let responder_static = vault.secret_generate(attributes).await?;
+        let responder_public = vault.secret_public_key_get(&responder_static).await?;
+
+        let initiator_exchanger = IKNewKeyExchanger::new(vault.async_try_clone().await?)
+            .with_remote_static_public_key(responder_public);
+        let responder_exchanger = IKNewKeyExchanger::new(vault.async_try_clone().await?)
+            .with_static_key(responder_static);
+
+        let mut initiator = initiator_exchanger.initiator().await?;
+        let mut responder = responder_exchanger.responder().await?;
+
+        let m1 = initiator.generate_request(b"hello").await?;
+        assert!(m1.len() >= IK_MESSAGE_1_MIN_LENGTH);
+        assert_eq!(responder.handle_response(&m1).await?, b"hello");
+        let m2 = responder.generate_request(b"world").await?;
+        assert_eq!(initiator.handle_response(&m2).await?, b"world");
+
+        assert!(initiator.is_complete().await?);
+        assert!(responder.is_complete().await?);
+
+        let initiator = initiator.finalize().await?;
+        let responder = responder.finalize().await?;
+
+        assert_eq!(initiator.h(), responder.h());
+
+        let s1 = vault.secret_export(initiator.encrypt_key()).await?;
+        let s2 = vault.secret_export(responder.decrypt_key()).await?;
+        assert_eq!(s1, s2);
+
+        let s1 = vault.secret_export(initiator.decrypt_key()).await?;
+        let s2 = vault.secret_export(responder.encrypt_key()).await?;
+        assert_eq!(s1, s2);
+
+        ctx.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test]
+    async fn full_flow__wrong_static_key__should_fail(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let attributes = SecretAttributes::new(
+            SecretType::X25519,
+            SecretPersistence::Ephemeral,
+            CURVE25519_SECRET_LENGTH_U32,
+        );
+        let responder_static = vault.secret_generate(attributes).await?;
+        let other_static = vault.secret_generate(attributes).await?;
+        let other_public = vault.secret_public_key_get(&other_static).await?;
+
+        let mut initiator = IKNewKeyExchanger::new(vault.async_try_clone().await?)
+            .with_remote_static_public_key(other_public)
+            .initiator()
+            .await?;
+        let mut responder = IKNewKeyExchanger::new(vault.async_try_clone().await?)
+            .with_static_key(responder_static)
+            .responder()
+            .await?;
+
+        let m1 = initiator.generate_request(&[]).await?;
+        assert!(responder.handle_response(&m1).await.is_err());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn missing_static_keys(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let new_key_exchanger = IKNewKeyExchanger::new(vault);
+
+        assert!(new_key_exchanger.initiator().await.is_err());
+        assert!(new_key_exchanger.responder().await.is_err());
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,71 @@
+use crate::state::State;
+use crate::{IKError, IKVault, Initiator, Responder};
+use ockam_core::vault::{KeyId, PublicKey};
+use ockam_core::{async_trait, compat::boxed::Box, AsyncTryClone, Result};
+
+use ockam_key_exchange_core::NewKeyExchanger;
+
+/// Represents an IK NewKeyExchanger
+///
+/// Initiators need the static public key of the responder, see
+/// [`IKNewKeyExchanger::with_remote_static_public_key`], and responders need
+/// the matching secret key, see [`IKNewKeyExchanger::with_static_key`].
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
+pub struct IKNewKeyExchanger<V: IKVault> {
+    vault: V,
+    static_key: Option<KeyId>,
+    remote_static_public_key: Option<PublicKey>,
+}
+
+impl<V: IKVault> IKNewKeyExchanger<V> {
+    /// Create a new IKNewKeyExchanger
+    pub fn new(vault: V) -> Self {
+        Self {
+            vault,
+            static_key: None,
+            remote_static_public_key: None,
+        }
+    }
+
+    /// Use the given X25519 key as static key. It is required to respond to
+    /// handshakes, initiators generate a new one for each handshake otherwise.
+    pub fn with_static_key(mut self, static_key: KeyId) -> Self {
+        self.static_key = Some(static_key);
+        self
+    }
+
+    /// Initiate handshakes with the responder which has the given static key
+    pub fn with_remote_static_public_key(mut self, public_key: PublicKey) -> Self {
+        self.remote_static_public_key = Some(public_key);
+        self
+    }
+}
+
+#[async_trait]
+impl<V: IKVault> NewKeyExchanger for IKNewKeyExchanger<V> {
+    type Initiator = Initiator<V>;
+    type Responder = Responder<V>;
+
+    /// Create a new initiator using the provided backing vault
+    async fn initiator(&self) -> Result<Initiator<V>> {
+        let remote_static_public_key = self
+            .remote_static_public_key
+            .clone()
+            .ok_or(IKError::MissingRemoteStaticKey)?;
+        let ss = State::new(
+            &self.vault,
+            self.static_key.clone(),
+            Some(remote_static_public_key),
+        )
+        .await?;
+        Ok(Initiator::new(ss))
+    }
+
+    /// Create a new responder using the provided backing vault
+    async fn responder(&self) -> Result<Responder<V>> {
+        let static_key = self.static_key.clone().ok_or(IKError::MissingStaticKey)?;
+        let ss = State::new(&self.vault, Some(static_key), None).await?;
+        Ok(Responder::new(ss))
+    }
+}
@@ -0,0 +1,76 @@
+use crate::state::State;
+use crate::{IKError, IKVault};
+use ockam_core::compat::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use ockam_core::{async_trait, compat::boxed::Box, Result};
+use ockam_key_exchange_core::{CompletedKeyExchange, KeyExchanger};
+
+#[derive(Debug)]
+enum ResponderState {
+    DecodeMessage1,
+    EncodeMessage2,
+    Done,
+}
+
+/// Represents an IK responder
+#[derive(Debug)]
+pub struct Responder<V: IKVault> {
+    state: ResponderState,
+    state_data: State<V>,
+}
+
+impl<V: IKVault> Responder<V> {
+    pub(crate) fn new(state_data: State<V>) -> Self {
+        Responder {
+            state: ResponderState::DecodeMessage1,
+            state_data,
+        }
+    }
+}
+
+#[async_trait]
+impl<V: IKVault> KeyExchanger for Responder<V> {
+    async fn name(&self) -> Result<String> {
+        Ok("NOISE_IK".to_string())
+    }
+
+    async fn generate_request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        match self.state {
+            ResponderState::EncodeMessage2 => {
+                let msg = self.state_data.encode_message_2(payload).await?;
+                self.state = ResponderState::Done;
+                Ok(msg)
+            }
+            ResponderState::DecodeMessage1 | ResponderState::Done => {
+                Err(IKError::InvalidState.into())
+            }
+        }
+    }
+
+    async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
+        match self.state {
+            ResponderState::DecodeMessage1 => {
+                self.state_data.run_prologue().await?;
+                let msg = self.state_data.decode_message_1(response).await?;
+                self.state = ResponderState::EncodeMessage2;
+                Ok(msg)
+            }
+            ResponderState::EncodeMessage2 | ResponderState::Done => {
+                Err(IKError::InvalidState.into())
+            }
+        }
+    }
+
+    async fn is_complete(&self) -> Result<bool> {
+        Ok(matches!(self.state, ResponderState::Done))
+    }
+
+    async fn finalize(self) -> Result<CompletedKeyExchange> {
+        match self.state {
+            ResponderState::Done => self.state_data.finalize_responder().await,
+            _ => Err(IKError::InvalidState.into()),
+        }
+    }
+}
@@ -0,0 +1,614 @@
+use crate::{IKError, IKVault, AES_GCM_TAGSIZE_USIZE, IK_MESSAGE_1_MIN_LENGTH, SHA256_SIZE_USIZE};
+use ockam_core::vault::{
+    KeyId, PublicKey, SecretAttributes, SecretPersistence, SecretType, AES256_SECRET_LENGTH_U32,
+    CURVE25519_PUBLIC_LENGTH_USIZE, CURVE25519_SECRET_LENGTH_U32,
+};
+use ockam_core::{compat::vec::Vec, Result};
+use ockam_key_exchange_core::CompletedKeyExchange;
+
+mod dh_state;
+pub(crate) use dh_state::*;
+
+/// Represents the IK Handshake
+pub(crate) struct State<V: IKVault> {
+    run_prologue: bool,
+    static_key: Option<KeyId>,
+    static_public_key: Option<PublicKey>,
+    ephemeral_secret: Option<KeyId>,
+    ephemeral_public: Option<PublicKey>,
+    remote_static_public_key: Option<PublicKey>,
+    remote_ephemeral_public_key: Option<PublicKey>,
+    dh_state: DhState<V>,
+    nonce: u16,
+    h: Option<[u8; SHA256_SIZE_USIZE]>,
+    vault: V,
+}
+
+impl<V: IKVault> core::fmt::Debug for State<V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "SymmetricState {{ key: {:?}, nonce: {:?}, h: {:?}, ck: {:?} }}",
+            self.dh_state.key(),
+            self.nonce,
+            self.h,
+            self.dh_state.ck()
+        )
+    }
+}
+
+impl<V: IKVault> State<V> {
+    /// Responders need their `static_key`, initiators need the
+    /// `remote_static_public_key` of the responder
+    pub(crate) async fn new(
+        vault: &V,
+        static_key: Option<KeyId>,
+        remote_static_public_key: Option<PublicKey>,
+    ) -> Result<Self> {
+        Ok(Self {
+            run_prologue: true,
+            static_key,
+            static_public_key: None,
+            ephemeral_secret: None,
+            ephemeral_public: None,
+            remote_static_public_key,
+            remote_ephemeral_public_key: None,
+            dh_state: DhState::empty(vault.async_try_clone().await?),
+            nonce: 0,
+            h: None,
+            vault: vault.async_try_clone().await?,
+        })
+    }
+}
+
+impl<V: IKVault> State<V> {
+    fn get_symmetric_key_type_and_length(&self) -> (SecretType, u32) {
+        (SecretType::Aes, AES256_SECRET_LENGTH_U32)
+    }
+
+    fn get_protocol_name(&self) -> &'static [u8] {
+        b"Noise_IK_25519_AESGCM_SHA256\0\0\0\0"
+    }
+
+    /// Create a new `HandshakeState` starting with the prologue
+    async fn prologue(&mut self) -> Result<()> {
+        let attributes = SecretAttributes::new(
+            SecretType::X25519,
+            SecretPersistence::Ephemeral,
+            CURVE25519_SECRET_LENGTH_U32,
+        );
+        // 1. Use the given static key pair or generate one for this handshake and set it to `s`
+        if let Some(sk) = &self.static_key {
+            self.static_public_key = Some(self.vault.secret_public_key_get(sk).await?);
+        } else {
+            let static_secret_handle = self.vault.secret_generate(attributes).await?;
+            self.static_public_key = Some(
+                self.vault
+                    .secret_public_key_get(&static_secret_handle)
+                    .await?,
+            );
+            self.static_key = Some(static_secret_handle)
+        };
+
+        // 2. Generate an ephemeral key pair for this handshake and set it to e
+        let ephemeral_secret_handle = self.vault.secret_generate(attributes).await?;
+        self.ephemeral_public = Some(
+            self.vault
+                .secret_public_key_get(&ephemeral_secret_handle)
+                .await?,
+        );
+        self.ephemeral_secret = Some(ephemeral_secret_handle);
+
+        // 3. Set k to empty, Set n to 0
+        self.nonce = 0;
+
+        // 4. Set h and ck to protocol name
+        // 5. h = SHA256(h || prologue),
+        // prologue is empty
+        let mut h = [0u8; SHA256_SIZE_USIZE];
+        h[..self.get_protocol_name().len()].copy_from_slice(self.get_protocol_name());
+        self.dh_state = DhState::new(&h, self.vault.async_try_clone().await?).await?;
+        self.h = Some(self.vault.sha256(&h).await?);
+
+        // 6. Pre-message `<- s`: both sides mix the static key of the responder
+        let responder_static_public_key = match &self.remote_static_public_key {
+            Some(rs) => rs.clone(),
+            None => self
+                .static_public_key
+                .clone()
+                .ok_or(IKError::InvalidState)?,
+        };
+        self.h = Some(self.mix_hash(responder_static_public_key.data()).await?);
+
+        Ok(())
+    }
+
+    /// mix hash step in Noise protocol
+    async fn mix_hash<B: AsRef<[u8]>>(&mut self, data: B) -> Result<[u8; 32]> {
+        let h = &self.h.ok_or(IKError::InvalidState)?;
+
+        let mut input = h.to_vec();
+        input.extend_from_slice(data.as_ref());
+        let h = self.vault.sha256(&input).await?;
+        Ok(h)
+    }
+
+    /// Encrypt and mix step in Noise protocol
+    async fn encrypt_and_mix_hash<B: AsRef<[u8]>>(
+        &mut self,
+        plaintext: B,
+    ) -> Result<(Vec<u8>, [u8; 32])> {
+        let h = &self.h.ok_or(IKError::InvalidState)?;
+
+        let mut nonce = [0u8; 12];
+        nonce[10..].copy_from_slice(&self.nonce.to_be_bytes());
+
+        let ciphertext_and_tag = {
+            let key = self.dh_state.key().ok_or(IKError::InvalidState)?;
+            self.vault
+                .aead_aes_gcm_encrypt(key, plaintext.as_ref(), nonce.as_ref(), h)
+                .await?
+        };
+        let h = self.mix_hash(&ciphertext_and_tag).await?;
+        Ok((ciphertext_and_tag, h))
+    }
+
+    /// Decrypt and mix step in Noise protocol
+    async fn decrypt_and_mix_hash<B: AsRef<[u8]>>(
+        &mut self,
+        ciphertext: B,
+    ) -> Result<(Vec<u8>, [u8; 32])> {
+        let h = &self.h.ok_or(IKError::InvalidState)?;
+
+        let mut nonce = [0u8; 12];
+        nonce[10..].copy_from_slice(&self.nonce.to_be_bytes());
+        let ciphertext = ciphertext.as_ref();
+        let plaintext = {
+            let key = self.dh_state.key().ok_or(IKError::InvalidState)?;
+            self.vault
+                .aead_aes_gcm_decrypt(key, ciphertext, nonce.as_ref(), h)
+                .await?
+        };
+        let h = self.mix_hash(ciphertext).await?;
+        Ok((plaintext, h))
+    }
+
+    /// Split step in Noise protocol
+    async fn split(&mut self) -> Result<(KeyId, KeyId)> {
+        let ck = self.dh_state.ck().ok_or(IKError::InvalidState)?;
+
+        let symmetric_key_info = self.get_symmetric_key_type_and_length();
+        let attributes = SecretAttributes::new(
+            symmetric_key_info.0,
+            SecretPersistence::Ephemeral,
+            symmetric_key_info.1,
+        );
+        let mut hkdf_output = self
+            .vault
+            .hkdf_sha256(ck, b"", None, vec![attributes, attributes])
+            .await?;
+
+        if hkdf_output.len() != 2 {
+            return Err(IKError::InternalVaultError.into());
+        }
+
+        let res1 = hkdf_output.pop().unwrap();
+        let res0 = hkdf_output.pop().unwrap();
+
+        Ok((res0, res1))
+    }
+
+    /// Set this state up to send and receive messages
+    fn finalize(self, encrypt_key: KeyId, decrypt_key: KeyId) -> Result<CompletedKeyExchange> {
+        let h = self.h.ok_or(IKError::InvalidState)?;
+
+        Ok(CompletedKeyExchange::new(h, encrypt_key, decrypt_key))
+    }
+}
+
+impl<V: IKVault> State<V> {
+    pub(crate) async fn run_prologue(&mut self) -> Result<()> {
+        if self.run_prologue {
+            self.prologue().await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<V: IKVault> State<V> {
+    /// Encode the first message to be sent
+    pub(crate) async fn encode_message_1<B: AsRef<[u8]>>(&mut self, payload: B) -> Result<Vec<u8>> {
+        let static_secret = self.static_key.clone().ok_or(IKError::InvalidState)?;
+        let static_public = self
+            .static_public_key
+            .clone()
+            .ok_or(IKError::InvalidState)?;
+        let ephemeral_public = self.ephemeral_public.clone().ok_or(IKError::InvalidState)?;
+        let ephemeral_secret = self.ephemeral_secret.clone().ok_or(IKError::InvalidState)?;
+        let remote_static_public_key = self
+            .remote_static_public_key
+            .clone()
+            .ok_or(IKError::MissingRemoteStaticKey)?;
+
+        self.h = Some(self.mix_hash(ephemeral_public.data()).await?);
+        self.dh_state
+            .dh(&ephemeral_secret, &remote_static_public_key)
+            .await?;
+
+        let (mut encrypted_s_and_tag, h) = self.encrypt_and_mix_hash(static_public.data()).await?;
+        self.h = Some(h);
+        self.dh_state
+            .dh(&static_secret, &remote_static_public_key)
+            .await?;
+        self.nonce = 0;
+        let (mut encrypted_payload_and_tag, h) = self.encrypt_and_mix_hash(payload).await?;
+        self.h = Some(h);
+        self.nonce += 1;
+
+        let mut output = ephemeral_public.data().to_vec();
+        output.append(&mut encrypted_s_and_tag);
+        output.append(&mut encrypted_payload_and_tag);
+        Ok(output)
+    }
+
+    /// Decode the second message in the sequence, sent from the responder
+    pub(crate) async fn decode_message_2<B: AsRef<[u8]>>(&mut self, message: B) -> Result<Vec<u8>> {
+        let public_key_size = CURVE25519_PUBLIC_LENGTH_USIZE;
+        let message = message.as_ref();
+        if message.len() < public_key_size + AES_GCM_TAGSIZE_USIZE {
+            return Err(IKError::MessageLenMismatch.into());
+        }
+
+        let static_secret = self.static_key.clone().ok_or(IKError::InvalidState)?;
+        let ephemeral_secret = self.ephemeral_secret.clone().ok_or(IKError::InvalidState)?;
+
+        let re = PublicKey::new(message[..public_key_size].to_vec(), SecretType::X25519);
+        self.h = Some(self.mix_hash(re.data()).await?);
+        self.dh_state.dh(&ephemeral_secret, &re).await?;
+        self.dh_state.dh(&static_secret, &re).await?;
+        self.remote_ephemeral_public_key = Some(re);
+        self.nonce = 0;
+
+        let (payload, h) = self
+            .decrypt_and_mix_hash(&message[public_key_size..])
+            .await?;
+        self.h = Some(h);
+        self.nonce += 1;
+        Ok(payload)
+    }
+
+    pub(crate) async fn finalize_initiator(mut self) -> Result<CompletedKeyExchange> {
+        let keys = { self.split().await? };
+
+        self.finalize(keys.1, keys.0)
+    }
+}
+
+impl<V: IKVault> State<V> {
+    /// Decode the first message sent
+    pub(crate) async fn decode_message_1<B: AsRef<[u8]>>(
+        &mut self,
+        message_1: B,
+    ) -> Result<Vec<u8>> {
+        let public_key_size = CURVE25519_PUBLIC_LENGTH_USIZE;
+        let message_1 = message_1.as_ref();
+        if message_1.len() < IK_MESSAGE_1_MIN_LENGTH {
+            return Err(IKError::MessageLenMismatch.into());
+        }
+
+        let static_secret = self.static_key.clone().ok_or(IKError::MissingStaticKey)?;
+
+        let re = PublicKey::new(message_1[..public_key_size].to_vec(), SecretType::X25519);
+        self.h = Some(self.mix_hash(re.data()).await?);
+        self.dh_state.dh(&static_secret, &re).await?;
+        self.remote_ephemeral_public_key = Some(re);
+
+        let index = 2 * public_key_size + AES_GCM_TAGSIZE_USIZE;
+        let (rs, h) = self
+            .decrypt_and_mix_hash(&message_1[public_key_size..index])
+            .await?;
+        self.h = Some(h);
+        let rs = PublicKey::new(rs, SecretType::X25519);
+        self.dh_state.dh(&static_secret, &rs).await?;
+        self.remote_static_public_key = Some(rs);
+        self.nonce = 0;
+
+        let (payload, h) = self.decrypt_and_mix_hash(&message_1[index..]).await?;
+        self.h = Some(h);
+        self.nonce += 1;
+        Ok(payload)
+    }
+
+    /// Encode the second and final message of the handshake
+    pub(crate) async fn encode_message_2<B: AsRef<[u8]>>(&mut self, payload: B) -> Result<Vec<u8>> {
+        let ephemeral_public = self.ephemeral_public.clone().ok_or(IKError::InvalidState)?;
+        let ephemeral_secret = self.ephemeral_secret.clone().ok_or(IKError::InvalidState)?;
+        let remote_ephemeral_public_key = self
+            .remote_ephemeral_public_key
+            .clone()
+            .ok_or(IKError::InvalidState)?;
+        let remote_static_public_key = self
+            .remote_static_public_key
+            .clone()
+            .ok_or(IKError::InvalidState)?;
+
+        self.h = Some(self.mix_hash(ephemeral_public.data()).await?);
+        self.dh_state
+            .dh(&ephemeral_secret, &remote_ephemeral_public_key)
+            .await?;
+        self.dh_state
+            .dh(&ephemeral_secret, &remote_static_public_key)
+            .await?;
+        self.nonce = 0;
+        let (mut encrypted_payload_and_tag, h) = self.encrypt_and_mix_hash(payload).await?;
+        self.h = Some(h);
+        self.nonce += 1;
+
+        let mut output = ephemeral_public.data().to_vec();
+        output.append(&mut encrypted_payload_and_tag);
+        Ok(output)
+    }
+
+    pub(crate) async fn finalize_responder(mut self) -> Result<CompletedKeyExchange> {
+        let keys = { self.split().await? };
+
+        self.finalize(keys.0, keys.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{DhState, State};
+    use crate::{IKVault, Initiator, Responder};
+    use hex::{decode, encode};
+    use ockam_core::vault::{
+        Hasher, PublicKey, Secret, SecretAttributes, SecretKey, SecretPersistence, SecretType,
+        SecretVault, CURVE25519_SECRET_LENGTH_U32,
+    };
+    use ockam_core::Result;
+    use ockam_key_exchange_core::KeyExchanger;
+    use ockam_node::Context;
+    use ockam_vault::Vault;
+
+    const INIT_STATIC: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const INIT_EPH: &str = "202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f";
+    const RESP_STATIC: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20";
+    const RESP_STATIC_PUBLIC: &str =
+        "07a37cbc142093c8b755dc1b10e86cb426374ad16aa853ed0bdfc0b2b86d1c7c";
+    const RESP_EPH: &str = "4142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f60";
+    /// Split keys of the handshakes below, in the order of the hkdf output
+    const SPLIT_KEY_1: &str = "4f818fa9ab5543d5e2507c05279ccdafa03e9409df59957650eb355f9898b0a3";
+    const SPLIT_KEY_2: &str = "38313ffe461ce6211154fb72ce7f14eac57e3eb1b2464fc73c3aa96cb36503bd";
+
+    #[ockam_macros::test]
+    async fn prologue(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let exp_h = [
+            248, 43, 251, 231, 0, 11, 120, 138, 179, 170, 118, 220, 3, 92, 47, 211, 249, 147, 64,
+            216, 77, 115, 156, 123, 213, 95, 53, 67, 233, 107, 46, 69,
+        ];
+
+        let mut state = State::new(&vault, None, None).await.unwrap();
+        let res = state.prologue().await;
+        assert!(res.is_ok());
+
+        // Without a remote static key the state mixes its own one
+        let mut input = exp_h.to_vec();
+        input.extend_from_slice(state.static_public_key.as_ref().unwrap().data());
+        assert_eq!(state.h.unwrap(), vault.sha256(&input).await.unwrap());
+
+        let ck = vault
+            .secret_export(&state.dh_state.ck.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ck.cast_as_key().as_ref(),
+            *b"Noise_IK_25519_AESGCM_SHA256\0\0\0\0"
+        );
+        assert_eq!(state.nonce, 0);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn handshake_1(ctx: &mut Context) -> Result<()> {
+        const MSG_1_PAYLOAD: &str = "";
+        const MSG_1_CIPHERTEXT: &str = "358072d6365880d1aeea329adf9121383851ed21a28e3b75e965d0d2cd16625419d6fab175300a577115c701c41ed681373f0432f81d3bf8676bd05216cd1919ba2eaa418fdd8e09ae59d7cf57869de42789c3b9ca915c2cacf009f9d0e4436e";
+        const MSG_2_PAYLOAD: &str = "";
+        const MSG_2_CIPHERTEXT: &str =
+            "64b101b1d0be5a8704bd078f9895001fc03e8e9f9522f188dd128d9846d4846623c019a124da3f096e964fe624cf65db";
+        const H: &str = "523d4c4b634988b79bb5abc6f04eff601302956583267473ea5f9d81ff99dc7e";
+
+        let mut vault = Vault::create();
+
+        mock_handshake(
+            &mut vault,
+            MSG_1_PAYLOAD,
+            MSG_1_CIPHERTEXT,
+            MSG_2_PAYLOAD,
+            MSG_2_CIPHERTEXT,
+            H,
+        )
+        .await;
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn handshake_2(ctx: &mut Context) -> Result<()> {
+        const MSG_1_PAYLOAD: &str = "746573745f6d73675f30";
+        const MSG_1_CIPHERTEXT: &str = "358072d6365880d1aeea329adf9121383851ed21a28e3b75e965d0d2cd16625419d6fab175300a577115c701c41ed681373f0432f81d3bf8676bd05216cd1919ba2eaa418fdd8e09ae59d7cf57869de4e6d8177aa9777fe9b843100e255aee76034f61b96b52af38660c";
+        const MSG_2_PAYLOAD: &str = "746573745f6d73675f31";
+        const MSG_2_CIPHERTEXT: &str = "64b101b1d0be5a8704bd078f9895001fc03e8e9f9522f188dd128d9846d4846658a7bb8caac509783390e5a04df4a3ca570b2bcdf65f8c1c40cd";
+        const H: &str = "d5ae390a41e4f0f2d4ea9f46ecad2826f44aa7f01fdd6e5d3a39a46c939f644e";
+
+        let mut vault = Vault::create();
+
+        mock_handshake(
+            &mut vault,
+            MSG_1_PAYLOAD,
+            MSG_1_CIPHERTEXT,
+            MSG_2_PAYLOAD,
+            MSG_2_CIPHERTEXT,
+            H,
+        )
+        .await;
+
+        ctx.stop().await
+    }
+
+    async fn mock_handshake<V: IKVault>(
+        vault: &mut V,
+        msg_1_payload: &'static str,
+        msg_1_ciphertext: &'static str,
+        msg_2_payload: &'static str,
+        msg_2_ciphertext: &'static str,
+        h: &'static str,
+    ) {
+        let responder_static_public_key =
+            PublicKey::new(decode(RESP_STATIC_PUBLIC).unwrap(), SecretType::X25519);
+        let initiator = mock_prologue(
+            vault,
+            INIT_STATIC,
+            INIT_EPH,
+            Some(responder_static_public_key),
+        )
+        .await;
+        let responder = mock_prologue(vault, RESP_STATIC, RESP_EPH, None).await;
+        assert_eq!(initiator.h, responder.h);
+
+        let mut initiator = Initiator::new(initiator);
+        let mut responder = Responder::new(responder);
+
+        let res = initiator
+            .generate_request(&decode(msg_1_payload).unwrap())
+            .await;
+        assert!(res.is_ok());
+        let msg1 = res.unwrap();
+        assert_eq!(encode(&msg1), msg_1_ciphertext);
+
+        let res = responder.handle_response(&msg1).await;
+        assert!(res.is_ok());
+        assert_eq!(encode(res.unwrap()), msg_1_payload);
+
+        let res = responder
+            .generate_request(&decode(msg_2_payload).unwrap())
+            .await;
+        assert!(res.is_ok());
+        let msg2 = res.unwrap();
+        assert_eq!(encode(&msg2), msg_2_ciphertext);
+
+        let res = initiator.handle_response(&msg2).await;
+        assert!(res.is_ok());
+        assert_eq!(encode(res.unwrap()), msg_2_payload);
+
+        let res = initiator.finalize().await;
+        assert!(res.is_ok());
+        let alice = res.unwrap();
+        let res = responder.finalize().await;
+        assert!(res.is_ok());
+        let bob = res.unwrap();
+        assert_eq!(encode(alice.h()), h);
+        assert_eq!(alice.h(), bob.h());
+
+        let key = vault.secret_export(alice.decrypt_key()).await.unwrap();
+        assert_eq!(encode(key.cast_as_key().as_ref()), SPLIT_KEY_1);
+        let key = vault.secret_export(alice.encrypt_key()).await.unwrap();
+        assert_eq!(encode(key.cast_as_key().as_ref()), SPLIT_KEY_2);
+
+        let res = vault
+            .aead_aes_gcm_encrypt(alice.encrypt_key(), b"hello bob", &[0u8; 12], alice.h())
+            .await;
+        assert!(res.is_ok());
+        let ciphertext = res.unwrap();
+        let res = vault
+            .aead_aes_gcm_decrypt(bob.decrypt_key(), &ciphertext, &[0u8; 12], bob.h())
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), b"hello bob");
+    }
+
+    async fn mock_prologue<V: IKVault>(
+        vault: &mut V,
+        static_private: &str,
+        ephemeral_private: &str,
+        remote_static_public_key: Option<PublicKey>,
+    ) -> State<V> {
+        let attributes = SecretAttributes::new(
+            SecretType::X25519,
+            SecretPersistence::Ephemeral,
+            CURVE25519_SECRET_LENGTH_U32,
+        );
+        // Static x25519 for this handshake, `s`
+        let static_secret_handle = vault
+            .secret_import(
+                Secret::Key(SecretKey::new(decode(static_private).unwrap())),
+                attributes,
+            )
+            .await
+            .unwrap();
+        let static_public_key = vault
+            .secret_public_key_get(&static_secret_handle)
+            .await
+            .unwrap();
+
+        // Ephemeral x25519 for this handshake, `e`
+        let ephemeral_secret_handle = vault
+            .secret_import(
+                Secret::Key(SecretKey::new(decode(ephemeral_private).unwrap())),
+                attributes,
+            )
+            .await
+            .unwrap();
+        let ephemeral_public_key = vault
+            .secret_public_key_get(&ephemeral_secret_handle)
+            .await
+            .unwrap();
+
+        // Pre-message `<- s`
+        let mut h = vault
+            .sha256(b"Noise_IK_25519_AESGCM_SHA256\0\0\0\0")
+            .await
+            .unwrap()
+            .to_vec();
+        h.extend_from_slice(
+            remote_static_public_key
+                .as_ref()
+                .unwrap_or(&static_public_key)
+                .data(),
+        );
+        let h = vault.sha256(&h).await.unwrap();
+        let ck = *b"Noise_IK_25519_AESGCM_SHA256\0\0\0\0";
+
+        let attributes = SecretAttributes::new(
+            SecretType::Buffer,
+            SecretPersistence::Ephemeral,
+            ck.len() as u32,
+        );
+        let ck = vault
+            .secret_import(Secret::Key(SecretKey::new(ck[..].to_vec())), attributes)
+            .await
+            .unwrap();
+
+        State {
+            run_prologue: false,
+            static_key: Some(static_secret_handle),
+            static_public_key: Some(static_public_key),
+            ephemeral_secret: Some(ephemeral_secret_handle),
+            ephemeral_public: Some(ephemeral_public_key),
+            remote_static_public_key,
+            remote_ephemeral_public_key: None,
+            dh_state: DhState {
+                key: None,
+                ck: Some(ck),
+                vault: vault.async_try_clone().await.unwrap(),
+            },
+            nonce: 0,
+            h: Some(h),
+            vault: vault.async_try_clone().await.unwrap(),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{XXError, XXVault};
+use ockam_core::compat::vec::Vec;
+use ockam_core::vault::{
+    KeyId, SecretType, AES256_SECRET_LENGTH_U32, CHACHA20POLY1305_SECRET_LENGTH_U32,
+    CURVE25519_PUBLIC_LENGTH_USIZE,
+};
+use ockam_core::Result;
+
+/// Prefix of the cipher suite offer in the payload of the first message
+const OFFER_TAG: &[u8] = b"XXCS";
+
+/// Cipher suites supported by the XX handshake
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XXCipherSuite {
+    /// `Noise_XX_25519_AESGCM_SHA256`
+    AesGcm,
+    /// `Noise_XX_25519_ChaChaPoly_SHA256`
+    ChaChaPoly,
+}
+
+impl Default for XXCipherSuite {
+    fn default() -> Self {
+        Self::AesGcm
+    }
+}
+
+impl XXCipherSuite {
+    /// Name of the cipher in Noise protocol names
+    pub(crate) fn name(&self) -> &'static [u8] {
+        match self {
+            Self::AesGcm => b"AESGCM",
+            Self::ChaChaPoly => b"ChaChaPoly",
+        }
+    }
+
+    /// Type and length of the symmetric keys of this suite
+    pub(crate) fn key_type_and_length(&self) -> (SecretType, u32) {
+        match self {
+            Self::AesGcm => (SecretType::Aes, AES256_SECRET_LENGTH_U32),
+            Self::ChaChaPoly => (
+                SecretType::ChaCha20Poly1305,
+                CHACHA20POLY1305_SECRET_LENGTH_U32,
+            ),
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            Self::AesGcm => 1,
+            Self::ChaChaPoly => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Self::AesGcm),
+            2 => Some(Self::ChaChaPoly),
+            _ => None,
+        }
+    }
+
+    pub(crate) async fn encrypt<V: XXVault>(
+        &self,
+        vault: &V,
+        key: &KeyId,
+        plaintext: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            Self::AesGcm => vault.aead_aes_gcm_encrypt(key, plaintext, nonce, aad).await,
+            Self::ChaChaPoly => {
+                vault
+                    .aead_chacha20_poly1305_encrypt(key, plaintext, nonce, aad)
+                    .await
+            }
+        }
+    }
+
+    pub(crate) async fn decrypt<V: XXVault>(
+        &self,
+        vault: &V,
+        key: &KeyId,
+        ciphertext: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            Self::AesGcm => {
+                vault
+                    .aead_aes_gcm_decrypt(key, ciphertext, nonce, aad)
+                    .await
+            }
+            Self::ChaChaPoly => {
+                vault
+                    .aead_chacha20_poly1305_decrypt(key, ciphertext, nonce, aad)
+                    .await
+            }
+        }
+    }
+}
+
+/// Negotiation is only used when something other than the default
+/// suite is configured, so that default handshakes stay plain Noise XX.
+pub(crate) fn is_negotiated(suites: &[XXCipherSuite]) -> bool {
+    suites != [XXCipherSuite::AesGcm]
+}
+
+/// Encode the offer sent by the initiator, in order of preference
+pub(crate) fn encode_offer(suites: &[XXCipherSuite]) -> Vec<u8> {
+    let mut offer = OFFER_TAG.to_vec();
+    offer.push(suites.len() as u8);
+    offer.extend(suites.iter().map(XXCipherSuite::id));
+    offer
+}
+
+/// Decode the offer at the start of a message 1 payload, returning the
+/// offered suite ids and the length of the offer
+pub(crate) fn decode_offer(payload: &[u8]) -> Result<Option<(&[u8], usize)>> {
+    if !payload.starts_with(OFFER_TAG) {
+        return Ok(None);
+    }
+    let count = *payload
+        .get(OFFER_TAG.len())
+        .ok_or(XXError::MessageLenMismatch)? as usize;
+    let start = OFFER_TAG.len() + 1;
+    let ids = payload
+        .get(start..start + count)
+        .ok_or(XXError::MessageLenMismatch)?;
+
+    Ok(Some((ids, start + count)))
+}
+
+/// Whether a first message of the handshake carries an offer, which makes
+/// it longer than usual, e.g. for hybrid handshakes
+pub fn message_1_has_offer(message_1: &[u8]) -> bool {
+    message_1
+        .get(CURVE25519_PUBLIC_LENGTH_USIZE..)
+        .map_or(false, |payload| payload.starts_with(OFFER_TAG))
+}
+
+/// Pick the first offered suite that is supported
+pub(crate) fn select(offered: &[u8], supported: &[XXCipherSuite]) -> Result<XXCipherSuite> {
+    offered
+        .iter()
+        .filter_map(|id| XXCipherSuite::from_id(*id))
+        .find(|suite| supported.contains(suite))
+        .ok_or_else(|| XXError::UnsupportedCipherSuite.into())
+}
+
+/// Id of the suite chosen by the responder, sent in front of message 2
+pub(crate) fn encode_choice(suite: XXCipherSuite) -> u8 {
+    suite.id()
+}
+
+/// Check the choice of the responder against our offer
+pub(crate) fn decode_choice(id: u8, offered: &[XXCipherSuite]) -> Result<XXCipherSuite> {
+    XXCipherSuite::from_id(id)
+        .filter(|suite| offered.contains(suite))
+        .ok_or_else(|| XXError::UnsupportedCipherSuite.into())
+}
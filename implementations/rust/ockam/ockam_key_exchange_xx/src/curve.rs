@@ -0,0 +1,59 @@
+use ockam_core::vault::{
+    SecretAttributes, SecretPersistence, SecretType, CURVE25519_PUBLIC_LENGTH_USIZE,
+    CURVE25519_SECRET_LENGTH_U32, NISTP256_PUBLIC_LENGTH_USIZE, NISTP256_SECRET_LENGTH_U32,
+};
+
+/// Curves used for the Diffie-Hellman key agreements of the XX handshake.
+/// Unlike cipher suites, the curve is not negotiated: both parties have to
+/// be configured with the same one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XXCurve {
+    /// Curve25519, the default
+    X25519,
+    /// NIST P-256, for deployments restricted to FIPS approved curves
+    NistP256,
+}
+
+impl Default for XXCurve {
+    fn default() -> Self {
+        Self::X25519
+    }
+}
+
+impl XXCurve {
+    /// Name of the curve in Noise protocol names
+    pub(crate) fn name(&self) -> &'static [u8] {
+        match self {
+            Self::X25519 => b"25519",
+            Self::NistP256 => b"P256",
+        }
+    }
+
+    /// Attributes of the static and ephemeral keys of a handshake
+    pub(crate) fn secret_attributes(&self) -> SecretAttributes {
+        match self {
+            Self::X25519 => SecretAttributes::new(
+                SecretType::X25519,
+                SecretPersistence::Ephemeral,
+                CURVE25519_SECRET_LENGTH_U32,
+            ),
+            Self::NistP256 => SecretAttributes::new(
+                SecretType::NistP256,
+                SecretPersistence::Ephemeral,
+                NISTP256_SECRET_LENGTH_U32,
+            ),
+        }
+    }
+
+    pub(crate) fn secret_type(&self) -> SecretType {
+        self.secret_attributes().stype()
+    }
+
+    /// Length of public keys on the wire, as returned by the vault
+    pub(crate) fn public_key_length(&self) -> usize {
+        match self {
+            Self::X25519 => CURVE25519_PUBLIC_LENGTH_USIZE,
+            Self::NistP256 => NISTP256_PUBLIC_LENGTH_USIZE,
+        }
+    }
+}
@@ -14,6 +14,8 @@ pub enum XXError {
     InternalVaultError,
     /// A message had an unexpected length.
     MessageLenMismatch,
+    /// None of the offered cipher suites is supported.
+    UnsupportedCipherSuite,
 }
 
 impl StdError for XXError {}
@@ -24,6 +26,7 @@ impl fmt::Display for XXError {
             Self::InvalidState => write!(f, "invalid state"),
             Self::InternalVaultError => write!(f, "internal vault error"),
             Self::MessageLenMismatch => write!(f, "message length mismatch"),
+            Self::UnsupportedCipherSuite => write!(f, "unsupported cipher suite"),
         }
     }
 }
@@ -35,6 +38,7 @@ impl From<XXError> for Error {
             XXError::InvalidState => Kind::Invalid,
             XXError::InternalVaultError => Kind::Internal,
             XXError::MessageLenMismatch => Kind::Misuse,
+            XXError::UnsupportedCipherSuite => Kind::Unsupported,
         };
 
         Error::new(Origin::KeyExchange, kind, err)
@@ -0,0 +1,37 @@
+use crate::XXError;
+use ockam_core::compat::vec::Vec;
+use ockam_core::vault::{
+    PublicKey, SecretType, KYBER768_CIPHERTEXT_LENGTH_USIZE, KYBER768_PUBLIC_LENGTH_USIZE,
+};
+use ockam_core::Result;
+
+/// Prefix of the Kyber public key in the payload of the first message,
+/// after the cipher suite offer
+const KEM_TAG: &[u8] = b"XXKM";
+
+/// Length of the KEM ciphertext sent in the second message
+pub(crate) const KEM_CIPHERTEXT_LENGTH: usize = KYBER768_CIPHERTEXT_LENGTH_USIZE;
+
+/// Encode the Kyber public key sent by the initiator
+pub(crate) fn encode_public_key(public_key: &PublicKey) -> Vec<u8> {
+    let mut output = KEM_TAG.to_vec();
+    output.extend_from_slice(public_key.data());
+    output
+}
+
+/// Decode the Kyber public key at the start of the given part of a message
+/// 1 payload, returning it with its encoded length
+pub(crate) fn decode_public_key(payload: &[u8]) -> Result<Option<(PublicKey, usize)>> {
+    if !payload.starts_with(KEM_TAG) {
+        return Ok(None);
+    }
+    let len = KEM_TAG.len() + KYBER768_PUBLIC_LENGTH_USIZE;
+    let data = payload
+        .get(KEM_TAG.len()..len)
+        .ok_or(XXError::MessageLenMismatch)?;
+
+    Ok(Some((
+        PublicKey::new(data.to_vec(), SecretType::Kyber768),
+        len,
+    )))
+}
@@ -55,6 +55,11 @@ impl<D> XXVault for D where
 {
 }
 
+mod cipher_suite;
+pub use cipher_suite::{message_1_has_offer, XXCipherSuite};
+mod curve;
+pub use curve::XXCurve;
+mod hybrid;
 mod initiator;
 mod state;
 pub use initiator::*;
@@ -67,8 +72,9 @@ use ockam_core::vault::{AsymmetricVault, Hasher, SecretVault, SymmetricVault};
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ockam_core::vault::SecretType;
     use ockam_core::Result;
-    use ockam_key_exchange_core::{KeyExchanger, NewKeyExchanger};
+    use ockam_key_exchange_core::{CompletedKeyExchange, KeyExchanger, NewKeyExchanger};
     use ockam_node::Context;
     use ockam_vault::Vault;
 
@@ -115,4 +121,130 @@ mod tests {
 
         ctx.stop().await
     }
+
+    async fn handshake<V: XXVault>(
+        initiator: &XXNewKeyExchanger<V>,
+        responder: &XXNewKeyExchanger<V>,
+    ) -> Result<(CompletedKeyExchange, CompletedKeyExchange)> {
+        let mut initiator = initiator.initiator().await?;
+        let mut responder = responder.responder().await?;
+
+        let m1 = initiator.generate_request(b"m1").await?;
+        assert_eq!(responder.handle_response(&m1).await?, b"m1");
+        let m2 = responder.generate_request(b"m2").await?;
+        assert_eq!(initiator.handle_response(&m2).await?, b"m2");
+        let m3 = initiator.generate_request(b"m3").await?;
+        assert_eq!(responder.handle_response(&m3).await?, b"m3");
+
+        Ok((initiator.finalize().await?, responder.finalize().await?))
+    }
+
+    #[ockam_macros::test]
+    async fn cipher_suite_negotiation(ctx: &mut Context) -> Result<()> {
+        use XXCipherSuite::*;
+        let vault = Vault::create();
+        let exchanger = |suites: Vec<XXCipherSuite>| {
+            XXNewKeyExchanger::new(vault.clone()).with_cipher_suites(suites)
+        };
+
+        let cases = [
+            (
+                vec![ChaChaPoly],
+                vec![ChaChaPoly],
+                SecretType::ChaCha20Poly1305,
+            ),
+            (vec![ChaChaPoly, AesGcm], vec![AesGcm], SecretType::Aes),
+            (
+                vec![AesGcm, ChaChaPoly],
+                vec![ChaChaPoly],
+                SecretType::ChaCha20Poly1305,
+            ),
+            (
+                vec![ChaChaPoly, AesGcm],
+                vec![AesGcm, ChaChaPoly],
+                SecretType::ChaCha20Poly1305,
+            ),
+        ];
+        for (offered, supported, stype) in cases {
+            let (initiator, responder) =
+                handshake(&exchanger(offered), &exchanger(supported)).await?;
+            assert_eq!(initiator.h(), responder.h());
+
+            let key = initiator.encrypt_key();
+            assert_eq!(vault.secret_attributes_get(key).await?.stype(), stype);
+            let s1 = vault.secret_export(key).await?;
+            let s2 = vault.secret_export(responder.decrypt_key()).await?;
+            assert_eq!(s1, s2);
+        }
+
+        let res = handshake(&exchanger(vec![ChaChaPoly]), &exchanger(vec![AesGcm])).await;
+        assert!(res.is_err());
+        let res = handshake(&exchanger(vec![AesGcm]), &exchanger(vec![ChaChaPoly])).await;
+        assert!(res.is_err());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn hybrid_kem(ctx: &mut Context) -> Result<()> {
+        use XXCipherSuite::*;
+        let vault = Vault::create();
+
+        for suites in [vec![AesGcm], vec![ChaChaPoly]] {
+            let initiator = XXNewKeyExchanger::new(vault.clone())
+                .with_cipher_suites(suites.clone())
+                .with_hybrid_kem();
+            let responder = XXNewKeyExchanger::new(vault.clone()).with_cipher_suites(suites);
+            let (initiator, responder) = handshake(&initiator, &responder).await?;
+            assert_eq!(initiator.h(), responder.h());
+
+            let s1 = vault.secret_export(initiator.encrypt_key()).await?;
+            let s2 = vault.secret_export(responder.decrypt_key()).await?;
+            assert_eq!(s1, s2);
+            let s1 = vault.secret_export(initiator.decrypt_key()).await?;
+            let s2 = vault.secret_export(responder.encrypt_key()).await?;
+            assert_eq!(s1, s2);
+        }
+
+        // The KEM ciphertext is authenticated like the rest of message 2
+        let initiator = XXNewKeyExchanger::new(vault.clone()).with_hybrid_kem();
+        let mut initiator = initiator.initiator().await?;
+        let mut responder = XXNewKeyExchanger::new(vault.clone()).responder().await?;
+        let m1 = initiator.generate_request(&[]).await?;
+        responder.handle_response(&m1).await?;
+        let mut m2 = responder.generate_request(&[]).await?;
+        m2[1 + 32 + 100] ^= 1;
+        assert!(initiator.handle_response(&m2).await.is_err());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn nist_p256(ctx: &mut Context) -> Result<()> {
+        use XXCipherSuite::*;
+        let vault = Vault::create();
+
+        for suites in [vec![AesGcm], vec![ChaChaPoly]] {
+            let exchanger = XXNewKeyExchanger::new(vault.clone())
+                .with_cipher_suites(suites)
+                .with_curve(XXCurve::NistP256);
+            let (initiator, responder) = handshake(&exchanger, &exchanger).await?;
+            assert_eq!(initiator.h(), responder.h());
+
+            let s1 = vault.secret_export(initiator.encrypt_key()).await?;
+            let s2 = vault.secret_export(responder.decrypt_key()).await?;
+            assert_eq!(s1, s2);
+        }
+
+        // Both sides have to agree on the curve
+        let initiator = XXNewKeyExchanger::new(vault.clone()).with_curve(XXCurve::NistP256);
+        let mut initiator = initiator.initiator().await?;
+        let mut responder = XXNewKeyExchanger::new(vault.clone()).responder().await?;
+        let m1 = initiator.generate_request(&[]).await?;
+        responder.handle_response(&m1).await?;
+        let m2 = responder.generate_request(&[]).await?;
+        assert!(initiator.handle_response(&m2).await.is_err());
+
+        ctx.stop().await
+    }
 }
@@ -1,5 +1,6 @@
 use crate::state::State;
-use crate::{Initiator, Responder, XXVault};
+use crate::{Initiator, Responder, XXCipherSuite, XXCurve, XXVault};
+use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, compat::boxed::Box, AsyncTryClone, Result};
 
 use ockam_key_exchange_core::NewKeyExchanger;
@@ -9,12 +10,48 @@ use ockam_key_exchange_core::NewKeyExchanger;
 #[async_try_clone(crate = "ockam_core")]
 pub struct XXNewKeyExchanger<V: XXVault> {
     vault: V,
+    cipher_suites: Vec<XXCipherSuite>,
+    curve: XXCurve,
+    hybrid: bool,
 }
 
 impl<V: XXVault> XXNewKeyExchanger<V> {
     /// Create a new XXNewKeyExchanger
     pub fn new(vault: V) -> Self {
-        Self { vault }
+        Self {
+            vault,
+            cipher_suites: vec![XXCipherSuite::AesGcm],
+            curve: XXCurve::X25519,
+            hybrid: false,
+        }
+    }
+
+    /// Cipher suites to use, in order of preference. An initiator offers
+    /// them to the responder, which picks the first one it supports.
+    /// Only `AesGcm` (the default) is understood by older peers.
+    pub fn with_cipher_suites(mut self, cipher_suites: Vec<XXCipherSuite>) -> Self {
+        if !cipher_suites.is_empty() {
+            self.cipher_suites = cipher_suites;
+        }
+        self
+    }
+
+    /// Curve of the static and ephemeral keys, which has to be the same on
+    /// both sides of the handshake
+    pub fn with_curve(mut self, curve: XXCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Mix a Kyber768 encapsulation into the handshake besides the X25519
+    /// key agreements, so that its keys stay secret even if X25519 gets broken.
+    /// Only initiators need this, responders accept both kinds of handshakes.
+    ///
+    /// Both vaults have to support key encapsulation, which is optional, see
+    /// [`AsymmetricVault::kem_encapsulate`](ockam_core::vault::AsymmetricVault::kem_encapsulate).
+    pub fn with_hybrid_kem(mut self) -> Self {
+        self.hybrid = true;
+        self
     }
 }
 
@@ -25,13 +62,19 @@ impl<V: XXVault> NewKeyExchanger for XXNewKeyExchanger<V> {
 
     /// Create a new initiator using the provided backing vault
     async fn initiator(&self) -> Result<Initiator<V>> {
-        let ss = State::new(&self.vault).await?;
+        let ss = State::new(
+            &self.vault,
+            self.cipher_suites.clone(),
+            self.curve,
+            self.hybrid,
+        )
+        .await?;
         Ok(Initiator::new(ss))
     }
 
     /// Create a new responder using the provided backing vault
     async fn responder(&self) -> Result<Responder<V>> {
-        let ss = State::new(&self.vault).await?;
+        let ss = State::new(&self.vault, self.cipher_suites.clone(), self.curve, false).await?;
         Ok(Responder::new(ss))
     }
 }
@@ -53,6 +53,7 @@ impl<V: XXVault> KeyExchanger for Responder<V> {
     async fn handle_response(&mut self, response: &[u8]) -> Result<Vec<u8>> {
         match self.state {
             ResponderState::DecodeMessage1 => {
+                self.state_data.read_offer(response)?;
                 self.state_data.run_prologue().await?;
                 let msg = self.state_data.decode_message_1(response).await?;
                 self.state = ResponderState::EncodeMessage2;
@@ -1,7 +1,8 @@
-use crate::{XXError, XXVault, AES_GCM_TAGSIZE_USIZE, SHA256_SIZE_USIZE};
+use crate::cipher_suite::{self, XXCipherSuite};
+use crate::hybrid;
+use crate::{XXCurve, XXError, XXVault, AES_GCM_TAGSIZE_USIZE, SHA256_SIZE_USIZE};
 use ockam_core::vault::{
-    KeyId, PublicKey, SecretAttributes, SecretPersistence, SecretType, AES256_SECRET_LENGTH_U32,
-    CURVE25519_PUBLIC_LENGTH_USIZE, CURVE25519_SECRET_LENGTH_U32,
+    KeyId, PublicKey, SecretAttributes, SecretPersistence, SecretType, KYBER768_SECRET_LENGTH_U32,
 };
 use ockam_core::{compat::vec::Vec, Result};
 use ockam_key_exchange_core::CompletedKeyExchange;
@@ -19,6 +20,20 @@ pub(crate) struct State<V: XXVault> {
     _remote_static_public_key: Option<PublicKey>,
     remote_ephemeral_public_key: Option<PublicKey>,
     dh_state: DhState<V>,
+    curve: XXCurve,
+    /// Suites offered by the initiator, or supported by the responder
+    cipher_suites: Vec<XXCipherSuite>,
+    cipher_suite: XXCipherSuite,
+    /// Whether the cipher suite is negotiated during this handshake
+    negotiated: bool,
+    /// Payload of message 1, kept by an initiator that offered cipher suites
+    message_1_payload: Vec<u8>,
+    /// Length of the offer at the start of the payload of message 1
+    message_1_offer_len: usize,
+    /// Whether a Kyber encapsulation is mixed into the handshake
+    hybrid: bool,
+    kem_secret: Option<KeyId>,
+    remote_kem_public_key: Option<PublicKey>,
     nonce: u16,
     h: Option<[u8; SHA256_SIZE_USIZE]>,
     vault: V,
@@ -38,7 +53,12 @@ impl<V: XXVault> core::fmt::Debug for State<V> {
 }
 
 impl<V: XXVault> State<V> {
-    pub(crate) async fn new(vault: &V) -> Result<Self> {
+    pub(crate) async fn new(
+        vault: &V,
+        cipher_suites: Vec<XXCipherSuite>,
+        curve: XXCurve,
+        hybrid: bool,
+    ) -> Result<Self> {
         Ok(Self {
             run_prologue: true,
             identity_key: None,
@@ -48,6 +68,15 @@ impl<V: XXVault> State<V> {
             _remote_static_public_key: None,
             remote_ephemeral_public_key: None,
             dh_state: DhState::empty(vault.async_try_clone().await?),
+            curve,
+            cipher_suite: cipher_suites.first().copied().unwrap_or_default(),
+            cipher_suites,
+            negotiated: false,
+            message_1_payload: Vec::new(),
+            message_1_offer_len: 0,
+            hybrid,
+            kem_secret: None,
+            remote_kem_public_key: None,
             nonce: 0,
             h: None,
             vault: vault.async_try_clone().await?,
@@ -57,20 +86,37 @@ impl<V: XXVault> State<V> {
 
 impl<V: XXVault> State<V> {
     fn get_symmetric_key_type_and_length(&self) -> (SecretType, u32) {
-        (SecretType::Aes, AES256_SECRET_LENGTH_U32)
+        self.cipher_suite.key_type_and_length()
     }
 
-    fn get_protocol_name(&self) -> &'static [u8] {
-        b"Noise_XX_25519_AESGCM_SHA256\0\0\0\0"
+    /// Protocol name, e.g. `Noise_XX_25519_AESGCM_SHA256`, padded to
+    /// 32 bytes, or hashed when it is longer
+    async fn get_protocol_name(&self) -> Result<[u8; SHA256_SIZE_USIZE]> {
+        let pattern: &[u8] = if self.hybrid {
+            b"Noise_XXhfs_"
+        } else {
+            b"Noise_XX_"
+        };
+        let mut name = pattern.to_vec();
+        name.extend_from_slice(self.curve.name());
+        if self.hybrid {
+            name.extend_from_slice(b"+Kyber768");
+        }
+        name.push(b'_');
+        name.extend_from_slice(self.cipher_suite.name());
+        name.extend_from_slice(b"_SHA256");
+
+        if name.len() > SHA256_SIZE_USIZE {
+            return self.vault.sha256(&name).await;
+        }
+        let mut h = [0u8; SHA256_SIZE_USIZE];
+        h[..name.len()].copy_from_slice(&name);
+        Ok(h)
     }
 
     /// Create a new `HandshakeState` starting with the prologue
     async fn prologue(&mut self) -> Result<()> {
-        let attributes = SecretAttributes::new(
-            SecretType::X25519,
-            SecretPersistence::Ephemeral,
-            CURVE25519_SECRET_LENGTH_U32,
-        );
+        let attributes = self.curve.secret_attributes();
         // 1. Generate a static key pair for this handshake and set it to `s`
         if let Some(ik) = &self.identity_key {
             self.identity_public_key = Some(self.vault.secret_public_key_get(ik).await?);
@@ -101,14 +147,89 @@ impl<V: XXVault> State<V> {
         // 5. h = SHA256(h || prologue),
         // prologue is empty
         // mix_hash(xx, NULL, 0);
-        let mut h = [0u8; SHA256_SIZE_USIZE];
-        h[..self.get_protocol_name().len()].copy_from_slice(self.get_protocol_name());
-        self.dh_state = DhState::new(&h, self.vault.async_try_clone().await?).await?;
+        let h = self.get_protocol_name().await?;
+        self.dh_state =
+            DhState::new(&h, self.cipher_suite, self.vault.async_try_clone().await?).await?;
         self.h = Some(self.vault.sha256(&h).await?);
 
         Ok(())
     }
 
+    /// Switch an initiator to the cipher suite chosen by the responder.
+    /// No key was derived yet, so this only means starting the hash over
+    /// with the protocol name of that suite and mixing message 1 again.
+    async fn switch_cipher_suite(&mut self, cipher_suite: XXCipherSuite) -> Result<()> {
+        let ephemeral_public_key = self
+            .ephemeral_public
+            .as_ref()
+            .ok_or(XXError::InvalidState)?
+            .clone();
+
+        self.cipher_suite = cipher_suite;
+        if let Some(ck) = self.dh_state.ck.take() {
+            self.vault.secret_destroy(ck).await?;
+        }
+        let h = self.get_protocol_name().await?;
+        self.dh_state = DhState::new(&h, cipher_suite, self.vault.async_try_clone().await?).await?;
+        self.h = Some(self.vault.sha256(&h).await?);
+        self.h = Some(self.mix_hash(ephemeral_public_key.data()).await?);
+        let payload = core::mem::take(&mut self.message_1_payload);
+        self.h = Some(self.mix_hash(&payload).await?);
+
+        Ok(())
+    }
+
+    /// Generate the Kyber key pair of an initiator of a hybrid handshake
+    async fn generate_kem_key(&mut self) -> Result<PublicKey> {
+        let attributes = SecretAttributes::new(
+            SecretType::Kyber768,
+            SecretPersistence::Ephemeral,
+            KYBER768_SECRET_LENGTH_U32,
+        );
+        let kem_secret = self.vault.secret_generate(attributes).await?;
+        let kem_public_key = self.vault.secret_public_key_get(&kem_secret).await?;
+        self.kem_secret = Some(kem_secret);
+
+        Ok(kem_public_key)
+    }
+
+    /// Encapsulate a shared secret to the Kyber key of the initiator and
+    /// mix it into the chaining key. Returns the encrypted KEM ciphertext.
+    async fn encapsulate_and_mix_key(&mut self) -> Result<Vec<u8>> {
+        let remote_kem_public_key = self
+            .remote_kem_public_key
+            .take()
+            .ok_or(XXError::InvalidState)?;
+
+        let (ciphertext, shared_secret) =
+            self.vault.kem_encapsulate(&remote_kem_public_key).await?;
+        let (encrypted_ciphertext_and_tag, h) = self.encrypt_and_mix_hash(&ciphertext).await?;
+        self.h = Some(h);
+        self.dh_state.mix_key(&shared_secret).await?;
+        self.vault.secret_destroy(shared_secret).await?;
+        self.nonce = 0;
+
+        Ok(encrypted_ciphertext_and_tag)
+    }
+
+    /// Decapsulate the shared secret sent by the responder and mix it into
+    /// the chaining key
+    async fn decapsulate_and_mix_key(&mut self, encrypted_ciphertext_and_tag: &[u8]) -> Result<()> {
+        let kem_secret = self.kem_secret.take().ok_or(XXError::InvalidState)?;
+
+        let (ciphertext, h) = self
+            .decrypt_and_mix_hash(encrypted_ciphertext_and_tag)
+            .await?;
+        self.h = Some(h);
+        let shared_secret = self.vault.kem_decapsulate(&kem_secret, &ciphertext).await?;
+        self.vault.secret_destroy(kem_secret).await?;
+        self.dh_state.mix_key(&shared_secret).await?;
+        self.vault.secret_destroy(shared_secret).await?;
+        self.nonce = 0;
+
+        Ok(())
+    }
+
     /// mix hash step in Noise protocol
     async fn mix_hash<B: AsRef<[u8]>>(&mut self, data: B) -> Result<[u8; 32]> {
         let h = &self.h.ok_or(XXError::InvalidState)?;
@@ -131,8 +252,8 @@ impl<V: XXVault> State<V> {
 
         let ciphertext_and_tag = {
             let key = self.dh_state.key().ok_or(XXError::InvalidState)?;
-            self.vault
-                .aead_aes_gcm_encrypt(key, plaintext.as_ref(), nonce.as_ref(), h)
+            self.cipher_suite
+                .encrypt(&self.vault, key, plaintext.as_ref(), nonce.as_ref(), h)
                 .await?
         };
         let h = self.mix_hash(&ciphertext_and_tag).await?;
@@ -151,8 +272,8 @@ impl<V: XXVault> State<V> {
         let ciphertext = ciphertext.as_ref();
         let plaintext = {
             let key = self.dh_state.key().ok_or(XXError::InvalidState)?;
-            self.vault
-                .aead_aes_gcm_decrypt(key, ciphertext, nonce.as_ref(), h)
+            self.cipher_suite
+                .decrypt(&self.vault, key, ciphertext, nonce.as_ref(), h)
                 .await?
         };
         let h = self.mix_hash(ciphertext).await?;
@@ -211,40 +332,65 @@ impl<V: XXVault> State<V> {
             .ok_or(XXError::InvalidState)?
             .clone();
 
-        let payload = payload.as_ref();
+        let mut payload = payload.as_ref().to_vec();
+        if self.hybrid || cipher_suite::is_negotiated(&self.cipher_suites) {
+            let mut offer = cipher_suite::encode_offer(&self.cipher_suites);
+            if self.hybrid {
+                let kem_public_key = self.generate_kem_key().await?;
+                offer.append(&mut hybrid::encode_public_key(&kem_public_key));
+            }
+            offer.append(&mut payload);
+            payload = offer;
+            self.negotiated = true;
+            self.message_1_payload = payload.clone();
+        }
         self.h = Some(self.mix_hash(ephemeral_public_key.data()).await?);
-        self.h = Some(self.mix_hash(payload).await?);
+        self.h = Some(self.mix_hash(&payload).await?);
 
         let mut output = ephemeral_public_key.data().to_vec();
-        output.extend_from_slice(payload);
+        output.append(&mut payload);
         Ok(output)
     }
 
     /// Decode the second message in the sequence, sent from the responder
     pub(crate) async fn decode_message_2<B: AsRef<[u8]>>(&mut self, message: B) -> Result<Vec<u8>> {
-        let public_key_size = CURVE25519_PUBLIC_LENGTH_USIZE;
-        let message = message.as_ref();
-        if message.len() < 2 * public_key_size + AES_GCM_TAGSIZE_USIZE {
+        let public_key_size = self.curve.public_key_length();
+        let mut message = message.as_ref();
+        if self.negotiated {
+            let (id, rest) = message.split_first().ok_or(XXError::MessageLenMismatch)?;
+            let chosen = cipher_suite::decode_choice(*id, &self.cipher_suites)?;
+            if chosen != self.cipher_suite {
+                self.switch_cipher_suite(chosen).await?;
+            }
+            message = rest;
+        }
+        let kem_len = if self.hybrid {
+            hybrid::KEM_CIPHERTEXT_LENGTH + AES_GCM_TAGSIZE_USIZE
+        } else {
+            0
+        };
+        if message.len() < 2 * public_key_size + kem_len + AES_GCM_TAGSIZE_USIZE {
             return Err(XXError::MessageLenMismatch.into());
         }
 
         let ephemeral_secret_handle = self.ephemeral_secret.clone().ok_or(XXError::InvalidState)?;
 
-        let mut index_l = 0;
-        let mut index_r = public_key_size;
-        let re = &message[..index_r];
-        let re = PublicKey::new(re.to_vec(), SecretType::X25519);
-        index_l += public_key_size;
-        index_r += public_key_size + AES_GCM_TAGSIZE_USIZE;
-        let encrypted_rs_and_tag = &message[index_l..index_r];
-        let encrypted_payload_and_tag = &message[index_r..];
+        let (re, message) = message.split_at(public_key_size);
+        let re = PublicKey::new(re.to_vec(), self.curve.secret_type());
+        let (encrypted_kem_ciphertext_and_tag, message) = message.split_at(kem_len);
+        let (encrypted_rs_and_tag, encrypted_payload_and_tag) =
+            message.split_at(public_key_size + AES_GCM_TAGSIZE_USIZE);
 
         self.h = Some(self.mix_hash(re.data()).await?);
         self.dh_state.dh(&ephemeral_secret_handle, &re).await?;
         self.remote_ephemeral_public_key = Some(re);
+        if self.hybrid {
+            self.decapsulate_and_mix_key(encrypted_kem_ciphertext_and_tag)
+                .await?;
+        }
         let (rs, h) = self.decrypt_and_mix_hash(encrypted_rs_and_tag).await?;
         self.h = Some(h);
-        let rs = PublicKey::new(rs, SecretType::X25519);
+        let rs = PublicKey::new(rs, self.curve.secret_type());
         self.dh_state.dh(&ephemeral_secret_handle, &rs).await?;
         self._remote_static_public_key = Some(rs);
         self.nonce = 0;
@@ -290,23 +436,52 @@ impl<V: XXVault> State<V> {
 }
 
 impl<V: XXVault> State<V> {
+    /// Choose the cipher suite from the offer in the first message, if
+    /// any, and whether the handshake is hybrid. This must happen before
+    /// the prologue, which depends on both.
+    pub(crate) fn read_offer(&mut self, message_1: &[u8]) -> Result<()> {
+        let payload = message_1
+            .get(self.curve.public_key_length()..)
+            .unwrap_or_default();
+        self.cipher_suite = match cipher_suite::decode_offer(payload)? {
+            Some((offered, offer_len)) => {
+                self.negotiated = true;
+                self.message_1_offer_len = offer_len;
+                if let Some((kem_public_key, kem_len)) =
+                    hybrid::decode_public_key(&payload[offer_len..])?
+                {
+                    self.hybrid = true;
+                    self.remote_kem_public_key = Some(kem_public_key);
+                    self.message_1_offer_len += kem_len;
+                }
+                cipher_suite::select(offered, &self.cipher_suites)?
+            }
+            None if self.cipher_suites.contains(&XXCipherSuite::AesGcm) => XXCipherSuite::AesGcm,
+            None => return Err(XXError::UnsupportedCipherSuite.into()),
+        };
+
+        Ok(())
+    }
+
     /// Decode the first message sent
     pub(crate) async fn decode_message_1<B: AsRef<[u8]>>(
         &mut self,
         message_1: B,
     ) -> Result<Vec<u8>> {
-        let public_key_size = CURVE25519_PUBLIC_LENGTH_USIZE;
+        let public_key_size = self.curve.public_key_length();
         let message_1 = message_1.as_ref();
         if message_1.len() < public_key_size {
             return Err(XXError::MessageLenMismatch.into());
         }
 
         let re = &message_1[..public_key_size];
-        let re = PublicKey::new(re.to_vec(), SecretType::X25519);
+        let re = PublicKey::new(re.to_vec(), self.curve.secret_type());
         self.h = Some(self.mix_hash(re.data()).await?);
-        self.h = Some(self.mix_hash(&message_1[public_key_size..]).await?);
+        let payload = &message_1[public_key_size..];
+        self.h = Some(self.mix_hash(payload).await?);
         self.remote_ephemeral_public_key = Some(re);
-        Ok(message_1[public_key_size..].to_vec())
+        let offer_len = self.message_1_offer_len.min(payload.len());
+        Ok(payload[offer_len..].to_vec())
     }
 
     /// Encode the second message to be sent
@@ -327,6 +502,11 @@ impl<V: XXVault> State<V> {
         self.dh_state
             .dh(&ephemeral_secret, &remote_ephemeral_public_key)
             .await?;
+        let mut encrypted_kem_ciphertext_and_tag = if self.hybrid {
+            self.encapsulate_and_mix_key().await?
+        } else {
+            Vec::new()
+        };
 
         let (mut encrypted_s_and_tag, h) = self.encrypt_and_mix_hash(static_public.data()).await?;
         self.h = Some(h);
@@ -338,7 +518,12 @@ impl<V: XXVault> State<V> {
         self.h = Some(h);
         self.nonce += 1;
 
-        let mut output = ephemeral_public.data().to_vec();
+        let mut output = Vec::new();
+        if self.negotiated {
+            output.push(cipher_suite::encode_choice(self.cipher_suite));
+        }
+        output.extend_from_slice(ephemeral_public.data());
+        output.append(&mut encrypted_kem_ciphertext_and_tag);
         output.append(&mut encrypted_s_and_tag);
         output.append(&mut encrypted_payload_and_tag);
         Ok(output)
@@ -349,7 +534,7 @@ impl<V: XXVault> State<V> {
         &mut self,
         message_3: B,
     ) -> Result<Vec<u8>> {
-        let public_key_size = CURVE25519_PUBLIC_LENGTH_USIZE;
+        let public_key_size = self.curve.public_key_length();
         let message_3 = message_3.as_ref();
         if message_3.len() < public_key_size + AES_GCM_TAGSIZE_USIZE {
             return Err(XXError::MessageLenMismatch.into());
@@ -361,7 +546,7 @@ impl<V: XXVault> State<V> {
             .decrypt_and_mix_hash(&message_3[..public_key_size + AES_GCM_TAGSIZE_USIZE])
             .await?;
         self.h = Some(h);
-        let rs = PublicKey::new(rs, SecretType::X25519);
+        let rs = PublicKey::new(rs, self.curve.secret_type());
         self.dh_state.dh(ephemeral_secret, &rs).await?;
         self.nonce = 0;
         let (payload, h) = self
@@ -383,7 +568,7 @@ impl<V: XXVault> State<V> {
 #[cfg(test)]
 mod tests {
     use crate::state::{DhState, State};
-    use crate::{Initiator, Responder, XXVault};
+    use crate::{Initiator, Responder, XXCipherSuite, XXCurve, XXVault};
     use hex::{decode, encode};
     use ockam_core::vault::{
         Secret, SecretAttributes, SecretKey, SecretPersistence, SecretType, SecretVault,
@@ -403,7 +588,9 @@ mod tests {
             100, 252, 104, 43, 230, 163, 171, 75, 104, 44, 141, 182, 75,
         ];
 
-        let mut state = State::new(&vault).await.unwrap();
+        let mut state = State::new(&vault, vec![XXCipherSuite::AesGcm], XXCurve::X25519, false)
+            .await
+            .unwrap();
         let res = state.prologue().await;
         assert!(res.is_ok());
         assert_eq!(state.h.unwrap(), exp_h);
@@ -697,8 +884,18 @@ mod tests {
             dh_state: DhState {
                 key: None,
                 ck: Some(ck),
+                cipher_suite: XXCipherSuite::AesGcm,
                 vault: vault.async_try_clone().await.unwrap(),
             },
+            cipher_suites: vec![XXCipherSuite::AesGcm],
+            curve: XXCurve::X25519,
+            cipher_suite: XXCipherSuite::AesGcm,
+            negotiated: false,
+            message_1_payload: Vec::new(),
+            message_1_offer_len: 0,
+            hybrid: false,
+            kem_secret: None,
+            remote_kem_public_key: None,
             nonce: 0,
             h: Some(h),
             vault: vault.async_try_clone().await.unwrap(),
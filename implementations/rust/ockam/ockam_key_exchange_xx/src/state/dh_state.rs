@@ -1,13 +1,13 @@
-use crate::{XXError, XXVault, SHA256_SIZE_U32};
+use crate::{XXCipherSuite, XXError, XXVault, SHA256_SIZE_U32};
 use ockam_core::vault::{
     KeyId, PublicKey, Secret, SecretAttributes, SecretKey, SecretPersistence, SecretType,
-    AES256_SECRET_LENGTH_U32,
 };
 use ockam_core::Result;
 
 pub(crate) struct DhState<V: XXVault> {
     pub(crate) key: Option<KeyId>,
     pub(crate) ck: Option<KeyId>,
+    pub(crate) cipher_suite: XXCipherSuite,
     pub(crate) vault: V,
 }
 
@@ -16,11 +16,16 @@ impl<V: XXVault> DhState<V> {
         Self {
             key: None,
             ck: None,
+            cipher_suite: XXCipherSuite::default(),
             vault,
         }
     }
 
-    pub(crate) async fn new(protocol_name: &[u8; 32], vault: V) -> Result<Self> {
+    pub(crate) async fn new(
+        protocol_name: &[u8; 32],
+        cipher_suite: XXCipherSuite,
+        vault: V,
+    ) -> Result<Self> {
         let attributes = SecretAttributes::new(
             SecretType::Buffer,
             SecretPersistence::Ephemeral,
@@ -33,6 +38,7 @@ impl<V: XXVault> DhState<V> {
         Ok(Self {
             key: None,
             ck: Some(ck),
+            cipher_suite,
             vault,
         })
     }
@@ -49,10 +55,20 @@ impl<V: XXVault> DhState<V> {
 
 impl<V: XXVault> DhState<V> {
     pub(crate) fn get_symmetric_key_type_and_length(&self) -> (SecretType, u32) {
-        (SecretType::Aes, AES256_SECRET_LENGTH_U32)
+        self.cipher_suite.key_type_and_length()
     }
     /// Perform the diffie-hellman computation
     pub(crate) async fn dh(&mut self, secret_handle: &KeyId, public_key: &PublicKey) -> Result<()> {
+        let ecdh = self
+            .vault
+            .ec_diffie_hellman(secret_handle, public_key)
+            .await?;
+
+        self.mix_key(&ecdh).await
+    }
+
+    /// Derive the next chaining key and key from a shared secret
+    pub(crate) async fn mix_key(&mut self, input_key_material: &KeyId) -> Result<()> {
         let ck = self.ck.as_ref().ok_or(XXError::InvalidState)?;
 
         let attributes_ck = SecretAttributes::new(
@@ -69,14 +85,14 @@ impl<V: XXVault> DhState<V> {
             symmetric_secret_info.1,
         );
 
-        let ecdh = self
-            .vault
-            .ec_diffie_hellman(secret_handle, public_key)
-            .await?;
-
         let mut hkdf_output = self
             .vault
-            .hkdf_sha256(ck, b"", Some(&ecdh), vec![attributes_ck, attributes_k])
+            .hkdf_sha256(
+                ck,
+                b"",
+                Some(input_key_material),
+                vec![attributes_ck, attributes_k],
+            )
             .await?;
 
         if hkdf_output.len() != 2 {
@@ -26,6 +26,7 @@ extern crate core;
 extern crate alloc;
 
 mod portal;
+mod reconnect;
 mod router;
 mod workers;
 
@@ -35,6 +36,7 @@ pub(crate) use workers::*;
 
 mod transport;
 
+pub use reconnect::*;
 pub use transport::*;
 
 use ockam_core::compat::net::SocketAddr;
@@ -3,9 +3,17 @@ mod outlet_listener;
 mod portal_message;
 mod portal_receiver;
 mod portal_worker;
+mod udp_inlet_listener;
+mod udp_outlet_listener;
+mod udp_portal_receiver;
+mod udp_portal_worker;
 
 pub(crate) use inlet_listener::*;
 pub(crate) use outlet_listener::*;
 pub(crate) use portal_message::*;
 pub(crate) use portal_receiver::*;
 pub(crate) use portal_worker::*;
+pub(crate) use udp_inlet_listener::*;
+pub(crate) use udp_outlet_listener::*;
+pub(crate) use udp_portal_receiver::*;
+pub(crate) use udp_portal_worker::*;
@@ -1,5 +1,5 @@
-use crate::{PortalMessage, TcpPortalWorker, TcpRouterHandle};
-use ockam_core::{async_trait, AccessControl, Result, Routed, Worker};
+use crate::{PortalHello, PortalMessage, TcpPortalWorker, TcpRouterHandle};
+use ockam_core::{async_trait, AccessControl, Any, Result, Routed, Worker};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
 use std::sync::Arc;
@@ -34,7 +34,7 @@ impl TcpOutletListenWorker {
 #[async_trait]
 impl Worker for TcpOutletListenWorker {
     type Context = Context;
-    type Message = PortalMessage;
+    type Message = Any;
 
     async fn handle_message(
         &mut self,
@@ -43,7 +43,8 @@ impl Worker for TcpOutletListenWorker {
     ) -> Result<()> {
         let return_route = msg.return_route();
 
-        if let PortalMessage::Ping = msg.body() {
+        let hello = PortalHello::decode_handshake(msg.payload())?;
+        if let PortalMessage::Ping = hello.message {
         } else {
             return Err(TransportError::Protocol.into());
         }
@@ -56,6 +57,8 @@ impl Worker for TcpOutletListenWorker {
             // self.router_address.clone(),
             return_route.clone(),
             self.access_control.clone(),
+            hello.flow_control,
+            hello.half_close,
         )
         .await?;
 
@@ -1,4 +1,4 @@
-use ockam_core::Message;
+use ockam_core::{Decodable, Message, Result};
 use serde::{Deserialize, Serialize};
 
 /// A command message type for a Portal
@@ -13,6 +13,60 @@ pub enum PortalMessage {
     Disconnect,
     /// Message with binary payload
     Payload(Vec<u8>),
+    /// Flow control window update: the other side may send payloads until
+    /// it sent this total number of bytes.
+    ///
+    /// It is only sent to portals which advertised flow control in their
+    /// [`PortalHello`]. Such portals implicitly grant each other a first
+    /// window, so that no payload is sent without credit.
+    Credit(u64),
+    /// Message to indicate that the connection from the target to the
+    /// Outlet, or from the client to the Inlet, reached its end while still
+    /// accepting data in the other direction. The other side shuts down the
+    /// write half of its connection.
+    ///
+    /// It is only sent to portals which advertised half-close in their
+    /// [`PortalHello`], as older portals don't know this message and expect
+    /// a `Disconnect` instead.
+    Eof,
+}
+
+/// The `Ping` or `Pong` a TCP Portal sends, followed by the features it
+/// supports.
+///
+/// Older portals send a bare `Ping`/`Pong` and ignore the bytes following
+/// it, so the features are only used once both sides advertised them.
+#[derive(Serialize, Deserialize, Message)]
+pub struct PortalHello {
+    /// Either [`PortalMessage::Ping`] or [`PortalMessage::Pong`]
+    pub message: PortalMessage,
+    /// The portal understands [`PortalMessage::Credit`]
+    pub flow_control: bool,
+    /// The portal understands [`PortalMessage::Eof`]
+    pub half_close: bool,
+}
+
+impl PortalHello {
+    /// A `Ping` or `Pong` advertising the features of this portal
+    pub fn new(message: PortalMessage) -> Self {
+        Self {
+            message,
+            flow_control: true,
+            half_close: true,
+        }
+    }
+
+    /// Decode a `Ping` or `Pong`, which older portals send without features
+    pub fn decode_handshake(data: &[u8]) -> Result<Self> {
+        match Self::decode(data) {
+            Ok(hello) => Ok(hello),
+            Err(_) => Ok(Self {
+                message: PortalMessage::decode(data)?,
+                flow_control: false,
+                half_close: false,
+            }),
+        }
+    }
 }
 
 /// An internal message type for a Portal
@@ -20,4 +74,15 @@ pub enum PortalMessage {
 pub enum PortalInternalMessage {
     /// Connection was dropped
     Disconnect,
+    /// Reading from the connection reached its end
+    Eof,
+}
+
+/// An internal message type for a UDP Portal
+#[derive(Serialize, Deserialize, Message, Clone)]
+pub enum UdpPortalInternalMessage {
+    /// Datagram received from the local socket
+    Datagram(Vec<u8>),
+    /// Check whether the session was idle since the last check
+    IdleCheck,
 }
@@ -3,8 +3,9 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Encodable, LocalMessage, Route, TransportMessage};
 use ockam_core::{route, Address, Processor, Result};
 use ockam_node::Context;
+use tokio::sync::watch;
 use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
-use tracing::{error, warn};
+use tracing::{error, trace, warn};
 
 const MAX_PAYLOAD_SIZE: usize = 48 * 1024;
 
@@ -13,21 +14,33 @@ const MAX_PAYLOAD_SIZE: usize = 48 * 1024;
 /// TCP Portal receiving message processor are created by
 /// `TcpPortalWorker` after a call is made to
 /// [`TcpPortalWorker::start_receiver`](crate::TcpPortalWorker::start_receiver)
+///
+/// Once the other side granted credit, the processor stops reading from
+/// the socket when the bytes it sent reach that credit.
 pub(crate) struct TcpPortalRecvProcessor {
     buf: Vec<u8>,
     rx: OwnedReadHalf,
     sender_address: Address,
     onward_route: Route,
+    credit: watch::Receiver<Option<u64>>,
+    sent: u64,
 }
 
 impl TcpPortalRecvProcessor {
     /// Create a new `TcpPortalRecvProcessor`
-    pub fn new(rx: OwnedReadHalf, sender_address: Address, onward_route: Route) -> Self {
+    pub fn new(
+        rx: OwnedReadHalf,
+        sender_address: Address,
+        onward_route: Route,
+        credit: watch::Receiver<Option<u64>>,
+    ) -> Self {
         Self {
-            buf: Vec::with_capacity(MAX_PAYLOAD_SIZE),
+            buf: vec![0; MAX_PAYLOAD_SIZE],
             rx,
             sender_address,
             onward_route,
+            credit,
+            sent: 0,
         }
     }
 }
@@ -37,9 +50,20 @@ impl Processor for TcpPortalRecvProcessor {
     type Context = Context;
 
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
-        self.buf.clear();
+        let max_len = match *self.credit.borrow() {
+            Some(credit) => credit
+                .saturating_sub(self.sent)
+                .min(MAX_PAYLOAD_SIZE as u64) as usize,
+            None => MAX_PAYLOAD_SIZE,
+        };
+
+        if max_len == 0 {
+            trace!("Tcp Portal credit exhausted, waiting for a window update");
+            // The channel is closed when the portal worker stopped
+            return Ok(self.credit.changed().await.is_ok());
+        }
 
-        let _len = match self.rx.read_buf(&mut self.buf).await {
+        let len = match self.rx.read(&mut self.buf[..max_len]).await {
             Ok(len) => len,
             Err(err) => {
                 error!("Tcp Portal connection read failed with error: {}", err);
@@ -47,40 +71,32 @@ impl Processor for TcpPortalRecvProcessor {
             }
         };
 
-        if self.buf.is_empty() {
-            // Notify Sender that connection was closed
+        if len == 0 {
+            // Notify Sender that the stream reached its end, it decides
+            // whether to close the whole connection or only its write side
             if let Err(err) = ctx
                 .send(
                     route![self.sender_address.clone()],
-                    PortalInternalMessage::Disconnect,
+                    PortalInternalMessage::Eof,
                 )
                 .await
             {
                 warn!(
-                    "Error notifying Tcp Portal Sender about dropped connection {}",
+                    "Error notifying Tcp Portal Sender about closed connection {}",
                     err
                 );
             }
 
-            let msg = TransportMessage::v1(
-                self.onward_route.clone(),
-                self.sender_address.clone(),
-                PortalMessage::Disconnect.encode()?,
-            );
-            ctx.forward(LocalMessage::new(msg, vec![])).await?;
-
             return Ok(false);
         }
 
-        // Loop just in case buf was extended (should not happen though)
-        for chunk in self.buf.chunks(MAX_PAYLOAD_SIZE) {
-            let msg = TransportMessage::v1(
-                self.onward_route.clone(),
-                self.sender_address.clone(),
-                PortalMessage::Payload(chunk.to_vec()).encode()?,
-            );
-            ctx.forward(LocalMessage::new(msg, vec![])).await?;
-        }
+        let msg = TransportMessage::v1(
+            self.onward_route.clone(),
+            self.sender_address.clone(),
+            PortalMessage::Payload(self.buf[..len].to_vec()).encode()?,
+        );
+        ctx.forward(LocalMessage::new(msg, vec![])).await?;
+        self.sent += len as u64;
 
         Ok(true)
     }
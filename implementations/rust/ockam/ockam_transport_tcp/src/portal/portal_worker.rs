@@ -1,4 +1,4 @@
-use crate::{PortalInternalMessage, PortalMessage, TcpPortalRecvProcessor};
+use crate::{PortalHello, PortalInternalMessage, PortalMessage, TcpPortalRecvProcessor};
 use core::time::Duration;
 use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
 use ockam_core::{async_trait, AccessControl, AllowAll, Decodable, Mailbox, Mailboxes};
@@ -8,8 +8,13 @@ use ockam_transport_core::TransportError;
 use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
+use tokio::sync::watch;
 use tracing::{debug, info, trace, warn};
 
+/// Number of bytes the other side of a portal may send ahead of what
+/// was written to the TCP stream
+const WINDOW_SIZE: u64 = 256 * 1024;
+
 /// Enumerate all `TcpPortalWorker` states
 ///
 /// Possible state transitions are:
@@ -49,6 +54,21 @@ pub(crate) struct TcpPortalWorker {
     remote_route: Option<Route>,
     is_disconnecting: bool,
     type_name: TypeName,
+    /// The other side advertised flow control in its `Ping` or `Pong`
+    remote_flow_control: bool,
+    /// The other side advertised half-close in its `Ping` or `Pong`
+    remote_half_close: bool,
+    /// Credit granted by the other side, if it supports flow control
+    credit: watch::Sender<Option<u64>>,
+    credit_rx: watch::Receiver<Option<u64>>,
+    /// Bytes received from the other side and written to the TCP stream
+    received: u64,
+    /// Credit granted to the other side, starting with the implicit first window
+    granted: u64,
+    /// The TCP stream reached EOF and the other side was told about it
+    read_closed: bool,
+    /// The other side reached EOF and the TCP stream was shut down for writing
+    write_closed: bool,
 }
 
 impl TcpPortalWorker {
@@ -69,6 +89,8 @@ impl TcpPortalWorker {
             Some(stream),
             TypeName::Inlet,
             access_control,
+            false,
+            false,
         )
         .await
     }
@@ -80,6 +102,8 @@ impl TcpPortalWorker {
         // router_address: Address, // for AccessControl
         pong_route: Route,
         access_control: Arc<dyn AccessControl>,
+        remote_flow_control: bool,
+        remote_half_close: bool,
     ) -> Result<Address> {
         Self::start(
             ctx,
@@ -89,11 +113,14 @@ impl TcpPortalWorker {
             None,
             TypeName::Outlet,
             access_control,
+            remote_flow_control,
+            remote_half_close,
         )
         .await
     }
 
     /// Start a new `TcpPortalWorker`
+    #[allow(clippy::too_many_arguments)]
     async fn start(
         ctx: &Context,
         peer: SocketAddr,
@@ -102,6 +129,8 @@ impl TcpPortalWorker {
         stream: Option<TcpStream>,
         type_name: TypeName,
         access_control: Arc<dyn AccessControl>,
+        remote_flow_control: bool,
+        remote_half_close: bool,
     ) -> Result<Address> {
         let internal_address = Address::random_tagged("TcpPortalWorker_internal");
         let remote_address = Address::random_tagged("TcpPortalWorker_remote");
@@ -120,6 +149,10 @@ impl TcpPortalWorker {
             None => (None, None),
         };
 
+        // The Outlet already knows whether the Inlet supports flow control,
+        // both sides then start with an implicit window
+        let initial_window = remote_flow_control.then(|| WINDOW_SIZE);
+        let (credit, credit_rx) = watch::channel(initial_window);
         let worker = Self {
             state,
             tx,
@@ -132,6 +165,14 @@ impl TcpPortalWorker {
             receiver_address,
             is_disconnecting: false,
             type_name,
+            remote_flow_control,
+            remote_half_close,
+            credit,
+            credit_rx,
+            received: 0,
+            granted: initial_window.unwrap_or(0),
+            read_closed: false,
+            write_closed: false,
         };
 
         // TODO: @ac 0#TcpPortalWorker_internal
@@ -187,6 +228,7 @@ enum DisconnectionReason {
     FailedTx,
     FailedRx,
     Remote,
+    Closed,
 }
 
 impl TcpPortalWorker {
@@ -197,8 +239,12 @@ impl TcpPortalWorker {
     /// Start a `TcpPortalRecvProcessor`
     async fn start_receiver(&mut self, ctx: &Context, onward_route: Route) -> Result<()> {
         if let Some(rx) = self.rx.take() {
-            let receiver =
-                TcpPortalRecvProcessor::new(rx, self.internal_address.clone(), onward_route);
+            let receiver = TcpPortalRecvProcessor::new(
+                rx,
+                self.internal_address.clone(),
+                onward_route,
+                self.credit_rx.clone(),
+            );
 
             // TODO: @ac 0#TcpPortalRecvProcessor
             // in:  n/a
@@ -229,6 +275,26 @@ impl TcpPortalWorker {
         }
     }
 
+    /// Let the other side send `WINDOW_SIZE` bytes more than we wrote
+    async fn grant_credit(&mut self, ctx: &Context) -> Result<()> {
+        // Older portals don't know about credit
+        if !self.remote_flow_control {
+            return Ok(());
+        }
+
+        if let Some(remote_route) = self.remote_route.clone() {
+            self.granted = self.received + WINDOW_SIZE;
+            ctx.send_from_address(
+                remote_route,
+                PortalMessage::Credit(self.granted),
+                self.remote_address.clone(),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
     async fn notify_remote_about_disconnection(&mut self, ctx: &Context) -> Result<()> {
         // Notify the other end
         if let Some(remote_route) = self.remote_route.take() {
@@ -277,6 +343,70 @@ impl TcpPortalWorker {
         Ok(())
     }
 
+    /// The TCP stream reached EOF: tell the other side to shut down its
+    /// TCP stream for writing, while still relaying data the other way
+    async fn handle_local_eof(&mut self, ctx: &Context) -> Result<()> {
+        info!(
+            "Tcp stream reached eof for {:?} at: {}",
+            self.type_name, self.internal_address
+        );
+
+        // Older portals don't know about `Eof`
+        if !self.remote_half_close {
+            return self
+                .start_disconnection(ctx, DisconnectionReason::FailedRx)
+                .await;
+        }
+
+        if self.write_closed {
+            return self
+                .start_disconnection(ctx, DisconnectionReason::Closed)
+                .await;
+        }
+
+        self.read_closed = true;
+        if let Some(remote_route) = self.remote_route.clone() {
+            ctx.send_from_address(
+                remote_route,
+                PortalMessage::Eof,
+                self.remote_address.clone(),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// The other side reached EOF: shut down the TCP stream for writing
+    async fn handle_remote_eof(&mut self, ctx: &Context) -> Result<()> {
+        debug!(
+            "{:?} at: {} received eof",
+            self.type_name, self.internal_address
+        );
+
+        if let Some(tx) = &mut self.tx {
+            if let Err(err) = tx.shutdown().await {
+                warn!(
+                    "Failed to shut down connection to peer {} with error: {}",
+                    self.peer, err
+                );
+                return self
+                    .start_disconnection(ctx, DisconnectionReason::FailedTx)
+                    .await;
+            }
+        } else {
+            return Err(TransportError::PortalInvalidState.into());
+        }
+        self.write_closed = true;
+
+        if self.read_closed {
+            self.start_disconnection(ctx, DisconnectionReason::Closed)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Start the portal disconnection process
     async fn start_disconnection(
         &mut self,
@@ -296,6 +426,9 @@ impl TcpPortalWorker {
             DisconnectionReason::Remote => {
                 self.stop_receiver(ctx).await?;
             }
+            DisconnectionReason::Closed => {
+                self.notify_remote_about_disconnection(ctx).await?;
+            }
         }
 
         ctx.stop_worker(self.internal_address.clone()).await?;
@@ -310,8 +443,12 @@ impl TcpPortalWorker {
 
     async fn handle_send_ping(&self, ctx: &Context, ping_route: Route) -> Result<State> {
         // Force creation of Outlet on the other side
-        ctx.send_from_address(ping_route, PortalMessage::Ping, self.remote_address.clone())
-            .await?;
+        ctx.send_from_address(
+            ping_route,
+            PortalHello::new(PortalMessage::Ping),
+            self.remote_address.clone(),
+        )
+        .await?;
 
         debug!("Inlet at: {} sent ping", self.internal_address);
 
@@ -322,7 +459,7 @@ impl TcpPortalWorker {
         // Respond to Inlet
         ctx.send_from_address(
             pong_route.clone(),
-            PortalMessage::Pong,
+            PortalHello::new(PortalMessage::Pong),
             self.remote_address.clone(),
         )
         .await?;
@@ -346,6 +483,7 @@ impl TcpPortalWorker {
         debug!("Outlet at: {} sent pong", self.internal_address);
 
         self.remote_route = Some(pong_route);
+
         Ok(State::Initialized)
     }
 }
@@ -399,18 +537,26 @@ impl Worker for TcpPortalWorker {
                     return Err(TransportError::PortalInvalidState.into());
                 }
 
-                let msg = PortalMessage::decode(msg.payload())?;
+                let hello = PortalHello::decode_handshake(msg.payload())?;
 
-                if let PortalMessage::Pong = msg {
+                if let PortalMessage::Pong = hello.message {
                 } else {
                     return Err(TransportError::Protocol.into());
                 }
 
+                self.remote_flow_control = hello.flow_control;
+                if self.remote_flow_control {
+                    // The receiver is kept by the worker, so this can't fail
+                    let _ = self.credit.send(Some(WINDOW_SIZE));
+                    self.granted = WINDOW_SIZE;
+                }
+
                 self.start_receiver(ctx, return_route.clone()).await?;
 
                 debug!("Inlet at: {} received pong", self.internal_address);
 
                 self.remote_route = Some(return_route);
+                self.remote_half_close = hello.half_close;
                 self.state = State::Initialized;
             }
             State::Initialized => {
@@ -432,6 +578,9 @@ impl Worker for TcpPortalWorker {
                             self.start_disconnection(ctx, DisconnectionReason::FailedRx)
                                 .await?;
                         }
+                        PortalInternalMessage::Eof => {
+                            self.handle_local_eof(ctx).await?;
+                        }
                     }
                 } else {
                     trace!(
@@ -444,10 +593,24 @@ impl Worker for TcpPortalWorker {
                     let msg = PortalMessage::decode(msg.payload())?;
 
                     match msg {
+                        PortalMessage::Payload(_) if self.write_closed => {
+                            warn!(
+                                "{:?} at: {} received payload after eof",
+                                self.type_name, self.internal_address
+                            );
+                        }
                         PortalMessage::Payload(payload) => {
                             if let Some(tx) = &mut self.tx {
                                 match tx.write_all(&payload).await {
-                                    Ok(()) => {}
+                                    Ok(()) => {
+                                        self.received += payload.len() as u64;
+                                        // Grant more credit once half of the window was used
+                                        if self.granted.saturating_sub(self.received)
+                                            < WINDOW_SIZE / 2
+                                        {
+                                            self.grant_credit(ctx).await?;
+                                        }
+                                    }
                                     Err(err) => {
                                         warn!(
                                             "Failed to send message to peer {} with error: {}",
@@ -464,6 +627,21 @@ impl Worker for TcpPortalWorker {
                                 return Err(TransportError::PortalInvalidState.into());
                             }
                         }
+                        PortalMessage::Credit(credit) => {
+                            trace!(
+                                "{:?} at: {} received credit up to {} bytes",
+                                self.type_name,
+                                self.internal_address,
+                                credit
+                            );
+                            if Some(credit) > *self.credit_rx.borrow() {
+                                // The receiver is kept by the worker, so this can't fail
+                                let _ = self.credit.send(Some(credit));
+                            }
+                        }
+                        PortalMessage::Eof => {
+                            self.handle_remote_eof(ctx).await?;
+                        }
                         PortalMessage::Disconnect => {
                             self.start_disconnection(ctx, DisconnectionReason::Remote)
                                 .await?;
@@ -0,0 +1,144 @@
+use crate::{UdpPortalInternalMessage, UdpPortalWorker, UdpSessions, MAX_DATAGRAM_SIZE};
+use core::time::Duration;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::{
+    async_trait,
+    compat::{boxed::Box, sync::Arc},
+};
+use ockam_core::{AccessControl, Address, Mailbox, Mailboxes, Processor, Result, Route};
+use ockam_node::{Context, ProcessorBuilder};
+use ockam_transport_core::TransportError;
+use tokio::net::UdpSocket;
+use tracing::{debug, error, warn};
+
+/// A UDP Portal Inlet listen processor
+///
+/// UDP Portal Inlet listen processors are created by `TcpTransport`
+/// after a call is made to
+/// [`TcpTransport::create_udp_inlet`](crate::TcpTransport::create_udp_inlet).
+///
+/// Datagrams are dispatched to a [`UdpPortalWorker`] per client address,
+/// started when the first datagram of that client is received. The
+/// datagrams of new clients are dropped while `max_sessions` are open.
+pub(crate) struct UdpInletListenProcessor {
+    socket: Arc<UdpSocket>,
+    buf: Vec<u8>,
+    outlet_listener_route: Route,
+    access_control: Arc<dyn AccessControl>,
+    idle_timeout: Duration,
+    max_sessions: usize,
+    sessions: UdpSessions,
+}
+
+impl UdpInletListenProcessor {
+    /// Start a new `UdpInletListenProcessor`
+    pub(crate) async fn start(
+        ctx: &Context,
+        outlet_listener_route: Route,
+        addr: SocketAddr,
+        access_control: Arc<dyn AccessControl>,
+        idle_timeout: Duration,
+        max_sessions: usize,
+    ) -> Result<(Address, SocketAddr)> {
+        let waddr = Address::random_tagged("UdpInletListenProcessor");
+
+        debug!("Binding UdpInletListenProcessor to {}", addr);
+        let socket = match UdpSocket::bind(addr).await {
+            Ok(socket) => socket,
+            Err(err) => {
+                error!(%addr, %err, "could not bind to address");
+                return Err(TransportError::from(err).into());
+            }
+        };
+        let saddr = socket.local_addr().map_err(TransportError::from)?;
+        let processor = Self {
+            socket: Arc::new(socket),
+            buf: vec![0; u16::MAX as usize],
+            outlet_listener_route,
+            access_control: access_control.clone(),
+            idle_timeout,
+            max_sessions,
+            sessions: UdpSessions::default(),
+        };
+
+        // TODO: @ac 0#UdpInletListenProcessor
+        // in:  n/a
+        // out: n/a
+        let mailbox = Mailbox::new(
+            waddr.clone(),
+            access_control,
+            Arc::new(ockam_core::AllowAll),
+        );
+        ProcessorBuilder::with_mailboxes(Mailboxes::new(mailbox, vec![]), processor)
+            .start(ctx)
+            .await?;
+
+        Ok((waddr, saddr))
+    }
+}
+
+#[async_trait]
+impl Processor for UdpInletListenProcessor {
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        let (len, client) = match self.socket.recv_from(&mut self.buf).await {
+            Ok(received) => received,
+            Err(err) => {
+                // Errors like ICMP port unreachable are reported on the
+                // socket, but don't prevent receiving other datagrams
+                warn!("Udp Portal Inlet read failed with error: {}", err);
+                return Ok(true);
+            }
+        };
+        if len > MAX_DATAGRAM_SIZE {
+            warn!("Dropping datagram of {} bytes from {}", len, client);
+            return Ok(true);
+        }
+        let datagram = self.buf[..len].to_vec();
+
+        // The session of this client may have expired since its last datagram
+        let session = self.sessions.get(&client);
+        if let Some(address) = session {
+            let msg = UdpPortalInternalMessage::Datagram(datagram.clone());
+            if ctx.send(address, msg).await.is_ok() {
+                return Ok(true);
+            }
+        }
+
+        if self.sessions.len() >= self.max_sessions {
+            warn!(
+                "Dropping datagram from {}, {} sessions are open",
+                client, self.max_sessions
+            );
+            return Ok(true);
+        }
+
+        // A client must not be able to stop the inlet for the other ones
+        let address = match UdpPortalWorker::start_new_inlet(
+            ctx,
+            self.socket.clone(),
+            client,
+            self.outlet_listener_route.clone(),
+            self.access_control.clone(),
+            self.idle_timeout,
+            self.sessions.clone(),
+        )
+        .await
+        {
+            Ok(address) => address,
+            Err(err) => {
+                error!(%client, %err, "Failed to start Udp Portal Inlet session");
+                return Ok(true);
+            }
+        };
+        if let Err(err) = ctx
+            .send(address, UdpPortalInternalMessage::Datagram(datagram))
+            .await
+        {
+            warn!(%client, %err, "Failed to relay datagram to its session");
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{PortalMessage, TcpRouterHandle, UdpPortalWorker};
+use core::time::Duration;
+use ockam_core::{async_trait, AccessControl, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+use std::sync::Arc;
+use tracing::debug;
+
+/// A UDP Portal Outlet listen worker
+///
+/// UDP Portal Outlet listen workers are created by `TcpTransport`
+/// after a call is made to
+/// [`TcpTransport::create_udp_outlet`](crate::TcpTransport::create_udp_outlet).
+pub(crate) struct UdpOutletListenWorker {
+    peer: String,
+    access_control: Arc<dyn AccessControl>,
+    idle_timeout: Duration,
+}
+
+impl UdpOutletListenWorker {
+    /// Create a new `UdpOutletListenWorker`
+    pub(crate) fn new(
+        peer: String,
+        access_control: Arc<dyn AccessControl>,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            peer,
+            access_control,
+            idle_timeout,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for UdpOutletListenWorker {
+    type Context = Context;
+    type Message = PortalMessage;
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let return_route = msg.return_route();
+
+        if let PortalMessage::Ping = msg.body() {
+        } else {
+            return Err(TransportError::Protocol.into());
+        }
+
+        let (peer_addr, _) = TcpRouterHandle::resolve_peer(self.peer.clone())?;
+
+        let address = UdpPortalWorker::start_new_outlet(
+            ctx,
+            peer_addr,
+            return_route,
+            self.access_control.clone(),
+            self.idle_timeout,
+        )
+        .await?;
+
+        debug!("Created Udp Outlet at {}", &address);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use crate::UdpPortalInternalMessage;
+use ockam_core::compat::{net::SocketAddr, sync::Arc, vec::Vec};
+use ockam_core::{async_trait, Address, Processor, Result};
+use ockam_node::Context;
+use tokio::net::UdpSocket;
+use tracing::{trace, warn};
+
+/// Largest datagram carried by a UDP Portal, so that a
+/// [`PortalMessage::Payload`](crate::PortalMessage::Payload) fits in a
+/// single transport message
+pub(crate) const MAX_DATAGRAM_SIZE: usize = 48 * 1024;
+
+/// A UDP Portal receiving message processor
+///
+/// UDP Portal receiving message processors are created by the Outlet
+/// side `UdpPortalWorker` and hand the datagrams sent by the target
+/// to it.
+pub(crate) struct UdpPortalRecvProcessor {
+    buf: Vec<u8>,
+    socket: Arc<UdpSocket>,
+    peer: SocketAddr,
+    sender_address: Address,
+}
+
+impl UdpPortalRecvProcessor {
+    /// Create a new `UdpPortalRecvProcessor`
+    pub fn new(socket: Arc<UdpSocket>, peer: SocketAddr, sender_address: Address) -> Self {
+        Self {
+            buf: vec![0; u16::MAX as usize],
+            socket,
+            peer,
+            sender_address,
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for UdpPortalRecvProcessor {
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        let (len, from) = match self.socket.recv_from(&mut self.buf).await {
+            Ok(received) => received,
+            Err(err) => {
+                warn!("Udp Portal read failed with error: {}", err);
+                return Ok(true);
+            }
+        };
+
+        if from != self.peer {
+            trace!("Ignoring datagram from unexpected address {}", from);
+            return Ok(true);
+        }
+        if len > MAX_DATAGRAM_SIZE {
+            warn!("Dropping datagram of {} bytes from {}", len, from);
+            return Ok(true);
+        }
+
+        let msg = UdpPortalInternalMessage::Datagram(self.buf[..len].to_vec());
+        ctx.send(self.sender_address.clone(), msg).await?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,375 @@
+use crate::{PortalMessage, UdpPortalInternalMessage, UdpPortalRecvProcessor};
+use core::time::Duration;
+use ockam_core::compat::collections::HashMap;
+use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc, vec::Vec};
+use ockam_core::{async_trait, AccessControl, AllowAll, Decodable, Mailbox, Mailboxes};
+use ockam_core::{Address, Any, Result, Route, Routed, Worker};
+use ockam_node::{Context, DelayedEvent, ProcessorBuilder, WorkerBuilder};
+use ockam_transport_core::TransportError;
+use std::sync::Mutex;
+use tokio::net::UdpSocket;
+use tracing::{debug, info, trace, warn};
+
+/// Number of datagrams an Inlet keeps while waiting for the Outlet
+const MAX_PENDING_DATAGRAMS: usize = 64;
+
+/// Sessions of a UDP Inlet, by client address
+#[derive(Clone, Default)]
+pub(crate) struct UdpSessions(Arc<Mutex<HashMap<SocketAddr, Address>>>);
+
+impl UdpSessions {
+    /// Internal address of the session of `client`
+    pub(crate) fn get(&self, client: &SocketAddr) -> Option<Address> {
+        self.0.lock().unwrap().get(client).cloned()
+    }
+
+    /// Number of open sessions
+    pub(crate) fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    fn insert(&self, client: SocketAddr, address: Address) {
+        self.0.lock().unwrap().insert(client, address);
+    }
+
+    /// Remove the session of `client`, unless it was replaced by another one
+    fn remove(&self, client: &SocketAddr, address: &Address) {
+        let mut sessions = self.0.lock().unwrap();
+        if sessions.get(client) == Some(address) {
+            sessions.remove(client);
+        }
+    }
+}
+
+/// Enumerate all portal types
+#[derive(Debug, Clone)]
+enum TypeName {
+    Inlet,
+    Outlet,
+}
+
+/// A UDP Portal worker
+///
+/// A UDP Portal worker relays the datagrams of a single client and is
+/// created by
+/// [`UdpInletListenProcessor::process`](crate::UdpInletListenProcessor)
+/// for each new client address, or by
+/// [`UdpOutletListenWorker`](crate::UdpOutletListenWorker) when
+/// receiving the `Ping` of a new Inlet session.
+///
+/// Each datagram is carried by a single [`PortalMessage::Payload`], so
+/// that datagram boundaries are preserved. The session stops once no
+/// datagram was relayed for an idle timeout.
+pub(crate) struct UdpPortalWorker {
+    socket: Arc<UdpSocket>,
+    peer: SocketAddr,
+    type_name: TypeName,
+    sessions: Option<UdpSessions>,
+    internal_address: Address,
+    remote_address: Address,
+    receiver_address: Option<Address>,
+    ping_route: Option<Route>,
+    remote_route: Option<Route>,
+    pending: Vec<Vec<u8>>,
+    active: bool,
+    idle_timeout: Duration,
+    idle_check: Option<DelayedEvent<UdpPortalInternalMessage>>,
+}
+
+impl UdpPortalWorker {
+    /// Start a new `UdpPortalWorker` relaying the datagrams of `client`
+    /// and return its internal address
+    pub(crate) async fn start_new_inlet(
+        ctx: &Context,
+        socket: Arc<UdpSocket>,
+        client: SocketAddr,
+        ping_route: Route,
+        access_control: Arc<dyn AccessControl>,
+        idle_timeout: Duration,
+        sessions: UdpSessions,
+    ) -> Result<Address> {
+        let mut worker = Self::new(
+            socket,
+            client,
+            TypeName::Inlet,
+            Some(ping_route),
+            idle_timeout,
+        );
+        worker.sessions = Some(sessions.clone());
+        let internal_address = worker.internal_address.clone();
+        sessions.insert(client, internal_address.clone());
+
+        if let Err(e) = worker.start(ctx, access_control).await {
+            sessions.remove(&client, &internal_address);
+            return Err(e);
+        }
+
+        Ok(internal_address)
+    }
+
+    /// Start a new `UdpPortalWorker` relaying datagrams to `peer` and
+    /// return its remote address
+    pub(crate) async fn start_new_outlet(
+        ctx: &Context,
+        peer: SocketAddr,
+        pong_route: Route,
+        access_control: Arc<dyn AccessControl>,
+        idle_timeout: Duration,
+    ) -> Result<Address> {
+        let bind_addr: SocketAddr = if peer.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(bind_addr)
+            .await
+            .map_err(TransportError::from)?;
+
+        let mut worker = Self::new(Arc::new(socket), peer, TypeName::Outlet, None, idle_timeout);
+        worker.remote_route = Some(pong_route);
+        worker.receiver_address = Some(Address::random_tagged("UdpPortalRecvProcessor"));
+        let remote_address = worker.remote_address.clone();
+
+        worker.start(ctx, access_control).await?;
+
+        Ok(remote_address)
+    }
+
+    fn new(
+        socket: Arc<UdpSocket>,
+        peer: SocketAddr,
+        type_name: TypeName,
+        ping_route: Option<Route>,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            socket,
+            peer,
+            type_name,
+            sessions: None,
+            internal_address: Address::random_tagged("UdpPortalWorker_internal"),
+            remote_address: Address::random_tagged("UdpPortalWorker_remote"),
+            receiver_address: None,
+            ping_route,
+            remote_route: None,
+            pending: Vec::new(),
+            active: false,
+            idle_timeout,
+            idle_check: None,
+        }
+    }
+
+    /// Start the `UdpPortalWorker`
+    async fn start(self, ctx: &Context, access_control: Arc<dyn AccessControl>) -> Result<()> {
+        info!(
+            "Creating new Udp {:?} at internal: {}, remote: {}",
+            self.type_name, self.internal_address, self.remote_address
+        );
+
+        // TODO: @ac 0#UdpPortalWorker_internal
+        // in:  0#UdpPortalWorker_internal  <=  [0#UdpInletListenProcessor, 0#UdpPortalRecvProcessor]
+        // out: n/a
+        let internal_mailbox = Mailbox::new(
+            self.internal_address.clone(),
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        );
+
+        // TODO: @ac 0#UdpPortalWorker_remote
+        // in:  0#UdpPortalWorker_remote  <=  [0#TcpRecvProcessor]
+        // out: 0#UdpPortalWorker_remote  =>  [0#TcpRouter_main_addr_0, 0#outlet, 0#UdpPortalWorker_remote_n]
+        let remote_mailbox = Mailbox::new(
+            self.remote_address.clone(),
+            access_control,
+            Arc::new(AllowAll),
+        );
+
+        WorkerBuilder::with_mailboxes(Mailboxes::new(internal_mailbox, vec![remote_mailbox]), self)
+            .start(ctx)
+            .await
+    }
+
+    /// Start a `UdpPortalRecvProcessor` receiving the datagrams of the target
+    async fn start_receiver(&self, ctx: &Context, receiver_address: Address) -> Result<()> {
+        let receiver = UdpPortalRecvProcessor::new(
+            self.socket.clone(),
+            self.peer,
+            self.internal_address.clone(),
+        );
+
+        // TODO: @ac 0#UdpPortalRecvProcessor
+        // in:  n/a
+        // out: 0#UdpPortalRecvProcessor  =>  [0#UdpPortalWorker_internal]
+        let mailbox = Mailbox::new(receiver_address, Arc::new(AllowAll), Arc::new(AllowAll));
+        ProcessorBuilder::with_mailboxes(Mailboxes::new(mailbox, vec![]), receiver)
+            .start(ctx)
+            .await
+    }
+
+    async fn schedule_idle_check(&mut self, ctx: &Context) -> Result<()> {
+        if self.idle_check.is_none() {
+            let idle_check = DelayedEvent::create(
+                ctx,
+                self.internal_address.clone(),
+                UdpPortalInternalMessage::IdleCheck,
+            )
+            .await?;
+            self.idle_check = Some(idle_check);
+        }
+        if let Some(idle_check) = self.idle_check.as_mut() {
+            idle_check.schedule(self.idle_timeout).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Relay a datagram from the local socket to the other side
+    async fn send_datagram(&mut self, ctx: &Context, datagram: Vec<u8>) -> Result<()> {
+        self.active = true;
+
+        match &self.remote_route {
+            Some(remote_route) => {
+                ctx.send_from_address(
+                    remote_route.clone(),
+                    PortalMessage::Payload(datagram),
+                    self.remote_address.clone(),
+                )
+                .await
+            }
+            None => {
+                if self.pending.len() < MAX_PENDING_DATAGRAMS {
+                    self.pending.push(datagram);
+                } else {
+                    warn!(
+                        "Dropping datagram for {} while waiting for the outlet",
+                        self.peer
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Stop the session, notifying the other side if `notify_remote` is set
+    async fn stop(&mut self, ctx: &Context, notify_remote: bool) -> Result<()> {
+        if let Some(remote_route) = self.remote_route.take() {
+            if notify_remote {
+                ctx.send_from_address(
+                    remote_route,
+                    PortalMessage::Disconnect,
+                    self.remote_address.clone(),
+                )
+                .await?;
+            }
+        }
+
+        ctx.stop_worker(self.internal_address.clone()).await
+    }
+}
+
+#[async_trait]
+impl Worker for UdpPortalWorker {
+    type Context = Context;
+    type Message = Any;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        if let Some(ping_route) = self.ping_route.take() {
+            // Force creation of Outlet on the other side
+            ctx.send_from_address(ping_route, PortalMessage::Ping, self.remote_address.clone())
+                .await?;
+            debug!("Udp Inlet at: {} sent ping", self.internal_address);
+        }
+
+        if let Some(receiver_address) = self.receiver_address.clone() {
+            self.start_receiver(ctx, receiver_address).await?;
+        }
+
+        if let Some(pong_route) = self.remote_route.clone() {
+            ctx.send_from_address(pong_route, PortalMessage::Pong, self.remote_address.clone())
+                .await?;
+            debug!("Udp Outlet at: {} sent pong", self.internal_address);
+        }
+
+        self.schedule_idle_check(ctx).await
+    }
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.idle_check = None;
+
+        if let Some(receiver_address) = self.receiver_address.take() {
+            let _ = ctx.stop_processor(receiver_address).await;
+        }
+
+        if let Some(sessions) = &self.sessions {
+            sessions.remove(&self.peer, &self.internal_address);
+        }
+
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let mut onward_route = msg.onward_route();
+        let recipient = onward_route.step()?;
+
+        if onward_route.next().is_ok() {
+            return Err(TransportError::UnknownRoute.into());
+        }
+
+        if recipient == self.internal_address {
+            match UdpPortalInternalMessage::decode(msg.payload())? {
+                UdpPortalInternalMessage::Datagram(datagram) => {
+                    trace!(
+                        "Udp {:?} at: {} received local datagram",
+                        self.type_name,
+                        self.internal_address
+                    );
+                    self.send_datagram(ctx, datagram).await?;
+                }
+                UdpPortalInternalMessage::IdleCheck => {
+                    if self.active {
+                        self.active = false;
+                        self.schedule_idle_check(ctx).await?;
+                    } else {
+                        info!(
+                            "Udp {:?} at: {} stopped after being idle",
+                            self.type_name, self.internal_address
+                        );
+                        self.stop(ctx, true).await?;
+                    }
+                }
+            }
+
+            return Ok(());
+        }
+
+        match PortalMessage::decode(msg.payload())? {
+            PortalMessage::Pong => {
+                if self.remote_route.is_some() {
+                    return Err(TransportError::PortalInvalidState.into());
+                }
+                debug!("Udp Inlet at: {} received pong", self.internal_address);
+                self.remote_route = Some(msg.return_route());
+                for datagram in core::mem::take(&mut self.pending) {
+                    self.send_datagram(ctx, datagram).await?;
+                }
+            }
+            PortalMessage::Payload(datagram) => {
+                self.active = true;
+                if let Err(err) = self.socket.send_to(&datagram, self.peer).await {
+                    warn!(
+                        "Failed to send datagram to peer {} with error: {}",
+                        self.peer, err
+                    );
+                }
+            }
+            PortalMessage::Disconnect => {
+                self.stop(ctx, false).await?;
+            }
+            PortalMessage::Ping | PortalMessage::Credit(_) | PortalMessage::Eof => {
+                return Err(TransportError::Protocol.into());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use core::time::Duration;
+use ockam_core::compat::rand::{thread_rng, Rng};
+use ockam_core::{Address, Message};
+use serde::{Deserialize, Serialize};
+
+/// How a connection created with
+/// [`TcpTransport::connect_reconnecting`](crate::TcpTransport::connect_reconnecting)
+/// recovers from the loss of its peer
+///
+/// ```rust
+/// use core::time::Duration;
+/// use ockam_transport_tcp::ReconnectOptions;
+///
+/// let options = ReconnectOptions::new()
+///     .with_initial_backoff(Duration::from_millis(50))
+///     .with_max_backoff(Duration::from_secs(10))
+///     .with_buffer_size(64);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectOptions {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    buffer_size: usize,
+    event_listener: Option<Address>,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            buffer_size: 128,
+            event_listener: None,
+        }
+    }
+}
+
+impl ReconnectOptions {
+    /// Options with a backoff from 100ms to 30s and a buffer of 128 messages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delay before the first reconnection attempt, doubled after each failure
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Upper bound of the delay between two reconnection attempts
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Number of outbound messages kept while disconnected. The oldest
+    /// messages are dropped once the buffer is full.
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Send [`TcpConnectionEvent`]s to this address
+    pub fn with_event_listener(mut self, address: impl Into<Address>) -> Self {
+        self.event_listener = Some(address.into());
+        self
+    }
+
+    pub(crate) fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    pub(crate) fn event_listener(&self) -> Option<&Address> {
+        self.event_listener.as_ref()
+    }
+
+    /// Delay before the given reconnection attempt, with jitter so that
+    /// peers losing a common server don't reconnect all at once
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .checked_mul(1 << attempt.min(16))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        backoff.mul_f64(thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+/// State change of a reconnecting TCP connection
+#[derive(Serialize, Deserialize, Message, Debug, Clone, PartialEq, Eq)]
+pub enum TcpConnectionEvent {
+    /// The connection worker at `address` (re)connected to `peer`
+    Connected {
+        /// Address returned by `connect_reconnecting`
+        address: Address,
+        /// Socket address of the peer
+        peer: String,
+    },
+    /// The connection worker at `address` lost its connection to `peer`
+    /// and is trying to reconnect
+    Disconnected {
+        /// Address returned by `connect_reconnecting`
+        address: Address,
+        /// Socket address of the peer
+        peer: String,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backoff_is_bounded() {
+        let options = ReconnectOptions::new()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_secs(1));
+
+        let first = options.backoff(0);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+        let third = options.backoff(2);
+        assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+
+        for attempt in [10, 31, u32::MAX] {
+            let backoff = options.backoff(attempt);
+            assert!(backoff >= Duration::from_millis(500) && backoff <= Duration::from_secs(1));
+        }
+    }
+}
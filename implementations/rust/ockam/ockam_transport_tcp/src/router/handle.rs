@@ -1,7 +1,8 @@
 use crate::{
-    parse_socket_addr, TcpInletListenProcessor, TcpListenProcessor, TcpRouterRequest,
-    TcpRouterResponse, WorkerPair, TCP,
+    parse_socket_addr, ReconnectOptions, TcpInletListenProcessor, TcpListenProcessor,
+    TcpRouterRequest, TcpRouterResponse, UdpInletListenProcessor, WorkerPair, TCP,
 };
+use core::time::Duration;
 use ockam_core::compat::net::{SocketAddr, ToSocketAddrs};
 use ockam_core::{
     async_trait,
@@ -74,12 +75,23 @@ impl TcpRouterHandle {
 
     /// Establish an outgoing TCP connection on an existing transport
     pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<Address> {
+        self.connect_extended(peer, None).await
+    }
+
+    /// Establish an outgoing TCP connection, reconnecting to the peer
+    /// after failures if `reconnect` is set
+    pub async fn connect_extended<S: AsRef<str>>(
+        &self,
+        peer: S,
+        reconnect: Option<ReconnectOptions>,
+    ) -> Result<Address> {
         let response = self
             .ctx
             .send_and_receive(
                 self.api_addr.clone(),
                 TcpRouterRequest::Connect {
                     peer: peer.as_ref().to_string(),
+                    reconnect,
                 },
             )
             .await?;
@@ -220,6 +232,26 @@ impl TcpRouterHandle {
         .await
     }
 
+    /// Bind a UDP portal inlet socket for this router
+    pub async fn bind_udp_inlet(
+        &self,
+        outlet_listener_route: impl Into<Route>,
+        addr: impl Into<SocketAddr>,
+        access_control: Arc<dyn AccessControl>,
+        idle_timeout: Duration,
+        max_sessions: usize,
+    ) -> Result<(Address, SocketAddr)> {
+        UdpInletListenProcessor::start(
+            &self.ctx,
+            outlet_listener_route.into(),
+            addr.into(),
+            access_control,
+            idle_timeout,
+            max_sessions,
+        )
+        .await
+    }
+
     /// Stop the inlet's [`TcpInletListenProcessor`]
     pub async fn stop_inlet(&self, addr: impl Into<Address>) -> Result<()> {
         let addr = addr.into();
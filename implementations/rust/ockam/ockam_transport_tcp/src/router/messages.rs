@@ -1,3 +1,4 @@
+use crate::ReconnectOptions;
 use ockam_core::{Address, Message, Result};
 use serde::{Deserialize, Serialize};
 
@@ -10,8 +11,11 @@ pub enum TcpRouterRequest {
         /// The clients own worker bus address.
         self_addr: Address,
     },
-    /// Connect
-    Connect { peer: String },
+    /// Connect, reconnecting after failures if `reconnect` is set
+    Connect {
+        peer: String,
+        reconnect: Option<ReconnectOptions>,
+    },
     /// Connect
     Disconnect { peer: String },
     /// Unregister (usually, after disconnection)
@@ -1,4 +1,6 @@
-use crate::{TcpRouterHandle, TcpRouterRequest, TcpRouterResponse, TcpSendWorker, TCP};
+use crate::{
+    ReconnectOptions, TcpRouterHandle, TcpRouterRequest, TcpRouterResponse, TcpSendWorker, TCP,
+};
 use core::ops::Deref;
 use ockam_core::{async_trait, compat::sync::Arc, AllowAll};
 use ockam_core::{
@@ -170,16 +172,26 @@ impl TcpRouter {
     /// This handler starts a `(TcpSendWorker, TcpRecvProcessor)` pair
     /// that open and manage a connection to the given peer and
     /// finally register the given peer with this `TcpRouter`.
-    async fn handle_connect(&mut self, peer: String) -> Result<Address> {
+    async fn handle_connect(
+        &mut self,
+        peer: String,
+        reconnect: Option<ReconnectOptions>,
+    ) -> Result<Address> {
         // Resolve peer address
         let (peer_addr, hostnames) = TcpRouterHandle::resolve_peer(peer)?;
 
         // Start a new `WorkerPair` for the given peer containing a
         // `TcpSendWorker` and `TcpRecvprocessor`
         let router_handle = self.create_self_handle().await?;
-        let pair =
-            TcpSendWorker::start_pair(&self.ctx, router_handle, None, peer_addr, hostnames.clone())
-                .await?;
+        let pair = TcpSendWorker::start_pair(
+            &self.ctx,
+            router_handle,
+            None,
+            peer_addr,
+            hostnames.clone(),
+            reconnect,
+        )
+        .await?;
 
         // Send this `TcpRouter` a `TcpRouterRequest::Register` message
         // containing the registration request
@@ -265,7 +277,7 @@ impl TcpRouter {
 
         // No existing connection
         if self.allow_auto_connection {
-            self.handle_connect(peer).await
+            self.handle_connect(peer, None).await
         } else {
             error!(
                 "Failed to resolve route, no existing connection to peer: {}",
@@ -307,8 +319,8 @@ impl Worker for TcpRouter {
                     ctx.send(return_route, TcpRouterResponse::Unregister(res))
                         .await?;
                 }
-                TcpRouterRequest::Connect { peer } => {
-                    let res = self.handle_connect(peer).await;
+                TcpRouterRequest::Connect { peer, reconnect } => {
+                    let res = self.handle_connect(peer, reconnect).await;
 
                     ctx.send(return_route, TcpRouterResponse::Connect(res))
                         .await?;
@@ -3,7 +3,17 @@ use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
 use ockam_core::{Address, AllowAll, AsyncTryClone, Result, Route};
 use ockam_node::Context;
 
-use crate::{parse_socket_addr, TcpOutletListenWorker, TcpRouter, TcpRouterHandle};
+use crate::{
+    parse_socket_addr, ReconnectOptions, TcpOutletListenWorker, TcpRouter, TcpRouterHandle,
+    UdpOutletListenWorker,
+};
+use core::time::Duration;
+
+/// Default time after which a UDP portal session with no traffic is closed
+pub const DEFAULT_UDP_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Default number of client sessions a UDP Inlet keeps at the same time
+pub const DEFAULT_UDP_MAX_SESSIONS: usize = 1024;
 
 /// High level management interface for TCP transports
 ///
@@ -86,6 +96,34 @@ impl TcpTransport {
         self.router_handle.connect(peer.as_ref()).await
     }
 
+    /// Establish an outgoing TCP connection that survives the loss of its peer.
+    ///
+    /// The returned address stays valid: when the connection drops, messages
+    /// sent to it are buffered while the transport reconnects with an
+    /// exponential backoff, then delivered in order. This is also the case
+    /// if the peer can't be reached yet. The connection only stops once
+    /// [`TcpTransport::disconnect`] is called.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{ReconnectOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let options = ReconnectOptions::new().with_event_listener("connection_events");
+    /// tcp.connect_reconnecting("127.0.0.1:5000", options).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect_reconnecting<S: AsRef<str>>(
+        &self,
+        peer: S,
+        options: ReconnectOptions,
+    ) -> Result<Address> {
+        self.router_handle
+            .connect_extended(peer.as_ref(), Some(options))
+            .await
+    }
+
     /// Disconnect from peer
     pub async fn disconnect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
         self.router_handle.disconnect(peer.as_ref()).await
@@ -154,6 +192,70 @@ impl OutletOptions {
     }
 }
 
+/// Args to start a UDP Inlet
+pub struct UdpInletOptions {
+    bind_addr: String,
+    outlet_route: Route,
+    access_control: Arc<dyn AccessControl>,
+    idle_timeout: Duration,
+    max_sessions: usize,
+}
+
+impl UdpInletOptions {
+    /// Constructor
+    pub fn new(
+        bind_addr: String,
+        outlet_route: Route,
+        access_control: Arc<dyn AccessControl>,
+    ) -> Self {
+        Self {
+            bind_addr,
+            outlet_route,
+            access_control,
+            idle_timeout: DEFAULT_UDP_IDLE_TIMEOUT,
+            max_sessions: DEFAULT_UDP_MAX_SESSIONS,
+        }
+    }
+
+    /// Close the session of a client after this duration without traffic
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Drop the datagrams of new clients while this many sessions are open
+    pub fn with_max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = max_sessions;
+        self
+    }
+}
+
+/// Args to start a UDP Outlet
+pub struct UdpOutletOptions {
+    address: Address,
+    peer: String,
+    access_control: Arc<dyn AccessControl>,
+    idle_timeout: Duration,
+}
+
+impl UdpOutletOptions {
+    /// Constructor
+    pub fn new(address: Address, peer: String, access_control: Arc<dyn AccessControl>) -> Self {
+        Self {
+            address,
+            peer,
+            access_control,
+            idle_timeout: DEFAULT_UDP_IDLE_TIMEOUT,
+        }
+    }
+
+    /// Close the session of an inlet client after this duration without traffic
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+}
+
 impl TcpTransport {
     /// Create an Inlet
     pub async fn create_inlet_extended(
@@ -325,3 +427,89 @@ impl TcpTransport {
         Ok(())
     }
 }
+
+impl TcpTransport {
+    /// Create a UDP Inlet
+    pub async fn create_udp_inlet_extended(
+        &self,
+        options: UdpInletOptions,
+    ) -> Result<(Address, SocketAddr)> {
+        let bind_addr = parse_socket_addr(options.bind_addr)?;
+        self.router_handle
+            .bind_udp_inlet(
+                options.outlet_route,
+                bind_addr,
+                options.access_control,
+                options.idle_timeout,
+                options.max_sessions,
+            )
+            .await
+    }
+
+    /// Create Udp Inlet that receives datagrams on bind_addr and forwards each of them as an
+    /// Ockam Routable Message to a Udp Outlet using outlet_route. Every client address of the
+    /// Inlet gets its own session with the Outlet, so that replies of the target are sent back
+    /// to the right client. Sessions are closed after [`DEFAULT_UDP_IDLE_TIMEOUT`] without
+    /// traffic, and at most [`DEFAULT_UDP_MAX_SESSIONS`] of them are open at the same time.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpTransport, TCP};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let hop_addr = "INTERMEDIARY_HOP:8000";
+    /// let route_path = route![(TCP, hop_addr), "outlet"];
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let (inlet, _) = tcp.create_udp_inlet("127.0.0.1:5353", route_path).await?;
+    /// # tcp.stop_inlet(inlet).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_udp_inlet(
+        &self,
+        bind_addr: impl Into<String>,
+        outlet_route: impl Into<Route>,
+    ) -> Result<(Address, SocketAddr)> {
+        let options =
+            UdpInletOptions::new(bind_addr.into(), outlet_route.into(), Arc::new(AllowAll));
+
+        self.create_udp_inlet_extended(options).await
+    }
+
+    /// Create a UDP Outlet
+    pub async fn create_udp_outlet_extended(&self, options: UdpOutletOptions) -> Result<()> {
+        let worker =
+            UdpOutletListenWorker::new(options.peer, options.access_control, options.idle_timeout);
+        self.router_handle
+            .ctx()
+            .start_worker(options.address, worker)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Create Udp Outlet Listener at address, that relays the datagrams received from each
+    /// Udp Inlet session to peer, and sends the datagrams replied by peer back to that session.
+    /// Outlets are stopped with [`TcpTransport::stop_outlet`].
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::TcpTransport;
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_udp_outlet("outlet", "localhost:53").await?;
+    /// # tcp.stop_outlet("outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_udp_outlet(
+        &self,
+        address: impl Into<Address>,
+        peer: impl Into<String>,
+    ) -> Result<()> {
+        let options = UdpOutletOptions::new(address.into(), peer.into(), Arc::new(AllowAll));
+
+        self.create_udp_outlet_extended(options).await
+    }
+}
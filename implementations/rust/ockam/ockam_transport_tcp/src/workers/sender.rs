@@ -1,15 +1,15 @@
-use crate::{TcpRecvProcessor, TcpRouterHandle};
+use crate::{ReconnectOptions, TcpConnectionEvent, TcpRecvProcessor, TcpRouterHandle};
 use core::time::Duration;
 use ockam_core::{
     async_trait,
-    compat::{net::SocketAddr, sync::Arc},
+    compat::{collections::VecDeque, net::SocketAddr, sync::Arc},
     AllowAll,
 };
 use ockam_core::{
     Address, Any, Decodable, Encodable, LocalMessage, Mailbox, Mailboxes, Message, Result, Routed,
     TransportMessage, Worker,
 };
-use ockam_node::{Context, ProcessorBuilder, WorkerBuilder};
+use ockam_node::{Context, DelayedEvent, ProcessorBuilder, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
 use socket2::{SockRef, TcpKeepalive};
@@ -46,6 +46,38 @@ impl WorkerPair {
 #[derive(Serialize, Deserialize, Message, Clone)]
 pub(crate) enum TcpSendWorkerMsg {
     ConnectionClosed,
+    Reconnect,
+}
+
+/// State of a connection that reconnects after losing its peer
+struct Reconnect {
+    options: ReconnectOptions,
+    attempt: u32,
+    buffer: VecDeque<Vec<u8>>,
+    timer: Option<DelayedEvent<TcpSendWorkerMsg>>,
+}
+
+impl Reconnect {
+    fn new(options: ReconnectOptions) -> Self {
+        Self {
+            options,
+            attempt: 0,
+            buffer: VecDeque::new(),
+            timer: None,
+        }
+    }
+
+    /// Keep a message until the connection is back, dropping the oldest
+    /// one if the buffer is full
+    fn buffer(&mut self, msg: Vec<u8>) {
+        if self.buffer.len() >= self.options.buffer_size() {
+            if self.buffer.pop_front().is_none() {
+                return;
+            }
+            warn!("Outbound buffer is full, dropping the oldest message");
+        }
+        self.buffer.push_back(msg);
+    }
 }
 
 /// A TCP sending message worker
@@ -63,6 +95,7 @@ pub(crate) struct TcpSendWorker {
     peer: SocketAddr,
     internal_addr: Address,
     rx_addr: Option<Address>,
+    reconnect: Option<Reconnect>,
 }
 
 impl TcpSendWorker {
@@ -88,6 +121,7 @@ impl TcpSendWorker {
             peer,
             internal_addr,
             rx_addr: None,
+            reconnect: None,
         }
     }
 
@@ -116,7 +150,8 @@ impl TcpSendWorker {
     }
 
     /// Start a `(TcpSendWorker, TcpRecvProcessor)` pair that opens and
-    /// manages the connection with the given peer
+    /// manages the connection with the given peer, reconnecting to it
+    /// if `reconnect` is set
     pub(crate) async fn start_pair(
         // NOTE context is 0#TcpRouter.detached _not_ 0#TcpRouter_main_addr!
         ctx: &Context,
@@ -124,6 +159,7 @@ impl TcpSendWorker {
         stream: Option<TcpStream>,
         peer: SocketAddr,
         hostnames: Vec<String>,
+        reconnect: Option<ReconnectOptions>,
     ) -> Result<WorkerPair> {
         // save the TcpRouter main address
         let _tcprouter_main_addr = router_handle.main_addr().clone();
@@ -134,6 +170,7 @@ impl TcpSendWorker {
         // TODO @ac gawd this is bad. Also assigned in `TcpSendWorker::initialize` depending on context.
         let rx_addr = Address::random_tagged("TcpRecvProcessor");
         worker.rx_addr = Some(rx_addr.clone());
+        worker.reconnect = reconnect.map(Reconnect::new);
 
         // TODO: @ac 0#TcpSendWorker_tx_addr
         // in:  0#TcpSendWorker_tx_addr_9  <=  [0#TcpRouter_main_addr_0]
@@ -171,6 +208,141 @@ impl TcpSendWorker {
 
         Ok(())
     }
+
+    /// Start the processor receiving the messages of the connection
+    async fn start_receiver(
+        &self,
+        ctx: &Context,
+        rx: OwnedReadHalf,
+        rx_addr: Address,
+    ) -> Result<()> {
+        let receiver = TcpRecvProcessor::new(
+            rx,
+            format!("{}#{}", crate::TCP, self.peer).into(),
+            self.internal_addr.clone(),
+        );
+
+        // TODO @ac 0#TcpRecvProcessor
+        // in:  n/a
+        // out: 0#TcpRecvProcessor_12  =>  [0#TcpPortalWorker_remote_6, 0#TcpSendWorker_int_addr_10, 0#outlet]
+        let mailbox = Mailbox::new(
+            rx_addr,
+            Arc::new(AllowAll),
+            // Arc::new(ockam_core::DenyAll),
+            Arc::new(AllowAll),
+            // Arc::new(ockam_core::ToDoAccessControl), // TODO @ac at least LocalOriginOnly
+        );
+        ProcessorBuilder::with_mailboxes(Mailboxes::new(mailbox, vec![]), receiver)
+            .start(ctx)
+            .await
+    }
+
+    /// Send an event to the listener of a reconnecting connection, if any
+    async fn notify(&self, ctx: &Context, connected: bool) {
+        let listener = match self
+            .reconnect
+            .as_ref()
+            .and_then(|r| r.options.event_listener())
+        {
+            Some(listener) => listener.clone(),
+            None => return,
+        };
+        let (address, peer) = (ctx.address(), self.peer.to_string());
+        let event = if connected {
+            TcpConnectionEvent::Connected { address, peer }
+        } else {
+            TcpConnectionEvent::Disconnected { address, peer }
+        };
+        if let Err(e) = ctx.send(listener.clone(), event).await {
+            warn!(%listener, err = %e, "Failed to send TCP connection event");
+        }
+    }
+
+    /// Try to connect again after a delay growing with each failed attempt
+    async fn schedule_reconnect(&mut self, ctx: &Context) -> Result<()> {
+        let reconnect = match &mut self.reconnect {
+            Some(reconnect) => reconnect,
+            None => return Ok(()),
+        };
+        let delay = reconnect.options.backoff(reconnect.attempt);
+        reconnect.attempt = reconnect.attempt.saturating_add(1);
+        if reconnect.timer.is_none() {
+            let timer =
+                DelayedEvent::create(ctx, self.internal_addr.clone(), TcpSendWorkerMsg::Reconnect)
+                    .await?;
+            reconnect.timer = Some(timer);
+        }
+        debug!(addr = %self.peer, ?delay, "Scheduling reconnection");
+        if let Some(timer) = reconnect.timer.as_mut() {
+            timer.schedule(delay).await?;
+        }
+        Ok(())
+    }
+
+    /// Drop a lost connection, keeping the worker and its address
+    async fn disconnected(&mut self, ctx: &Context) -> Result<()> {
+        self.tx = None;
+        if let Some(rx_addr) = self.rx_addr.take() {
+            let _ = ctx.stop_processor(rx_addr).await;
+        }
+        self.notify(ctx, false).await;
+        self.schedule_reconnect(ctx).await
+    }
+
+    async fn reconnect(&mut self, ctx: &Context) -> Result<()> {
+        if self.tx.is_some() {
+            return Ok(());
+        }
+
+        let connection = match connect(self.peer).await {
+            Ok(connection) => connection,
+            Err(_) => return self.schedule_reconnect(ctx).await,
+        };
+        let (rx, mut tx) = connection.into_split();
+        let rx_addr = Address::random_tagged("TcpRecvProcessor");
+        self.start_receiver(ctx, rx, rx_addr.clone()).await?;
+        self.rx_addr = Some(rx_addr);
+
+        // Send the messages buffered during the outage, in order
+        if let Some(reconnect) = &mut self.reconnect {
+            reconnect.attempt = 0;
+            while let Some(msg) = reconnect.buffer.pop_front() {
+                if tx.write_all(msg.as_slice()).await.is_err() {
+                    warn!("Failed to send buffered message to peer {}", self.peer);
+                    reconnect.buffer.push_front(msg);
+                    return self.disconnected(ctx).await;
+                }
+            }
+        }
+        self.tx = Some(tx);
+        self.notify(ctx, true).await;
+
+        Ok(())
+    }
+}
+
+/// Open a connection to `peer` with TCP keepalive
+async fn connect(peer: SocketAddr) -> Result<TcpStream> {
+    debug!(addr = %peer, "Connecting");
+    let connection = match TcpStream::connect(peer).await {
+        Ok(c) => {
+            debug!(addr = %peer, "Connected");
+            c
+        }
+        Err(e) => {
+            debug!(addr = %peer, err = %e, "Failed to connect");
+            return Err(TransportError::from(e).into());
+        }
+    };
+
+    let keepalive = TcpKeepalive::new()
+        .with_time(Duration::from_secs(300))
+        .with_retries(2)
+        .with_interval(Duration::from_secs(75));
+    let socket = SockRef::from(&connection);
+    socket.set_tcp_keepalive(&keepalive).unwrap();
+
+    Ok(connection)
 }
 
 #[async_trait]
@@ -182,27 +354,20 @@ impl Worker for TcpSendWorker {
         ctx.set_cluster(crate::CLUSTER_NAME).await?;
 
         if self.tx.is_none() {
-            debug!(addr = %self.peer, "Connecting");
-            let connection = match TcpStream::connect(self.peer).await {
-                Ok(c) => {
-                    debug!(addr = %self.peer, "Connected");
-                    c
+            let connection = match connect(self.peer).await {
+                Ok(c) => c,
+                // A reconnecting connection starts buffering right away
+                Err(_) if self.reconnect.is_some() => {
+                    self.rx_addr = None;
+                    return self.schedule_reconnect(ctx).await;
                 }
                 Err(e) => {
-                    debug!(addr = %self.peer, err = %e, "Failed to connect");
                     self.stop_and_unregister(ctx).await?;
 
-                    return Err(TransportError::from(e).into());
+                    return Err(e);
                 }
             };
 
-            let keepalive = TcpKeepalive::new()
-                .with_time(Duration::from_secs(300))
-                .with_retries(2)
-                .with_interval(Duration::from_secs(75));
-            let socket = SockRef::from(&connection);
-            socket.set_tcp_keepalive(&keepalive).unwrap();
-
             let (rx, tx) = connection.into_split();
             self.tx = Some(tx);
             self.rx = Some(rx);
@@ -217,33 +382,20 @@ impl Worker for TcpSendWorker {
             // TODO @ac gawd this is bad. Also assigned in `TcpSendWorker.start_pair` depending on context.
             Address::random_tagged("TcpRecvProcessor")
         };
-        let receiver = TcpRecvProcessor::new(
-            rx,
-            format!("{}#{}", crate::TCP, self.peer).into(),
-            self.internal_addr.clone(),
-        );
-
-        // TODO @ac 0#TcpRecvProcessor
-        // in:  n/a
-        // out: 0#TcpRecvProcessor_12  =>  [0#TcpPortalWorker_remote_6, 0#TcpSendWorker_int_addr_10, 0#outlet]
-        let mailbox = Mailbox::new(
-            rx_addr,
-            Arc::new(AllowAll),
-            // Arc::new(ockam_core::DenyAll),
-            Arc::new(AllowAll),
-            // Arc::new(ockam_core::ToDoAccessControl), // TODO @ac at least LocalOriginOnly
-        );
-        ProcessorBuilder::with_mailboxes(Mailboxes::new(mailbox, vec![]), receiver)
-            .start(ctx)
-            .await?;
+        self.start_receiver(ctx, rx, rx_addr).await?;
 
         // TODO see above
         //self.rx_addr = Some(rx_addr);
 
+        self.notify(ctx, true).await;
+
         Ok(())
     }
 
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // Cancels a pending reconnection
+        self.reconnect = None;
+
         if let Some(rx_addr) = self.rx_addr.take() {
             let _ = ctx.stop_processor(rx_addr).await;
         }
@@ -258,16 +410,27 @@ impl Worker for TcpSendWorker {
         ctx: &mut Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
-        let tx = match &mut self.tx {
-            Some(tx) => tx,
-            None => return Err(TransportError::PeerNotFound.into()),
-        };
+        if self.tx.is_none() && self.reconnect.is_none() {
+            return Err(TransportError::PeerNotFound.into());
+        }
 
         let recipient = msg.msg_addr();
         if recipient == self.internal_addr {
+            let sender = msg.sender();
             let msg = TcpSendWorkerMsg::decode(msg.payload())?;
 
             match msg {
+                // The receiver of a previous connection may report its
+                // closing after we reconnected
+                TcpSendWorkerMsg::ConnectionClosed if self.reconnect.is_some() => {
+                    if self.tx.is_some() && self.rx_addr.as_ref() == Some(&sender) {
+                        warn!("Connection to {} closed, reconnecting", self.peer);
+                        // The receiver stops itself
+                        self.rx_addr = None;
+                        self.disconnected(ctx).await?;
+                    }
+                }
+                TcpSendWorkerMsg::Reconnect => self.reconnect(ctx).await?,
                 TcpSendWorkerMsg::ConnectionClosed => {
                     warn!("Stopping sender due to closed connection {}", self.peer);
                     // No need to stop Receiver as it notified us about connection drop and will
@@ -286,8 +449,25 @@ impl Worker for TcpSendWorker {
             // Create a message buffer with prepended length
             let msg = prepare_message(msg)?;
 
+            let tx = match &mut self.tx {
+                Some(tx) => tx,
+                None => {
+                    if let Some(reconnect) = &mut self.reconnect {
+                        reconnect.buffer(msg);
+                    }
+                    return Ok(());
+                }
+            };
+
             if tx.write_all(msg.as_slice()).await.is_err() {
                 warn!("Failed to send message to peer {}", self.peer);
+                if self.reconnect.is_some() {
+                    self.disconnected(ctx).await?;
+                    if let Some(reconnect) = &mut self.reconnect {
+                        reconnect.buffer(msg);
+                    }
+                    return Ok(());
+                }
                 self.stop_and_unregister(ctx).await?;
 
                 return Ok(());
@@ -1,15 +1,22 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
 
 use ockam_core::compat::rand::random;
-use ockam_core::{route, Result};
+use ockam_core::{async_trait, route, Any, Decodable, Message, Result, Routed, Worker};
 use ockam_node::Context;
 use ockam_transport_tcp::TcpTransport;
 
 const LENGTH: usize = 32;
 
+/// Flow control window of the portal workers
+const WINDOW_SIZE: usize = 256 * 1024;
+
 async fn setup(ctx: &Context) -> Result<(String, TcpListener)> {
     let tcp = TcpTransport::create(ctx).await?;
 
@@ -101,3 +108,187 @@ async fn portal__reverse_flow__should_succeed(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 30000)]
+async fn portal__slow_target__should_receive_everything(ctx: &mut Context) -> Result<()> {
+    // Far more than the flow control window and the socket buffers on the way
+    const TOTAL: usize = 32 * 1024 * 1024;
+    // Upper bound for the kernel buffers of the four sockets between
+    // the client and the target
+    const SOCKET_BUFFERS: usize = 12 * 1024 * 1024;
+
+    let (inlet_addr, listener) = setup(ctx).await?;
+    let (resume_tx, resume_rx) = oneshot::channel();
+
+    let target = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        // Don't read anything until the client is blocked
+        resume_rx.await.unwrap();
+        let mut received = vec![0u8; TOTAL];
+        stream.read_exact(&mut received).await.unwrap();
+        received
+    });
+
+    // Wait till listener is up
+    tokio::time::sleep(Duration::new(0, 250_000)).await;
+
+    let payload: Arc<Vec<u8>> = Arc::new((0..TOTAL).map(|i| i as u8).collect());
+    let written = Arc::new(AtomicUsize::new(0));
+    let client = {
+        let payload = payload.clone();
+        let written = written.clone();
+        tokio::spawn(async move {
+            let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+            for chunk in payload.chunks(16 * 1024) {
+                stream.write_all(chunk).await.unwrap();
+                written.fetch_add(chunk.len(), Ordering::SeqCst);
+            }
+            // Keep the connection open until the target read everything
+            stream
+        })
+    };
+
+    // Wait for the portal to stop reading from the client
+    let mut stalled = 0;
+    loop {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let now = written.load(Ordering::SeqCst);
+        if now == stalled {
+            break;
+        }
+        stalled = now;
+    }
+    assert!(
+        stalled < WINDOW_SIZE + SOCKET_BUFFERS,
+        "{} bytes were in flight",
+        stalled
+    );
+
+    resume_tx.send(()).unwrap();
+    let received = target.await.unwrap();
+    assert_eq!(received, *payload);
+    let _stream = client.await.unwrap();
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// The messages of the portal protocol, as they are sent on the wire
+#[derive(Serialize, Deserialize, Message)]
+enum PortalMessage {
+    Ping,
+    Pong,
+    Disconnect,
+    Payload(Vec<u8>),
+    Credit(u64),
+    Eof,
+}
+
+#[derive(Serialize, Deserialize, Message)]
+struct PortalHello {
+    message: PortalMessage,
+    flow_control: bool,
+    half_close: bool,
+}
+
+/// An Outlet supporting flow control, which never grants more than the
+/// implicit first window and counts the payload bytes it receives
+struct StalledOutlet {
+    received: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Worker for StalledOutlet {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        // Portals ignore the features following a `Ping`
+        match PortalMessage::decode(msg.payload())? {
+            PortalMessage::Ping => {
+                let pong = PortalHello {
+                    message: PortalMessage::Pong,
+                    flow_control: true,
+                    half_close: true,
+                };
+                ctx.send(msg.return_route(), pong).await
+            }
+            PortalMessage::Payload(payload) => {
+                self.received.fetch_add(payload.len(), Ordering::SeqCst);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__stalled_outlet__should_receive_the_first_window(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let received = Arc::new(AtomicUsize::new(0));
+    ctx.start_worker(
+        "outlet",
+        StalledOutlet {
+            received: received.clone(),
+        },
+    )
+    .await?;
+    let (_, inlet_addr) = tcp.create_inlet("127.0.0.1:0", route!["outlet"]).await?;
+
+    // The client has data ready before the Inlet even received the `Pong`
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let payload = vec![0u8; 16 * WINDOW_SIZE];
+    let _ = tokio::time::timeout(Duration::from_secs(1), stream.write_all(&payload)).await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert_eq!(received.load(Ordering::SeqCst), WINDOW_SIZE);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__half_closed_connection__should_still_receive(ctx: &mut Context) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let (inlet_addr, listener) = setup(ctx).await?;
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        let mut request = Vec::new();
+        stream.read_to_end(&mut request).await.unwrap();
+        assert_eq!(request, payload1);
+        write_binary(&mut stream, payload2).await;
+    });
+
+    // Wait till listener is up
+    tokio::time::sleep(Duration::new(0, 250_000)).await;
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload1).await;
+    stream.shutdown().await.unwrap();
+    read_assert_binary(&mut stream, payload2).await;
+
+    let mut rest = Vec::new();
+    stream.read_to_end(&mut rest).await.unwrap();
+    assert!(rest.is_empty());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
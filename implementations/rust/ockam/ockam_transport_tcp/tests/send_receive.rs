@@ -2,8 +2,12 @@ use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Address, Result, Routed, Worker};
 use ockam_node::Context;
 
-use ockam_transport_tcp::{TcpTransport, TCP};
+use ockam_transport_tcp::{ReconnectOptions, TcpConnectionEvent, TcpTransport, TCP};
+use std::net::SocketAddr;
 use std::time::Duration;
+use tokio::io::copy_bidirectional;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
 use tracing::info;
 
 #[ockam_macros::test]
@@ -96,6 +100,134 @@ async fn tcp_lifecycle__reconnect__should_not_error(ctx: &mut Context) -> Result
     Ok(())
 }
 
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_reconnecting__unreachable_peer__should_buffer(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+
+    // Find a free port, nobody listens on it yet
+    let peer = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+
+    let mut events = ctx.new_detached(Address::random_local()).await?;
+    let options = ReconnectOptions::new()
+        .with_initial_backoff(Duration::from_millis(50))
+        .with_max_backoff(Duration::from_millis(200))
+        .with_event_listener(events.address());
+    let tx_address = transport.connect_reconnecting(&peer, options).await?;
+
+    let mut child_ctx = ctx.new_detached(Address::random_local()).await?;
+    let msg = "Hello".to_string();
+    child_ctx
+        .send(route![tx_address.clone(), "echoer"], msg.clone())
+        .await?;
+
+    // The buffered message is delivered once the peer is up
+    transport.listen(&peer).await?;
+
+    let event = events.receive::<TcpConnectionEvent>().await?.take().body();
+    assert_eq!(
+        event,
+        TcpConnectionEvent::Connected {
+            address: tx_address,
+            peer
+        }
+    );
+
+    let reply = child_ctx.receive::<String>().await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Forward the connections accepted on `bind`, one at a time, to `target`
+/// until the returned task is aborted, which drops them
+async fn start_proxy(bind: &str, target: SocketAddr) -> JoinHandle<()> {
+    let listener = TcpListener::bind(bind).await.unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut inbound, _)) = listener.accept().await {
+            if let Ok(mut outbound) = TcpStream::connect(target).await {
+                let _ = copy_bidirectional(&mut inbound, &mut outbound).await;
+            }
+        }
+    })
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tcp_reconnecting__peer_restart__should_deliver_in_order(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener_address = transport.listen("127.0.0.1:0").await?;
+
+    // The connection goes through a proxy, which is the peer we stop and restart
+    let peer = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    let proxy = start_proxy(&peer, listener_address).await;
+
+    let mut events = ctx.new_detached(Address::random_local()).await?;
+    let options = ReconnectOptions::new()
+        .with_initial_backoff(Duration::from_millis(50))
+        .with_max_backoff(Duration::from_millis(200))
+        .with_event_listener(events.address());
+    let tx_address = transport.connect_reconnecting(&peer, options).await?;
+
+    let connected = TcpConnectionEvent::Connected {
+        address: tx_address.clone(),
+        peer: peer.clone(),
+    };
+    let event = events.receive::<TcpConnectionEvent>().await?.take().body();
+    assert_eq!(event, connected);
+
+    proxy.abort();
+    let event = events.receive::<TcpConnectionEvent>().await?.take().body();
+    assert_eq!(
+        event,
+        TcpConnectionEvent::Disconnected {
+            address: tx_address.clone(),
+            peer: peer.clone()
+        }
+    );
+
+    // Messages sent while the peer is down are buffered
+    let mut child_ctx = ctx.new_detached(Address::random_local()).await?;
+    let msgs: Vec<String> = (0..5).map(|i| format!("Hello {i}")).collect();
+    for msg in &msgs {
+        child_ctx
+            .send(route![tx_address.clone(), "echoer"], msg.clone())
+            .await?;
+    }
+
+    let proxy = start_proxy(&peer, listener_address).await;
+    let event = events.receive::<TcpConnectionEvent>().await?.take().body();
+    assert_eq!(event, connected);
+
+    for msg in msgs {
+        let reply = child_ctx.receive::<String>().await?;
+        assert_eq!(reply, msg, "Should receive the messages in order");
+    }
+
+    proxy.abort();
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
 #[ignore]
 #[ockam_macros::test(timeout = 400000)]
 async fn tcp_keepalive_test(ctx: &mut Context) -> Result<()> {
@@ -0,0 +1,131 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use ockam_core::compat::rand::random;
+use ockam_core::{route, AllowAll, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpTransport, UdpInletOptions, UdpOutletOptions, DEFAULT_UDP_IDLE_TIMEOUT,
+};
+
+const LENGTH: usize = 32;
+
+/// Start a UDP echo server behind a UDP portal and return the inlet address,
+/// with the addresses the server receives datagrams from
+async fn setup(
+    ctx: &Context,
+    idle_timeout: Duration,
+) -> Result<(String, UnboundedReceiver<SocketAddr>)> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let options = UdpOutletOptions::new(
+        "outlet".into(),
+        server.local_addr().unwrap().to_string(),
+        Arc::new(AllowAll),
+    )
+    .with_idle_timeout(idle_timeout);
+    tcp.create_udp_outlet_extended(options).await?;
+    let (peers_tx, peers_rx) = unbounded_channel();
+    tokio::spawn(async move {
+        let mut buf = [0u8; 1024];
+        loop {
+            let (len, peer) = server.recv_from(&mut buf).await.unwrap();
+            let _ = peers_tx.send(peer);
+            server.send_to(&buf[..len], peer).await.unwrap();
+        }
+    });
+
+    let options = UdpInletOptions::new("127.0.0.1:0".into(), route!["outlet"], Arc::new(AllowAll))
+        .with_idle_timeout(idle_timeout);
+    let (_, inlet_saddr) = tcp.create_udp_inlet_extended(options).await?;
+
+    Ok((inlet_saddr.to_string(), peers_rx))
+}
+
+async fn client(inlet_addr: &str) -> UdpSocket {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    socket.connect(inlet_addr).await.unwrap();
+    socket
+}
+
+async fn read_assert_datagram(socket: &UdpSocket, expected: &[u8]) {
+    let mut buf = [0u8; 1024];
+    let length = socket.recv(&mut buf).await.unwrap();
+    assert_eq!(&buf[..length], expected);
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn udp_portal__datagrams__should_keep_boundaries(ctx: &mut Context) -> Result<()> {
+    let (inlet_addr, _) = setup(ctx, DEFAULT_UDP_IDLE_TIMEOUT).await?;
+    let socket = client(&inlet_addr).await;
+
+    let payload1: [u8; LENGTH] = random();
+    let payload2: [u8; LENGTH / 2] = random();
+    socket.send(&payload1).await.unwrap();
+    socket.send(&payload2).await.unwrap();
+
+    read_assert_datagram(&socket, &payload1).await;
+    read_assert_datagram(&socket, &payload2).await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn udp_portal__multiple_clients__should_get_their_replies(ctx: &mut Context) -> Result<()> {
+    let (inlet_addr, _) = setup(ctx, DEFAULT_UDP_IDLE_TIMEOUT).await?;
+    let socket1 = client(&inlet_addr).await;
+    let socket2 = client(&inlet_addr).await;
+
+    let payload1: [u8; LENGTH] = random();
+    let payload2: [u8; LENGTH] = random();
+    socket1.send(&payload1).await.unwrap();
+    socket2.send(&payload2).await.unwrap();
+
+    read_assert_datagram(&socket2, &payload2).await;
+    read_assert_datagram(&socket1, &payload1).await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn udp_portal__idle_session__should_expire(ctx: &mut Context) -> Result<()> {
+    let idle_timeout = Duration::from_millis(200);
+    let (inlet_addr, mut peers) = setup(ctx, idle_timeout).await?;
+    let socket = client(&inlet_addr).await;
+
+    let payload1: [u8; LENGTH] = random();
+    socket.send(&payload1).await.unwrap();
+    read_assert_datagram(&socket, &payload1).await;
+    let first_peer = peers.recv().await.unwrap();
+
+    // The outlet relays the datagrams of a session from its own socket,
+    // so a new session reaches the server from another address
+    ctx.sleep(idle_timeout * 3).await;
+
+    let payload2: [u8; LENGTH] = random();
+    socket.send(&payload2).await.unwrap();
+    read_assert_datagram(&socket, &payload2).await;
+    let second_peer = peers.recv().await.unwrap();
+    assert_ne!(first_peer, second_peer, "The idle session should expire");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -1,12 +1,17 @@
+use crate::audit::AuditOperation;
 use crate::{Vault, VaultError};
 use arrayref::array_ref;
+use cfg_if::cfg_if;
 use ockam_core::vault::{
     AsymmetricVault, Buffer, Hasher, KeyId, PublicKey, Secret, SecretAttributes, SecretKey,
-    SecretPersistence, SecretType, SecretVault, VaultEntry, CURVE25519_PUBLIC_LENGTH_USIZE,
-    CURVE25519_SECRET_LENGTH_USIZE,
+    SecretPersistence, SecretType, SecretUsage, SecretVault, VaultEntry,
+    CURVE25519_PUBLIC_LENGTH_USIZE, CURVE25519_SECRET_LENGTH_USIZE,
 };
 use ockam_core::Result;
-use ockam_core::{async_trait, compat::boxed::Box};
+use ockam_core::{async_trait, compat::boxed::Box, compat::vec::Vec};
+
+#[cfg(feature = "rustcrypto")]
+use crate::error::from_pkcs8;
 
 impl Vault {
     fn ecdh_internal(vault_entry: &VaultEntry, peer_public_key: &PublicKey) -> Result<Buffer<u8>> {
@@ -32,9 +37,27 @@ impl Vault {
                 let secret = sk.diffie_hellman(&pk_t);
                 Ok(secret.as_bytes().to_vec())
             }
-            SecretType::NistP256 | SecretType::Buffer | SecretType::Aes | SecretType::Ed25519 => {
-                Err(VaultError::UnknownEcdhKeyType.into())
+            SecretType::NistP256 => {
+                cfg_if! {
+                    if #[cfg(feature = "rustcrypto")] {
+                        use p256::pkcs8::{DecodePrivateKey, DecodePublicKey};
+                        // Software keys are PKCS#8 documents, public keys SPKI ones
+                        let key = vault_entry.secret().try_as_key()?;
+                        let sk = p256::SecretKey::from_pkcs8_der(key.as_ref()).map_err(from_pkcs8)?;
+                        let pk = p256::PublicKey::from_public_key_der(peer_public_key.data())
+                            .map_err(|_| VaultError::InvalidPublicKey)?;
+                        let secret = p256::ecdh::diffie_hellman(sk.to_nonzero_scalar(), pk.as_affine());
+                        Ok(secret.raw_secret_bytes().to_vec())
+                    } else {
+                        compile_error!("NIST P-256 requires feature `rustcrypto`")
+                    }
+                }
             }
+            SecretType::Buffer
+            | SecretType::Aes
+            | SecretType::ChaCha20Poly1305
+            | SecretType::Kyber768
+            | SecretType::Ed25519 => Err(VaultError::UnknownEcdhKeyType.into()),
         }
     }
 }
@@ -46,14 +69,29 @@ impl AsymmetricVault for Vault {
         secret: &KeyId,
         peer_public_key: &PublicKey,
     ) -> Result<KeyId> {
+        self.preload_from_storage(secret).await;
+
         let entries = self.data.entries.read().await;
         let entry = entries.get(secret).ok_or(VaultError::EntryNotFound)?;
+        Self::check_usage(entry, SecretUsage::ECDH)?;
 
-        let dh = Self::ecdh_internal(entry, peer_public_key)?;
+        let dh = '_block: {
+            if let Some(kms) = &self.kms {
+                if let Secret::Kms(kid) = entry.secret() {
+                    let dh = kms.ecdh(kid, peer_public_key).await?;
+                    break '_block dh.as_ref().to_vec();
+                }
+            }
+            Self::ecdh_internal(entry, peer_public_key)?
+        };
 
         // Prevent dead-lock by freeing entries lock, since we don't need it
+        let key_attributes = entry.key_attributes();
         drop(entries);
 
+        self.audit(AuditOperation::Ecdh, secret, key_attributes)
+            .await?;
+
         let attributes = SecretAttributes::new(
             SecretType::Buffer,
             SecretPersistence::Ephemeral,
@@ -67,6 +105,14 @@ impl AsymmetricVault for Vault {
         let key_id = self.sha256(public_key.data()).await?;
         Ok(hex::encode(key_id))
     }
+
+    async fn kem_encapsulate(&self, peer_public_key: &PublicKey) -> Result<(Vec<u8>, KeyId)> {
+        self.kyber768_encapsulate(peer_public_key).await
+    }
+
+    async fn kem_decapsulate(&self, secret: &KeyId, ciphertext: &[u8]) -> Result<KeyId> {
+        self.kyber768_decapsulate(secret, ciphertext).await
+    }
 }
 
 #[cfg(test)]
@@ -79,4 +125,7 @@ mod tests {
 
     #[ockam_macros::vault_test]
     fn ec_diffie_hellman_curve25519() {}
+
+    #[ockam_macros::vault_test]
+    fn ec_diffie_hellman_nist_p256() {}
 }
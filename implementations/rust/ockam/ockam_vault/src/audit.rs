@@ -0,0 +1,101 @@
+use core::fmt;
+use ockam_core::compat::string::String;
+use ockam_core::vault::{KeyId, SecretAttributes};
+use ockam_core::{async_trait, compat::boxed::Box, Result};
+
+#[cfg(feature = "storage")]
+mod file;
+#[cfg(feature = "storage")]
+pub use file::{AuditRecord, FileAuditLog};
+
+/// Operation of a vault on a secret
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "storage",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AuditOperation {
+    /// A secret was generated
+    Generate,
+    /// A secret was imported
+    Import,
+    /// A secret was exported
+    Export,
+    /// A secret was destroyed
+    Destroy,
+    /// Data was signed with a secret
+    Sign,
+    /// A secret was used for ECDH or KEM decapsulation
+    Ecdh,
+    /// Data was decrypted with a secret
+    Decrypt,
+}
+
+impl fmt::Display for AuditOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Generate => "generate",
+            Self::Import => "import",
+            Self::Export => "export",
+            Self::Destroy => "destroy",
+            Self::Sign => "sign",
+            Self::Ecdh => "ecdh",
+            Self::Decrypt => "decrypt",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Use of a secret by a vault. It never contains secret material.
+#[derive(Clone, Debug)]
+pub struct AuditEvent {
+    operation: AuditOperation,
+    key_id: KeyId,
+    attributes: SecretAttributes,
+    caller: Option<String>,
+}
+
+impl AuditEvent {
+    pub(crate) fn new(
+        operation: AuditOperation,
+        key_id: KeyId,
+        attributes: SecretAttributes,
+        caller: Option<String>,
+    ) -> Self {
+        Self {
+            operation,
+            key_id,
+            attributes,
+            caller,
+        }
+    }
+
+    /// Operation of the vault
+    pub fn operation(&self) -> AuditOperation {
+        self.operation
+    }
+    /// Id of the secret
+    pub fn key_id(&self) -> &KeyId {
+        &self.key_id
+    }
+    /// Attributes of the secret
+    pub fn attributes(&self) -> SecretAttributes {
+        self.attributes
+    }
+    /// Caller given to [`crate::Vault::with_audit_caller`], if any
+    pub fn caller(&self) -> Option<&str> {
+        self.caller.as_deref()
+    }
+}
+
+/// Destination of the audit events of a vault.
+///
+/// Once enabled with [`crate::Vault::enable_audit`], the vault records the
+/// operations on its persistent secrets. The operation fails if the event
+/// can't be recorded.
+#[async_trait]
+pub trait AuditSink: Send + Sync + 'static {
+    /// Record an event.
+    async fn record(&self, event: AuditEvent) -> Result<()>;
+}
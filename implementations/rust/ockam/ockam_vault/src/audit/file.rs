@@ -0,0 +1,259 @@
+use super::{AuditEvent, AuditOperation, AuditSink};
+use crate::VaultError;
+use fs2::FileExt; //locking
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::vault::{KeyId, SecretAttributes};
+use ockam_core::{async_trait, compat::boxed::Box, Error, Result};
+use ockam_node::tokio::sync::Mutex;
+use ockam_node::tokio::task::{self, JoinError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Hash preceding the first record of a log
+const GENESIS_HASH: [u8; 32] = [0; 32];
+
+/// Line of a [`FileAuditLog`]
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct AuditRecord {
+    timestamp: u64,
+    operation: AuditOperation,
+    key_id: KeyId,
+    attributes: SecretAttributes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    caller: Option<String>,
+    hash: String,
+}
+
+impl AuditRecord {
+    /// Seconds since the Unix epoch
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    /// Operation of the vault
+    pub fn operation(&self) -> AuditOperation {
+        self.operation
+    }
+    /// Id of the secret
+    pub fn key_id(&self) -> &KeyId {
+        &self.key_id
+    }
+    /// Attributes of the secret
+    pub fn attributes(&self) -> SecretAttributes {
+        self.attributes
+    }
+    /// Caller of the vault, if known
+    pub fn caller(&self) -> Option<&str> {
+        self.caller.as_deref()
+    }
+    /// Hex encoded SHA-256 of the hash of the previous record and of this one
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    fn compute_hash(&self, prev_hash: &[u8; 32]) -> Result<[u8; 32]> {
+        let unhashed = Self {
+            hash: String::new(),
+            ..self.clone()
+        };
+        let json = serde_json::to_vec(&unhashed).map_err(|_| VaultError::StorageError)?;
+        Ok(Sha256::new()
+            .chain_update(prev_hash)
+            .chain_update(json)
+            .finalize()
+            .into())
+    }
+}
+
+/// State of the log as last seen by this process
+#[derive(Clone, Copy)]
+struct Tail {
+    len: u64,
+    hash: [u8; 32],
+}
+
+/// Append-only [`AuditSink`] writing one JSON record per line.
+///
+/// Each record holds the hash of the previous one, so removing or altering
+/// records is detected by [`FileAuditLog::read`].
+pub struct FileAuditLog {
+    path: PathBuf,
+    tail: Mutex<Tail>,
+}
+
+impl FileAuditLog {
+    /// Open the log at `path`, creating it if needed, and check its records.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let p = path.clone();
+        let tail = task::spawn_blocking(move || Self::load(&p))
+            .await
+            .map_err(map_join_err)??
+            .1;
+        Ok(Self {
+            path,
+            tail: Mutex::new(tail),
+        })
+    }
+
+    /// Read the records of the log at `path`, failing if they were altered.
+    pub async fn read(path: impl AsRef<Path>) -> Result<Vec<AuditRecord>> {
+        let path = path.as_ref().to_path_buf();
+        let (records, _) = task::spawn_blocking(move || Self::load(&path))
+            .await
+            .map_err(map_join_err)??;
+        Ok(records)
+    }
+
+    fn load(path: &Path) -> Result<(Vec<AuditRecord>, Tail)> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let tail = Tail {
+                    len: 0,
+                    hash: GENESIS_HASH,
+                };
+                return Ok((Vec::new(), tail));
+            }
+            Err(e) => return Err(map_io_err(e)),
+        };
+        file.lock_shared().map_err(map_io_err)?;
+        let loaded = Self::read_records(&file)?;
+        file.unlock().map_err(map_io_err)?;
+        Ok(loaded)
+    }
+
+    /// Read the records of a locked log file, checking their hashes
+    fn read_records(file: &File) -> Result<(Vec<AuditRecord>, Tail)> {
+        let len = file.metadata().map_err(map_io_err)?.len();
+        let mut records = Vec::new();
+        let mut hash = GENESIS_HASH;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(map_io_err)?;
+            let record: AuditRecord =
+                serde_json::from_str(&line).map_err(|_| VaultError::InvalidAuditLog)?;
+            hash = record.compute_hash(&hash)?;
+            if record.hash != hex::encode(hash) {
+                return Err(VaultError::InvalidAuditLog.into());
+            }
+            records.push(record);
+        }
+        Ok((records, Tail { len, hash }))
+    }
+
+    /// Append a record and return the new tail of the log
+    fn append(path: &Path, mut tail: Tail, mut record: AuditRecord) -> Result<Tail> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .map_err(map_io_err)?;
+        file.lock_exclusive().map_err(map_io_err)?;
+
+        // Another process may have appended records since we last looked
+        if file.metadata().map_err(map_io_err)?.len() != tail.len {
+            tail = Self::read_records(&file)?.1;
+        }
+
+        let hash = record.compute_hash(&tail.hash)?;
+        record.hash = hex::encode(hash);
+        let mut line = serde_json::to_vec(&record).map_err(|_| VaultError::StorageError)?;
+        line.push(b'\n');
+        file.write_all(&line).map_err(map_io_err)?;
+        file.sync_data().map_err(map_io_err)?;
+        file.unlock().map_err(map_io_err)?;
+
+        Ok(Tail {
+            len: tail.len + line.len() as u64,
+            hash,
+        })
+    }
+}
+
+#[async_trait]
+impl AuditSink for FileAuditLog {
+    async fn record(&self, event: AuditEvent) -> Result<()> {
+        let record = AuditRecord {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            operation: event.operation,
+            key_id: event.key_id,
+            attributes: event.attributes,
+            caller: event.caller,
+            hash: String::new(),
+        };
+        let mut tail = self.tail.lock().await;
+        let (path, current) = (self.path.clone(), *tail);
+        *tail = task::spawn_blocking(move || Self::append(&path, current, record))
+            .await
+            .map_err(map_join_err)??;
+        Ok(())
+    }
+}
+
+fn map_join_err(err: JoinError) -> Error {
+    Error::new(Origin::Application, Kind::Io, err)
+}
+fn map_io_err(err: std::io::Error) -> Error {
+    Error::new(Origin::Application, Kind::Io, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::compat::rand::{thread_rng, RngCore};
+    use ockam_core::vault::{SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32};
+
+    fn temp_path() -> PathBuf {
+        let mut rand_id = [0u8; 32];
+        thread_rng().fill_bytes(&mut rand_id);
+        std::env::temp_dir().join(hex::encode(rand_id))
+    }
+
+    fn event(operation: AuditOperation) -> AuditEvent {
+        let attributes = SecretAttributes::new(
+            SecretType::Ed25519,
+            SecretPersistence::Persistent,
+            CURVE25519_SECRET_LENGTH_U32,
+        );
+        AuditEvent::new(operation, "key".into(), attributes, Some("test".into()))
+    }
+
+    #[tokio::test]
+    async fn hash_chain() {
+        let path = temp_path();
+        let log = FileAuditLog::open(&path).await.unwrap();
+        log.record(event(AuditOperation::Generate)).await.unwrap();
+        log.record(event(AuditOperation::Sign)).await.unwrap();
+
+        // A second writer continues the same chain
+        let other = FileAuditLog::open(&path).await.unwrap();
+        other.record(event(AuditOperation::Destroy)).await.unwrap();
+        log.record(event(AuditOperation::Export)).await.unwrap();
+
+        let records = FileAuditLog::read(&path).await.unwrap();
+        let operations: Vec<_> = records.iter().map(|r| r.operation()).collect();
+        assert_eq!(
+            operations,
+            [
+                AuditOperation::Generate,
+                AuditOperation::Sign,
+                AuditOperation::Destroy,
+                AuditOperation::Export
+            ]
+        );
+        assert_eq!(records[0].caller(), Some("test"));
+
+        // Removing a record breaks the chain
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        std::fs::write(&path, [lines[0], lines[2], lines[3]].join("\n")).unwrap();
+        assert!(FileAuditLog::read(&path).await.is_err());
+    }
+}
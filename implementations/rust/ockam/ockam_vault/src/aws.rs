@@ -1,11 +1,12 @@
+use crate::kms;
 use aws_sdk_kms::error::{CreateKeyError, GetPublicKeyError, SignError, VerifyError};
 use aws_sdk_kms::error::{ScheduleKeyDeletionError, ScheduleKeyDeletionErrorKind};
 use aws_sdk_kms::model::{KeySpec, KeyUsageType, MessageType, SigningAlgorithmSpec};
 use aws_sdk_kms::types::{Blob, SdkError};
 use aws_sdk_kms::Client;
 use ockam_core::vault::SecretType;
-use ockam_core::vault::{KeyId, PublicKey, Signature};
-use ockam_core::Result;
+use ockam_core::vault::{KeyId, PublicKey, SecretKey, Signature};
+use ockam_core::{async_trait, compat::boxed::Box, Result};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 use tracing as log;
@@ -43,9 +44,12 @@ impl Kms {
     pub async fn default() -> Result<Self> {
         Self::new(Config::default()).await
     }
+}
 
+#[async_trait]
+impl kms::Kms for Kms {
     /// Create a new NIST P-256 key-pair in AWS KMS and return its ID.
-    pub async fn create_key(&self) -> Result<KeyId> {
+    async fn create_key(&self) -> Result<KeyId> {
         log::trace!("create new key");
         let mut client = self
             .client
@@ -69,8 +73,13 @@ impl Kms {
         Err(Error::MissingKeyId.into())
     }
 
+    /// Key material of asymmetric AWS KMS keys can't be imported.
+    async fn import_key(&self, _key: &SecretKey) -> Result<KeyId> {
+        Err(Error::UnsupportedImport.into())
+    }
+
     /// Have AWS KMS schedule key deletion.
-    pub async fn delete_key(&self, kid: &KeyId) -> Result<bool> {
+    async fn delete_key(&self, kid: &KeyId) -> Result<bool> {
         log::trace!(%kid, "schedule key for deletion");
         const DAYS: i32 = 7;
         let client = self
@@ -101,7 +110,7 @@ impl Kms {
     }
 
     /// Get the public key part of a AWS KMS key-pair.
-    pub async fn public_key(&self, kid: &KeyId) -> Result<PublicKey> {
+    async fn public_key(&self, kid: &KeyId) -> Result<PublicKey> {
         log::trace!(%kid, "get public key");
         let output = self
             .client
@@ -133,7 +142,7 @@ impl Kms {
     }
 
     /// Have AWS KMS verify a message signature.
-    pub async fn verify(&self, kid: &KeyId, msg: &[u8], sig: &Signature) -> Result<bool> {
+    async fn verify(&self, kid: &KeyId, msg: &[u8], sig: &Signature) -> Result<bool> {
         log::trace!(%kid, "verify message signature");
         let client = self
             .client
@@ -156,7 +165,7 @@ impl Kms {
     }
 
     /// Have AWS KMS sign a message.
-    pub async fn sign(&self, kid: &KeyId, msg: &[u8]) -> Result<Signature> {
+    async fn sign(&self, kid: &KeyId, msg: &[u8]) -> Result<Signature> {
         log::trace!(%kid, "sign message");
         let client = self
             .client
@@ -219,6 +228,8 @@ enum Error {
     MissingSignature,
     #[error("key type is not supported")]
     UnsupportedKeyType,
+    #[error("aws kms can't import key-pairs")]
+    UnsupportedImport,
 }
 
 impl From<Error> for ockam_core::Error {
@@ -231,6 +242,7 @@ impl From<Error> for ockam_core::Error {
 #[cfg(test)]
 mod tests {
     use super::Kms;
+    use crate::kms::Kms as _;
     use crate::Vault;
     use ockam_core::vault::{Signer, Verifier};
     use ockam_node::tokio;
@@ -43,6 +43,28 @@ pub enum VaultError {
     StorageError,
     /// Invalid Storage data
     InvalidStorageData,
+    /// Invalid ChaCha20-Poly1305 key length
+    InvalidChaCha20Poly1305KeyLength,
+    /// ChaCha20-Poly1305 encryption failed
+    AeadChaCha20Poly1305Encrypt,
+    /// ChaCha20-Poly1305 decryption failed
+    AeadChaCha20Poly1305Decrypt,
+    /// Invalid Kyber key or ciphertext length
+    InvalidKyberLength,
+    /// Kyber encapsulation failed
+    KyberEncapsulate,
+    /// Kyber decapsulation failed
+    KyberDecapsulate,
+    /// Storage is encrypted and no key was given
+    StorageLocked,
+    /// Wrong passphrase or keyfile for an encrypted storage
+    InvalidStorageKey,
+    /// A copied secret got a different key id
+    KeyIdMismatch,
+    /// The secret may not be used for this operation
+    InvalidKeyUsage,
+    /// Records of an audit log were altered or removed
+    InvalidAuditLog,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -67,6 +89,21 @@ impl core::fmt::Display for VaultError {
             Self::InvalidSecretAttributes => write!(f, "invalid secret attributes"),
             Self::StorageError => write!(f, "invalid storage"),
             Self::InvalidStorageData => write!(f, "invalid storage data"),
+            Self::InvalidChaCha20Poly1305KeyLength => {
+                write!(f, "invalid ChaCha20-Poly1305 key length")
+            }
+            Self::AeadChaCha20Poly1305Encrypt => write!(f, "chacha20-poly1305 encryption failed"),
+            Self::AeadChaCha20Poly1305Decrypt => write!(f, "chacha20-poly1305 decryption failed"),
+            Self::InvalidKyberLength => write!(f, "invalid Kyber key or ciphertext length"),
+            Self::KyberEncapsulate => write!(f, "Kyber encapsulation failed"),
+            Self::KyberDecapsulate => write!(f, "Kyber decapsulation failed"),
+            Self::StorageLocked => {
+                write!(f, "storage is encrypted, a passphrase or keyfile is needed")
+            }
+            Self::InvalidStorageKey => write!(f, "wrong passphrase or keyfile for the storage"),
+            Self::KeyIdMismatch => write!(f, "copied secret has a different key id"),
+            Self::InvalidKeyUsage => write!(f, "secret may not be used for this operation"),
+            Self::InvalidAuditLog => write!(f, "audit log records were altered or removed"),
         }
     }
 }
@@ -80,9 +117,12 @@ impl From<VaultError> for Error {
             | InvalidPublicKey
             | InvalidKeyType
             | InvalidAesKeyLength
+            | InvalidChaCha20Poly1305KeyLength
+            | InvalidKyberLength
             | InvalidHkdfOutputType
             | InvalidPrivateKeyLen
-            | InvalidX25519SecretLength => Kind::Misuse,
+            | InvalidX25519SecretLength
+            | InvalidKeyUsage => Kind::Misuse,
             UnknownEcdhKeyType | EntryNotFound | SecretNotFound => Kind::NotFound,
             _ => Kind::Invalid,
         };
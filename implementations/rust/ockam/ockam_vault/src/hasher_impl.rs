@@ -3,8 +3,8 @@ use crate::VaultError;
 use arrayref::array_ref;
 use ockam_core::compat::vec::Vec;
 use ockam_core::vault::{
-    Hasher, KeyId, Secret, SecretAttributes, SecretKey, SecretType, SecretVault,
-    AES128_SECRET_LENGTH_USIZE, AES256_SECRET_LENGTH_USIZE,
+    Hasher, KeyId, Secret, SecretAttributes, SecretKey, SecretType, SecretUsage, SecretVault,
+    AES128_SECRET_LENGTH_USIZE, AES256_SECRET_LENGTH_USIZE, CHACHA20POLY1305_SECRET_LENGTH_USIZE,
 };
 use ockam_core::{async_trait, compat::boxed::Box, Result};
 use sha2::{Digest, Sha256};
@@ -36,6 +36,7 @@ impl Hasher for Vault {
         let ikm: Result<&[u8]> = match ikm {
             Some(ikm) => {
                 let ikm = entries.get(ikm).ok_or(VaultError::EntryNotFound)?;
+                Self::check_usage(ikm, SecretUsage::DERIVE)?;
                 if ikm.key_attributes().stype() == SecretType::Buffer {
                     Ok(ikm.secret().try_as_key()?.as_ref())
                 } else {
@@ -48,6 +49,7 @@ impl Hasher for Vault {
         let ikm = ikm?;
 
         let salt = entries.get(salt).ok_or(VaultError::EntryNotFound)?;
+        Self::check_usage(salt, SecretUsage::DERIVE)?;
 
         if salt.key_attributes().stype() != SecretType::Buffer {
             return Err(VaultError::InvalidKeyType.into());
@@ -77,6 +79,10 @@ impl Hasher for Vault {
                 if length != AES256_SECRET_LENGTH_USIZE && length != AES128_SECRET_LENGTH_USIZE {
                     return Err(VaultError::InvalidAesKeyLength.into());
                 }
+            } else if attributes.stype() == SecretType::ChaCha20Poly1305 {
+                if length != CHACHA20POLY1305_SECRET_LENGTH_USIZE {
+                    return Err(VaultError::InvalidChaCha20Poly1305KeyLength.into());
+                }
             } else if attributes.stype() != SecretType::Buffer {
                 return Err(VaultError::InvalidHkdfOutputType.into());
             }
@@ -0,0 +1,114 @@
+use crate::audit::AuditOperation;
+use crate::{Vault, VaultError};
+use ockam_core::compat::rand::thread_rng;
+use ockam_core::compat::vec::Vec;
+use ockam_core::vault::{
+    KeyId, PublicKey, Secret, SecretAttributes, SecretKey, SecretPersistence, SecretType,
+    SecretUsage, SecretVault, KYBER768_CIPHERTEXT_LENGTH_USIZE, KYBER768_PUBLIC_LENGTH_USIZE,
+    KYBER768_SECRET_LENGTH_USIZE, KYBER768_SHARED_SECRET_LENGTH_U32,
+};
+use ockam_core::Result;
+
+/// Offset of the encapsulation key inside of a Kyber768 decapsulation
+/// key, which is laid out as `dk_pke || ek || H(ek) || z`
+const KYBER768_PUBLIC_OFFSET: usize =
+    KYBER768_SECRET_LENGTH_USIZE - KYBER768_PUBLIC_LENGTH_USIZE - 64;
+
+/// Generate a Kyber768 decapsulation key
+pub(crate) fn kyber768_keypair() -> Result<Vec<u8>> {
+    let keypair =
+        pqc_kyber::keypair(&mut thread_rng()).map_err(|_| VaultError::KyberEncapsulate)?;
+    Ok(keypair.secret.to_vec())
+}
+
+/// Extract the encapsulation key of a Kyber768 decapsulation key
+pub(crate) fn kyber768_public_key(secret: &[u8]) -> Result<PublicKey> {
+    if secret.len() != KYBER768_SECRET_LENGTH_USIZE {
+        return Err(VaultError::InvalidKyberLength.into());
+    }
+    let public =
+        &secret[KYBER768_PUBLIC_OFFSET..KYBER768_PUBLIC_OFFSET + KYBER768_PUBLIC_LENGTH_USIZE];
+    Ok(PublicKey::new(public.to_vec(), SecretType::Kyber768))
+}
+
+impl Vault {
+    async fn import_shared_secret(&self, shared_secret: &[u8]) -> Result<KeyId> {
+        let attributes = SecretAttributes::new(
+            SecretType::Buffer,
+            SecretPersistence::Ephemeral,
+            KYBER768_SHARED_SECRET_LENGTH_U32,
+        );
+        self.secret_import(
+            Secret::Key(SecretKey::new(shared_secret.to_vec())),
+            attributes,
+        )
+        .await
+    }
+
+    /// Kyber768 encapsulation, see [`AsymmetricVault::kem_encapsulate`]
+    ///
+    /// [`AsymmetricVault::kem_encapsulate`]: ockam_core::vault::AsymmetricVault::kem_encapsulate
+    pub(crate) async fn kyber768_encapsulate(
+        &self,
+        peer_public_key: &PublicKey,
+    ) -> Result<(Vec<u8>, KeyId)> {
+        if peer_public_key.stype() != SecretType::Kyber768
+            || peer_public_key.data().len() != KYBER768_PUBLIC_LENGTH_USIZE
+        {
+            return Err(VaultError::InvalidPublicKey.into());
+        }
+
+        let (ciphertext, shared_secret) =
+            pqc_kyber::encapsulate(peer_public_key.data(), &mut thread_rng())
+                .map_err(|_| VaultError::KyberEncapsulate)?;
+        let shared_secret = self.import_shared_secret(&shared_secret).await?;
+
+        Ok((ciphertext.to_vec(), shared_secret))
+    }
+
+    /// Kyber768 decapsulation, see [`AsymmetricVault::kem_decapsulate`]
+    ///
+    /// [`AsymmetricVault::kem_decapsulate`]: ockam_core::vault::AsymmetricVault::kem_decapsulate
+    pub(crate) async fn kyber768_decapsulate(
+        &self,
+        secret: &KeyId,
+        ciphertext: &[u8],
+    ) -> Result<KeyId> {
+        if ciphertext.len() != KYBER768_CIPHERTEXT_LENGTH_USIZE {
+            return Err(VaultError::InvalidKyberLength.into());
+        }
+
+        self.preload_from_storage(secret).await;
+        let (attributes, shared_secret) = {
+            let entries = self.data.entries.read().await;
+            let entry = entries.get(secret).ok_or(VaultError::EntryNotFound)?;
+            Self::check_usage(entry, SecretUsage::ECDH)?;
+            if entry.key_attributes().stype() != SecretType::Kyber768 {
+                return Err(VaultError::InvalidKeyType.into());
+            }
+            let key = entry.secret().try_as_key()?.as_ref();
+            if key.len() != KYBER768_SECRET_LENGTH_USIZE {
+                return Err(VaultError::InvalidKyberLength.into());
+            }
+            let shared_secret = pqc_kyber::decapsulate(ciphertext, key)
+                .map_err(|_| VaultError::KyberDecapsulate)?;
+            (entry.key_attributes(), shared_secret)
+        };
+
+        self.audit(AuditOperation::Ecdh, secret, attributes).await?;
+
+        self.import_shared_secret(&shared_secret).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vault;
+
+    fn new_vault() -> Vault {
+        Vault::default()
+    }
+
+    #[ockam_macros::vault_test]
+    fn kem_encapsulate_decapsulate_kyber768() {}
+}
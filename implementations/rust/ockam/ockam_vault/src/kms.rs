@@ -0,0 +1,46 @@
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::vault::{KeyId, PublicKey, SecretKey, Signature};
+use ockam_core::{async_trait, compat::boxed::Box, Error, Result};
+
+#[cfg(feature = "rustcrypto")]
+mod mock;
+#[cfg(feature = "rustcrypto")]
+pub use mock::MockKms;
+
+/// A key management service holding NIST P-256 key-pairs.
+///
+/// Once enabled with [`crate::Vault::enable_kms`], persistent NIST P-256
+/// secrets of the vault are created in the KMS and referred to by
+/// [`ockam_core::vault::Secret::Kms`] entries, whatever the KMS is.
+#[async_trait]
+pub trait Kms: Send + Sync + 'static {
+    /// Create a new key-pair and return its ID.
+    async fn create_key(&self) -> Result<KeyId>;
+
+    /// Import a key-pair from a DER encoded PKCS#8 private key and return its ID.
+    async fn import_key(&self, key: &SecretKey) -> Result<KeyId>;
+
+    /// Delete a key-pair, returning false if it does not exist.
+    async fn delete_key(&self, kid: &KeyId) -> Result<bool>;
+
+    /// Get the public key part of a key-pair, as a DER encoded SubjectPublicKeyInfo.
+    async fn public_key(&self, kid: &KeyId) -> Result<PublicKey>;
+
+    /// Verify a DER encoded ECDSA signature of a message.
+    async fn verify(&self, kid: &KeyId, msg: &[u8], sig: &Signature) -> Result<bool>;
+
+    /// Sign a message, returning a DER encoded ECDSA signature.
+    async fn sign(&self, kid: &KeyId, msg: &[u8]) -> Result<Signature>;
+
+    /// Compute an ECDH shared secret with a DER encoded peer public key.
+    ///
+    /// KMSs which do not support key agreement keep this default, which
+    /// returns an error with [`Kind::Unsupported`].
+    async fn ecdh(&self, _kid: &KeyId, _peer: &PublicKey) -> Result<SecretKey> {
+        Err(Error::new(
+            Origin::Vault,
+            Kind::Unsupported,
+            "the KMS does not support ECDH",
+        ))
+    }
+}
@@ -0,0 +1,143 @@
+use super::Kms;
+use crate::error::{from_ecdsa, from_pkcs8};
+use crate::VaultError;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::sync::Arc;
+use ockam_core::vault::{KeyId, PublicKey, SecretKey, SecretType, Signature};
+use ockam_core::{async_trait, compat::boxed::Box, Result};
+use ockam_node::compat::asynchronous::RwLock;
+use p256::ecdsa::signature::{Signer, Verifier};
+use p256::ecdsa::SigningKey;
+use p256::pkcs8::{DecodePrivateKey, EncodePublicKey};
+
+/// In-process [`Kms`] keeping its keys in memory, to test KMS-backed
+/// vaults without credentials for a real service.
+#[derive(Default, Clone)]
+pub struct MockKms {
+    keys: Arc<RwLock<BTreeMap<KeyId, SigningKey>>>,
+}
+
+impl MockKms {
+    /// Create an empty KMS
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of key-pairs held by the KMS
+    pub async fn len(&self) -> usize {
+        self.keys.read().await.len()
+    }
+
+    /// Whether the KMS holds no key-pair
+    pub async fn is_empty(&self) -> bool {
+        self.keys.read().await.is_empty()
+    }
+
+    async fn insert(&self, key: SigningKey) -> KeyId {
+        let mut id = [0u8; 16];
+        thread_rng().fill_bytes(&mut id);
+        let kid = hex::encode(id);
+        self.keys.write().await.insert(kid.clone(), key);
+        kid
+    }
+}
+
+#[async_trait]
+impl Kms for MockKms {
+    async fn create_key(&self) -> Result<KeyId> {
+        Ok(self.insert(SigningKey::random(thread_rng())).await)
+    }
+
+    async fn import_key(&self, key: &SecretKey) -> Result<KeyId> {
+        let key = SigningKey::from_pkcs8_der(key.as_ref()).map_err(from_pkcs8)?;
+        Ok(self.insert(key).await)
+    }
+
+    async fn delete_key(&self, kid: &KeyId) -> Result<bool> {
+        Ok(self.keys.write().await.remove(kid).is_some())
+    }
+
+    async fn public_key(&self, kid: &KeyId) -> Result<PublicKey> {
+        let keys = self.keys.read().await;
+        let key = keys.get(kid).ok_or(VaultError::EntryNotFound)?;
+        let der = key
+            .verifying_key()
+            .to_public_key_der()
+            .map_err(from_pkcs8)?;
+        Ok(PublicKey::new(der.as_ref().to_vec(), SecretType::NistP256))
+    }
+
+    async fn verify(&self, kid: &KeyId, msg: &[u8], sig: &Signature) -> Result<bool> {
+        let keys = self.keys.read().await;
+        let key = keys.get(kid).ok_or(VaultError::EntryNotFound)?;
+        let sig = p256::ecdsa::Signature::from_der(sig.as_ref()).map_err(from_ecdsa)?;
+        Ok(key.verifying_key().verify(msg, &sig).is_ok())
+    }
+
+    async fn sign(&self, kid: &KeyId, msg: &[u8]) -> Result<Signature> {
+        let keys = self.keys.read().await;
+        let key = keys.get(kid).ok_or(VaultError::EntryNotFound)?;
+        let sig: p256::ecdsa::Signature = key.sign(msg);
+        Ok(Signature::new(sig.to_der().as_bytes().to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockKms;
+    use crate::kms::Kms;
+    use crate::Vault;
+    use ockam_core::vault::{
+        SecretAttributes, SecretPersistence, SecretType, SecretVault, Signer, Verifier,
+        NISTP256_SECRET_LENGTH_U32,
+    };
+
+    fn attributes(persistence: SecretPersistence) -> SecretAttributes {
+        SecretAttributes::new(
+            SecretType::NistP256,
+            persistence,
+            NISTP256_SECRET_LENGTH_U32,
+        )
+    }
+
+    #[tokio::test]
+    async fn sign_with_kms_verify_locally() {
+        let kms = MockKms::new();
+        let kid = kms.create_key().await.unwrap();
+        let msg = b"hello world";
+        let sig = kms.sign(&kid, &msg[..]).await.unwrap();
+        assert!(kms.verify(&kid, &msg[..], &sig).await.unwrap());
+        let pky = kms.public_key(&kid).await.unwrap();
+        assert!(Vault::create().verify(&sig, &pky, msg).await.unwrap());
+        assert!(kms.delete_key(&kid).await.unwrap());
+        assert!(!kms.delete_key(&kid).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn vault_keys_in_kms() {
+        let kms = MockKms::new();
+        let mut vault = Vault::create();
+        vault.enable_kms(kms.clone());
+
+        let kid = vault
+            .secret_generate(attributes(SecretPersistence::Persistent))
+            .await
+            .unwrap();
+        assert_eq!(kms.len().await, 1);
+        let msg = b"hello world";
+        let sig = vault.sign(&kid, &msg[..]).await.unwrap();
+        let pky = vault.secret_public_key_get(&kid).await.unwrap();
+        assert!(vault.verify(&sig, &pky, msg).await.unwrap());
+
+        // Ephemeral keys stay in the vault
+        vault
+            .secret_generate(attributes(SecretPersistence::Ephemeral))
+            .await
+            .unwrap();
+        assert_eq!(kms.len().await, 1);
+
+        vault.secret_destroy(kid).await.unwrap();
+        assert!(kms.is_empty().await)
+    }
+}
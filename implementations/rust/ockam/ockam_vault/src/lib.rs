@@ -22,8 +22,10 @@ extern crate alloc;
 pub use ockam_core;
 
 mod asymmetric_impl;
+pub mod audit;
 mod error;
 mod hasher_impl;
+mod kem_impl;
 mod secret_impl;
 mod signer_impl;
 
@@ -31,6 +33,13 @@ mod signer_impl;
 #[cfg(feature = "aws")]
 pub mod aws;
 
+/// Key management services
+pub mod kms;
+
+/// PKCS#11
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+
 /// Storage
 #[cfg(feature = "storage")]
 pub mod storage;
@@ -48,6 +57,7 @@ pub use ockam_core::vault::{
 pub use asymmetric_impl::*;
 pub use error::*;
 pub use hasher_impl::*;
+pub use kem_impl::*;
 pub use secret_impl::*;
 pub use signer_impl::*;
 pub use symmetric_impl::*;
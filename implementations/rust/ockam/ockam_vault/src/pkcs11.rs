@@ -0,0 +1,515 @@
+use crate::kms;
+use cryptoki::context::{CInitializeArgs, Pkcs11 as Context};
+use cryptoki::error::RvError;
+use cryptoki::mechanism::elliptic_curve::{EcKdf, Ecdh1DeriveParams};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::vault::{KeyId, PublicKey, SecretKey, SecretType, Signature};
+use ockam_core::{async_trait, compat::boxed::Box, Result};
+use ockam_node::tokio::task::{self, JoinError};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePublicKey};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tracing as log;
+
+/// DER encoding of the OID of NIST P-256 (prime256v1)
+const P256_PARAMS: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+/// Length of the uncompressed SEC1 encoding of a NIST P-256 point
+const P256_POINT_LENGTH: usize = 65;
+const KEY_ID_LENGTH: usize = 16;
+const ECDH_SECRET_LENGTH: u64 = 32;
+
+/// PKCS#11 configuration.
+#[derive(Debug, Clone)]
+pub struct Config {
+    module: PathBuf,
+    slot: u64,
+    pin: Option<String>,
+}
+
+impl Config {
+    /// Use the token in `slot` of the PKCS#11 library at `module`,
+    /// e.g. `/usr/lib/softhsm/libsofthsm2.so`.
+    pub fn new(module: impl Into<PathBuf>, slot: u64) -> Self {
+        Self {
+            module: module.into(),
+            slot,
+            pin: None,
+        }
+    }
+
+    /// Log into the token as a user with this PIN.
+    pub fn pin(mut self, pin: impl Into<String>) -> Self {
+        self.pin = Some(pin.into());
+        self
+    }
+}
+
+/// PKCS#11 client.
+///
+/// Keys live on the token and are referred to by their `CKA_ID`, hex
+/// encoded. Only NIST P-256 keys are supported.
+#[derive(Clone)]
+pub struct Pkcs11 {
+    // The session has to be dropped before the library is finalized
+    session: Arc<Mutex<Session>>,
+    _context: Arc<Context>,
+}
+
+impl core::fmt::Debug for Pkcs11 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Pkcs11").finish()
+    }
+}
+
+impl Pkcs11 {
+    /// Load the PKCS#11 library and open a session with the configured token.
+    pub fn new(c: Config) -> Result<Self> {
+        log::trace!(module = %c.module.display(), slot = %c.slot, "open session");
+        let context = Context::new(&c.module).map_err(Error::Load)?;
+        context
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(Error::Load)?;
+        let slot = context
+            .get_slots_with_token()
+            .map_err(Error::Session)?
+            .into_iter()
+            .find(|s| s.id() == c.slot)
+            .ok_or(Error::MissingSlot(c.slot))?;
+        let session = context.open_rw_session(slot).map_err(Error::Session)?;
+        if let Some(pin) = c.pin {
+            session
+                .login(UserType::User, Some(&AuthPin::new(pin)))
+                .map_err(Error::Session)?;
+        }
+        log::debug!(slot = %c.slot, "opened session");
+        Ok(Self {
+            session: Arc::new(Mutex::new(session)),
+            _context: Arc::new(context),
+        })
+    }
+
+    /// Run `f` with the session on a thread where blocking is allowed, as
+    /// the calls into the PKCS#11 library wait for the token to answer.
+    async fn with_session<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&Session) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let session = self.session.clone();
+        let run = move || {
+            let session = session.lock().map_err(|_| Error::Poisoned)?;
+            f(&session)
+        };
+        task::spawn_blocking(run).await.map_err(Error::Task)?
+    }
+
+    /// Find the public or private key object with the given ID.
+    fn find(session: &Session, kid: &KeyId, class: ObjectClass) -> Result<ObjectHandle> {
+        let id = hex::decode(kid).map_err(|_| Error::MissingKey(kid.clone()))?;
+        let template = [Attribute::Class(class), Attribute::Id(id)];
+        session
+            .find_objects(&template)
+            .map_err(Error::Session)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::MissingKey(kid.clone()).into())
+    }
+
+    /// Uncompressed SEC1 point of a public key object.
+    async fn ec_point(&self, kid: &KeyId) -> Result<Vec<u8>> {
+        let id = kid.clone();
+        let attributes = self
+            .with_session(move |session| {
+                let handle = Self::find(session, &id, ObjectClass::PUBLIC_KEY)?;
+                session
+                    .get_attributes(handle, &[AttributeType::EcPoint])
+                    .map_err(|err| Error::Session(err).into())
+            })
+            .await?;
+        match attributes.as_slice() {
+            // CKA_EC_POINT holds a DER octet string, but some tokens omit the header
+            [Attribute::EcPoint(point)] if point.len() == P256_POINT_LENGTH => Ok(point.clone()),
+            [Attribute::EcPoint(point)] if point.len() == P256_POINT_LENGTH + 2 => {
+                Ok(point[2..].to_vec())
+            }
+            _ => {
+                log::error!(%kid, "key type not supported to get a public key");
+                Err(Error::UnsupportedKeyType.into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl kms::Kms for Pkcs11 {
+    /// Create a new NIST P-256 key-pair on the token and return its ID.
+    async fn create_key(&self) -> Result<KeyId> {
+        log::trace!("create new key");
+        let mut id = [0u8; KEY_ID_LENGTH];
+        thread_rng().fill_bytes(&mut id);
+        let public = [
+            Attribute::Token(true),
+            Attribute::Private(false),
+            Attribute::Verify(true),
+            Attribute::EcParams(P256_PARAMS.to_vec()),
+            Attribute::Id(id.to_vec()),
+        ];
+        let private = [
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+            Attribute::Derive(true),
+            Attribute::Id(id.to_vec()),
+        ];
+        self.with_session(move |session| {
+            session
+                .generate_key_pair(&Mechanism::EccKeyPairGen, &public, &private)
+                .map_err(|err| {
+                    log::error!(%err, "failed to create new key");
+                    Error::Create(err).into()
+                })
+        })
+        .await?;
+        let kid = hex::encode(id);
+        log::debug!(%kid, "created new key");
+        Ok(kid)
+    }
+
+    /// Import a key-pair from a DER encoded PKCS#8 private key and return its ID.
+    async fn import_key(&self, key: &SecretKey) -> Result<KeyId> {
+        log::trace!("import key");
+        let sk =
+            p256::SecretKey::from_pkcs8_der(key.as_ref()).map_err(|_| Error::UnsupportedKeyType)?;
+        let point = sk.public_key().to_encoded_point(false);
+        // CKA_EC_POINT is a DER octet string
+        let mut ec_point = vec![0x04, point.len() as u8];
+        ec_point.extend_from_slice(point.as_bytes());
+        let mut id = [0u8; KEY_ID_LENGTH];
+        thread_rng().fill_bytes(&mut id);
+        let public = [
+            Attribute::Class(ObjectClass::PUBLIC_KEY),
+            Attribute::KeyType(KeyType::EC),
+            Attribute::Token(true),
+            Attribute::Private(false),
+            Attribute::Verify(true),
+            Attribute::EcParams(P256_PARAMS.to_vec()),
+            Attribute::EcPoint(ec_point),
+            Attribute::Id(id.to_vec()),
+        ];
+        let private = [
+            Attribute::Class(ObjectClass::PRIVATE_KEY),
+            Attribute::KeyType(KeyType::EC),
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+            Attribute::Derive(true),
+            Attribute::EcParams(P256_PARAMS.to_vec()),
+            Attribute::Value(sk.to_be_bytes().to_vec()),
+            Attribute::Id(id.to_vec()),
+        ];
+        self.with_session(move |session| {
+            for template in [&public[..], &private[..]] {
+                session.create_object(template).map_err(|err| {
+                    log::error!(%err, "failed to import key");
+                    Error::Create(err)
+                })?;
+            }
+            Ok(())
+        })
+        .await?;
+        let kid = hex::encode(id);
+        log::debug!(%kid, "imported key");
+        Ok(kid)
+    }
+
+    /// Destroy a key-pair on the token.
+    async fn delete_key(&self, kid: &KeyId) -> Result<bool> {
+        log::trace!(%kid, "destroy key");
+        let id = kid.clone();
+        let found = self
+            .with_session(move |session| {
+                let mut found = false;
+                for class in [ObjectClass::PRIVATE_KEY, ObjectClass::PUBLIC_KEY] {
+                    if let Ok(handle) = Self::find(session, &id, class) {
+                        session.destroy_object(handle).map_err(|err| {
+                            log::error!(kid = %id, %err, "failed to destroy key");
+                            Error::Delete {
+                                keyid: id.to_string(),
+                                error: err,
+                            }
+                        })?;
+                        found = true;
+                    }
+                }
+                Ok(found)
+            })
+            .await?;
+        if !found {
+            log::debug!(%kid, "key does not exist");
+        }
+        Ok(found)
+    }
+
+    /// Get the public key part of a key-pair, as a DER encoded SubjectPublicKeyInfo.
+    async fn public_key(&self, kid: &KeyId) -> Result<PublicKey> {
+        log::trace!(%kid, "get public key");
+        let point = self.ec_point(kid).await?;
+        let pk = p256::PublicKey::from_sec1_bytes(&point).map_err(|_| Error::UnsupportedKeyType)?;
+        let der = pk
+            .to_public_key_der()
+            .map_err(|_| Error::UnsupportedKeyType)?;
+        log::debug!(%kid, "received public key");
+        Ok(PublicKey::new(der.as_ref().to_vec(), SecretType::NistP256))
+    }
+
+    /// Have the token verify a message signature.
+    async fn verify(&self, kid: &KeyId, msg: &[u8], sig: &Signature) -> Result<bool> {
+        log::trace!(%kid, "verify message signature");
+        // Tokens expect `r || s`, the software vault uses DER signatures
+        let sig = match p256::ecdsa::Signature::from_der(sig.as_ref()) {
+            Ok(sig) => sig,
+            Err(_) => return Ok(false),
+        };
+        let id = kid.clone();
+        let digest = Sha256::digest(msg);
+        let is_valid = self
+            .with_session(move |session| {
+                let handle = Self::find(session, &id, ObjectClass::PUBLIC_KEY)?;
+                match session.verify(&Mechanism::Ecdsa, handle, &digest, sig.as_ref()) {
+                    Ok(()) => Ok(true),
+                    Err(cryptoki::error::Error::Pkcs11(RvError::SignatureInvalid)) => Ok(false),
+                    Err(err) => {
+                        log::error!(kid = %id, %err, "failed to verify message signature");
+                        Err(Error::Verify {
+                            keyid: id.to_string(),
+                            error: err,
+                        }
+                        .into())
+                    }
+                }
+            })
+            .await?;
+        log::debug!(%kid, %is_valid, "verified message signature");
+        Ok(is_valid)
+    }
+
+    /// Have the token sign a message.
+    async fn sign(&self, kid: &KeyId, msg: &[u8]) -> Result<Signature> {
+        log::trace!(%kid, "sign message");
+        let id = kid.clone();
+        let digest = Sha256::digest(msg);
+        let sig = self
+            .with_session(move |session| {
+                let handle = Self::find(session, &id, ObjectClass::PRIVATE_KEY)?;
+                session
+                    .sign(&Mechanism::Ecdsa, handle, &digest)
+                    .map_err(|err| {
+                        log::error!(kid = %id, %err, "failed to sign message");
+                        Error::Sign {
+                            keyid: id.to_string(),
+                            error: err,
+                        }
+                        .into()
+                    })
+            })
+            .await?;
+        // Tokens return `r || s`, the software vault uses DER signatures
+        let sig = p256::ecdsa::Signature::try_from(sig.as_slice())
+            .map_err(|_| Error::MissingSignature)?;
+        log::debug!(%kid, "signed message");
+        Ok(Signature::new(sig.to_der().as_bytes().to_vec()))
+    }
+
+    /// Have the token compute an ECDH shared secret. Fails if the token
+    /// does not support `CKM_ECDH1_DERIVE` or does not let the derived
+    /// secret be extracted.
+    async fn ecdh(&self, kid: &KeyId, peer: &PublicKey) -> Result<SecretKey> {
+        log::trace!(%kid, "derive shared secret");
+        let peer = p256::PublicKey::from_public_key_der(peer.data())
+            .map_err(|_| Error::UnsupportedKeyType)?
+            .to_encoded_point(false);
+        let id = kid.clone();
+        let template = [
+            Attribute::Class(ObjectClass::SECRET_KEY),
+            Attribute::KeyType(KeyType::GENERIC_SECRET),
+            Attribute::ValueLen(ECDH_SECRET_LENGTH.into()),
+            Attribute::Token(false),
+            Attribute::Sensitive(false),
+            Attribute::Extractable(true),
+        ];
+        let value = self
+            .with_session(move |session| {
+                let handle = Self::find(session, &id, ObjectClass::PRIVATE_KEY)?;
+                let params = Ecdh1DeriveParams::new(EcKdf::null(), peer.as_bytes());
+                let derive = || {
+                    let mechanism = Mechanism::Ecdh1Derive(params);
+                    let secret = session.derive_key(&mechanism, handle, &template)?;
+                    let value = session.get_attributes(secret, &[AttributeType::Value]);
+                    session.destroy_object(secret)?;
+                    value
+                };
+                derive().map_err(|err| {
+                    log::error!(kid = %id, %err, "failed to derive shared secret");
+                    Error::Derive {
+                        keyid: id.to_string(),
+                        error: err,
+                    }
+                    .into()
+                })
+            })
+            .await?;
+        match value.as_slice() {
+            [Attribute::Value(value)] => {
+                log::debug!(%kid, "derived shared secret");
+                Ok(SecretKey::new(value.clone()))
+            }
+            _ => Err(Error::UnsupportedKeyType.into()),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+enum Error {
+    #[error("failed to load the pkcs11 module")]
+    Load(#[source] cryptoki::error::Error),
+    #[error("no token in slot {0}")]
+    MissingSlot(u64),
+    #[error("pkcs11 session error")]
+    Session(#[source] cryptoki::error::Error),
+    #[error("pkcs11 session lock is poisoned")]
+    Poisoned,
+    #[error("pkcs11 task failed")]
+    Task(#[source] JoinError),
+    #[error("pkcs11 error creating new key")]
+    Create(#[source] cryptoki::error::Error),
+    #[error("pkcs11 error verifying a message signature with key {keyid}")]
+    Verify {
+        keyid: String,
+        #[source]
+        error: cryptoki::error::Error,
+    },
+    #[error("pkcs11 error signing message with key {keyid}")]
+    Sign {
+        keyid: String,
+        #[source]
+        error: cryptoki::error::Error,
+    },
+    #[error("pkcs11 error deriving a shared secret with key {keyid}")]
+    Derive {
+        keyid: String,
+        #[source]
+        error: cryptoki::error::Error,
+    },
+    #[error("pkcs11 error destroying key {keyid}")]
+    Delete {
+        keyid: String,
+        #[source]
+        error: cryptoki::error::Error,
+    },
+    #[error("key {0} is not on the token")]
+    MissingKey(KeyId),
+    #[error("the token returned an invalid signature")]
+    MissingSignature,
+    #[error("key type is not supported")]
+    UnsupportedKeyType,
+}
+
+impl From<Error> for ockam_core::Error {
+    fn from(e: Error) -> Self {
+        use ockam_core::errcode::{Kind, Origin};
+        ockam_core::Error::new(Origin::Other, Kind::Io, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! These tests need a token, e.g. from SoftHSM2:
+    //!
+    //! ```sh
+    //! softhsm2-util --init-token --free --label ockam --so-pin 1234 --pin 1234
+    //! export OCKAM_PKCS11_MODULE=/usr/lib/softhsm/libsofthsm2.so
+    //! export OCKAM_PKCS11_SLOT=<slot printed by softhsm2-util>
+    //! export OCKAM_PKCS11_PIN=1234
+    //! cargo test -p ockam_vault --features pkcs11 -- --ignored pkcs11
+    //! ```
+    use super::{Config, Pkcs11};
+    use crate::kms::Kms;
+    use crate::Vault;
+    use ockam_core::vault::{
+        AsymmetricVault, SecretAttributes, SecretPersistence, SecretType, SecretVault, Signer,
+        Verifier, NISTP256_SECRET_LENGTH_U32,
+    };
+    use ockam_node::tokio;
+
+    fn config() -> Config {
+        let var = |name| std::env::var(name).unwrap_or_else(|_| panic!("{name} is not set"));
+        Config::new(
+            var("OCKAM_PKCS11_MODULE"),
+            var("OCKAM_PKCS11_SLOT").parse().unwrap(),
+        )
+        .pin(var("OCKAM_PKCS11_PIN"))
+    }
+
+    fn attributes(persistence: SecretPersistence) -> SecretAttributes {
+        SecretAttributes::new(
+            SecretType::NistP256,
+            persistence,
+            NISTP256_SECRET_LENGTH_U32,
+        )
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn pkcs11_sign_with_token_verify_locally() {
+        let token = Pkcs11::new(config()).unwrap();
+        let kid = token.create_key().await.unwrap();
+        let msg = b"hello world";
+        let sig = token.sign(&kid, &msg[..]).await.unwrap();
+        let pky = token.public_key(&kid).await.unwrap();
+        let vlt = Vault::create();
+        assert!(vlt.verify(&sig, &pky, msg).await.unwrap());
+        assert!(token.delete_key(&kid).await.unwrap());
+        assert!(!token.delete_key(&kid).await.unwrap())
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn pkcs11_vault_keys() {
+        let mut vlt = Vault::create();
+        vlt.enable_pkcs11(config()).unwrap();
+        let kid = vlt
+            .secret_generate(attributes(SecretPersistence::Persistent))
+            .await
+            .unwrap();
+        let msg = b"hello world";
+        let sig = vlt.sign(&kid, &msg[..]).await.unwrap();
+        let pky = vlt.secret_public_key_get(&kid).await.unwrap();
+        assert!(vlt.verify(&sig, &pky, msg).await.unwrap());
+
+        // Ephemeral keys stay in software
+        let eph = vlt
+            .secret_generate(attributes(SecretPersistence::Ephemeral))
+            .await
+            .unwrap();
+        let eph_pky = vlt.secret_public_key_get(&eph).await.unwrap();
+        let dh1 = vlt.ec_diffie_hellman(&kid, &eph_pky).await.unwrap();
+        let dh2 = vlt.ec_diffie_hellman(&eph, &pky).await.unwrap();
+        assert_eq!(
+            vlt.secret_export(&dh1).await.unwrap(),
+            vlt.secret_export(&dh2).await.unwrap()
+        );
+
+        vlt.secret_destroy(kid).await.unwrap();
+    }
+}
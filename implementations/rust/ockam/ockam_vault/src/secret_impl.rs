@@ -1,3 +1,5 @@
+use crate::audit::AuditOperation;
+use crate::kem_impl::{kyber768_keypair, kyber768_public_key};
 use crate::vault::Vault;
 use crate::VaultError;
 use arrayref::array_ref;
@@ -5,8 +7,8 @@ use cfg_if::cfg_if;
 use ockam_core::compat::rand::{thread_rng, RngCore};
 use ockam_core::vault::{
     AsymmetricVault, KeyId, PublicKey, Secret, SecretAttributes, SecretKey, SecretPersistence,
-    SecretType, SecretVault, VaultEntry, AES128_SECRET_LENGTH_U32, AES256_SECRET_LENGTH_U32,
-    CURVE25519_SECRET_LENGTH_USIZE,
+    SecretType, SecretUsage, SecretVault, VaultEntry, AES128_SECRET_LENGTH_U32,
+    AES256_SECRET_LENGTH_U32, CHACHA20POLY1305_SECRET_LENGTH_U32, CURVE25519_SECRET_LENGTH_USIZE,
 };
 use ockam_core::{async_trait, compat::boxed::Box, Result};
 
@@ -49,10 +51,9 @@ impl Vault {
                 .await?
             }
             SecretType::NistP256 => '_block: {
-                #[cfg(feature = "aws")]
                 if attributes.persistence() == SecretPersistence::Persistent {
-                    if let Some(kms) = &self.aws_kms {
-                        if let Secret::Aws(kid) = secret {
+                    if let Some(kms) = &self.kms {
+                        if let Secret::Kms(kid) = secret {
                             let pk = kms.public_key(kid).await?;
                             break '_block self.compute_key_id_for_public_key(&pk).await?;
                         }
@@ -67,7 +68,11 @@ impl Vault {
                     }
                 }
             }
-            SecretType::Buffer | SecretType::Aes => {
+            SecretType::Kyber768 => {
+                let public = kyber768_public_key(secret.try_as_key()?.as_ref())?;
+                self.compute_key_id_for_public_key(&public).await?
+            }
+            SecretType::Buffer | SecretType::Aes | SecretType::ChaCha20Poly1305 => {
                 // NOTE: Buffer and Aes secrets in the system are ephemeral and it should be fine,
                 // that every time we import the same secret - it gets different KeyId value.
                 // However, if we decide to have persistent Buffer or Aes secrets, that should be
@@ -88,6 +93,74 @@ impl Vault {
         Ok(())
     }
 
+    /// Check that a secret may be used for an operation
+    pub(crate) fn check_usage(entry: &VaultEntry, usage: SecretUsage) -> Result<()> {
+        if entry.key_attributes().usage().contains(usage) {
+            Ok(())
+        } else {
+            Err(VaultError::InvalidKeyUsage.into())
+        }
+    }
+
+    /// Export a copy of an entry. Secret keys which are not
+    /// [`SecretUsage::EXPORTABLE`] are only given out with `moved`, when the
+    /// caller removes them from this vault afterwards
+    pub(crate) async fn secret_entry(&self, key_id: &KeyId, moved: bool) -> Result<VaultEntry> {
+        self.preload_from_storage(key_id).await;
+        let entry = self
+            .data
+            .entries
+            .read()
+            .await
+            .get(key_id)
+            .cloned()
+            .ok_or(VaultError::EntryNotFound)?;
+        if let Secret::Key(_) = entry.secret() {
+            if !moved {
+                Self::check_usage(&entry, SecretUsage::EXPORTABLE)?;
+            }
+        }
+        self.audit(AuditOperation::Export, key_id, entry.key_attributes())
+            .await?;
+        Ok(entry)
+    }
+
+    /// Add a secret copied from another vault, checking that it keeps its key id
+    pub(crate) async fn insert_copied_secret(
+        &self,
+        key_id: &KeyId,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<()> {
+        if &self.compute_key_id(&secret, &attributes).await? != key_id {
+            return Err(VaultError::KeyIdMismatch.into());
+        }
+        let entry = VaultEntry::new(attributes, secret);
+        self.store_secret(key_id, &entry).await?;
+
+        self.data
+            .entries
+            .write()
+            .await
+            .insert(key_id.clone(), entry);
+
+        self.audit(AuditOperation::Import, key_id, attributes).await
+    }
+
+    /// Remove a secret copied to another vault. A key held by a KMS stays
+    /// there, as the other vault refers to it as well
+    pub(crate) async fn remove_copied_secret(&self, key_id: &KeyId) -> Result<()> {
+        let mut entries = self.data.entries.write().await;
+        let entry = entries.remove(key_id).ok_or(VaultError::EntryNotFound)?;
+        if let Some(storage) = &self.storage {
+            storage.delete(key_id).await?;
+        }
+        drop(entries);
+
+        self.audit(AuditOperation::Destroy, key_id, entry.key_attributes())
+            .await
+    }
+
     async fn store_secret(&self, key_id: &KeyId, vault_entry: &VaultEntry) -> Result<()> {
         if vault_entry.key_attributes().persistence() == SecretPersistence::Persistent {
             if let Some(storage) = &self.storage {
@@ -145,12 +218,28 @@ impl SecretVault for Vault {
 
                 Secret::Key(SecretKey::new(key))
             }
+            SecretType::ChaCha20Poly1305 => {
+                if attributes.length() != CHACHA20POLY1305_SECRET_LENGTH_U32 {
+                    return Err(VaultError::InvalidChaCha20Poly1305KeyLength.into());
+                };
+                if attributes.persistence() != SecretPersistence::Ephemeral {
+                    return Err(VaultError::InvalidKeyType.into());
+                };
+                let key = {
+                    let mut rng = thread_rng();
+                    let mut key = vec![0u8; attributes.length() as usize];
+                    rng.fill_bytes(key.as_mut_slice());
+                    key
+                };
+
+                Secret::Key(SecretKey::new(key))
+            }
+            SecretType::Kyber768 => Secret::Key(SecretKey::new(kyber768_keypair()?)),
             SecretType::NistP256 => '_block: {
-                #[cfg(feature = "aws")]
                 if attributes.persistence() == SecretPersistence::Persistent {
-                    if let Some(kms) = &self.aws_kms {
-                        let aws_id = kms.create_key().await?;
-                        break '_block Secret::Aws(aws_id);
+                    if let Some(kms) = &self.kms {
+                        let kid = kms.create_key().await?;
+                        break '_block Secret::Kms(kid);
                     }
                 }
                 cfg_if! {
@@ -178,6 +267,9 @@ impl SecretVault for Vault {
             .await
             .insert(key_id.clone(), entry);
 
+        self.audit(AuditOperation::Generate, &key_id, attributes)
+            .await?;
+
         Ok(key_id)
     }
 
@@ -196,18 +288,28 @@ impl SecretVault for Vault {
             .await
             .insert(key_id.clone(), entry);
 
+        self.audit(AuditOperation::Import, &key_id, attributes)
+            .await?;
+
         Ok(key_id)
     }
 
     async fn secret_export(&self, key_id: &KeyId) -> Result<Secret> {
         self.preload_from_storage(key_id).await;
-        let entries = self.data.entries.read().await;
+        let entry = self
+            .data
+            .entries
+            .read()
+            .await
+            .get(key_id)
+            .cloned()
+            .ok_or(VaultError::EntryNotFound)?;
+        Self::check_usage(&entry, SecretUsage::EXPORTABLE)?;
 
-        if let Some(entry) = entries.get(key_id) {
-            return Ok(entry.secret().clone());
-        }
+        self.audit(AuditOperation::Export, key_id, entry.key_attributes())
+            .await?;
 
-        Err(VaultError::EntryNotFound.into())
+        Ok(entry.secret().clone())
     }
 
     async fn secret_attributes_get(&self, key_id: &KeyId) -> Result<SecretAttributes> {
@@ -254,9 +356,8 @@ impl SecretVault for Vault {
                 Ok(PublicKey::new(pk.to_bytes().to_vec(), SecretType::Ed25519))
             }
             SecretType::NistP256 => {
-                #[cfg(feature = "aws")]
-                if let Some(kms) = &self.aws_kms {
-                    if let Secret::Aws(kid) = entry.secret() {
+                if let Some(kms) = &self.kms {
+                    if let Secret::Kms(kid) = entry.secret() {
                         return kms.public_key(kid).await;
                     }
                 }
@@ -272,7 +373,10 @@ impl SecretVault for Vault {
                     }
                 }
             }
-            SecretType::Buffer | SecretType::Aes => Err(VaultError::InvalidKeyType.into()),
+            SecretType::Kyber768 => kyber768_public_key(entry.secret().try_as_key()?.as_ref()),
+            SecretType::Buffer | SecretType::Aes | SecretType::ChaCha20Poly1305 => {
+                Err(VaultError::InvalidKeyType.into())
+            }
         }
     }
 
@@ -295,11 +399,9 @@ impl SecretVault for Vault {
 
         match entries.remove(&key_id) {
             None => return Err(VaultError::EntryNotFound.into()),
-            Some(_entry) =>
-            {
-                #[cfg(feature = "aws")]
-                if let Some(kms) = &self.aws_kms {
-                    if let Secret::Aws(kid) = _entry.secret() {
+            Some(entry) => {
+                if let Some(kms) = &self.kms {
+                    if let Secret::Kms(kid) = entry.secret() {
                         if !kms.delete_key(kid).await? {
                             return Err(VaultError::EntryNotFound.into());
                         }
@@ -307,8 +409,10 @@ impl SecretVault for Vault {
                 }
             }
         }
+        drop(entries);
 
-        res
+        res?;
+        self.audit(AuditOperation::Destroy, &key_id, attrs).await
     }
 }
 
@@ -325,7 +429,7 @@ fn public_key(secret: &[u8]) -> Result<PublicKey> {
 
 #[cfg(test)]
 mod tests {
-    use ockam_core::vault::{Secret, SecretKey};
+    use ockam_core::vault::{AsymmetricVault, Hasher, Secret, SecretKey, SecretUsage, Signer};
 
     use crate::{
         ockam_core::vault::{SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32},
@@ -374,4 +478,24 @@ mod tests {
             &key_id
         );
     }
+
+    #[tokio::test]
+    async fn secret_usage_is_enforced() {
+        let vault = new_vault();
+        let attrs = new_x255519_attrs().unwrap().with_usage(SecretUsage::ECDH);
+        let key_id = vault.secret_generate(attrs).await.unwrap();
+        let public = vault.secret_public_key_get(&key_id).await.unwrap();
+
+        assert_eq!(
+            vault.secret_attributes_get(&key_id).await.unwrap().usage(),
+            SecretUsage::ECDH
+        );
+        assert!(vault.ec_diffie_hellman(&key_id, &public).await.is_ok());
+        assert!(vault.secret_export(&key_id).await.is_err());
+        assert!(vault.sign(&key_id, b"hello").await.is_err());
+        assert!(vault
+            .hkdf_sha256(&key_id, b"", None, Vec::new())
+            .await
+            .is_err());
+    }
 }
@@ -1,12 +1,10 @@
+use crate::audit::AuditOperation;
 use crate::vault::Vault;
 use crate::VaultError;
 use cfg_if::cfg_if;
-use ockam_core::vault::{KeyId, SecretType, Signature, Signer};
+use ockam_core::vault::{KeyId, Secret, SecretType, SecretUsage, Signature, Signer, VaultEntry};
 use ockam_core::{async_trait, compat::boxed::Box, Result};
 
-#[cfg(feature = "aws")]
-use ockam_core::vault::Secret;
-
 #[cfg(feature = "rustcrypto")]
 use crate::error::from_pkcs8;
 
@@ -18,7 +16,20 @@ impl Signer for Vault {
 
         let entries = self.data.entries.read().await;
         let entry = entries.get(secret_key).ok_or(VaultError::EntryNotFound)?;
+        Self::check_usage(entry, SecretUsage::SIGN)?;
+        let attributes = entry.key_attributes();
+        let signature = self.sign_entry(entry, data).await?;
+        drop(entries);
+
+        self.audit(AuditOperation::Sign, secret_key, attributes)
+            .await?;
+
+        Ok(signature)
+    }
+}
 
+impl Vault {
+    async fn sign_entry(&self, entry: &VaultEntry, data: &[u8]) -> Result<Signature> {
         match entry.key_attributes().stype() {
             SecretType::X25519 => {
                 use crate::xeddsa::XEddsaSigner;
@@ -56,9 +67,8 @@ impl Signer for Vault {
                 Ok(Signature::new(sig.to_bytes().to_vec()))
             }
             SecretType::NistP256 => {
-                #[cfg(feature = "aws")]
-                if let Some(kms) = &self.aws_kms {
-                    if let Secret::Aws(kid) = entry.secret() {
+                if let Some(kms) = &self.kms {
+                    if let Secret::Kms(kid) = entry.secret() {
                         return kms.sign(kid, data).await;
                     }
                 }
@@ -75,7 +85,10 @@ impl Signer for Vault {
                     }
                 }
             }
-            SecretType::Buffer | SecretType::Aes => Err(VaultError::InvalidKeyType.into()),
+            SecretType::Buffer
+            | SecretType::Aes
+            | SecretType::ChaCha20Poly1305
+            | SecretType::Kyber768 => Err(VaultError::InvalidKeyType.into()),
         }
     }
 }
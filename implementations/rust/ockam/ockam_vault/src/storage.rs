@@ -1,3 +1,7 @@
+mod backup;
+mod encryption;
 mod file_storage;
 
+pub use backup::VaultBackup;
+pub use encryption::FileStorageKey;
 pub use file_storage::*;
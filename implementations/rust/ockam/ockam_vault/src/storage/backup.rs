@@ -0,0 +1,181 @@
+use super::encryption::{EncryptedData, FileStorageKey, Kdf, StorageCipher};
+use crate::{Vault, VaultError};
+use ockam_core::vault::{KeyId, Secret, SecretAttributes, SecretUsage};
+use ockam_core::Result;
+use serde::{Deserialize, Serialize};
+
+/// Authenticated data of the entries of a backup
+const BACKUP_AAD: &[u8] = b"ockam vault backup";
+const BACKUP_VERSION: u8 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct BackupEntry {
+    key_id: KeyId,
+    key_attributes: SecretAttributes,
+    #[serde(default)]
+    key_usage: SecretUsage,
+    key: Secret,
+}
+
+/// Encrypted bundle of the persistent secrets of a vault, see [`Vault::backup`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VaultBackup {
+    version: u8,
+    kdf: Kdf,
+    entries: EncryptedData,
+}
+
+impl Vault {
+    /// Encrypt all the persistent secrets of the vault into a bundle, with a
+    /// key derived from a passphrase or keyfile. Secrets held by a KMS or a
+    /// PKCS#11 token are only referenced. Fails if a secret key is not
+    /// [`SecretUsage::EXPORTABLE`].
+    pub async fn backup(&self, key: &FileStorageKey) -> Result<VaultBackup> {
+        let mut entries = Vec::new();
+        for key_id in self.persistent_key_ids().await? {
+            let entry = self.secret_entry(&key_id, false).await?;
+            entries.push(BackupEntry {
+                key_attributes: entry.key_attributes(),
+                key_usage: entry.key_attributes().usage(),
+                key: entry.secret().clone(),
+                key_id,
+            });
+        }
+        let plaintext = serde_json::to_vec(&entries).map_err(|_| VaultError::StorageError)?;
+        let (kdf, cipher) = StorageCipher::create(key)?;
+        Ok(VaultBackup {
+            version: BACKUP_VERSION,
+            kdf,
+            entries: cipher.encrypt(&plaintext, BACKUP_AAD)?,
+        })
+    }
+
+    /// Import the secrets of a bundle, keeping their key ids, and return these ids.
+    pub async fn restore(&self, backup: &VaultBackup, key: &FileStorageKey) -> Result<Vec<KeyId>> {
+        if backup.version != BACKUP_VERSION {
+            return Err(VaultError::InvalidStorageData.into());
+        }
+        let cipher = StorageCipher::derive(&backup.kdf, key)?;
+        let plaintext = cipher
+            .decrypt(&backup.entries, BACKUP_AAD)
+            .map_err(|_| VaultError::InvalidStorageKey)?;
+        let entries: Vec<BackupEntry> = serde_json::from_slice(plaintext.as_ref())
+            .map_err(|_| VaultError::InvalidStorageData)?;
+
+        let mut key_ids = Vec::new();
+        for entry in entries {
+            let attributes = entry.key_attributes.with_usage(entry.key_usage);
+            self.insert_copied_secret(&entry.key_id, entry.key, attributes)
+                .await?;
+            key_ids.push(entry.key_id);
+        }
+        Ok(key_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use ockam_core::compat::rand::{thread_rng, RngCore};
+    use ockam_core::vault::{
+        SecretPersistence, SecretType, SecretVault, Signer, Verifier, CURVE25519_SECRET_LENGTH_U32,
+        NISTP256_SECRET_LENGTH_U32,
+    };
+    use std::sync::Arc;
+
+    async fn file_vault() -> Vault {
+        let mut rand_id = [0u8; 32];
+        thread_rng().fill_bytes(&mut rand_id);
+        let path = std::env::temp_dir().join(hex::encode(rand_id));
+        let storage = FileStorage::create(path).await.unwrap();
+        Vault::new(Some(Arc::new(storage)))
+    }
+
+    #[tokio::test]
+    async fn backup_and_restore() {
+        let vault = file_vault().await;
+        let ed25519 = vault
+            .secret_generate(
+                SecretAttributes::new(
+                    SecretType::Ed25519,
+                    SecretPersistence::Persistent,
+                    CURVE25519_SECRET_LENGTH_U32,
+                )
+                .with_usage(SecretUsage::SIGN | SecretUsage::EXPORTABLE),
+            )
+            .await
+            .unwrap();
+        let p256 = vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::NistP256,
+                SecretPersistence::Persistent,
+                NISTP256_SECRET_LENGTH_U32,
+            ))
+            .await
+            .unwrap();
+        // Ephemeral secrets are not backed up
+        vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::X25519,
+                SecretPersistence::Ephemeral,
+                CURVE25519_SECRET_LENGTH_U32,
+            ))
+            .await
+            .unwrap();
+
+        let key = FileStorageKey::Passphrase("backup passphrase".into());
+        let backup = vault.backup(&key).await.unwrap();
+        let backup: VaultBackup =
+            serde_json::from_slice(&serde_json::to_vec(&backup).unwrap()).unwrap();
+
+        let wrong_key = FileStorageKey::Passphrase("wrong passphrase".into());
+        assert!(file_vault()
+            .await
+            .restore(&backup, &wrong_key)
+            .await
+            .is_err());
+
+        let restored = file_vault().await;
+        let mut key_ids = restored.restore(&backup, &key).await.unwrap();
+        key_ids.sort();
+        let mut expected = vec![ed25519.clone(), p256.clone()];
+        expected.sort();
+        assert_eq!(key_ids, expected);
+        assert_eq!(restored.persistent_key_ids().await.unwrap(), expected);
+
+        for key_id in [&ed25519, &p256] {
+            let sig = restored.sign(key_id, b"hello").await.unwrap();
+            let public = vault.secret_public_key_get(key_id).await.unwrap();
+            assert!(vault.verify(&sig, &public, b"hello").await.unwrap());
+        }
+        // Usage restrictions are kept
+        assert_eq!(
+            restored
+                .secret_attributes_get(&ed25519)
+                .await
+                .unwrap()
+                .usage(),
+            SecretUsage::SIGN | SecretUsage::EXPORTABLE
+        );
+    }
+
+    #[tokio::test]
+    async fn backup_refuses_unexportable_keys() {
+        let vault = file_vault().await;
+        vault
+            .secret_generate(
+                SecretAttributes::new(
+                    SecretType::Ed25519,
+                    SecretPersistence::Persistent,
+                    CURVE25519_SECRET_LENGTH_U32,
+                )
+                .with_usage(SecretUsage::SIGN),
+            )
+            .await
+            .unwrap();
+
+        let key = FileStorageKey::Passphrase("backup passphrase".into());
+        assert!(vault.backup(&key).await.is_err());
+    }
+}
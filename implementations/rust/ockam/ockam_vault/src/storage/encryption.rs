@@ -0,0 +1,174 @@
+use crate::VaultError;
+use aes_gcm::aead::{generic_array::GenericArray, Aead, NewAead, Payload};
+use aes_gcm::Aes256Gcm;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::vault::{SecretKey, AES256_SECRET_LENGTH_USIZE};
+use ockam_core::Result;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::PathBuf;
+
+/// Plaintext of the value used to check the storage key
+const CHECK_VALUE: &[u8] = b"ockam vault storage";
+/// HKDF info of the storage key derived from a keyfile
+const KEYFILE_INFO: &[u8] = b"ockam vault storage key";
+/// Keyfiles need at least as much entropy as the key derived from them
+const KEYFILE_MIN_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+/// Where the key encrypting the entries of a [`super::FileStorage`] comes from
+#[derive(Clone)]
+pub enum FileStorageKey {
+    /// A passphrase, stretched with scrypt
+    Passphrase(String),
+    /// A file of at least 32 random bytes, e.g. on a removable drive
+    Keyfile(PathBuf),
+}
+
+impl core::fmt::Debug for FileStorageKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Passphrase(_) => write!(f, "Passphrase(..)"),
+            Self::Keyfile(path) => f.debug_tuple("Keyfile").field(path).finish(),
+        }
+    }
+}
+
+/// How the storage key is derived, stored in the clear next to the entries
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub(super) enum Kdf {
+    Scrypt {
+        salt: String,
+        log_n: u8,
+        r: u32,
+        p: u32,
+    },
+    Keyfile {
+        salt: String,
+    },
+}
+
+impl Kdf {
+    fn new(key: &FileStorageKey) -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        thread_rng().fill_bytes(&mut salt);
+        let salt = hex::encode(salt);
+        match key {
+            // Recommended parameters for interactive logins
+            FileStorageKey::Passphrase(_) => Self::Scrypt {
+                salt,
+                log_n: 15,
+                r: 8,
+                p: 1,
+            },
+            FileStorageKey::Keyfile(_) => Self::Keyfile { salt },
+        }
+    }
+}
+
+/// AES-GCM encrypted data, hex encoded
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(super) struct EncryptedData {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Encrypts and decrypts the entries of a storage with a key derived
+/// from a [`FileStorageKey`]
+pub(super) struct StorageCipher {
+    key: SecretKey,
+}
+
+impl StorageCipher {
+    /// Derive the key of a new encrypted storage
+    pub(super) fn create(key: &FileStorageKey) -> Result<(Kdf, Self)> {
+        let kdf = Kdf::new(key);
+        let cipher = Self::derive(&kdf, key)?;
+        Ok((kdf, cipher))
+    }
+
+    /// Derive the key of an existing encrypted storage
+    pub(super) fn derive(kdf: &Kdf, key: &FileStorageKey) -> Result<Self> {
+        let mut output = vec![0u8; AES256_SECRET_LENGTH_USIZE];
+        match (kdf, key) {
+            (Kdf::Scrypt { salt, log_n, r, p }, FileStorageKey::Passphrase(passphrase)) => {
+                let salt = hex::decode(salt).map_err(|_| VaultError::InvalidStorageData)?;
+                let params = scrypt::Params::new(*log_n, *r, *p)
+                    .map_err(|_| VaultError::InvalidStorageData)?;
+                scrypt::scrypt(passphrase.as_bytes(), &salt, &params, &mut output)
+                    .map_err(|_| VaultError::InvalidStorageData)?;
+            }
+            (Kdf::Keyfile { salt }, FileStorageKey::Keyfile(path)) => {
+                let salt = hex::decode(salt).map_err(|_| VaultError::InvalidStorageData)?;
+                let ikm =
+                    SecretKey::new(std::fs::read(path).map_err(|_| VaultError::StorageError)?);
+                if ikm.as_ref().len() < KEYFILE_MIN_LENGTH {
+                    return Err(VaultError::InvalidStorageKey.into());
+                }
+                hkdf::Hkdf::<Sha256>::new(Some(&salt), ikm.as_ref())
+                    .expand(KEYFILE_INFO, &mut output)
+                    .map_err(|_| VaultError::HkdfExpandError)?;
+            }
+            _ => return Err(VaultError::InvalidStorageKey.into()),
+        }
+
+        Ok(Self {
+            key: SecretKey::new(output),
+        })
+    }
+
+    fn aes_gcm(&self) -> Aes256Gcm {
+        Aes256Gcm::new(GenericArray::from_slice(self.key.as_ref()))
+    }
+
+    pub(super) fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<EncryptedData> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        thread_rng().fill_bytes(&mut nonce);
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        let ciphertext = self
+            .aes_gcm()
+            .encrypt(GenericArray::from_slice(&nonce), payload)
+            .map_err(|_| VaultError::AeadAesGcmEncrypt)?;
+
+        Ok(EncryptedData {
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub(super) fn decrypt(&self, data: &EncryptedData, aad: &[u8]) -> Result<SecretKey> {
+        let nonce = hex::decode(&data.nonce).map_err(|_| VaultError::InvalidStorageData)?;
+        let ciphertext =
+            hex::decode(&data.ciphertext).map_err(|_| VaultError::InvalidStorageData)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(VaultError::InvalidStorageData.into());
+        }
+        let payload = Payload {
+            msg: &ciphertext,
+            aad,
+        };
+        let plaintext = self
+            .aes_gcm()
+            .decrypt(GenericArray::from_slice(&nonce), payload)
+            .map_err(|_| VaultError::AeadAesGcmDecrypt)?;
+
+        Ok(SecretKey::new(plaintext))
+    }
+
+    /// Value stored with the entries to tell a wrong key from corrupted entries
+    pub(super) fn check_value(&self) -> Result<EncryptedData> {
+        self.encrypt(CHECK_VALUE, &[])
+    }
+
+    pub(super) fn verify(&self, check: &EncryptedData) -> Result<()> {
+        match self.decrypt(check, &[]) {
+            Ok(value) if value.as_ref() == CHECK_VALUE => Ok(()),
+            _ => Err(VaultError::InvalidStorageKey.into()),
+        }
+    }
+}
@@ -1,9 +1,13 @@
+use super::encryption::{EncryptedData, FileStorageKey, Kdf, StorageCipher};
 use crate::VaultError;
 use fs2::FileExt; //locking
 use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::vault::storage::Storage;
-use ockam_core::vault::{KeyId, Secret, SecretAttributes, SecretPersistence, VaultEntry};
+use ockam_core::vault::{
+    KeyId, Secret, SecretAttributes, SecretPersistence, SecretUsage, VaultEntry,
+};
 use ockam_core::{async_trait, Error, Result};
 use ockam_node::tokio::task::{self, JoinError};
 use serde::{Deserialize, Serialize};
@@ -15,9 +19,85 @@ use std::path::{Path, PathBuf};
 struct LegacyVaultEntry {
     key_id: Option<String>,
     key_attributes: SecretAttributes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_usage: Option<SecretUsage>,
     key: Secret,
 }
 
+/// Usage to store next to `attributes`, if it's restricted
+fn stored_usage(attributes: &SecretAttributes) -> Option<SecretUsage> {
+    Some(attributes.usage()).filter(|usage| *usage != SecretUsage::ALL)
+}
+
+fn with_stored_usage(attributes: SecretAttributes, usage: Option<SecretUsage>) -> SecretAttributes {
+    match usage {
+        Some(usage) => attributes.with_usage(usage),
+        None => attributes,
+    }
+}
+
+impl LegacyVaultEntry {
+    fn vault_entry(&self) -> VaultEntry {
+        VaultEntry::new(
+            with_stored_usage(self.key_attributes, self.key_usage),
+            self.key.clone(),
+        )
+    }
+}
+
+/// Entry whose secret is encrypted, authenticating its id and attributes
+#[derive(Serialize, Deserialize, Debug)]
+struct EncryptedVaultEntry {
+    key_id: String,
+    key_attributes: SecretAttributes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_usage: Option<SecretUsage>,
+    key: EncryptedData,
+}
+
+impl EncryptedVaultEntry {
+    fn aad(
+        key_id: &str,
+        key_attributes: &SecretAttributes,
+        key_usage: Option<SecretUsage>,
+    ) -> Result<Vec<u8>> {
+        let mut aad = key_id.as_bytes().to_vec();
+        aad.append(&mut serde_json::to_vec(key_attributes).map_err(|_| VaultError::StorageError)?);
+        if let Some(usage) = key_usage {
+            aad.append(&mut serde_json::to_vec(&usage).map_err(|_| VaultError::StorageError)?);
+        }
+        Ok(aad)
+    }
+
+    fn seal(
+        cipher: &StorageCipher,
+        key_id: String,
+        key_attributes: SecretAttributes,
+        key: &Secret,
+    ) -> Result<Self> {
+        let plaintext = serde_json::to_vec(key).map_err(|_| VaultError::StorageError)?;
+        let key_usage = stored_usage(&key_attributes);
+        let key = cipher.encrypt(&plaintext, &Self::aad(&key_id, &key_attributes, key_usage)?)?;
+        Ok(Self {
+            key_id,
+            key_attributes,
+            key_usage,
+            key,
+        })
+    }
+
+    fn open(&self, cipher: &StorageCipher) -> Result<VaultEntry> {
+        let aad = Self::aad(&self.key_id, &self.key_attributes, self.key_usage)?;
+        let plaintext = cipher.decrypt(&self.key, &aad)?;
+        let key = serde_json::from_slice(plaintext.as_ref())
+            .map_err(|_| VaultError::InvalidStorageData)?;
+        Ok(VaultEntry::new(
+            with_stored_usage(self.key_attributes, self.key_usage),
+            key,
+        ))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "version")]
 #[non_exhaustive]
@@ -26,6 +106,161 @@ enum LegacySerializedVault {
         entries: Vec<(usize, LegacyVaultEntry)>,
         next_id: usize,
     },
+    /// Entries encrypted with a key derived according to `kdf`
+    V2 {
+        kdf: Kdf,
+        check: EncryptedData,
+        entries: Vec<EncryptedVaultEntry>,
+    },
+}
+
+impl LegacySerializedVault {
+    fn empty(cipher: Option<(Kdf, &StorageCipher)>) -> Result<Self> {
+        Ok(match cipher {
+            None => Self::V1 {
+                entries: Vec::new(),
+                next_id: 0,
+            },
+            Some((kdf, cipher)) => Self::V2 {
+                kdf,
+                check: cipher.check_value()?,
+                entries: Vec::new(),
+            },
+        })
+    }
+
+    /// Plaintext vaults are only used without a key, and encrypted ones with it
+    fn cipher(cipher: Option<&StorageCipher>) -> Result<&StorageCipher> {
+        cipher.ok_or_else(|| VaultError::StorageLocked.into())
+    }
+
+    fn plaintext(cipher: Option<&StorageCipher>) -> Result<()> {
+        match cipher {
+            None => Ok(()),
+            Some(_) => Err(VaultError::InvalidStorageData.into()),
+        }
+    }
+
+    fn push(
+        &mut self,
+        key_id: KeyId,
+        key_attributes: SecretAttributes,
+        key: Secret,
+        cipher: Option<&StorageCipher>,
+    ) -> Result<()> {
+        match self {
+            Self::V1 { entries, .. } => {
+                Self::plaintext(cipher)?;
+                entries.push((
+                    0,
+                    LegacyVaultEntry {
+                        key_id: Some(key_id),
+                        key_usage: stored_usage(&key_attributes),
+                        key_attributes,
+                        key,
+                    },
+                ))
+            }
+            Self::V2 { entries, .. } => entries.push(EncryptedVaultEntry::seal(
+                Self::cipher(cipher)?,
+                key_id,
+                key_attributes,
+                &key,
+            )?),
+        }
+        Ok(())
+    }
+
+    fn find(&self, key_id: &str, cipher: Option<&StorageCipher>) -> Result<VaultEntry> {
+        let is_persistent = |attributes: &SecretAttributes| {
+            attributes.persistence() == SecretPersistence::Persistent
+        };
+        match self {
+            Self::V1 { entries, .. } => {
+                Self::plaintext(cipher)?;
+                entries
+                    .iter()
+                    .find(|(_, e)| {
+                        e.key_id.as_deref() == Some(key_id) && is_persistent(&e.key_attributes)
+                    })
+                    .map(|(_, e)| e.vault_entry())
+                    .ok_or_else(|| VaultError::EntryNotFound.into())
+            }
+            Self::V2 { entries, .. } => entries
+                .iter()
+                .find(|e| e.key_id == key_id && is_persistent(&e.key_attributes))
+                .ok_or(VaultError::EntryNotFound)?
+                .open(Self::cipher(cipher)?),
+        }
+    }
+
+    /// Ids of the persistent entries, which don't need the key
+    fn key_ids(&self) -> Vec<KeyId> {
+        let is_persistent = |attributes: &SecretAttributes| {
+            attributes.persistence() == SecretPersistence::Persistent
+        };
+        match self {
+            Self::V1 { entries, .. } => entries
+                .iter()
+                .filter(|(_, e)| is_persistent(&e.key_attributes))
+                .filter_map(|(_, e)| e.key_id.clone())
+                .collect(),
+            Self::V2 { entries, .. } => entries
+                .iter()
+                .filter(|e| is_persistent(&e.key_attributes))
+                .map(|e| e.key_id.clone())
+                .collect(),
+        }
+    }
+
+    fn remove(&mut self, key_id: &str, cipher: Option<&StorageCipher>) -> Result<VaultEntry> {
+        match self {
+            Self::V1 { entries, .. } => {
+                Self::plaintext(cipher)?;
+                let index = entries
+                    .iter()
+                    .position(|(_, e)| e.key_id.as_deref() == Some(key_id))
+                    .ok_or(VaultError::EntryNotFound)?;
+                let (_, removed) = entries.swap_remove(index);
+                Ok(removed.vault_entry())
+            }
+            Self::V2 { entries, .. } => {
+                let cipher = Self::cipher(cipher)?;
+                let index = entries
+                    .iter()
+                    .position(|e| e.key_id == key_id)
+                    .ok_or(VaultError::EntryNotFound)?;
+                let removed = entries.swap_remove(index);
+                removed.open(cipher)
+            }
+        }
+    }
+
+    /// Encrypt the entries of a plaintext vault
+    fn encrypt(self, kdf: Kdf, cipher: &StorageCipher) -> Result<Self> {
+        match self {
+            Self::V1 { entries, .. } => {
+                let entries = entries
+                    .into_iter()
+                    .filter_map(|(_, e)| e.key_id.clone().map(|id| (id, e.vault_entry())))
+                    .map(|(id, entry)| {
+                        EncryptedVaultEntry::seal(
+                            cipher,
+                            id,
+                            entry.key_attributes(),
+                            entry.secret(),
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Self::V2 {
+                    kdf,
+                    check: cipher.check_value()?,
+                    entries,
+                })
+            }
+            Self::V2 { .. } => Err(VaultError::InvalidStorageData.into()),
+        }
+    }
 }
 
 /// File Storage
@@ -36,11 +271,16 @@ enum LegacySerializedVault {
  * - A "lock" file.  It's used to control inter-process access to the vault.
  *   Before reading or writting to the vault, first need to get a shared or exclusive lock
  *   on this file.  We don't lock over the vault file directly, because doesn't play well with
- *   the file rename we do */
+ *   the file rename we do
+ *
+ * When a key is given with `with_key`, the secrets of the entries are
+ * encrypted with AES-GCM. Their ids and attributes stay readable. */
 pub struct FileStorage {
     path: PathBuf,
     temp_path: PathBuf,
     lock_path: PathBuf,
+    key: Option<FileStorageKey>,
+    cipher: Option<Arc<StorageCipher>>,
 }
 
 fn map_join_err(err: JoinError) -> Error {
@@ -53,17 +293,42 @@ fn map_io_err(err: std::io::Error) -> Error {
 impl FileStorage {
     /// Create FileStorage using file at given Path
     /// If file doesn't exist, it will be created
+    /// A plaintext file is encrypted if a key was given, which fails
+    /// if it was encrypted with another key
     pub async fn init(&mut self) -> Result<()> {
         // This can block, but only when first initializing and just need to write an empty vault.
         // So didn't bother to do it async
         let lock_file = Self::open_lock_file(&self.lock_path)?;
         lock_file.lock_exclusive().map_err(map_io_err)?;
         if !self.path.exists() {
-            let empty = LegacySerializedVault::V1 {
-                entries: Vec::new(),
-                next_id: 0,
+            let empty = match &self.key {
+                None => LegacySerializedVault::empty(None)?,
+                Some(key) => {
+                    let (kdf, cipher) = StorageCipher::create(key)?;
+                    let empty = LegacySerializedVault::empty(Some((kdf, &cipher)))?;
+                    self.cipher = Some(Arc::new(cipher));
+                    empty
+                }
             };
             Self::flush_to_file(&self.path, &self.temp_path, &empty)?;
+        } else {
+            match (Self::load(&self.path)?, &self.key) {
+                (LegacySerializedVault::V1 { .. }, None) => {}
+                (vault @ LegacySerializedVault::V1 { .. }, Some(key)) => {
+                    let (kdf, cipher) = StorageCipher::create(key)?;
+                    let encrypted = vault.encrypt(kdf, &cipher)?;
+                    Self::flush_to_file(&self.path, &self.temp_path, &encrypted)?;
+                    self.cipher = Some(Arc::new(cipher));
+                }
+                (LegacySerializedVault::V2 { kdf, check, .. }, Some(key)) => {
+                    let cipher = StorageCipher::derive(&kdf, key)?;
+                    cipher.verify(&check)?;
+                    self.cipher = Some(Arc::new(cipher));
+                }
+                (LegacySerializedVault::V2 { .. }, None) => {
+                    return Err(VaultError::StorageLocked.into());
+                }
+            }
         }
         lock_file.unlock().map_err(map_io_err)?;
         Ok(())
@@ -99,9 +364,18 @@ impl FileStorage {
             path,
             temp_path,
             lock_path,
+            key: None,
+            cipher: None,
         }
     }
 
+    /// Encrypt the entries with a key derived from the given passphrase
+    /// or keyfile. Must be called before [`FileStorage::init()`].
+    pub fn with_key(mut self, key: FileStorageKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
     /// Create and init Storage
     pub async fn create(path: PathBuf) -> Result<Self> {
         let mut s = Self::new(path);
@@ -110,6 +384,14 @@ impl FileStorage {
         Ok(s)
     }
 
+    /// Create and init encrypted Storage
+    pub async fn create_encrypted(path: PathBuf, key: FileStorageKey) -> Result<Self> {
+        let mut s = Self::new(path).with_key(key);
+        s.init().await?;
+
+        Ok(s)
+    }
+
     // Flush vault to target, using temp_path as intermediary file.
     fn flush_to_file(
         target: &PathBuf,
@@ -184,71 +466,37 @@ impl Storage for FileStorage {
         let key_id = key_id.clone();
         let attributes = key.key_attributes();
         let key = key.secret().clone();
-        let t = move |v: LegacySerializedVault| {
-            let new_entry = (
-                0,
-                LegacyVaultEntry {
-                    key_id: Some(key_id),
-                    key_attributes: attributes,
-                    key,
-                },
-            );
-            let LegacySerializedVault::V1 {
-                mut entries,
-                next_id,
-            } = v;
-            entries.push(new_entry);
-            Ok((LegacySerializedVault::V1 { entries, next_id }, ()))
+        let cipher = self.cipher.clone();
+        let t = move |mut v: LegacySerializedVault| {
+            v.push(key_id, attributes, key, cipher.as_deref())?;
+            Ok((v, ()))
         };
         self.write_transaction(t).await
     }
 
     async fn load(&self, key_id: &KeyId) -> Result<VaultEntry> {
         let key_id = key_id.clone();
+        let cipher = self.cipher.clone();
         let t = move |v: LegacySerializedVault| -> Result<VaultEntry> {
-            let LegacySerializedVault::V1 {
-                entries,
-                next_id: _,
-            } = v;
-            Ok(entries
-                .iter()
-                .find(|x| {
-                    if let Some(id) = &x.1.key_id {
-                        id.eq(&key_id)
-                            && x.1.key_attributes.persistence() == SecretPersistence::Persistent
-                    } else {
-                        false
-                    }
-                })
-                .map(|le| VaultEntry::new(le.1.key_attributes, le.1.key.clone()))
-                .ok_or(VaultError::EntryNotFound)?)
+            v.find(&key_id, cipher.as_deref())
         };
         self.read_transaction(t).await
     }
 
     async fn delete(&self, key_id: &KeyId) -> Result<VaultEntry> {
         let key_id = key_id.clone();
-        let t = move |v: LegacySerializedVault| -> Result<(LegacySerializedVault, VaultEntry)> {
-            let LegacySerializedVault::V1 {
-                mut entries,
-                next_id,
-            } = v;
-            if let Some(index) = entries.iter_mut().position(|(_, entry)| {
-                if let Some(id) = &entry.key_id {
-                    id.eq(&key_id)
-                } else {
-                    false
-                }
-            }) {
-                let removed = entries.swap_remove(index);
-                let vault_entry = VaultEntry::new(removed.1.key_attributes, removed.1.key);
-                Ok((LegacySerializedVault::V1 { entries, next_id }, vault_entry))
-            } else {
-                Err(Error::from(VaultError::EntryNotFound))
-            }
+        let cipher = self.cipher.clone();
+        let t = move |mut v: LegacySerializedVault| -> Result<(LegacySerializedVault, VaultEntry)> {
+            let vault_entry = v.remove(&key_id, cipher.as_deref())?;
+            Ok((v, vault_entry))
         };
         self.write_transaction(t).await
     }
+
+    async fn list(&self) -> Result<Vec<KeyId>> {
+        let t = move |v: LegacySerializedVault| -> Result<Vec<KeyId>> { Ok(v.key_ids()) };
+        self.read_transaction(t).await
+    }
 }
 
 #[cfg(test)]
@@ -342,4 +590,74 @@ mod tests {
         assert_eq!(attributes2, attributes22.unwrap());
         assert_eq!(attributes3, attributes32.unwrap());
     }
+
+    fn random_path() -> PathBuf {
+        let mut rand_id = [0u8; 32];
+        thread_rng().fill_bytes(&mut rand_id);
+        std::env::temp_dir().join(hex::encode(rand_id))
+    }
+
+    #[tokio::test]
+    #[allow(non_snake_case)]
+    async fn encrypted_storage__plaintext_vault__is_migrated() {
+        let path = random_path();
+        let keyfile = random_path();
+        let mut key = [0u8; 32];
+        thread_rng().fill_bytes(&mut key);
+        std::fs::write(&keyfile, key).unwrap();
+        let key = FileStorageKey::Keyfile(keyfile);
+
+        let storage = FileStorage::create(path.clone()).await.unwrap();
+        let vault = Vault::new(Some(Arc::new(storage)));
+        let attributes =
+            SecretAttributes::new(SecretType::Ed25519, SecretPersistence::Persistent, 0);
+        let key_id = vault.secret_generate(attributes).await.unwrap();
+        let secret = vault.secret_export(&key_id).await.unwrap();
+
+        let storage = FileStorage::create_encrypted(path.clone(), key.clone())
+            .await
+            .unwrap();
+        let vault = Vault::new(Some(Arc::new(storage)));
+        assert_eq!(vault.secret_export(&key_id).await.unwrap(), secret);
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        assert!(data.contains("\"V2\""));
+        assert!(!data.contains(&serde_json::to_string(&secret).unwrap()));
+
+        // The encrypted vault can't be opened without the key
+        assert!(FileStorage::create(path.clone()).await.is_err());
+        let wrong_key = FileStorageKey::Passphrase("wrong".to_string());
+        assert!(FileStorage::create_encrypted(path, wrong_key)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    #[allow(non_snake_case)]
+    async fn encrypted_storage__recreate_vault__loads_from_storage() {
+        let path = random_path();
+        let key = FileStorageKey::Passphrase("passphrase".to_string());
+
+        let storage = FileStorage::create_encrypted(path.clone(), key.clone())
+            .await
+            .unwrap();
+        let vault = Vault::new(Some(Arc::new(storage)));
+        let attributes =
+            SecretAttributes::new(SecretType::X25519, SecretPersistence::Persistent, 0);
+        let key_id = vault.secret_generate(attributes).await.unwrap();
+        let secret = vault.secret_export(&key_id).await.unwrap();
+
+        let storage = FileStorage::create_encrypted(path.clone(), key)
+            .await
+            .unwrap();
+        let vault = Vault::new(Some(Arc::new(storage)));
+        assert_eq!(vault.secret_export(&key_id).await.unwrap(), secret);
+        vault.secret_destroy(key_id.clone()).await.unwrap();
+        assert!(vault.secret_export(&key_id).await.is_err());
+
+        let wrong_key = FileStorageKey::Passphrase("wrong".to_string());
+        assert!(FileStorage::create_encrypted(path, wrong_key)
+            .await
+            .is_err());
+    }
 }
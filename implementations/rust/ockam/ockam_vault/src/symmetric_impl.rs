@@ -1,9 +1,12 @@
+use crate::audit::AuditOperation;
 use crate::{Vault, VaultError};
 use aes_gcm::aead::{generic_array::GenericArray, Aead, NewAead, Payload};
 use aes_gcm::{Aes128Gcm, Aes256Gcm};
+use chacha20poly1305::ChaCha20Poly1305;
 use ockam_core::vault::{
-    Buffer, KeyId, SecretType, SymmetricVault, AES128_SECRET_LENGTH_U32,
+    Buffer, KeyId, SecretType, SecretUsage, SymmetricVault, AES128_SECRET_LENGTH_U32,
     AES128_SECRET_LENGTH_USIZE, AES256_SECRET_LENGTH_U32, AES256_SECRET_LENGTH_USIZE,
+    CHACHA20POLY1305_SECRET_LENGTH_USIZE,
 };
 use ockam_core::{async_trait, compat::boxed::Box, Result};
 
@@ -20,6 +23,7 @@ impl SymmetricVault for Vault {
 
         let entries = self.data.entries.read().await;
         let entry = entries.get(key_id).ok_or(VaultError::EntryNotFound)?;
+        Self::check_usage(entry, SecretUsage::ENCRYPT)?;
 
         if entry.key_attributes().stype() != SecretType::Aes {
             return Err(VaultError::AeadAesGcmEncrypt.into());
@@ -69,6 +73,7 @@ impl SymmetricVault for Vault {
 
         let entries = self.data.entries.read().await;
         let entry = entries.get(key_id).ok_or(VaultError::EntryNotFound)?;
+        Self::check_usage(entry, SecretUsage::ENCRYPT)?;
 
         if entry.key_attributes().stype() != SecretType::Aes {
             return Err(VaultError::AeadAesGcmEncrypt.into());
@@ -80,7 +85,7 @@ impl SymmetricVault for Vault {
             msg: cipher_text,
         };
 
-        match entry.key_attributes().length() {
+        let plaintext = match entry.key_attributes().length() {
             AES128_SECRET_LENGTH_U32 => {
                 let key = entry.secret().try_as_key()?.as_ref();
                 if key.len() != AES128_SECRET_LENGTH_USIZE {
@@ -89,7 +94,7 @@ impl SymmetricVault for Vault {
                 let key = GenericArray::from_slice(key);
                 Aes128Gcm::new(key)
                     .decrypt(nonce, payload)
-                    .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
+                    .map_err(|_| VaultError::AeadAesGcmEncrypt)
             }
             AES256_SECRET_LENGTH_U32 => {
                 let key = entry.secret().try_as_key()?.as_ref();
@@ -99,10 +104,92 @@ impl SymmetricVault for Vault {
                 let key = GenericArray::from_slice(key);
                 Aes256Gcm::new(key)
                     .decrypt(nonce, payload)
-                    .map_err(|_| VaultError::AeadAesGcmEncrypt.into())
+                    .map_err(|_| VaultError::AeadAesGcmEncrypt)
             }
-            _ => Err(VaultError::AeadAesGcmEncrypt.into()),
+            _ => Err(VaultError::AeadAesGcmEncrypt),
+        }?;
+        let attributes = entry.key_attributes();
+        drop(entries);
+
+        self.audit(AuditOperation::Decrypt, key_id, attributes)
+            .await?;
+
+        Ok(plaintext)
+    }
+
+    async fn aead_chacha20_poly1305_encrypt(
+        &self,
+        key_id: &KeyId,
+        plaintext: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        self.preload_from_storage(key_id).await;
+
+        let entries = self.data.entries.read().await;
+        let entry = entries.get(key_id).ok_or(VaultError::EntryNotFound)?;
+        Self::check_usage(entry, SecretUsage::ENCRYPT)?;
+
+        if entry.key_attributes().stype() != SecretType::ChaCha20Poly1305 {
+            return Err(VaultError::AeadChaCha20Poly1305Encrypt.into());
+        }
+
+        let key = entry.secret().try_as_key()?.as_ref();
+        if key.len() != CHACHA20POLY1305_SECRET_LENGTH_USIZE {
+            return Err(VaultError::AeadChaCha20Poly1305Encrypt.into());
         }
+
+        let nonce = GenericArray::from_slice(nonce);
+        let payload = Payload {
+            aad,
+            msg: plaintext,
+        };
+
+        let key = GenericArray::from_slice(key);
+        ChaCha20Poly1305::new(key)
+            .encrypt(nonce, payload)
+            .map_err(|_| VaultError::AeadChaCha20Poly1305Encrypt.into())
+    }
+
+    async fn aead_chacha20_poly1305_decrypt(
+        &self,
+        key_id: &KeyId,
+        cipher_text: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        self.preload_from_storage(key_id).await;
+
+        let entries = self.data.entries.read().await;
+        let entry = entries.get(key_id).ok_or(VaultError::EntryNotFound)?;
+        Self::check_usage(entry, SecretUsage::ENCRYPT)?;
+
+        if entry.key_attributes().stype() != SecretType::ChaCha20Poly1305 {
+            return Err(VaultError::AeadChaCha20Poly1305Decrypt.into());
+        }
+
+        let key = entry.secret().try_as_key()?.as_ref();
+        if key.len() != CHACHA20POLY1305_SECRET_LENGTH_USIZE {
+            return Err(VaultError::AeadChaCha20Poly1305Decrypt.into());
+        }
+
+        let nonce = GenericArray::from_slice(nonce);
+        let payload = Payload {
+            aad,
+            msg: cipher_text,
+        };
+
+        let key = GenericArray::from_slice(key);
+        let plaintext = ChaCha20Poly1305::new(key)
+            .decrypt(nonce, payload)
+            .map_err(|_| VaultError::AeadChaCha20Poly1305Decrypt)?;
+        let attributes = entry.key_attributes();
+        drop(entries);
+
+        self.audit(AuditOperation::Decrypt, key_id, attributes)
+            .await?;
+
+        Ok(plaintext)
     }
 }
 
@@ -115,4 +202,7 @@ mod tests {
 
     #[ockam_macros::vault_test]
     fn encryption() {}
+
+    #[ockam_macros::vault_test]
+    fn encryption_chacha20_poly1305() {}
 }
@@ -1,6 +1,11 @@
-use ockam_core::compat::{collections::BTreeMap, sync::Arc};
+use crate::audit::{AuditEvent, AuditOperation, AuditSink};
+use crate::kms::Kms;
+use ockam_core::compat::collections::{BTreeMap, BTreeSet};
+use ockam_core::compat::{string::String, sync::Arc, vec::Vec};
 use ockam_core::vault::storage::Storage;
-use ockam_core::vault::{KeyId, VaultEntry};
+use ockam_core::vault::{
+    KeyId, Secret, SecretAttributes, SecretPersistence, SecretType, SecretVault, VaultEntry,
+};
 use ockam_node::compat::asynchronous::RwLock;
 
 /// Vault implementation that stores secrets in memory and uses software crypto.
@@ -35,8 +40,9 @@ use ockam_node::compat::asynchronous::RwLock;
 pub struct Vault {
     pub(crate) data: VaultData,
     pub(crate) storage: Option<Arc<dyn Storage>>,
-    #[cfg(feature = "aws")]
-    pub(crate) aws_kms: Option<crate::aws::Kms>,
+    pub(crate) kms: Option<Arc<dyn Kms>>,
+    pub(crate) audit: Option<Arc<dyn AuditSink>>,
+    pub(crate) audit_caller: Option<String>,
 }
 
 #[derive(Default, Clone)]
@@ -50,8 +56,9 @@ impl Vault {
         Self {
             data: Default::default(),
             storage,
-            #[cfg(feature = "aws")]
-            aws_kms: None,
+            kms: None,
+            audit: None,
+            audit_caller: None,
         }
     }
 
@@ -60,14 +67,135 @@ impl Vault {
         Self::new(None)
     }
 
+    /// Create persistent NIST P-256 secrets in a KMS.
+    pub fn enable_kms(&mut self, kms: impl Kms) {
+        self.kms = Some(Arc::new(kms));
+    }
+
     /// Enable AWS KMS.
     #[cfg(feature = "aws")]
     pub async fn enable_aws_kms(&mut self) -> Result<(), ockam_core::Error> {
         let kms = crate::aws::Kms::default().await?;
-        self.aws_kms = Some(kms);
+        self.enable_kms(kms);
+        Ok(())
+    }
+
+    /// Enable a PKCS#11 token, as the KMS of the vault.
+    #[cfg(feature = "pkcs11")]
+    pub fn enable_pkcs11(
+        &mut self,
+        config: crate::pkcs11::Config,
+    ) -> Result<(), ockam_core::Error> {
+        let token = crate::pkcs11::Pkcs11::new(config)?;
+        self.enable_kms(token);
         Ok(())
     }
 
+    /// Record the operations on persistent secrets in `sink`.
+    pub fn enable_audit(&mut self, sink: impl AuditSink) {
+        self.audit = Some(Arc::new(sink));
+    }
+
+    /// Return a handle on the same secrets whose audit events are attributed
+    /// to `caller`, e.g. the name of a node.
+    pub fn with_audit_caller(&self, caller: impl Into<String>) -> Self {
+        let mut vault = self.clone();
+        vault.audit_caller = Some(caller.into());
+        vault
+    }
+
+    /// Record an operation on a secret if it's persistent and auditing is enabled
+    pub(crate) async fn audit(
+        &self,
+        operation: AuditOperation,
+        key_id: &KeyId,
+        attributes: SecretAttributes,
+    ) -> Result<(), ockam_core::Error> {
+        match &self.audit {
+            Some(sink) if attributes.persistence() == SecretPersistence::Persistent => {
+                let caller = self.audit_caller.clone();
+                sink.record(AuditEvent::new(
+                    operation,
+                    key_id.clone(),
+                    attributes,
+                    caller,
+                ))
+                .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Ids of the persistent secrets, whether they are loaded or not.
+    pub async fn persistent_key_ids(&self) -> Result<Vec<KeyId>, ockam_core::Error> {
+        let mut key_ids = BTreeSet::new();
+        if let Some(storage) = &self.storage {
+            key_ids.extend(storage.list().await?);
+        }
+        let entries = self.data.entries.read().await;
+        key_ids.extend(
+            entries
+                .iter()
+                .filter(|(_, e)| e.key_attributes().persistence() == SecretPersistence::Persistent)
+                .map(|(key_id, _)| key_id.clone()),
+        );
+        Ok(key_ids.into_iter().collect())
+    }
+
+    /// Copy the persistent secrets of this vault to `target`, keeping their
+    /// key ids, and return these ids.
+    ///
+    /// NIST P-256 secret keys are imported into the KMS or PKCS#11 token of
+    /// `target` if it has one. Key ids are derived from public keys, so the
+    /// identities using these keys don't change.
+    ///
+    /// With `delete_source`, the secrets are removed from this vault once all
+    /// of them were copied, so that no copy of the keys is left behind. Keys
+    /// of a KMS stay there, as both vaults refer to them. Without it, secret
+    /// keys which are not [`SecretUsage::EXPORTABLE`] can't be copied.
+    pub async fn migrate_to(
+        &self,
+        target: &Vault,
+        delete_source: bool,
+    ) -> Result<Vec<KeyId>, ockam_core::Error> {
+        let key_ids = self.persistent_key_ids().await?;
+        for key_id in &key_ids {
+            let entry = self.secret_entry(key_id, delete_source).await?;
+            let attributes = entry.key_attributes();
+            let secret = target
+                .import_into_backend(entry.secret().clone(), attributes)
+                .await?;
+            target
+                .insert_copied_secret(key_id, secret, attributes)
+                .await?;
+        }
+        if delete_source {
+            for key_id in &key_ids {
+                self.remove_copied_secret(key_id).await?;
+            }
+        }
+        Ok(key_ids)
+    }
+
+    /// Move a NIST P-256 secret key into the KMS or token of this vault, if any
+    async fn import_into_backend(
+        &self,
+        secret: Secret,
+        attributes: SecretAttributes,
+    ) -> Result<Secret, ockam_core::Error> {
+        if attributes.stype() != SecretType::NistP256
+            || attributes.persistence() != SecretPersistence::Persistent
+        {
+            return Ok(secret);
+        }
+        if let Secret::Key(key) = &secret {
+            if let Some(kms) = &self.kms {
+                return Ok(Secret::Kms(kms.import_key(key).await?));
+            }
+        }
+        Ok(secret)
+    }
+
     pub(crate) async fn preload_from_storage(&self, key_id: &KeyId) {
         // Do nothing if there is no Storage
         let storage = match &self.storage {
@@ -106,4 +234,108 @@ mod tests {
         let vault = Vault::create();
         assert_eq!(vault.data.entries.read().await.len(), 0);
     }
+
+    #[cfg(feature = "rustcrypto")]
+    #[tokio::test]
+    async fn migrate_to_kms() {
+        use crate::kms::MockKms;
+        use ockam_core::vault::{
+            SecretAttributes, SecretPersistence, SecretType, SecretVault, Signer, Verifier,
+            CURVE25519_SECRET_LENGTH_U32, NISTP256_SECRET_LENGTH_U32,
+        };
+
+        let vault = Vault::create();
+        let ed25519 = vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::Ed25519,
+                SecretPersistence::Persistent,
+                CURVE25519_SECRET_LENGTH_U32,
+            ))
+            .await
+            .unwrap();
+        let p256 = vault
+            .secret_generate(SecretAttributes::new(
+                SecretType::NistP256,
+                SecretPersistence::Persistent,
+                NISTP256_SECRET_LENGTH_U32,
+            ))
+            .await
+            .unwrap();
+
+        let kms = MockKms::new();
+        let mut target = Vault::create();
+        target.enable_kms(kms.clone());
+        let mut key_ids = vault.migrate_to(&target, true).await.unwrap();
+        key_ids.sort();
+        let mut expected = vec![ed25519.clone(), p256.clone()];
+        expected.sort();
+        assert_eq!(key_ids, expected);
+
+        // Only the NIST P-256 key moved to the KMS
+        assert_eq!(kms.len().await, 1);
+        for key_id in [ed25519, p256] {
+            let sig = target.sign(&key_id, b"hello").await.unwrap();
+            let public = target.secret_public_key_get(&key_id).await.unwrap();
+            assert!(target.verify(&sig, &public, b"hello").await.unwrap());
+            // No copy of the key is left in the source vault
+            assert!(vault.secret_attributes_get(&key_id).await.is_err());
+        }
+    }
+
+    #[cfg(feature = "storage")]
+    #[tokio::test]
+    async fn audit_persistent_secrets() {
+        use crate::audit::{AuditOperation, FileAuditLog};
+        use ockam_core::compat::rand::{thread_rng, RngCore};
+        use ockam_core::vault::{
+            SecretAttributes, SecretPersistence, SecretType, SecretVault, Signer,
+            CURVE25519_SECRET_LENGTH_U32,
+        };
+
+        let mut rand_id = [0u8; 32];
+        thread_rng().fill_bytes(&mut rand_id);
+        let path = std::env::temp_dir().join(hex::encode(rand_id));
+
+        let mut vault = Vault::create();
+        vault.enable_audit(FileAuditLog::open(&path).await.unwrap());
+        let vault = vault.with_audit_caller("node n1");
+
+        let attributes = |persistence| {
+            SecretAttributes::new(
+                SecretType::Ed25519,
+                persistence,
+                CURVE25519_SECRET_LENGTH_U32,
+            )
+        };
+        let key_id = vault
+            .secret_generate(attributes(SecretPersistence::Persistent))
+            .await
+            .unwrap();
+        vault.sign(&key_id, b"hello").await.unwrap();
+        vault.secret_export(&key_id).await.unwrap();
+        vault.secret_destroy(key_id.clone()).await.unwrap();
+
+        // Ephemeral secrets are not audited
+        let ephemeral = vault
+            .secret_generate(attributes(SecretPersistence::Ephemeral))
+            .await
+            .unwrap();
+        vault.sign(&ephemeral, b"hello").await.unwrap();
+
+        let records = FileAuditLog::read(&path).await.unwrap();
+        let operations: Vec<_> = records.iter().map(|r| r.operation()).collect();
+        assert_eq!(
+            operations,
+            [
+                AuditOperation::Generate,
+                AuditOperation::Sign,
+                AuditOperation::Export,
+                AuditOperation::Destroy
+            ]
+        );
+        for record in records {
+            assert_eq!(record.key_id(), &key_id);
+            assert_eq!(record.caller(), Some("node n1"));
+        }
+    }
 }
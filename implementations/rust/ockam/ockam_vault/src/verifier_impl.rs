@@ -62,7 +62,10 @@ impl Verifier for Vault {
                     }
                 }
             }
-            SecretType::Buffer | SecretType::Aes => Err(VaultError::InvalidPublicKey.into()),
+            SecretType::Buffer
+            | SecretType::Aes
+            | SecretType::ChaCha20Poly1305
+            | SecretType::Kyber768 => Err(VaultError::InvalidPublicKey.into()),
         }
     }
 }